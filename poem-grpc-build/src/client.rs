@@ -31,6 +31,12 @@ pub(crate) fn generate(config: &GrpcConfig, service: &Service, buf: &mut String)
             format!("/{}/{}", service.proto_name, method.proto_name)
         };
 
+        // `NO_SIDE_EFFECTS` or `IDEMPOTENT` methods can be retried
+        let idempotent = method
+            .options
+            .idempotency_level
+            .is_some_and(|level| level != 0);
+
         match (method.client_streaming, method.server_streaming) {
             (false, false) => methods.push(generate_unary(
                 &crate_name,
@@ -38,6 +44,7 @@ pub(crate) fn generate(config: &GrpcConfig, service: &Service, buf: &mut String)
                 &path,
                 &input_type,
                 &output_type,
+                idempotent,
             )),
             (true, false) => methods.push(generate_client_streaming(
                 &crate_name,
@@ -52,6 +59,7 @@ pub(crate) fn generate(config: &GrpcConfig, service: &Service, buf: &mut String)
                 &path,
                 &input_type,
                 &output_type,
+                idempotent,
             )),
             (true, true) => methods.push(generate_bidirectional_streaming(
                 &crate_name,
@@ -127,6 +135,31 @@ pub(crate) fn generate(config: &GrpcConfig, service: &Service, buf: &mut String)
                 self.cli.set_accept_compressed(encodings);
             }
 
+            /// Set the default timeout of the calls
+            pub fn set_timeout(&mut self, timeout: ::std::time::Duration) {
+                self.cli.set_timeout(timeout);
+            }
+
+            /// Set the max size of the received messages after decompression
+            pub fn set_max_decoding_message_size(&mut self, limit: usize) {
+                self.cli.set_max_decoding_message_size(limit);
+            }
+
+            /// Set the max size of the sent messages
+            pub fn set_max_encoding_message_size(&mut self, limit: usize) {
+                self.cli.set_max_encoding_message_size(limit);
+            }
+
+            /// Set the retry policy of the idempotent calls
+            pub fn set_retry_policy(&mut self, policy: #crate_name::RetryPolicy) {
+                self.cli.set_retry_policy(policy);
+            }
+
+            /// Set the hedging policy of the idempotent calls
+            pub fn set_hedging_policy(&mut self, policy: #crate_name::HedgingPolicy) {
+                self.cli.set_hedging_policy(policy);
+            }
+
             #(
             #[allow(dead_code)]
             #methods
@@ -143,11 +176,12 @@ fn generate_unary(
     path: &str,
     input_type: &Type,
     output_type: &Type,
+    idempotent: bool,
 ) -> TokenStream {
     quote! {
         pub async fn #name(&self, request: #crate_name::Request<#input_type>) -> ::std::result::Result<#crate_name::Response<#output_type>, #crate_name::Status> {
             let codec = <#crate_name::codec::ProstCodec<_, _> as ::std::default::Default>::default();
-            self.cli.unary(#path, codec, request).await
+            self.cli.unary(#path, codec, request, #idempotent).await
         }
     }
 }
@@ -173,11 +207,12 @@ fn generate_server_streaming(
     path: &str,
     input_type: &Type,
     output_type: &Type,
+    idempotent: bool,
 ) -> TokenStream {
     quote! {
         pub async fn #name(&self, request: #crate_name::Request<#input_type>) -> ::std::result::Result<#crate_name::Response<#crate_name::Streaming<#output_type>>, #crate_name::Status> {
             let codec = <#crate_name::codec::ProstCodec<_, _> as ::std::default::Default>::default();
-            self.cli.server_streaming(#path, codec, request).await
+            self.cli.server_streaming(#path, codec, request, #idempotent).await
         }
     }
 }
@@ -103,6 +103,8 @@ pub(crate) fn generate(config: &GrpcConfig, service: &Service, buf: &mut String)
             inner: ::std::sync::Arc<T>,
             send_compressed: ::std::option::Option<#crate_name::CompressionEncoding>,
             accept_compressed: ::std::sync::Arc<[#crate_name::CompressionEncoding]>,
+            max_decoding_message_size: ::std::option::Option<usize>,
+            max_encoding_message_size: ::std::option::Option<usize>,
         }
 
         impl<T> ::std::clone::Clone for #server_ident<T> {
@@ -112,6 +114,8 @@ pub(crate) fn generate(config: &GrpcConfig, service: &Service, buf: &mut String)
                     inner: self.inner.clone(),
                     send_compressed: self.send_compressed,
                     accept_compressed: self.accept_compressed.clone(),
+                    max_decoding_message_size: self.max_decoding_message_size,
+                    max_encoding_message_size: self.max_encoding_message_size,
                 }
             }
         }
@@ -129,6 +133,8 @@ pub(crate) fn generate(config: &GrpcConfig, service: &Service, buf: &mut String)
                     inner: ::std::sync::Arc::new(service),
                     send_compressed: ::std::option::Option::None,
                     accept_compressed: ::std::sync::Arc::new([]),
+                    max_decoding_message_size: ::std::option::Option::None,
+                    max_encoding_message_size: ::std::option::Option::None,
                 }
             }
 
@@ -147,6 +153,40 @@ pub(crate) fn generate(config: &GrpcConfig, service: &Service, buf: &mut String)
                     ..self
                 }
             }
+
+            /// Set the max size of the received messages after decompression
+            ///
+            /// Default is `4MiB`.
+            pub fn max_decoding_message_size(self, limit: usize) -> Self {
+                Self {
+                    max_decoding_message_size: Some(limit),
+                    ..self
+                }
+            }
+
+            /// Set the max size of the sent messages
+            ///
+            /// Default is `usize::MAX`.
+            pub fn max_encoding_message_size(self, limit: usize) -> Self {
+                Self {
+                    max_encoding_message_size: Some(limit),
+                    ..self
+                }
+            }
+        }
+
+        #[allow(dead_code)]
+        impl<T> #server_ident<T> {
+            fn grpc_server<C: #crate_name::codec::Codec>(&self, codec: C) -> #crate_name::server::GrpcServer<'_, C> {
+                let mut server = #crate_name::server::GrpcServer::new(codec, self.send_compressed, &self.accept_compressed);
+                if let ::std::option::Option::Some(limit) = self.max_decoding_message_size {
+                    server = server.max_decoding_message_size(limit);
+                }
+                if let ::std::option::Option::Some(limit) = self.max_encoding_message_size {
+                    server = server.max_encoding_message_size(limit);
+                }
+                server
+            }
         }
 
         impl<T: #service_ident> ::poem::IntoEndpoint for #server_ident<T> {
@@ -226,7 +266,7 @@ fn generate_unary(codec_list: &[Path], method_info: MethodInfo) -> TokenStream {
         crate_name,
         codec_list,
         quote! {
-            #crate_name::server::GrpcServer::new(codec, server.send_compressed, &server.accept_compressed).unary(#proxy_service_ident(server.inner.clone()), req).await
+            server.grpc_server(codec).unary(#proxy_service_ident(server.inner.clone()), req).await
         },
     );
 
@@ -270,7 +310,7 @@ fn generate_client_streaming(codec_list: &[Path], method_info: MethodInfo) -> To
         crate_name,
         codec_list,
         quote! {
-            #crate_name::server::GrpcServer::new(codec, server.send_compressed, &server.accept_compressed).client_streaming(#proxy_service_ident(server.inner.clone()), req).await
+            server.grpc_server(codec).client_streaming(#proxy_service_ident(server.inner.clone()), req).await
         },
     );
 
@@ -314,7 +354,7 @@ fn generate_server_streaming(codec_list: &[Path], method_info: MethodInfo) -> To
         crate_name,
         codec_list,
         quote! {
-            #crate_name::server::GrpcServer::new(codec, server.send_compressed, &server.accept_compressed).server_streaming(#proxy_service_ident(server.inner.clone()), req).await
+            server.grpc_server(codec).server_streaming(#proxy_service_ident(server.inner.clone()), req).await
         },
     );
 
@@ -358,7 +398,7 @@ fn generate_bidirectional_streaming(codec_list: &[Path], method_info: MethodInfo
         crate_name,
         codec_list,
         quote! {
-            #crate_name::server::GrpcServer::new(codec, server.send_compressed, &server.accept_compressed).bidirectional_streaming(#proxy_service_ident(server.inner.clone()), req).await
+            server.grpc_server(codec).bidirectional_streaming(#proxy_service_ident(server.inner.clone()), req).await
         },
     );
 
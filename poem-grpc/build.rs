@@ -10,6 +10,14 @@ fn main() -> Result<()> {
             &["proto/"],
         )?;
 
+    poem_grpc_build::Config::new().internal().compile(
+        &[
+            "proto/google/rpc/status.proto",
+            "proto/google/rpc/error_details.proto",
+        ],
+        &["proto/"],
+    )?;
+
     // for test
     poem_grpc_build::Config::new()
         .internal()
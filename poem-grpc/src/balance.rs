@@ -0,0 +1,510 @@
+use std::{
+    sync::{
+        Arc, Mutex, RwLock,
+        atomic::{AtomicU32, AtomicUsize, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use http::{Uri, uri::InvalidUri};
+
+/// The strategy to choose an endpoint for each call.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LoadBalancer {
+    /// Chooses the endpoints in turn.
+    #[default]
+    RoundRobin,
+    /// Chooses the endpoint with the fewest outstanding calls.
+    LeastOutstandingRequests,
+    /// Chooses two endpoints at random, and uses the one with fewer
+    /// outstanding calls.
+    PowerOfTwoChoices,
+}
+
+/// The configuration of the passive outlier detection, which temporarily
+/// stops sending calls to an endpoint after consecutive failures.
+///
+/// A call fails if the connection fails, the HTTP status is not `200`, or the
+/// GRPC status is [`Code::Unavailable`](crate::Code::Unavailable).
+#[derive(Debug, Clone)]
+pub struct OutlierDetection {
+    consecutive_failures: u32,
+    base_ejection_time: Duration,
+    max_ejection_time: Duration,
+    max_ejection_percent: u8,
+}
+
+impl Default for OutlierDetection {
+    fn default() -> Self {
+        Self {
+            consecutive_failures: 5,
+            base_ejection_time: Duration::from_secs(30),
+            max_ejection_time: Duration::from_secs(300),
+            max_ejection_percent: 50,
+        }
+    }
+}
+
+impl OutlierDetection {
+    /// Create an `OutlierDetection` with the default configuration.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets the number of consecutive failures which ejects an endpoint.
+    ///
+    /// Default is `5`.
+    #[must_use]
+    pub fn consecutive_failures(self, count: u32) -> Self {
+        Self {
+            consecutive_failures: count.max(1),
+            ..self
+        }
+    }
+
+    /// Sets the base ejection time, which is multiplied by the number of times
+    /// the endpoint has been ejected. The multiplier decreases by one for each
+    /// base ejection time that the endpoint is not ejected.
+    ///
+    /// Default is `30s`.
+    #[must_use]
+    pub fn base_ejection_time(self, duration: Duration) -> Self {
+        Self {
+            base_ejection_time: duration,
+            ..self
+        }
+    }
+
+    /// Sets the max ejection time.
+    ///
+    /// Default is `300s`.
+    #[must_use]
+    pub fn max_ejection_time(self, duration: Duration) -> Self {
+        Self {
+            max_ejection_time: duration,
+            ..self
+        }
+    }
+
+    /// Sets the max percentage of the endpoints which can be ejected at the
+    /// same time, at least one endpoint can always be ejected.
+    ///
+    /// Default is `50`.
+    #[must_use]
+    pub fn max_ejection_percent(self, percent: u8) -> Self {
+        Self {
+            max_ejection_percent: percent.min(100),
+            ..self
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Ejection {
+    until: Option<Instant>,
+    count: u32,
+}
+
+#[derive(Debug)]
+pub(crate) struct EndpointState {
+    pub(crate) uri: Uri,
+    outstanding: AtomicUsize,
+    consecutive_failures: AtomicU32,
+    ejection: Mutex<Ejection>,
+}
+
+impl EndpointState {
+    fn new(uri: Uri) -> Self {
+        Self {
+            uri,
+            outstanding: AtomicUsize::new(0),
+            consecutive_failures: AtomicU32::new(0),
+            ejection: Mutex::new(Ejection::default()),
+        }
+    }
+
+    fn is_ejected(&self, now: Instant) -> bool {
+        self.ejection
+            .lock()
+            .unwrap()
+            .until
+            .is_some_and(|until| until > now)
+    }
+
+    #[inline]
+    fn outstanding(&self) -> usize {
+        self.outstanding.load(Ordering::Relaxed)
+    }
+}
+
+/// A set of endpoints which can be updated at runtime, such as by a service
+/// discovery.
+///
+/// # Example
+///
+/// ```
+/// use poem_grpc::{ClientConfig, Resolver};
+///
+/// let resolver = Resolver::new();
+/// resolver
+///     .update(["http://server1:3000", "http://server2:3000"])
+///     .unwrap();
+/// let config = ClientConfig::builder()
+///     .resolver(resolver.clone())
+///     .build()
+///     .unwrap();
+///
+/// // later
+/// resolver
+///     .update(["http://server2:3000", "http://server3:3000"])
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Resolver {
+    endpoints: Arc<RwLock<Vec<Arc<EndpointState>>>>,
+}
+
+impl Resolver {
+    /// Create an empty `Resolver`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Replaces the endpoints, the state of the endpoints which are still
+    /// present, such as the outstanding calls and the ejection, is kept.
+    pub fn update<I, T>(&self, uris: I) -> Result<(), InvalidUri>
+    where
+        I: IntoIterator<Item = T>,
+        T: TryInto<Uri, Error = InvalidUri>,
+    {
+        let uris = uris
+            .into_iter()
+            .map(TryInto::try_into)
+            .collect::<Result<Vec<_>, _>>()?;
+        self.set_uris(uris);
+        Ok(())
+    }
+
+    pub(crate) fn set_uris(&self, uris: Vec<Uri>) {
+        let mut endpoints = self.endpoints.write().unwrap();
+        let new_endpoints = uris
+            .into_iter()
+            .map(|uri| {
+                endpoints
+                    .iter()
+                    .find(|endpoint| endpoint.uri == uri)
+                    .cloned()
+                    .unwrap_or_else(|| Arc::new(EndpointState::new(uri)))
+            })
+            .collect();
+        *endpoints = new_endpoints;
+    }
+
+    /// Returns the current endpoints.
+    pub fn uris(&self) -> Vec<Uri> {
+        self.endpoints
+            .read()
+            .unwrap()
+            .iter()
+            .map(|endpoint| endpoint.uri.clone())
+            .collect()
+    }
+}
+
+pub(crate) struct Balancer {
+    strategy: LoadBalancer,
+    resolver: Resolver,
+    outlier_detection: Option<OutlierDetection>,
+    next: AtomicUsize,
+}
+
+impl Balancer {
+    pub(crate) fn new(
+        strategy: LoadBalancer,
+        resolver: Resolver,
+        outlier_detection: Option<OutlierDetection>,
+    ) -> Self {
+        Self {
+            strategy,
+            resolver,
+            outlier_detection,
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// Chooses an endpoint, or returns `None` if there are no endpoints.
+    ///
+    /// The ejected endpoints are skipped, unless all endpoints are ejected.
+    pub(crate) fn pick(self: &Arc<Self>) -> Option<EndpointGuard> {
+        let endpoints = self.resolver.endpoints.read().unwrap();
+        let now = Instant::now();
+        let mut available = endpoints
+            .iter()
+            .filter(|endpoint| !endpoint.is_ejected(now))
+            .collect::<Vec<_>>();
+        if available.is_empty() {
+            available = endpoints.iter().collect();
+        }
+
+        let endpoint = match available.len() {
+            0 => return None,
+            1 => available[0],
+            len => match self.strategy {
+                LoadBalancer::RoundRobin => {
+                    available[self.next.fetch_add(1, Ordering::Relaxed) % len]
+                }
+                LoadBalancer::LeastOutstandingRequests => {
+                    // starts from a different endpoint each time to break ties
+                    let start = self.next.fetch_add(1, Ordering::Relaxed);
+                    (0..len)
+                        .map(|idx| available[(start + idx) % len])
+                        .min_by_key(|endpoint| endpoint.outstanding())
+                        .unwrap()
+                }
+                LoadBalancer::PowerOfTwoChoices => {
+                    let a = fastrand::usize(0..len);
+                    let b = (a + fastrand::usize(1..len)) % len;
+                    if available[b].outstanding() < available[a].outstanding() {
+                        available[b]
+                    } else {
+                        available[a]
+                    }
+                }
+            },
+        }
+        .clone();
+
+        endpoint.outstanding.fetch_add(1, Ordering::Relaxed);
+        Some(EndpointGuard {
+            balancer: self.clone(),
+            endpoint,
+            reported: false,
+        })
+    }
+
+    fn report(&self, endpoint: &EndpointState, success: bool) {
+        let Some(config) = &self.outlier_detection else {
+            return;
+        };
+
+        if success {
+            endpoint.consecutive_failures.store(0, Ordering::Relaxed);
+            return;
+        }
+
+        let failures = endpoint
+            .consecutive_failures
+            .fetch_add(1, Ordering::Relaxed)
+            + 1;
+        if failures < config.consecutive_failures {
+            return;
+        }
+        endpoint.consecutive_failures.store(0, Ordering::Relaxed);
+
+        let now = Instant::now();
+        let (total, ejected) = {
+            let endpoints = self.resolver.endpoints.read().unwrap();
+            let ejected = endpoints
+                .iter()
+                .filter(|endpoint| endpoint.is_ejected(now))
+                .count();
+            (endpoints.len(), ejected)
+        };
+        let max_ejected = (total * config.max_ejection_percent as usize / 100).max(1);
+        if ejected >= max_ejected {
+            return;
+        }
+
+        let mut ejection = endpoint.ejection.lock().unwrap();
+        // the multiplier decreases by one for each base ejection time that the
+        // endpoint has not been ejected
+        if let Some(until) = ejection.until.filter(|until| *until <= now) {
+            let periods = (now - until).as_nanos() / config.base_ejection_time.as_nanos().max(1);
+            ejection.count = ejection
+                .count
+                .saturating_sub(u32::try_from(periods).unwrap_or(u32::MAX));
+        }
+        ejection.count += 1;
+        let ejection_time = config
+            .base_ejection_time
+            .saturating_mul(ejection.count)
+            .min(config.max_ejection_time);
+        ejection.until = Some(now + ejection_time);
+    }
+}
+
+/// An endpoint chosen by the [`Balancer`], which is counted as an
+/// outstanding call until dropped.
+pub(crate) struct EndpointGuard {
+    balancer: Arc<Balancer>,
+    endpoint: Arc<EndpointState>,
+    reported: bool,
+}
+
+impl EndpointGuard {
+    #[inline]
+    pub(crate) fn uri(&self) -> &Uri {
+        &self.endpoint.uri
+    }
+
+    /// Reports the result of the call for the outlier detection.
+    pub(crate) fn report(&mut self, success: bool) {
+        if !self.reported {
+            self.reported = true;
+            self.balancer.report(&self.endpoint, success);
+        }
+    }
+}
+
+impl Drop for EndpointGuard {
+    fn drop(&mut self) {
+        self.endpoint.outstanding.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_balancer(
+        strategy: LoadBalancer,
+        outlier_detection: Option<OutlierDetection>,
+    ) -> Arc<Balancer> {
+        let resolver = Resolver::new();
+        resolver
+            .update(["http://a:3000", "http://b:3000", "http://c:3000"])
+            .unwrap();
+        Arc::new(Balancer::new(strategy, resolver, outlier_detection))
+    }
+
+    fn host(guard: &EndpointGuard) -> &str {
+        guard.uri().host().unwrap()
+    }
+
+    #[test]
+    fn round_robin() {
+        let balancer = create_balancer(LoadBalancer::RoundRobin, None);
+        let hosts = (0..6)
+            .map(|_| host(&balancer.pick().unwrap()).to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(hosts, vec!["a", "b", "c", "a", "b", "c"]);
+    }
+
+    #[test]
+    fn least_outstanding_requests() {
+        let balancer = create_balancer(LoadBalancer::LeastOutstandingRequests, None);
+        let guards = (0..3).map(|_| balancer.pick().unwrap()).collect::<Vec<_>>();
+        let mut hosts = guards.iter().map(host).collect::<Vec<_>>();
+        hosts.sort();
+        assert_eq!(hosts, vec!["a", "b", "c"]);
+
+        let busy = balancer.pick().unwrap();
+        let busy_host = host(&busy).to_string();
+        for _ in 0..10 {
+            assert_ne!(host(&balancer.pick().unwrap()), busy_host);
+        }
+    }
+
+    #[test]
+    fn power_of_two_choices() {
+        let balancer = create_balancer(LoadBalancer::PowerOfTwoChoices, None);
+        balancer
+            .resolver
+            .update(["http://a:3000", "http://b:3000"])
+            .unwrap();
+        let busy = balancer.pick().unwrap();
+        let busy_host = host(&busy).to_string();
+        for _ in 0..10 {
+            assert_ne!(host(&balancer.pick().unwrap()), busy_host);
+        }
+    }
+
+    #[test]
+    fn outlier_detection() {
+        let balancer = create_balancer(
+            LoadBalancer::RoundRobin,
+            Some(
+                OutlierDetection::new()
+                    .consecutive_failures(2)
+                    .max_ejection_percent(50),
+            ),
+        );
+
+        for _ in 0..2 {
+            let mut guard = balancer.pick().unwrap();
+            assert_eq!(host(&guard), "a");
+            guard.report(false);
+            balancer.pick().unwrap().report(true);
+            balancer.pick().unwrap().report(true);
+        }
+        for _ in 0..4 {
+            assert_ne!(host(&balancer.pick().unwrap()), "a");
+        }
+
+        // only one endpoint can be ejected
+        for _ in 0..4 {
+            let mut guard = balancer.pick().unwrap();
+            if host(&guard) == "b" {
+                guard.report(false);
+            }
+        }
+        let hosts = (0..4)
+            .map(|_| host(&balancer.pick().unwrap()).to_string())
+            .collect::<Vec<_>>();
+        assert!(hosts.iter().any(|host| host == "b"));
+        assert!(hosts.iter().all(|host| host != "a"));
+    }
+
+    #[test]
+    fn ejection_decay() {
+        let balancer = create_balancer(
+            LoadBalancer::RoundRobin,
+            Some(
+                OutlierDetection::new()
+                    .consecutive_failures(1)
+                    .base_ejection_time(Duration::from_millis(20))
+                    .max_ejection_percent(100),
+            ),
+        );
+        let endpoint = balancer.resolver.endpoints.read().unwrap()[0].clone();
+        let ejection_count = || endpoint.ejection.lock().unwrap().count;
+
+        balancer.report(&endpoint, false);
+        assert_eq!(ejection_count(), 1);
+        balancer.report(&endpoint, false);
+        assert_eq!(ejection_count(), 2);
+
+        // healthy for more than two base ejection times after the ejection ends
+        std::thread::sleep(Duration::from_millis(40 + 50));
+        balancer.report(&endpoint, false);
+        assert_eq!(ejection_count(), 1);
+    }
+
+    #[test]
+    fn resolver_update() {
+        let balancer = create_balancer(LoadBalancer::RoundRobin, None);
+        let guard = balancer.pick().unwrap();
+        assert_eq!(host(&guard), "a");
+
+        balancer
+            .resolver
+            .update(["http://a:3000", "http://d:3000"])
+            .unwrap();
+        assert_eq!(
+            balancer.resolver.uris(),
+            vec![
+                Uri::from_static("http://a:3000"),
+                Uri::from_static("http://d:3000")
+            ]
+        );
+        // the state of `a` is kept
+        assert!(Arc::ptr_eq(
+            &guard.endpoint,
+            &balancer.resolver.endpoints.read().unwrap()[0]
+        ));
+        assert_eq!(guard.endpoint.outstanding(), 1);
+
+        balancer.resolver.update(Vec::<&str>::new()).unwrap();
+        assert!(balancer.pick().is_none());
+    }
+}
@@ -1,27 +1,44 @@
-use std::{io::Error as IoError, sync::Arc};
+use std::{
+    future::Future,
+    io::Error as IoError,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use bytes::Bytes;
 use futures_util::TryStreamExt;
-use http_body_util::BodyExt;
+use http_body_util::{BodyExt, StreamBody};
+use hyper::body::Incoming;
 use hyper_util::{client::legacy::Client, rt::TokioExecutor};
 use poem::{
     Endpoint, EndpointExt, IntoEndpoint, Middleware, Request as HttpRequest,
     Response as HttpResponse,
     endpoint::{DynEndpoint, ToDynEndpoint},
     http::{
-        Extensions, HeaderValue, Method, StatusCode, Uri, Version,
+        Extensions, HeaderMap, HeaderValue, Method, StatusCode, Uri, Version,
         header::{self, InvalidHeaderValue},
         uri::InvalidUri,
     },
 };
 use rustls::ClientConfig as TlsClientConfig;
+use sync_wrapper::SyncStream;
 
 use crate::{
-    Code, CompressionEncoding, Metadata, Request, Response, Status, Streaming,
+    Code, CompressionEncoding, HedgingPolicy, LoadBalancer, Metadata, OutlierDetection, Request,
+    Resolver, Response, RetryPolicy, Status, Streaming,
+    balance::{Balancer, EndpointGuard},
     codec::Codec,
     compression::get_incoming_encodings,
     connector::HttpsConnector,
-    encoding::{create_decode_response_body, create_encode_request_body},
+    deadline::{
+        GRPC_TIMEOUT_HEADER, deadline_exceeded, encode_grpc_timeout, streaming_with_deadline,
+        with_deadline,
+    },
+    encoding::{
+        DEFAULT_MAX_DECODING_MESSAGE_SIZE, DEFAULT_MAX_ENCODING_MESSAGE_SIZE,
+        create_decode_response_body, create_encode_request_body, encode_request_message,
+    },
+    retry::{call_with_hedging, call_with_retries},
 };
 
 pub(crate) type BoxBody = http_body_util::combinators::BoxBody<Bytes, IoError>;
@@ -33,6 +50,14 @@ pub struct ClientConfig {
     user_agent: Option<HeaderValue>,
     tls_config: Option<TlsClientConfig>,
     max_header_list_size: u32,
+    timeout: Option<Duration>,
+    max_decoding_message_size: usize,
+    max_encoding_message_size: usize,
+    load_balancer: LoadBalancer,
+    outlier_detection: Option<OutlierDetection>,
+    resolver: Option<Resolver>,
+    retry_policy: Option<RetryPolicy>,
+    hedging_policy: Option<HedgingPolicy>,
 }
 
 impl ClientConfig {
@@ -45,6 +70,14 @@ impl ClientConfig {
                 user_agent: None,
                 tls_config: None,
                 max_header_list_size: 16384,
+                timeout: None,
+                max_decoding_message_size: DEFAULT_MAX_DECODING_MESSAGE_SIZE,
+                max_encoding_message_size: DEFAULT_MAX_ENCODING_MESSAGE_SIZE,
+                load_balancer: LoadBalancer::default(),
+                outlier_detection: None,
+                resolver: None,
+                retry_policy: None,
+                hedging_policy: None,
             }),
         }
     }
@@ -162,6 +195,95 @@ impl ClientConfigBuilder {
         self
     }
 
+    /// Sets the default timeout of the calls, which is sent to the server with
+    /// the `grpc-timeout` header.
+    ///
+    /// The timeout can be overridden for each call with
+    /// [`Request::set_timeout`] or [`Request::set_deadline`].
+    ///
+    /// Default is no timeout.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        if let Ok(config) = &mut self.config {
+            config.timeout = Some(timeout);
+        }
+        self
+    }
+
+    /// Sets the max size of the received messages after decompression, the
+    /// call fails with [`Code::ResourceExhausted`] if a message exceeds it.
+    ///
+    /// Default is `4MiB`.
+    pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
+        if let Ok(config) = &mut self.config {
+            config.max_decoding_message_size = limit;
+        }
+        self
+    }
+
+    /// Sets the max size of the sent messages before compression, the call
+    /// fails with [`Code::ResourceExhausted`] if a message exceeds it.
+    ///
+    /// Default is `usize::MAX`.
+    pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
+        if let Ok(config) = &mut self.config {
+            config.max_encoding_message_size = limit;
+        }
+        self
+    }
+
+    /// Sets the strategy to choose an endpoint for each call.
+    ///
+    /// Default is [`LoadBalancer::RoundRobin`].
+    pub fn load_balancer(mut self, load_balancer: LoadBalancer) -> Self {
+        if let Ok(config) = &mut self.config {
+            config.load_balancer = load_balancer;
+        }
+        self
+    }
+
+    /// Enables the passive outlier detection, which temporarily ejects the
+    /// endpoints after consecutive failures.
+    ///
+    /// Default is disabled.
+    pub fn outlier_detection(mut self, outlier_detection: OutlierDetection) -> Self {
+        if let Ok(config) = &mut self.config {
+            config.outlier_detection = Some(outlier_detection);
+        }
+        self
+    }
+
+    /// Sets the [`Resolver`] which provides the endpoints, so that they can
+    /// be changed at runtime.
+    ///
+    /// The uris added with [`ClientConfigBuilder::uri`] are ignored if the
+    /// resolver is set.
+    pub fn resolver(mut self, resolver: Resolver) -> Self {
+        if let Ok(config) = &mut self.config {
+            config.resolver = Some(resolver);
+        }
+        self
+    }
+
+    /// Sets the retry policy of the calls.
+    ///
+    /// Default is no retries.
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        if let Ok(config) = &mut self.config {
+            config.retry_policy = Some(policy);
+        }
+        self
+    }
+
+    /// Sets the hedging policy of the calls.
+    ///
+    /// Default is no hedging.
+    pub fn hedging_policy(mut self, policy: HedgingPolicy) -> Self {
+        if let Ok(config) = &mut self.config {
+            config.hedging_policy = Some(policy);
+        }
+        self
+    }
+
     /// Consumes this builder and returns the `ClientConfig`
     pub fn build(self) -> Result<ClientConfig, ClientBuilderError> {
         self.config
@@ -174,15 +296,30 @@ pub struct GrpcClient {
     ep: Arc<dyn DynEndpoint<Output = HttpResponse> + 'static>,
     send_compressed: Option<CompressionEncoding>,
     accept_compressed: Arc<[CompressionEncoding]>,
+    timeout: Option<Duration>,
+    max_decoding_message_size: usize,
+    max_encoding_message_size: usize,
+    retry_policy: Option<RetryPolicy>,
+    hedging_policy: Option<HedgingPolicy>,
 }
 
 impl GrpcClient {
     #[inline]
-    pub fn new(config: ClientConfig) -> Self {
+    pub fn new(mut config: ClientConfig) -> Self {
+        let timeout = config.timeout;
+        let max_decoding_message_size = config.max_decoding_message_size;
+        let max_encoding_message_size = config.max_encoding_message_size;
+        let retry_policy = config.retry_policy.take();
+        let hedging_policy = config.hedging_policy.take();
         Self {
             ep: create_client_endpoint(config),
             send_compressed: None,
             accept_compressed: Arc::new([]),
+            timeout,
+            max_decoding_message_size,
+            max_encoding_message_size,
+            retry_policy,
+            hedging_policy,
         }
     }
 
@@ -196,6 +333,11 @@ impl GrpcClient {
             ep: Arc::new(ToDynEndpoint(ep.map_to_response())),
             send_compressed: None,
             accept_compressed: Arc::new([]),
+            timeout: None,
+            max_decoding_message_size: DEFAULT_MAX_DECODING_MESSAGE_SIZE,
+            max_encoding_message_size: DEFAULT_MAX_ENCODING_MESSAGE_SIZE,
+            retry_policy: None,
+            hedging_policy: None,
         }
     }
 
@@ -207,6 +349,31 @@ impl GrpcClient {
         self.accept_compressed = encodings.into();
     }
 
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = Some(timeout);
+    }
+
+    pub fn set_max_decoding_message_size(&mut self, limit: usize) {
+        self.max_decoding_message_size = limit;
+    }
+
+    pub fn set_max_encoding_message_size(&mut self, limit: usize) {
+        self.max_encoding_message_size = limit;
+    }
+
+    pub fn set_retry_policy(&mut self, policy: RetryPolicy) {
+        self.retry_policy = Some(policy);
+    }
+
+    pub fn set_hedging_policy(&mut self, policy: HedgingPolicy) {
+        self.hedging_policy = Some(policy);
+    }
+
+    fn default_deadline(&self) -> Option<Instant> {
+        self.timeout
+            .and_then(|timeout| Instant::now().checked_add(timeout))
+    }
+
     pub fn with<M>(mut self, middleware: M) -> Self
     where
         M: Middleware<Arc<dyn DynEndpoint<Output = HttpResponse> + 'static>>,
@@ -218,30 +385,17 @@ impl GrpcClient {
         self
     }
 
-    pub async fn unary<T: Codec>(
-        &self,
-        path: &str,
-        mut codec: T,
-        request: Request<T::Encode>,
-    ) -> Result<Response<T::Decode>, Status> {
-        let Request {
-            metadata,
-            message,
-            extensions,
-        } = request;
-        let mut http_request =
-            create_http_request::<T>(path, metadata, extensions, self.send_compressed);
-        http_request.set_body(create_encode_request_body(
-            codec.encoder(),
-            Streaming::new(futures_util::stream::once(async move { Ok(message) })),
-            self.send_compressed,
-        ));
-
-        let mut resp = self
-            .ep
-            .call(http_request)
-            .await
-            .map_err(|err| Status::new(Code::Internal).with_message(err))?;
+    /// Sends the request, and checks the HTTP status of the response.
+    async fn send(&self, http_request: HttpRequest) -> Result<HttpResponse, Status> {
+        let resp = self.ep.call(http_request).await.map_err(|err| {
+            // the errors of the connections are retryable
+            let code = if err.status() == StatusCode::SERVICE_UNAVAILABLE {
+                Code::Unavailable
+            } else {
+                Code::Internal
+            };
+            Status::new(code).with_message(err)
+        })?;
 
         if resp.status() != StatusCode::OK {
             return Err(Status::new(Code::Internal).with_message(format!(
@@ -249,11 +403,60 @@ impl GrpcClient {
                 resp.status().as_u16()
             )));
         }
+        Ok(resp)
+    }
+
+    /// Calls `f` with the retry or hedging policy if the method is
+    /// idempotent.
+    async fn call_with_policies<R, F, Fut>(
+        &self,
+        idempotent: bool,
+        hedging: bool,
+        deadline: Option<Instant>,
+        mut f: F,
+    ) -> Result<R, Status>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<R, Status>>,
+    {
+        if !idempotent {
+            return f().await;
+        }
+        match (&self.hedging_policy, &self.retry_policy) {
+            (Some(policy), _) if hedging => call_with_hedging(policy, f).await,
+            (_, Some(policy)) => call_with_retries(policy, deadline, f).await,
+            _ => f().await,
+        }
+    }
+
+    async fn unary_attempt<T: Codec>(
+        &self,
+        path: &str,
+        metadata: &Metadata,
+        extensions: &Extensions,
+        deadline: Option<Instant>,
+        body: Bytes,
+        decoder: T::Decoder,
+    ) -> Result<Response<T::Decode>, Status> {
+        let mut http_request = create_http_request::<T>(
+            path,
+            metadata.clone(),
+            extensions.clone(),
+            self.send_compressed,
+            deadline,
+        )?;
+        http_request.set_body(body);
 
+        let mut resp = self.send(http_request).await?;
         let body = resp.take_body();
         let incoming_encoding = get_incoming_encodings(resp.headers(), &self.accept_compressed)?;
-        let mut stream =
-            create_decode_response_body(codec.decoder(), resp.headers(), body, incoming_encoding)?;
+        let mut stream = create_decode_response_body(
+            decoder,
+            resp.headers(),
+            body,
+            incoming_encoding,
+            self.max_decoding_message_size,
+        )?;
 
         let message = stream
             .try_next()
@@ -267,6 +470,44 @@ impl GrpcClient {
         })
     }
 
+    pub async fn unary<T: Codec>(
+        &self,
+        path: &str,
+        mut codec: T,
+        request: Request<T::Encode>,
+        idempotent: bool,
+    ) -> Result<Response<T::Decode>, Status> {
+        let Request {
+            metadata,
+            message,
+            extensions,
+            deadline,
+        } = request;
+        let deadline = deadline.or_else(|| self.default_deadline());
+        let body = encode_request_message(
+            codec.encoder(),
+            message,
+            self.send_compressed,
+            self.max_encoding_message_size,
+        )
+        .await?;
+
+        with_deadline(
+            deadline,
+            self.call_with_policies(idempotent, true, deadline, || {
+                self.unary_attempt::<T>(
+                    path,
+                    &metadata,
+                    &extensions,
+                    deadline,
+                    body.clone(),
+                    codec.decoder(),
+                )
+            }),
+        )
+        .await
+    }
+
     pub async fn client_streaming<T: Codec>(
         &self,
         path: &str,
@@ -277,42 +518,78 @@ impl GrpcClient {
             metadata,
             message,
             extensions,
+            deadline,
         } = request;
+        let deadline = deadline.or_else(|| self.default_deadline());
         let mut http_request =
-            create_http_request::<T>(path, metadata, extensions, self.send_compressed);
+            create_http_request::<T>(path, metadata, extensions, self.send_compressed, deadline)?;
         http_request.set_body(create_encode_request_body(
             codec.encoder(),
             message,
             self.send_compressed,
+            self.max_encoding_message_size,
         ));
 
-        let mut resp = self
-            .ep
-            .call(http_request)
-            .await
-            .map_err(|err| Status::new(Code::Internal).with_message(err))?;
+        with_deadline(deadline, async move {
+            let mut resp = self.send(http_request).await?;
+            let body = resp.take_body();
+            let incoming_encoding =
+                get_incoming_encodings(resp.headers(), &self.accept_compressed)?;
+            let mut stream = create_decode_response_body(
+                codec.decoder(),
+                resp.headers(),
+                body,
+                incoming_encoding,
+                self.max_decoding_message_size,
+            )?;
+
+            let message = stream.try_next().await?.ok_or_else(|| {
+                Status::new(Code::Internal).with_message("missing response message")
+            })?;
+            Ok(Response {
+                metadata: Metadata {
+                    headers: std::mem::take(resp.headers_mut()),
+                },
+                message,
+            })
+        })
+        .await
+    }
 
-        if resp.status() != StatusCode::OK {
-            return Err(Status::new(Code::Internal).with_message(format!(
-                "invalid http status code: {}",
-                resp.status().as_u16()
-            )));
-        }
+    async fn server_streaming_attempt<T: Codec>(
+        &self,
+        path: &str,
+        metadata: &Metadata,
+        extensions: &Extensions,
+        deadline: Option<Instant>,
+        body: Bytes,
+        decoder: T::Decoder,
+    ) -> Result<Response<Streaming<T::Decode>>, Status> {
+        let mut http_request = create_http_request::<T>(
+            path,
+            metadata.clone(),
+            extensions.clone(),
+            self.send_compressed,
+            deadline,
+        )?;
+        http_request.set_body(body);
 
+        let mut resp = self.send(http_request).await?;
         let body = resp.take_body();
         let incoming_encoding = get_incoming_encodings(resp.headers(), &self.accept_compressed)?;
-        let mut stream =
-            create_decode_response_body(codec.decoder(), resp.headers(), body, incoming_encoding)?;
+        let stream = create_decode_response_body(
+            decoder,
+            resp.headers(),
+            body,
+            incoming_encoding,
+            self.max_decoding_message_size,
+        )?;
 
-        let message = stream
-            .try_next()
-            .await?
-            .ok_or_else(|| Status::new(Code::Internal).with_message("missing response message"))?;
         Ok(Response {
             metadata: Metadata {
                 headers: std::mem::take(resp.headers_mut()),
             },
-            message,
+            message: streaming_with_deadline(deadline, stream),
         })
     }
 
@@ -321,44 +598,38 @@ impl GrpcClient {
         path: &str,
         mut codec: T,
         request: Request<T::Encode>,
+        idempotent: bool,
     ) -> Result<Response<Streaming<T::Decode>>, Status> {
         let Request {
             metadata,
             message,
             extensions,
+            deadline,
         } = request;
-        let mut http_request =
-            create_http_request::<T>(path, metadata, extensions, self.send_compressed);
-        http_request.set_body(create_encode_request_body(
+        let deadline = deadline.or_else(|| self.default_deadline());
+        let body = encode_request_message(
             codec.encoder(),
-            Streaming::new(futures_util::stream::once(async move { Ok(message) })),
+            message,
             self.send_compressed,
-        ));
-
-        let mut resp = self
-            .ep
-            .call(http_request)
-            .await
-            .map_err(|err| Status::new(Code::Internal).with_message(err))?;
-
-        if resp.status() != StatusCode::OK {
-            return Err(Status::new(Code::Internal).with_message(format!(
-                "invalid http status code: {}",
-                resp.status().as_u16()
-            )));
-        }
-
-        let body = resp.take_body();
-        let incoming_encoding = get_incoming_encodings(resp.headers(), &self.accept_compressed)?;
-        let stream =
-            create_decode_response_body(codec.decoder(), resp.headers(), body, incoming_encoding)?;
-
-        Ok(Response {
-            metadata: Metadata {
-                headers: std::mem::take(resp.headers_mut()),
-            },
-            message: stream,
-        })
+            self.max_encoding_message_size,
+        )
+        .await?;
+
+        // the call is only retried until the response headers are received
+        with_deadline(
+            deadline,
+            self.call_with_policies(idempotent, false, deadline, || {
+                self.server_streaming_attempt::<T>(
+                    path,
+                    &metadata,
+                    &extensions,
+                    deadline,
+                    body.clone(),
+                    codec.decoder(),
+                )
+            }),
+        )
+        .await
     }
 
     pub async fn bidirectional_streaming<T: Codec>(
@@ -371,48 +642,50 @@ impl GrpcClient {
             metadata,
             message,
             extensions,
+            deadline,
         } = request;
+        let deadline = deadline.or_else(|| self.default_deadline());
         let mut http_request =
-            create_http_request::<T>(path, metadata, extensions, self.send_compressed);
+            create_http_request::<T>(path, metadata, extensions, self.send_compressed, deadline)?;
         http_request.set_body(create_encode_request_body(
             codec.encoder(),
             message,
             self.send_compressed,
+            self.max_encoding_message_size,
         ));
 
-        let mut resp = self
-            .ep
-            .call(http_request)
-            .await
-            .map_err(|err| Status::new(Code::Internal).with_message(err))?;
-
-        if resp.status() != StatusCode::OK {
-            return Err(Status::new(Code::Internal).with_message(format!(
-                "invalid http status code: {}",
-                resp.status().as_u16()
-            )));
-        }
-
-        let body = resp.take_body();
-        let incoming_encoding = get_incoming_encodings(resp.headers(), &self.accept_compressed)?;
-        let stream =
-            create_decode_response_body(codec.decoder(), resp.headers(), body, incoming_encoding)?;
-
-        Ok(Response {
-            metadata: Metadata {
-                headers: std::mem::take(resp.headers_mut()),
-            },
-            message: stream,
+        with_deadline(deadline, async move {
+            let mut resp = self.send(http_request).await?;
+            let body = resp.take_body();
+            let incoming_encoding =
+                get_incoming_encodings(resp.headers(), &self.accept_compressed)?;
+            let stream = create_decode_response_body(
+                codec.decoder(),
+                resp.headers(),
+                body,
+                incoming_encoding,
+                self.max_decoding_message_size,
+            )?;
+
+            Ok(Response {
+                metadata: Metadata {
+                    headers: std::mem::take(resp.headers_mut()),
+                },
+                message: streaming_with_deadline(deadline, stream),
+            })
         })
+        .await
     }
 }
 
+#[allow(clippy::result_large_err)]
 fn create_http_request<T: Codec>(
     path: &str,
     metadata: Metadata,
     extensions: Extensions,
     send_compressed: Option<CompressionEncoding>,
-) -> HttpRequest {
+    deadline: Option<Instant>,
+) -> Result<HttpRequest, Status> {
     let mut http_request = HttpRequest::builder()
         .uri_str(path)
         .method(Method::POST)
@@ -432,14 +705,16 @@ fn create_http_request<T: Codec>(
             HeaderValue::from_str(send_compressed.as_str()).expect("BUG: invalid encoding"),
         );
     }
-    http_request
-}
-
-#[inline]
-fn to_boxed_error(
-    err: impl std::error::Error + Send + Sync + 'static,
-) -> Box<dyn std::error::Error + Send + Sync> {
-    Box::new(err)
+    if let Some(deadline) = deadline {
+        let now = Instant::now();
+        if deadline <= now {
+            return Err(deadline_exceeded());
+        }
+        http_request
+            .headers_mut()
+            .insert(GRPC_TIMEOUT_HEADER, encode_grpc_timeout(deadline - now));
+    }
+    Ok(http_request)
 }
 
 fn make_uri(base_uri: &Uri, path: &Uri) -> Uri {
@@ -461,6 +736,34 @@ fn make_uri(base_uri: &Uri, path: &Uri) -> Uri {
     Uri::from_parts(parts).unwrap()
 }
 
+/// Returns `true` if the GRPC status in the headers indicates that the
+/// endpoint is unavailable.
+fn is_unavailable(headers: &HeaderMap) -> bool {
+    headers
+        .get("grpc-status")
+        .is_some_and(|status| status.as_bytes() == b"14")
+}
+
+/// Reports the result of the call to the balancer when the trailers are
+/// received.
+fn report_response_body(body: Incoming, mut guard: EndpointGuard) -> BoxBody {
+    let mut body = body.map_err(IoError::other);
+    let stream = async_stream::stream! {
+        while let Some(frame) = body.frame().await {
+            match &frame {
+                Ok(frame) => {
+                    if let Some(trailers) = frame.trailers_ref() {
+                        guard.report(!is_unavailable(trailers));
+                    }
+                }
+                Err(_) => guard.report(false),
+            }
+            yield frame;
+        }
+    };
+    BodyExt::boxed(StreamBody::new(SyncStream::new(stream)))
+}
+
 fn create_client_endpoint(
     config: ClientConfig,
 ) -> Arc<dyn DynEndpoint<Output = HttpResponse> + 'static> {
@@ -470,27 +773,32 @@ fn create_client_endpoint(
         .http2_max_header_list_size(config.max_header_list_size)
         .build(HttpsConnector::new(config.tls_config.take()));
 
+    let resolver = config.resolver.take().unwrap_or_else(|| {
+        let resolver = Resolver::new();
+        resolver.set_uris(std::mem::take(&mut config.uris));
+        resolver
+    });
+    let balancer = Arc::new(Balancer::new(
+        config.load_balancer,
+        resolver,
+        config.outlier_detection.take(),
+    ));
     let config = Arc::new(config);
 
     Arc::new(ToDynEndpoint(poem::endpoint::make(move |request| {
         let config = config.clone();
         let cli = cli.clone();
+        let balancer = balancer.clone();
         async move {
             let mut request: hyper::Request<BoxBody> = request.into();
 
-            if config.uris.is_empty() {
+            let Some(mut guard) = balancer.pick() else {
                 return Err(poem::Error::from_string(
-                    "uris is empty",
-                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "no endpoints available",
+                    StatusCode::SERVICE_UNAVAILABLE,
                 ));
-            }
-
-            let base_uri = if config.uris.len() == 1 {
-                &config.uris[0]
-            } else {
-                &config.uris[fastrand::usize(0..config.uris.len())]
             };
-            *request.uri_mut() = make_uri(base_uri, request.uri());
+            *request.uri_mut() = make_uri(guard.uri(), request.uri());
 
             if let Some(origin) = &config.origin {
                 if let Ok(value) = HeaderValue::from_maybe_shared(origin.to_string()) {
@@ -504,12 +812,25 @@ fn create_client_endpoint(
                     .insert(header::ORIGIN, user_agent.clone());
             }
 
-            let resp = cli.request(request).await.map_err(to_boxed_error)?;
+            let resp = match cli.request(request).await {
+                Ok(resp) => resp,
+                Err(err) => {
+                    guard.report(false);
+                    return Err(poem::Error::new(err, StatusCode::SERVICE_UNAVAILABLE));
+                }
+            };
             let (parts, body) = resp.into_parts();
 
+            if parts.status != StatusCode::OK || is_unavailable(&parts.headers) {
+                guard.report(false);
+            } else if parts.headers.contains_key("grpc-status") {
+                // trailers-only response
+                guard.report(true);
+            }
+
             Ok::<_, poem::Error>(HttpResponse::from(hyper::Response::from_parts(
                 parts,
-                body.map_err(IoError::other),
+                report_response_body(body, guard),
             )))
         }
     })))
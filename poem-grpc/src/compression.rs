@@ -104,6 +104,8 @@ impl CompressionEncoding {
         Ok(buf)
     }
 
+    /// Decodes the data, and stops reading after `limit + 1` bytes so that
+    /// the caller can detect the decompressed data exceeds the limit.
     #[allow(
         unreachable_code,
         unused_imports,
@@ -111,33 +113,38 @@ impl CompressionEncoding {
         unused_variables,
         unreachable_patterns
     )]
-    pub(crate) async fn decode(&self, data: &[u8]) -> IoResult<Vec<u8>> {
+    pub(crate) async fn decode(&self, data: &[u8], limit: usize) -> IoResult<Vec<u8>> {
         use tokio::io::AsyncReadExt;
 
         let mut buf = Vec::new();
+        let limit = (limit as u64).saturating_add(1);
 
         match self {
             #[cfg(feature = "gzip")]
             CompressionEncoding::GZIP => {
                 async_compression::tokio::bufread::GzipDecoder::new(data)
+                    .take(limit)
                     .read_to_end(&mut buf)
                     .await?;
             }
             #[cfg(feature = "deflate")]
             CompressionEncoding::DEFLATE => {
                 async_compression::tokio::bufread::DeflateDecoder::new(data)
+                    .take(limit)
                     .read_to_end(&mut buf)
                     .await?;
             }
             #[cfg(feature = "brotli")]
             CompressionEncoding::BROTLI => {
                 async_compression::tokio::bufread::BrotliDecoder::new(data)
+                    .take(limit)
                     .read_to_end(&mut buf)
                     .await?;
             }
             #[cfg(feature = "zstd")]
             CompressionEncoding::ZSTD => {
                 async_compression::tokio::bufread::ZstdDecoder::new(data)
+                    .take(limit)
                     .read_to_end(&mut buf)
                     .await?;
             }
@@ -0,0 +1,183 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use futures_util::{Stream, StreamExt};
+use http::{HeaderMap, HeaderValue};
+use tokio::time::Sleep;
+
+use crate::{Code, Status, Streaming};
+
+pub(crate) const GRPC_TIMEOUT_HEADER: &str = "grpc-timeout";
+
+/// The max value of the `grpc-timeout` header is 8 digits.
+const MAX_TIMEOUT_VALUE: u128 = 99_999_999;
+
+/// Parses the `grpc-timeout` header.
+///
+/// Reference: <https://github.com/grpc/grpc/blob/master/doc/PROTOCOL-HTTP2.md#requests>
+pub(crate) fn parse_grpc_timeout(value: &str) -> Option<Duration> {
+    if value.len() < 2 || value.len() > 9 {
+        return None;
+    }
+    let (value, unit) = value.split_at(value.len() - 1);
+    if !value.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let value = value.parse::<u64>().ok()?;
+    Some(match unit {
+        "H" => Duration::from_secs(value * 60 * 60),
+        "M" => Duration::from_secs(value * 60),
+        "S" => Duration::from_secs(value),
+        "m" => Duration::from_millis(value),
+        "u" => Duration::from_micros(value),
+        "n" => Duration::from_nanos(value),
+        _ => return None,
+    })
+}
+
+/// Encodes the timeout as the value of the `grpc-timeout` header, with the
+/// most precise unit which fits in 8 digits.
+pub(crate) fn encode_grpc_timeout(timeout: Duration) -> HeaderValue {
+    let units = [
+        (timeout.as_nanos(), 'n'),
+        (timeout.as_micros(), 'u'),
+        (timeout.as_millis(), 'm'),
+        (timeout.as_secs() as u128, 'S'),
+        (timeout.as_secs() as u128 / 60, 'M'),
+    ];
+    let (value, unit) = units
+        .into_iter()
+        .find(|(value, _)| *value <= MAX_TIMEOUT_VALUE)
+        .unwrap_or((
+            (timeout.as_secs() as u128 / 3600).min(MAX_TIMEOUT_VALUE),
+            'H',
+        ));
+    HeaderValue::from_str(&format!("{value}{unit}")).expect("valid header value")
+}
+
+/// Returns the deadline of the request with the `grpc-timeout` header.
+pub(crate) fn deadline_from_headers(headers: &HeaderMap) -> Option<Instant> {
+    let timeout = headers
+        .get(GRPC_TIMEOUT_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_grpc_timeout)?;
+    Instant::now().checked_add(timeout)
+}
+
+#[inline]
+pub(crate) fn deadline_exceeded() -> Status {
+    Status::new(Code::DeadlineExceeded).with_message("deadline exceeded")
+}
+
+/// Runs the future, and fails with `DeadlineExceeded` if the deadline
+/// expires before it completes.
+#[allow(clippy::result_large_err)]
+pub(crate) async fn with_deadline<T, F>(deadline: Option<Instant>, fut: F) -> Result<T, Status>
+where
+    F: Future<Output = Result<T, Status>>,
+{
+    match deadline {
+        Some(deadline) => tokio::time::timeout_at(deadline.into(), fut)
+            .await
+            .unwrap_or_else(|_| Err(deadline_exceeded())),
+        None => fut.await,
+    }
+}
+
+/// Returns a stream which fails with `DeadlineExceeded` and ends if the
+/// deadline expires before the stream ends.
+pub(crate) fn streaming_with_deadline<T: Send + 'static>(
+    deadline: Option<Instant>,
+    stream: Streaming<T>,
+) -> Streaming<T> {
+    match deadline {
+        Some(deadline) => Streaming::new(DeadlineStream {
+            inner: Some(stream),
+            sleep: Box::pin(tokio::time::sleep_until(deadline.into())),
+        }),
+        None => stream,
+    }
+}
+
+struct DeadlineStream<T> {
+    inner: Option<Streaming<T>>,
+    sleep: Pin<Box<Sleep>>,
+}
+
+impl<T> Stream for DeadlineStream<T> {
+    type Item = Result<T, Status>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let Some(inner) = &mut self.inner else {
+            return Poll::Ready(None);
+        };
+        if let Poll::Ready(item) = inner.poll_next_unpin(cx) {
+            if item.is_none() {
+                self.inner = None;
+            }
+            return Poll::Ready(item);
+        }
+        if self.sleep.as_mut().poll(cx).is_ready() {
+            self.inner = None;
+            return Poll::Ready(Some(Err(deadline_exceeded())));
+        }
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::TryStreamExt;
+
+    use super::*;
+
+    #[test]
+    fn grpc_timeout() {
+        assert_eq!(parse_grpc_timeout("1H"), Some(Duration::from_secs(3600)));
+        assert_eq!(parse_grpc_timeout("2M"), Some(Duration::from_secs(120)));
+        assert_eq!(parse_grpc_timeout("3S"), Some(Duration::from_secs(3)));
+        assert_eq!(parse_grpc_timeout("40m"), Some(Duration::from_millis(40)));
+        assert_eq!(parse_grpc_timeout("50u"), Some(Duration::from_micros(50)));
+        assert_eq!(
+            parse_grpc_timeout("99999999n"),
+            Some(Duration::from_nanos(99_999_999))
+        );
+        assert_eq!(parse_grpc_timeout("123456789n"), None);
+        assert_eq!(parse_grpc_timeout("1s"), None);
+        assert_eq!(parse_grpc_timeout("-1S"), None);
+        assert_eq!(parse_grpc_timeout("S"), None);
+
+        assert_eq!(encode_grpc_timeout(Duration::from_nanos(1500)), "1500n");
+        assert_eq!(encode_grpc_timeout(Duration::from_millis(250)), "250000u");
+        assert_eq!(encode_grpc_timeout(Duration::from_secs(300)), "300000m");
+        assert_eq!(
+            encode_grpc_timeout(Duration::from_secs(86400 * 365)),
+            "31536000S"
+        );
+        assert_eq!(
+            encode_grpc_timeout(Duration::from_secs(u64::MAX)),
+            "99999999H"
+        );
+    }
+
+    #[tokio::test]
+    async fn stream_deadline() {
+        let stream = Streaming::new(async_stream::try_stream! {
+            yield 1;
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            yield 2;
+        });
+        let mut stream =
+            streaming_with_deadline(Some(Instant::now() + Duration::from_millis(50)), stream);
+        assert_eq!(stream.try_next().await.unwrap(), Some(1));
+        assert_eq!(
+            stream.try_next().await.unwrap_err().code(),
+            Code::DeadlineExceeded
+        );
+        assert!(stream.next().await.is_none());
+    }
+}
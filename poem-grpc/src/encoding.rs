@@ -1,5 +1,3 @@
-use std::io::Result as IoResult;
-
 use bytes::{Buf, BufMut, Bytes, BytesMut};
 use futures_util::StreamExt;
 use http_body_util::{BodyExt, StreamBody};
@@ -13,21 +11,44 @@ use crate::{
     codec::{Decoder, Encoder},
 };
 
+/// The default max size of the decoded messages, which is the same as the
+/// other GRPC implementations.
+pub(crate) const DEFAULT_MAX_DECODING_MESSAGE_SIZE: usize = 4 * 1024 * 1024;
+
+/// The default max size of the encoded messages.
+pub(crate) const DEFAULT_MAX_ENCODING_MESSAGE_SIZE: usize = usize::MAX;
+
+fn message_too_large(direction: &str, len: usize, limit: usize) -> Status {
+    Status::new(Code::ResourceExhausted).with_message(format!(
+        "{direction} message larger than max ({len} vs. {limit})"
+    ))
+}
+
+#[allow(clippy::result_large_err)]
 async fn encode_data_frame<T: Encoder>(
     encoder: &mut T,
     buf: &mut BytesMut,
     message: T::Item,
     compression: Option<CompressionEncoding>,
-) -> IoResult<Bytes> {
-    buf.put_slice(&[compression.is_some() as u8, 0, 0, 0, 0]);
+    max_message_size: usize,
+) -> Result<Bytes, Status> {
+    let mut data = BytesMut::new();
+    encoder
+        .encode(message, &mut data)
+        .map_err(Status::from_std_error)?;
+    if data.len() > max_message_size {
+        return Err(message_too_large("sent", data.len(), max_message_size));
+    }
 
+    buf.put_slice(&[compression.is_some() as u8, 0, 0, 0, 0]);
     if let Some(compression) = compression {
-        let mut data = BytesMut::new();
-        encoder.encode(message, &mut data)?;
-        let data = compression.encode(&data).await?;
+        let data = compression
+            .encode(&data)
+            .await
+            .map_err(Status::from_std_error)?;
         buf.extend(data);
     } else {
-        encoder.encode(message, buf)?;
+        buf.extend(data);
     }
 
     let msg_len = (buf.len() - 5) as u32;
@@ -38,14 +59,16 @@ async fn encode_data_frame<T: Encoder>(
 struct DataFrameDecoder {
     buf: BytesMut,
     compression: Option<CompressionEncoding>,
+    max_message_size: usize,
 }
 
 impl DataFrameDecoder {
     #[inline]
-    fn new(compression: Option<CompressionEncoding>) -> Self {
+    fn new(compression: Option<CompressionEncoding>, max_message_size: usize) -> Self {
         Self {
             buf: BytesMut::new(),
             compression,
+            max_message_size,
         }
     }
 
@@ -75,20 +98,35 @@ impl DataFrameDecoder {
                 .with_message(format!("unsupported compressed flag: {compressed}")))?,
         };
 
+        // checks the frame header before buffering the message, the size
+        // after decompression is checked again below
         let len = u32::from_be_bytes(self.buf[1..5].try_into().unwrap()) as usize;
+        if len > self.max_message_size {
+            return Err(message_too_large("received", len, self.max_message_size));
+        }
+        let compression = match (compressed, self.compression) {
+            (true, None) => {
+                return Err(Status::new(Code::Internal)
+                    .with_message("compressed message received without grpc-encoding"));
+            }
+            (true, compression) => compression,
+            (false, _) => None,
+        };
         if self.buf.len() >= len + 5 {
             self.buf.advance(5);
             let data = self.buf.split_to(len).freeze();
 
-            if compressed {
-                let compression = self.compression.ok_or_else(|| {
-                    Status::new(Code::Unimplemented)
-                        .with_message(format!("unsupported compressed flag: {compressed}"))
-                })?;
+            if let Some(compression) = compression {
                 let data = compression
-                    .decode(&data)
+                    .decode(&data, self.max_message_size)
                     .await
                     .map_err(|err| Status::new(Code::Internal).with_message(err.to_string()))?;
+                if data.len() > self.max_message_size {
+                    return Err(Status::new(Code::ResourceExhausted).with_message(format!(
+                        "received message larger than max after decompression (limit: {})",
+                        self.max_message_size
+                    )));
+                }
                 Ok(Some(data.into()))
             } else {
                 Ok(Some(data))
@@ -103,11 +141,12 @@ pub(crate) fn create_decode_request_body<T: Decoder>(
     mut decoder: T,
     body: Body,
     compression: Option<CompressionEncoding>,
+    max_message_size: usize,
 ) -> Streaming<T::Item> {
     let mut body: BoxBody = body.into();
 
     Streaming::new(async_stream::try_stream! {
-        let mut frame_decoder = DataFrameDecoder::new(compression);
+        let mut frame_decoder = DataFrameDecoder::new(compression, max_message_size);
 
         loop {
             match body.frame().await.transpose().map_err(Status::from_std_error)? {
@@ -133,41 +172,67 @@ pub(crate) fn create_encode_response_body<T: Encoder>(
     mut encoder: T,
     mut stream: Streaming<T::Item>,
     compression: Option<CompressionEncoding>,
+    max_message_size: usize,
 ) -> Body {
     let stream = async_stream::try_stream! {
         let mut buf = BytesMut::new();
+        let mut status = Status::new(Code::Ok);
 
         while let Some(item) = stream.next().await {
-            match item {
-                Ok(message) => {
-                    if let Ok(data) = encode_data_frame(&mut encoder, &mut buf, message, compression).await {
-                        yield Frame::data(data);
-                    }
-                }
-                Err(status) => {
-                    yield Frame::trailers(status.to_headers());
+            let res = match item {
+                Ok(message) => encode_data_frame(&mut encoder, &mut buf, message, compression, max_message_size).await,
+                Err(err) => Err(err),
+            };
+            match res {
+                Ok(data) => yield Frame::data(data),
+                Err(err) => {
+                    status = err;
+                    break;
                 }
             }
         }
 
-        yield Frame::trailers(Status::new(Code::Ok).to_headers());
+        yield Frame::trailers(status.to_headers());
     };
 
     BodyExt::boxed(StreamBody::new(SyncStream::new(stream))).into()
 }
 
+/// Encodes the request body with a single message, so that the call fails
+/// before sending the request if the message can not be encoded, and the
+/// body can be sent again when the call is retried.
+pub(crate) async fn encode_request_message<T: Encoder>(
+    mut encoder: T,
+    message: T::Item,
+    compression: Option<CompressionEncoding>,
+    max_message_size: usize,
+) -> Result<Bytes, Status> {
+    let mut buf = BytesMut::new();
+    encode_data_frame(
+        &mut encoder,
+        &mut buf,
+        message,
+        compression,
+        max_message_size,
+    )
+    .await
+}
+
 pub(crate) fn create_encode_request_body<T: Encoder>(
     mut encoder: T,
     mut stream: Streaming<T::Item>,
     compression: Option<CompressionEncoding>,
+    max_message_size: usize,
 ) -> Body {
     let stream = async_stream::try_stream! {
         let mut buf = BytesMut::new();
 
         while let Some(Ok(message)) = stream.next().await {
-            if let Ok(data) = encode_data_frame(&mut encoder, &mut buf, message, compression).await {
-                yield Frame::data(data);
-            }
+            // aborts the request if the message can not be sent
+            let data = encode_data_frame(&mut encoder, &mut buf, message, compression, max_message_size)
+                .await
+                .map_err(std::io::Error::other)?;
+            yield Frame::data(data);
         }
     };
 
@@ -180,6 +245,7 @@ pub(crate) fn create_decode_response_body<T: Decoder>(
     headers: &HeaderMap,
     body: Body,
     compression: Option<CompressionEncoding>,
+    max_message_size: usize,
 ) -> Result<Streaming<T::Item>, Status> {
     // check is trailers-only
     if let Some(status) = Status::from_headers(headers)? {
@@ -193,7 +259,7 @@ pub(crate) fn create_decode_response_body<T: Decoder>(
     let mut body: BoxBody = body.into();
 
     Ok(Streaming::new(async_stream::try_stream! {
-        let mut frame_decoder = DataFrameDecoder::new(compression);
+        let mut frame_decoder = DataFrameDecoder::new(compression, max_message_size);
         let mut status = None;
 
         while let Some(frame) = body.frame().await.transpose().map_err(Status::from_std_error)? {
@@ -223,13 +289,10 @@ pub(crate) fn create_decode_response_body<T: Decoder>(
 
 #[cfg(test)]
 mod tests {
-    use bytes::Bytes;
     use futures_util::TryStreamExt;
-    use http::HeaderMap;
-    use poem::Body;
     use prost::Message;
 
-    use super::create_decode_response_body;
+    use super::*;
     use crate::codec::{Codec, ProstCodec};
 
     #[derive(Clone, PartialEq, Message)]
@@ -264,9 +327,14 @@ mod tests {
         let body = Body::from_bytes_stream(bytes_stream);
 
         let mut codec = ProstCodec::<TestMsg, TestMsg>::default();
-        let mut streaming =
-            create_decode_response_body(codec.decoder(), &HeaderMap::default(), body, None)
-                .expect("streaming");
+        let mut streaming = create_decode_response_body(
+            codec.decoder(),
+            &HeaderMap::default(),
+            body,
+            None,
+            usize::MAX,
+        )
+        .expect("streaming");
 
         let stream_msg = streaming
             .try_next()
@@ -276,4 +344,70 @@ mod tests {
 
         assert_eq!(msg, stream_msg);
     }
+
+    #[tokio::test]
+    async fn max_message_size() {
+        let mut frame_decoder = DataFrameDecoder::new(None, 4);
+        frame_decoder.put_slice([0, 0, 0, 0, 5]);
+        assert_eq!(
+            frame_decoder.next().await.unwrap_err().code(),
+            Code::ResourceExhausted
+        );
+
+        // the declared length of compressed frames is checked before buffering
+        let mut frame_decoder = DataFrameDecoder::new(None, 4);
+        frame_decoder.put_slice([1, 0xff, 0xff, 0xff, 0xff]);
+        assert_eq!(
+            frame_decoder.next().await.unwrap_err().code(),
+            Code::ResourceExhausted
+        );
+
+        // compressed frames are rejected if no compression is negotiated
+        let mut frame_decoder = DataFrameDecoder::new(None, 4);
+        frame_decoder.put_slice([1, 0, 0, 0, 2]);
+        assert_eq!(
+            frame_decoder.next().await.unwrap_err().code(),
+            Code::Internal
+        );
+
+        let mut codec = ProstCodec::<TestMsg, TestMsg>::default();
+        let err = encode_data_frame(
+            &mut codec.encoder(),
+            &mut BytesMut::new(),
+            TestMsg {
+                value: "abcdef".into(),
+            },
+            None,
+            4,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.code(), Code::ResourceExhausted);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[tokio::test]
+    async fn max_decompressed_message_size() {
+        let data = CompressionEncoding::GZIP
+            .encode(&vec![0; 1024 * 1024])
+            .await
+            .unwrap();
+        let mut frame = vec![1];
+        frame.extend((data.len() as u32).to_be_bytes());
+        frame.extend(data);
+
+        let mut frame_decoder = DataFrameDecoder::new(Some(CompressionEncoding::GZIP), 1024);
+        frame_decoder.put_slice(&frame);
+        assert_eq!(
+            frame_decoder.next().await.unwrap_err().code(),
+            Code::ResourceExhausted
+        );
+
+        let mut frame_decoder = DataFrameDecoder::new(Some(CompressionEncoding::GZIP), 1024 * 1024);
+        frame_decoder.put_slice(&frame);
+        assert_eq!(
+            frame_decoder.next().await.unwrap().unwrap().len(),
+            1024 * 1024
+        );
+    }
 }
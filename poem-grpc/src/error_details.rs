@@ -0,0 +1,263 @@
+//! The standard error details of the [richer error model](https://grpc.io/docs/guides/error/#richer-error-model).
+//!
+//! The details are attached to a [`Status`](crate::Status) with
+//! [`Status::with_detail`](crate::Status::with_detail), and sent in the
+//! `grpc-status-details-bin` trailer.
+//!
+//! # Example
+//!
+//! ```
+//! use poem_grpc::{
+//!     Code, Status,
+//!     error_details::{BadRequest, ErrorInfo},
+//! };
+//!
+//! let status = Status::new(Code::InvalidArgument)
+//!     .with_message("invalid request")
+//!     .with_detail(BadRequest::new().add_field_violation("name", "must not be empty"))
+//!     .with_detail(ErrorInfo::new("EMPTY_NAME", "example.com").with_metadata("field", "name"));
+//!
+//! let bad_request = status.get_detail::<BadRequest>().unwrap();
+//! assert_eq!(bad_request.field_violations[0].field, "name");
+//! ```
+
+use std::time::Duration;
+
+use prost::Message;
+use prost_types::Any;
+
+#[allow(unreachable_pub)]
+#[allow(missing_docs)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+mod proto {
+    include!(concat!(env!("OUT_DIR"), "/google.rpc.rs"));
+}
+
+pub(crate) use proto::Status as RpcStatus;
+pub use proto::{
+    BadRequest, DebugInfo, ErrorInfo, Help, LocalizedMessage, PreconditionFailure, QuotaFailure,
+    RequestInfo, ResourceInfo, RetryInfo, bad_request, help, precondition_failure, quota_failure,
+};
+
+/// Represents a message which can be attached to a
+/// [`Status`](crate::Status) as an error detail.
+pub trait ErrorDetail: Message + Default + Sized {
+    /// The fully qualified name of the message, such as
+    /// `google.rpc.BadRequest`.
+    const NAME: &'static str;
+
+    /// Encodes the message to [`Any`].
+    fn to_any(&self) -> Any {
+        Any {
+            type_url: format!("type.googleapis.com/{}", Self::NAME),
+            value: self.encode_to_vec(),
+        }
+    }
+
+    /// Decodes the message from [`Any`], returns `None` if the type does not
+    /// match or the message is invalid.
+    fn from_any(any: &Any) -> Option<Self> {
+        let name = any
+            .type_url
+            .rsplit_once('/')
+            .map(|(_, name)| name)
+            .unwrap_or(&any.type_url);
+        if name != Self::NAME {
+            return None;
+        }
+        Self::decode(any.value.as_slice()).ok()
+    }
+}
+
+macro_rules! impl_error_detail {
+    ($($ty:ident),*) => {
+        $(
+        impl ErrorDetail for $ty {
+            const NAME: &'static str = concat!("google.rpc.", stringify!($ty));
+        }
+        )*
+    };
+}
+
+impl_error_detail!(
+    ErrorInfo,
+    RetryInfo,
+    DebugInfo,
+    QuotaFailure,
+    PreconditionFailure,
+    BadRequest,
+    RequestInfo,
+    ResourceInfo,
+    Help,
+    LocalizedMessage
+);
+
+impl ErrorInfo {
+    /// Create an `ErrorInfo` with the reason and the domain.
+    pub fn new(reason: impl Into<String>, domain: impl Into<String>) -> Self {
+        Self {
+            reason: reason.into(),
+            domain: domain.into(),
+            metadata: Default::default(),
+        }
+    }
+
+    /// Add a metadata entry.
+    #[must_use]
+    pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+}
+
+impl RetryInfo {
+    /// Create a `RetryInfo` with the delay before retrying the request.
+    pub fn new(retry_delay: Duration) -> Self {
+        Self {
+            retry_delay: Some(prost_types::Duration {
+                seconds: retry_delay.as_secs().min(i64::MAX as u64) as i64,
+                nanos: retry_delay.subsec_nanos() as i32,
+            }),
+        }
+    }
+
+    /// Returns the delay before retrying the request.
+    pub fn delay(&self) -> Option<Duration> {
+        self.retry_delay
+            .as_ref()
+            .and_then(|delay| Duration::try_from(*delay).ok())
+    }
+}
+
+impl DebugInfo {
+    /// Create a `DebugInfo` with the stack entries and the detail.
+    pub fn new<I, T>(stack_entries: I, detail: impl Into<String>) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        Self {
+            stack_entries: stack_entries.into_iter().map(Into::into).collect(),
+            detail: detail.into(),
+        }
+    }
+}
+
+impl QuotaFailure {
+    /// Create an empty `QuotaFailure`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Add a quota violation.
+    #[must_use]
+    pub fn add_violation(
+        mut self,
+        subject: impl Into<String>,
+        description: impl Into<String>,
+    ) -> Self {
+        self.violations.push(quota_failure::Violation {
+            subject: subject.into(),
+            description: description.into(),
+        });
+        self
+    }
+}
+
+impl PreconditionFailure {
+    /// Create an empty `PreconditionFailure`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Add a precondition violation.
+    #[must_use]
+    pub fn add_violation(
+        mut self,
+        ty: impl Into<String>,
+        subject: impl Into<String>,
+        description: impl Into<String>,
+    ) -> Self {
+        self.violations.push(precondition_failure::Violation {
+            r#type: ty.into(),
+            subject: subject.into(),
+            description: description.into(),
+        });
+        self
+    }
+}
+
+impl BadRequest {
+    /// Create an empty `BadRequest`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Add a field violation.
+    #[must_use]
+    pub fn add_field_violation(
+        mut self,
+        field: impl Into<String>,
+        description: impl Into<String>,
+    ) -> Self {
+        self.field_violations.push(bad_request::FieldViolation {
+            field: field.into(),
+            description: description.into(),
+        });
+        self
+    }
+}
+
+impl RequestInfo {
+    /// Create a `RequestInfo` with the request id and the serving data.
+    pub fn new(request_id: impl Into<String>, serving_data: impl Into<String>) -> Self {
+        Self {
+            request_id: request_id.into(),
+            serving_data: serving_data.into(),
+        }
+    }
+}
+
+impl ResourceInfo {
+    /// Create a `ResourceInfo`.
+    pub fn new(
+        resource_type: impl Into<String>,
+        resource_name: impl Into<String>,
+        owner: impl Into<String>,
+        description: impl Into<String>,
+    ) -> Self {
+        Self {
+            resource_type: resource_type.into(),
+            resource_name: resource_name.into(),
+            owner: owner.into(),
+            description: description.into(),
+        }
+    }
+}
+
+impl Help {
+    /// Create an empty `Help`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Add a link.
+    #[must_use]
+    pub fn add_link(mut self, description: impl Into<String>, url: impl Into<String>) -> Self {
+        self.links.push(help::Link {
+            description: description.into(),
+            url: url.into(),
+        });
+        self
+    }
+}
+
+impl LocalizedMessage {
+    /// Create a `LocalizedMessage` with the locale and the message.
+    pub fn new(locale: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            locale: locale.into(),
+            message: message.into(),
+        }
+    }
+}
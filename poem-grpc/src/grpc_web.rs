@@ -0,0 +1,280 @@
+use base64::engine::{Engine, general_purpose::STANDARD};
+use bytes::{BufMut, Bytes, BytesMut};
+use futures_util::{Stream, StreamExt};
+use http::{HeaderMap, HeaderValue, Method, Version, header};
+use http_body_util::{BodyExt, StreamBody};
+use hyper::body::Frame;
+use poem::{Body, Endpoint, IntoResponse, Middleware, Request, Response, Result, RouteNode};
+use sync_wrapper::SyncStream;
+
+use crate::client::BoxBody;
+
+const GRPC_WEB: &str = "application/grpc-web";
+const GRPC_WEB_TEXT: &str = "application/grpc-web-text";
+
+/// The flag of the frame which contains the trailers.
+const TRAILERS_FLAG: u8 = 0x80;
+
+/// The headers which should be readable by the browser clients.
+const EXPOSE_HEADERS: &str = "grpc-status, grpc-message, grpc-status-details-bin";
+
+/// Middleware for translating the [gRPC-Web](https://github.com/grpc/grpc/blob/master/doc/PROTOCOL-WEB.md)
+/// requests from browser clients, which supports `application/grpc-web` and
+/// `application/grpc-web-text` (base64 encoded) over HTTP/1.1 or HTTP/2.
+///
+/// The requests are converted to the native GRPC requests, and the trailers of
+/// the responses are encoded into the last frame of the body. Requests which
+/// are not gRPC-Web are passed to the inner endpoint unchanged.
+///
+/// The browser can only read the `grpc-status` and `grpc-message` headers of
+/// a cross-origin response if they are exposed, so this middleware adds the
+/// `Access-Control-Expose-Headers` header if the request has an `Origin`
+/// header. Use the [`Cors`](poem::middleware::Cors) middleware to answer the
+/// preflight requests.
+///
+/// # Example
+///
+/// ```
+/// use poem::{EndpointExt, http::Method, middleware::Cors};
+/// use poem_grpc::{GrpcWeb, RouteGrpc};
+///
+/// let route = RouteGrpc::new().with(GrpcWeb::new()).with(
+///     Cors::new()
+///         .allow_method(Method::POST)
+///         .allow_headers(["content-type", "x-grpc-web", "x-user-agent", "grpc-timeout"]),
+/// );
+/// ```
+#[derive(Debug, Default, Clone, Copy)]
+pub struct GrpcWeb;
+
+impl GrpcWeb {
+    /// Create a new `GrpcWeb` middleware.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for GrpcWeb {
+    type Output = GrpcWebEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        GrpcWebEndpoint { inner: ep }
+    }
+}
+
+/// Endpoint for the GrpcWeb middleware.
+pub struct GrpcWebEndpoint<E> {
+    inner: E,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Binary,
+    Text,
+}
+
+/// Returns the mode and the suffix of the gRPC-Web content type, such as
+/// `+proto` or `+json`.
+fn parse_content_type(content_type: &str) -> Option<(Mode, &str)> {
+    if let Some(suffix) = content_type.strip_prefix(GRPC_WEB_TEXT) {
+        Some((Mode::Text, suffix))
+    } else {
+        content_type
+            .strip_prefix(GRPC_WEB)
+            .map(|suffix| (Mode::Binary, suffix))
+    }
+    .filter(|(_, suffix)| suffix.is_empty() || suffix.starts_with('+'))
+}
+
+impl<E: Endpoint> Endpoint for GrpcWebEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, mut req: Request) -> Result<Self::Output> {
+        let Some((mode, suffix)) = req
+            .content_type()
+            .filter(|_| req.method() == Method::POST)
+            .and_then(parse_content_type)
+            .map(|(mode, suffix)| (mode, suffix.to_string()))
+        else {
+            return self.inner.call(req).await.map(IntoResponse::into_response);
+        };
+        let has_origin = req.headers().contains_key(header::ORIGIN);
+
+        // the GRPC services only accept HTTP/2 requests
+        req.set_version(Version::HTTP_2);
+        req.headers_mut().insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_str(&format!("application/grpc{suffix}"))
+                .expect("valid content type"),
+        );
+        req.headers_mut()
+            .insert(header::TE, HeaderValue::from_static("trailers"));
+        if mode == Mode::Text {
+            let body = req.take_body();
+            req.set_body(Body::from_bytes_stream(decode_text_body(
+                body.into_bytes_stream(),
+            )));
+        }
+
+        let mut resp = self.inner.call(req).await?.into_response();
+        let content_type = match mode {
+            Mode::Binary => format!("{GRPC_WEB}{suffix}"),
+            Mode::Text => format!("{GRPC_WEB_TEXT}{suffix}"),
+        };
+        resp.headers_mut().insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_str(&content_type).expect("valid content type"),
+        );
+        if has_origin
+            && !resp
+                .headers()
+                .contains_key(header::ACCESS_CONTROL_EXPOSE_HEADERS)
+        {
+            resp.headers_mut().insert(
+                header::ACCESS_CONTROL_EXPOSE_HEADERS,
+                HeaderValue::from_static(EXPOSE_HEADERS),
+            );
+        }
+        let body = resp.take_body();
+        resp.set_body(encode_response_body(body, mode));
+        Ok(resp)
+    }
+
+    fn describe_routes(&self) -> RouteNode {
+        self.inner.describe_routes()
+    }
+}
+
+fn decode_text_body(
+    stream: impl Stream<Item = std::io::Result<Bytes>> + Send + 'static,
+) -> impl Stream<Item = std::io::Result<Bytes>> + Send + 'static {
+    async_stream::try_stream! {
+        let mut stream = Box::pin(stream);
+        let mut buf = BytesMut::new();
+
+        while let Some(data) = stream.next().await.transpose()? {
+            buf.extend(data.iter().filter(|b| !b.is_ascii_whitespace()));
+            let input = buf.split_to(buf.len() / 4 * 4);
+            if !input.is_empty() {
+                yield decode_base64(&input)?;
+            }
+        }
+
+        if !buf.is_empty() {
+            Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "incomplete base64 data"))?;
+        }
+    }
+}
+
+/// Decodes the base64 data, which may be the concatenation of several padded
+/// chunks.
+pub(crate) fn decode_base64(input: &[u8]) -> std::io::Result<Bytes> {
+    let mut data = Vec::with_capacity(input.len() / 4 * 3);
+    let mut start = 0;
+    for (idx, quad) in input.chunks(4).enumerate() {
+        if quad.last() == Some(&b'=') {
+            let end = (idx + 1) * 4;
+            STANDARD
+                .decode_vec(&input[start..end], &mut data)
+                .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+            start = end;
+        }
+    }
+    STANDARD
+        .decode_vec(&input[start..], &mut data)
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+    Ok(data.into())
+}
+
+fn encode_trailers(trailers: &HeaderMap) -> Bytes {
+    let mut block = BytesMut::new();
+    for (name, value) in trailers {
+        block.put_slice(name.as_str().as_bytes());
+        block.put_slice(b":");
+        block.put_slice(value.as_bytes());
+        block.put_slice(b"\r\n");
+    }
+
+    let mut data = BytesMut::with_capacity(block.len() + 5);
+    data.put_u8(TRAILERS_FLAG);
+    data.put_u32(block.len() as u32);
+    data.put_slice(&block);
+    data.freeze()
+}
+
+fn encode_response_body(body: Body, mode: Mode) -> Body {
+    let mut body: BoxBody = body.into();
+    let encode = move |data: Bytes| match mode {
+        Mode::Binary => data,
+        Mode::Text => STANDARD.encode(data).into(),
+    };
+
+    let stream = async_stream::try_stream! {
+        while let Some(frame) = body.frame().await.transpose()? {
+            match frame.into_data() {
+                Ok(data) => yield Frame::data(encode(data)),
+                Err(frame) => {
+                    if let Ok(trailers) = frame.into_trailers() {
+                        yield Frame::data(encode(encode_trailers(&trailers)));
+                    }
+                }
+            }
+        }
+    };
+
+    BodyExt::boxed(StreamBody::new(SyncStream::new(stream))).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use poem::{EndpointExt, IntoEndpoint};
+
+    use super::*;
+    use crate::{RouteGrpc, health_service};
+
+    #[test]
+    fn content_type() {
+        assert_eq!(
+            parse_content_type("application/grpc-web"),
+            Some((Mode::Binary, ""))
+        );
+        assert_eq!(
+            parse_content_type("application/grpc-web+proto"),
+            Some((Mode::Binary, "+proto"))
+        );
+        assert_eq!(
+            parse_content_type("application/grpc-web-text+json"),
+            Some((Mode::Text, "+json"))
+        );
+        assert_eq!(parse_content_type("application/grpc"), None);
+        assert_eq!(parse_content_type("application/grpc-webx"), None);
+    }
+
+    #[test]
+    fn base64_chunks() {
+        let input = format!("{}{}", STANDARD.encode("a"), STANDARD.encode("bcdef"));
+        assert_eq!(decode_base64(input.as_bytes()).unwrap(), "abcdef");
+        assert!(decode_base64(b"YQ==YQ").is_err());
+    }
+
+    #[test]
+    fn trailers() {
+        let mut trailers = HeaderMap::new();
+        trailers.insert("grpc-status", HeaderValue::from_static("0"));
+        trailers.insert("grpc-message", HeaderValue::from_static("ok"));
+        assert_eq!(
+            encode_trailers(&trailers),
+            Bytes::from_static(b"\x80\x00\x00\x00\x20grpc-status:0\r\ngrpc-message:ok\r\n")
+        );
+    }
+
+    #[test]
+    fn describe_routes() {
+        let route = RouteGrpc::new()
+            .add_service(health_service().0)
+            .into_endpoint();
+        let expected = route.describe_routes();
+        assert!(matches!(&expected, RouteNode::Path { routes } if !routes.is_empty()));
+        assert_eq!(route.with(GrpcWeb::new()).describe_routes(), expected);
+    }
+}
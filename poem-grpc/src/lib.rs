@@ -18,30 +18,38 @@ pub mod server;
 pub mod service;
 
 pub mod codec;
+pub mod error_details;
 pub mod metadata;
 
+mod balance;
 mod compression;
 mod connector;
+mod deadline;
 mod encoding;
 #[cfg(feature = "example_generated")]
 pub mod example_generated;
+mod grpc_web;
 mod health;
 mod reflection;
 mod request;
 mod response;
+mod retry;
 mod route;
 mod status;
 mod streaming;
 #[cfg(test)]
 mod test_harness;
 
+pub use balance::{LoadBalancer, OutlierDetection, Resolver};
 pub use client::{ClientBuilderError, ClientConfig, ClientConfigBuilder};
 pub use compression::CompressionEncoding;
+pub use grpc_web::{GrpcWeb, GrpcWebEndpoint};
 pub use health::{HealthReporter, ServingStatus, health_service};
 pub use metadata::Metadata;
 pub use reflection::Reflection;
 pub use request::Request;
 pub use response::Response;
+pub use retry::{HedgingPolicy, RetryPolicy};
 pub use route::RouteGrpc;
 pub use service::Service;
 pub use status::{Code, Status};
@@ -1,6 +1,7 @@
 use std::{
     fmt::Debug,
     ops::{Deref, DerefMut},
+    time::{Duration, Instant},
 };
 
 use futures_util::Stream;
@@ -13,6 +14,7 @@ pub struct Request<T> {
     pub(crate) metadata: Metadata,
     pub(crate) message: T,
     pub(crate) extensions: Extensions,
+    pub(crate) deadline: Option<Instant>,
 }
 
 impl<T: Debug> Debug for Request<T> {
@@ -48,6 +50,7 @@ impl<T> Request<T> {
             metadata: Metadata::default(),
             message,
             extensions: Extensions::default(),
+            deadline: None,
         }
     }
 
@@ -81,6 +84,45 @@ impl<T> Request<T> {
         &mut self.extensions
     }
 
+    /// Returns the deadline of the call.
+    ///
+    /// For the server, it is the deadline sent by the client with the
+    /// `grpc-timeout` header, the call is cancelled with
+    /// [`Code::DeadlineExceeded`](crate::Code::DeadlineExceeded) when it
+    /// expires.
+    #[inline]
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Returns the remaining time until the deadline of the call.
+    #[inline]
+    pub fn remaining_time(&self) -> Option<Duration> {
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    /// Sets the deadline of the call.
+    ///
+    /// For the client, it is sent to the server with the `grpc-timeout`
+    /// header, and the call fails with
+    /// [`Code::DeadlineExceeded`](crate::Code::DeadlineExceeded) when it
+    /// expires. Use the deadline of an incoming request to propagate it to the
+    /// downstream calls.
+    #[inline]
+    pub fn set_deadline(&mut self, deadline: Instant) {
+        self.deadline = Some(deadline);
+    }
+
+    /// Sets the timeout of the call, which overrides the default timeout of
+    /// the client.
+    ///
+    /// See also: [`Request::set_deadline`]
+    #[inline]
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.deadline = Instant::now().checked_add(timeout);
+    }
+
     /// Get a reference from extensions, similar to `self.extensions().get()`.
     #[inline]
     pub fn data<D: Send + Sync + 'static>(&self) -> Option<&D> {
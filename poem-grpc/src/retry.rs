@@ -0,0 +1,420 @@
+use std::{
+    future::Future,
+    pin::pin,
+    time::{Duration, Instant},
+};
+
+use futures_util::{
+    StreamExt,
+    future::{Either, select},
+    stream::FuturesUnordered,
+};
+
+use crate::{Code, Status, error_details::RetryInfo};
+
+/// The retry policy of the calls.
+///
+/// Only the unary and server streaming calls of the methods which are marked
+/// as `IDEMPOTENT` or `NO_SIDE_EFFECTS` with the `idempotency_level` option
+/// are retried, and a server streaming call is not retried after the response
+/// headers are received.
+///
+/// The delay before the next attempt is the one in the
+/// [`RetryInfo`](crate::error_details::RetryInfo) detail of the status if
+/// present, otherwise it is a random duration between zero and the
+/// exponential backoff.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use poem_grpc::{ClientConfig, Code, RetryPolicy};
+///
+/// let config = ClientConfig::builder()
+///     .uri("http://localhost:3000")
+///     .retry_policy(
+///         RetryPolicy::new()
+///             .max_attempts(4)
+///             .initial_backoff(Duration::from_millis(50))
+///             .retryable_codes([Code::Unavailable, Code::ResourceExhausted]),
+///     )
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    backoff_multiplier: f64,
+    retryable_codes: Vec<Code>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(1),
+            backoff_multiplier: 2.0,
+            retryable_codes: vec![Code::Unavailable],
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Create a `RetryPolicy` with the default configuration.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets the max number of attempts, including the original call.
+    ///
+    /// Default is `3`.
+    #[must_use]
+    pub fn max_attempts(self, max_attempts: u32) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            ..self
+        }
+    }
+
+    /// Sets the backoff of the first retry.
+    ///
+    /// Default is `100ms`.
+    #[must_use]
+    pub fn initial_backoff(self, backoff: Duration) -> Self {
+        Self {
+            initial_backoff: backoff,
+            ..self
+        }
+    }
+
+    /// Sets the max backoff.
+    ///
+    /// Default is `1s`.
+    #[must_use]
+    pub fn max_backoff(self, backoff: Duration) -> Self {
+        Self {
+            max_backoff: backoff,
+            ..self
+        }
+    }
+
+    /// Sets the multiplier of the backoff after each retry.
+    ///
+    /// Default is `2.0`.
+    #[must_use]
+    pub fn backoff_multiplier(self, multiplier: f64) -> Self {
+        Self {
+            backoff_multiplier: multiplier.max(1.0),
+            ..self
+        }
+    }
+
+    /// Sets the status codes which can be retried.
+    ///
+    /// Default is `[Code::Unavailable]`.
+    #[must_use]
+    pub fn retryable_codes(self, codes: impl IntoIterator<Item = Code>) -> Self {
+        Self {
+            retryable_codes: codes.into_iter().collect(),
+            ..self
+        }
+    }
+
+    fn backoff(&self, retries: u32) -> Duration {
+        let backoff = self
+            .initial_backoff
+            .mul_f64(self.backoff_multiplier.powi(retries as i32 - 1).min(1e9))
+            .min(self.max_backoff);
+        backoff.mul_f64(fastrand::f64())
+    }
+}
+
+/// The hedging policy of the calls, which sends the call to the other
+/// endpoints if no response is received after the hedging delay, and uses the
+/// first successful response.
+///
+/// Like the [`RetryPolicy`], only the unary calls of the idempotent methods
+/// are hedged, and the retry policy is not used for them if the hedging
+/// policy is set.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use poem_grpc::{ClientConfig, HedgingPolicy};
+///
+/// let config = ClientConfig::builder()
+///     .uris(["http://server1:3000", "http://server2:3000"])
+///     .hedging_policy(HedgingPolicy::new().hedging_delay(Duration::from_millis(20)))
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct HedgingPolicy {
+    max_attempts: u32,
+    hedging_delay: Duration,
+    non_fatal_codes: Vec<Code>,
+}
+
+impl Default for HedgingPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 2,
+            hedging_delay: Duration::from_millis(100),
+            non_fatal_codes: vec![Code::Unavailable],
+        }
+    }
+}
+
+impl HedgingPolicy {
+    /// Create a `HedgingPolicy` with the default configuration.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets the max number of attempts, including the original call.
+    ///
+    /// Default is `2`.
+    #[must_use]
+    pub fn max_attempts(self, max_attempts: u32) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            ..self
+        }
+    }
+
+    /// Sets the delay before sending the next attempt.
+    ///
+    /// Default is `100ms`.
+    #[must_use]
+    pub fn hedging_delay(self, delay: Duration) -> Self {
+        Self {
+            hedging_delay: delay,
+            ..self
+        }
+    }
+
+    /// Sets the status codes which do not stop the other attempts, the next
+    /// attempt is sent immediately when an attempt fails with one of them.
+    ///
+    /// Default is `[Code::Unavailable]`.
+    #[must_use]
+    pub fn non_fatal_codes(self, codes: impl IntoIterator<Item = Code>) -> Self {
+        Self {
+            non_fatal_codes: codes.into_iter().collect(),
+            ..self
+        }
+    }
+}
+
+/// Calls `f` until it succeeds, fails with a status which can not be
+/// retried, or the attempts are exhausted.
+pub(crate) async fn call_with_retries<T, F, Fut>(
+    policy: &RetryPolicy,
+    deadline: Option<Instant>,
+    mut f: F,
+) -> Result<T, Status>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, Status>>,
+{
+    let mut attempts = 1;
+    loop {
+        let status = match f().await {
+            Ok(resp) => return Ok(resp),
+            Err(status) => status,
+        };
+        if attempts >= policy.max_attempts || !policy.retryable_codes.contains(&status.code()) {
+            return Err(status);
+        }
+
+        let backoff = status
+            .get_detail::<RetryInfo>()
+            .and_then(|retry_info| retry_info.delay())
+            .unwrap_or_else(|| policy.backoff(attempts));
+        if deadline.is_some_and(|deadline| Instant::now() + backoff >= deadline) {
+            return Err(status);
+        }
+        tokio::time::sleep(backoff).await;
+        attempts += 1;
+    }
+}
+
+/// Starts the attempts of `f` concurrently with the hedging delay, and
+/// returns the first successful response or fatal error.
+pub(crate) async fn call_with_hedging<T, F, Fut>(
+    policy: &HedgingPolicy,
+    mut f: F,
+) -> Result<T, Status>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, Status>>,
+{
+    let mut pending = FuturesUnordered::new();
+    pending.push(f());
+    let mut attempts = 1;
+
+    loop {
+        let res = if attempts < policy.max_attempts {
+            let delay = pin!(tokio::time::sleep(policy.hedging_delay));
+            match select(pending.next(), delay).await {
+                Either::Left((res, _)) => res,
+                Either::Right(_) => None,
+            }
+        } else {
+            pending.next().await
+        };
+
+        match res {
+            Some(Ok(resp)) => return Ok(resp),
+            Some(Err(status))
+                if !policy.non_fatal_codes.contains(&status.code())
+                    || (pending.is_empty() && attempts >= policy.max_attempts) =>
+            {
+                return Err(status);
+            }
+            // the hedging delay expired, or an attempt failed with a non-fatal
+            // status
+            _ if attempts < policy.max_attempts => {
+                pending.push(f());
+                attempts += 1;
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicU32, Ordering},
+    };
+
+    use super::*;
+
+    #[test]
+    fn backoff() {
+        let policy = RetryPolicy::new()
+            .initial_backoff(Duration::from_millis(100))
+            .max_backoff(Duration::from_millis(300));
+        for _ in 0..10 {
+            assert!(policy.backoff(1) <= Duration::from_millis(100));
+            assert!(policy.backoff(2) <= Duration::from_millis(200));
+            assert!(policy.backoff(10) <= Duration::from_millis(300));
+        }
+    }
+
+    #[tokio::test]
+    async fn retries() {
+        let policy = RetryPolicy::new()
+            .max_attempts(3)
+            .initial_backoff(Duration::from_millis(1));
+        let calls = AtomicU32::new(0);
+        let call = |fail_times: u32, code: Code| {
+            calls.store(0, Ordering::SeqCst);
+            let calls = &calls;
+            move || async move {
+                if calls.fetch_add(1, Ordering::SeqCst) < fail_times {
+                    Err(Status::new(code))
+                } else {
+                    Ok(())
+                }
+            }
+        };
+
+        call_with_retries(&policy, None, call(2, Code::Unavailable))
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        let err = call_with_retries(&policy, None, call(3, Code::Unavailable))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::Unavailable);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        let err = call_with_retries(&policy, None, call(1, Code::Internal))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::Internal);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // the delay of `RetryInfo` exceeds the deadline
+        calls.store(0, Ordering::SeqCst);
+        let err = call_with_retries(
+            &policy,
+            Some(Instant::now() + Duration::from_secs(1)),
+            || async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err::<(), _>(
+                    Status::new(Code::Unavailable)
+                        .with_detail(RetryInfo::new(Duration::from_secs(10))),
+                )
+            },
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.code(), Code::Unavailable);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn hedging() {
+        let policy = HedgingPolicy::new()
+            .max_attempts(3)
+            .hedging_delay(Duration::from_millis(20));
+
+        // the first attempt is slow, the second one wins
+        let attempts = Arc::new(AtomicU32::new(0));
+        let res = call_with_hedging(&policy, || {
+            let attempts = attempts.clone();
+            async move {
+                let attempt = attempts.fetch_add(1, Ordering::SeqCst);
+                if attempt == 0 {
+                    tokio::time::sleep(Duration::from_secs(10)).await;
+                }
+                Ok(attempt)
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(res, 1);
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+
+        // non-fatal errors start the next attempt immediately
+        let attempts = Arc::new(AtomicU32::new(0));
+        let start = Instant::now();
+        let err = call_with_hedging(&policy, || {
+            let attempts = attempts.clone();
+            async move {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err::<(), _>(Status::new(Code::Unavailable))
+            }
+        })
+        .await
+        .unwrap_err();
+        assert_eq!(err.code(), Code::Unavailable);
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+        assert!(start.elapsed() < Duration::from_secs(1));
+
+        // fatal errors stop the call
+        let attempts = Arc::new(AtomicU32::new(0));
+        let err = call_with_hedging(&policy, || {
+            let attempts = attempts.clone();
+            async move {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err::<(), _>(Status::new(Code::InvalidArgument))
+            }
+        })
+        .await
+        .unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+}
@@ -7,7 +7,11 @@ use crate::{
     Streaming,
     codec::Codec,
     compression::get_incoming_encodings,
-    encoding::{create_decode_request_body, create_encode_response_body},
+    deadline::{deadline_from_headers, streaming_with_deadline, with_deadline},
+    encoding::{
+        DEFAULT_MAX_DECODING_MESSAGE_SIZE, DEFAULT_MAX_ENCODING_MESSAGE_SIZE,
+        create_decode_request_body, create_encode_response_body,
+    },
     service::{
         BidirectionalStreamingService, ClientStreamingService, ServerStreamingService, UnaryService,
     },
@@ -18,6 +22,8 @@ pub struct GrpcServer<'a, T> {
     codec: T,
     send_compressed: Option<CompressionEncoding>,
     accept_compressed: &'a [CompressionEncoding],
+    max_decoding_message_size: usize,
+    max_encoding_message_size: usize,
 }
 
 impl<'a, T: Codec> GrpcServer<'a, T> {
@@ -31,6 +37,24 @@ impl<'a, T: Codec> GrpcServer<'a, T> {
             codec,
             send_compressed,
             accept_compressed,
+            max_decoding_message_size: DEFAULT_MAX_DECODING_MESSAGE_SIZE,
+            max_encoding_message_size: DEFAULT_MAX_ENCODING_MESSAGE_SIZE,
+        }
+    }
+
+    #[inline]
+    pub fn max_decoding_message_size(self, limit: usize) -> Self {
+        Self {
+            max_decoding_message_size: limit,
+            ..self
+        }
+    }
+
+    #[inline]
+    pub fn max_encoding_message_size(self, limit: usize) -> Self {
+        Self {
+            max_encoding_message_size: limit,
+            ..self
         }
     }
 
@@ -39,6 +63,7 @@ impl<'a, T: Codec> GrpcServer<'a, T> {
         S: UnaryService<T::Decode, Response = T::Encode>,
     {
         let (parts, body) = request.into_parts();
+        let deadline = deadline_from_headers(&parts.headers);
         let mut resp = Response::default().set_content_type(T::CONTENT_TYPES[0]);
         let incoming_encoding = match get_incoming_encodings(&parts.headers, self.accept_compressed)
         {
@@ -48,23 +73,32 @@ impl<'a, T: Codec> GrpcServer<'a, T> {
                 return resp;
             }
         };
-        let mut stream = create_decode_request_body(self.codec.decoder(), body, incoming_encoding);
-
-        let res = match stream.next().await {
-            Some(Ok(message)) => {
-                service
-                    .call(GrpcRequest {
-                        metadata: Metadata {
-                            headers: parts.headers,
-                        },
-                        message,
-                        extensions: parts.extensions,
-                    })
-                    .await
+        let mut stream = create_decode_request_body(
+            self.codec.decoder(),
+            body,
+            incoming_encoding,
+            self.max_decoding_message_size,
+        );
+
+        let res = with_deadline(deadline, async move {
+            match stream.next().await {
+                Some(Ok(message)) => {
+                    service
+                        .call(GrpcRequest {
+                            metadata: Metadata {
+                                headers: parts.headers,
+                            },
+                            message,
+                            extensions: parts.extensions,
+                            deadline,
+                        })
+                        .await
+                }
+                Some(Err(status)) => Err(status),
+                None => Err(Status::new(Code::Internal).with_message("missing request message")),
             }
-            Some(Err(status)) => Err(status),
-            None => Err(Status::new(Code::Internal).with_message("missing request message")),
-        };
+        })
+        .await;
 
         match res {
             Ok(grpc_resp) => {
@@ -73,6 +107,7 @@ impl<'a, T: Codec> GrpcServer<'a, T> {
                     self.codec.encoder(),
                     Streaming::new(futures_util::stream::once(async move { Ok(message) })),
                     self.send_compressed,
+                    self.max_encoding_message_size,
                 );
                 update_http_response(&mut resp, metadata, body, self.send_compressed);
             }
@@ -87,6 +122,7 @@ impl<'a, T: Codec> GrpcServer<'a, T> {
         S: ClientStreamingService<T::Decode, Response = T::Encode>,
     {
         let (parts, body) = request.into_parts();
+        let deadline = deadline_from_headers(&parts.headers);
         let mut resp = Response::default().set_content_type(T::CONTENT_TYPES[0]);
         let incoming_encoding = match get_incoming_encodings(&parts.headers, self.accept_compressed)
         {
@@ -96,17 +132,25 @@ impl<'a, T: Codec> GrpcServer<'a, T> {
                 return resp;
             }
         };
-        let stream = create_decode_request_body(self.codec.decoder(), body, incoming_encoding);
+        let stream = create_decode_request_body(
+            self.codec.decoder(),
+            body,
+            incoming_encoding,
+            self.max_decoding_message_size,
+        );
 
-        let res = service
-            .call(GrpcRequest {
+        let res = with_deadline(
+            deadline,
+            service.call(GrpcRequest {
                 metadata: Metadata {
                     headers: parts.headers,
                 },
                 extensions: parts.extensions,
                 message: stream,
-            })
-            .await;
+                deadline,
+            }),
+        )
+        .await;
 
         match res {
             Ok(grpc_resp) => {
@@ -115,6 +159,7 @@ impl<'a, T: Codec> GrpcServer<'a, T> {
                     self.codec.encoder(),
                     Streaming::new(futures_util::stream::once(async move { Ok(message) })),
                     self.send_compressed,
+                    self.max_encoding_message_size,
                 );
                 update_http_response(&mut resp, metadata, body, self.send_compressed);
             }
@@ -131,6 +176,7 @@ impl<'a, T: Codec> GrpcServer<'a, T> {
         S: ServerStreamingService<T::Decode, Response = T::Encode>,
     {
         let (parts, body) = request.into_parts();
+        let deadline = deadline_from_headers(&parts.headers);
         let mut resp = Response::default().set_content_type(T::CONTENT_TYPES[0]);
         let incoming_encoding = match get_incoming_encodings(&parts.headers, self.accept_compressed)
         {
@@ -140,31 +186,41 @@ impl<'a, T: Codec> GrpcServer<'a, T> {
                 return resp;
             }
         };
-        let mut stream = create_decode_request_body(self.codec.decoder(), body, incoming_encoding);
-
-        let res = match stream.next().await {
-            Some(Ok(message)) => {
-                service
-                    .call(GrpcRequest {
-                        metadata: Metadata {
-                            headers: parts.headers,
-                        },
-                        message,
-                        extensions: parts.extensions,
-                    })
-                    .await
+        let mut stream = create_decode_request_body(
+            self.codec.decoder(),
+            body,
+            incoming_encoding,
+            self.max_decoding_message_size,
+        );
+
+        let res = with_deadline(deadline, async move {
+            match stream.next().await {
+                Some(Ok(message)) => {
+                    service
+                        .call(GrpcRequest {
+                            metadata: Metadata {
+                                headers: parts.headers,
+                            },
+                            message,
+                            extensions: parts.extensions,
+                            deadline,
+                        })
+                        .await
+                }
+                Some(Err(status)) => Err(status),
+                None => Err(Status::new(Code::Internal).with_message("missing request message")),
             }
-            Some(Err(status)) => Err(status),
-            None => Err(Status::new(Code::Internal).with_message("missing request message")),
-        };
+        })
+        .await;
 
         match res {
             Ok(grpc_resp) => {
                 let GrpcResponse { metadata, message } = grpc_resp;
                 let body = create_encode_response_body(
                     self.codec.encoder(),
-                    message,
+                    streaming_with_deadline(deadline, message),
                     self.send_compressed,
+                    self.max_encoding_message_size,
                 );
                 update_http_response(&mut resp, metadata, body, self.send_compressed);
             }
@@ -181,6 +237,7 @@ impl<'a, T: Codec> GrpcServer<'a, T> {
         S: BidirectionalStreamingService<T::Decode, Response = T::Encode>,
     {
         let (parts, body) = request.into_parts();
+        let deadline = deadline_from_headers(&parts.headers);
         let mut resp = Response::default().set_content_type(T::CONTENT_TYPES[0]);
         let incoming_encoding = match get_incoming_encodings(&parts.headers, self.accept_compressed)
         {
@@ -190,25 +247,34 @@ impl<'a, T: Codec> GrpcServer<'a, T> {
                 return resp;
             }
         };
-        let stream = create_decode_request_body(self.codec.decoder(), body, incoming_encoding);
+        let stream = create_decode_request_body(
+            self.codec.decoder(),
+            body,
+            incoming_encoding,
+            self.max_decoding_message_size,
+        );
 
-        let res = service
-            .call(GrpcRequest {
+        let res = with_deadline(
+            deadline,
+            service.call(GrpcRequest {
                 metadata: Metadata {
                     headers: parts.headers,
                 },
                 message: stream,
                 extensions: parts.extensions,
-            })
-            .await;
+                deadline,
+            }),
+        )
+        .await;
 
         match res {
             Ok(grpc_resp) => {
                 let GrpcResponse { metadata, message } = grpc_resp;
                 let body = create_encode_response_body(
                     self.codec.encoder(),
-                    message,
+                    streaming_with_deadline(deadline, message),
                     self.send_compressed,
+                    self.max_encoding_message_size,
                 );
                 update_http_response(&mut resp, metadata, body, self.send_compressed);
             }
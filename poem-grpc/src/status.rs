@@ -1,12 +1,31 @@
 use std::fmt::Display;
 
+use base64::{
+    alphabet,
+    engine::{
+        DecodePaddingMode, Engine, GeneralPurpose, GeneralPurposeConfig,
+        general_purpose::STANDARD_NO_PAD,
+    },
+};
 use http::{HeaderMap, header::HeaderValue};
 use percent_encoding::{AsciiSet, CONTROLS, percent_decode_str, percent_encode};
+use prost::Message;
+use prost_types::Any;
 
-use crate::Metadata;
+use crate::{
+    Metadata,
+    error_details::{ErrorDetail, RpcStatus},
+};
 
 const GRPC_STATUS_HEADER_CODE: &str = "grpc-status";
 const GRPC_STATUS_MESSAGE_HEADER: &str = "grpc-message";
+const GRPC_STATUS_DETAILS_HEADER: &str = "grpc-status-details-bin";
+
+/// Some implementations send the binary headers with padding.
+const BASE64_DECODER: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
 
 const ENCODING_SET: &AsciiSet = &CONTROLS
     .add(b' ')
@@ -234,6 +253,7 @@ pub struct Status {
     code: Code,
     message: Option<String>,
     metadata: Metadata,
+    details: Vec<Any>,
 }
 
 impl Display for Status {
@@ -261,6 +281,7 @@ impl Status {
             code,
             message: None,
             metadata: Default::default(),
+            details: Vec::new(),
         }
     }
 
@@ -315,6 +336,33 @@ impl Status {
         &mut self.metadata
     }
 
+    /// Attach an error detail to this status, which is sent in the
+    /// `grpc-status-details-bin` trailer.
+    ///
+    /// See also: [`error_details`](crate::error_details)
+    #[inline]
+    pub fn with_detail(mut self, detail: impl ErrorDetail) -> Self {
+        self.details.push(detail.to_any());
+        self
+    }
+
+    /// Returns the error details.
+    #[inline]
+    pub fn details(&self) -> &[Any] {
+        &self.details
+    }
+
+    /// Returns a mutable reference to the error details.
+    #[inline]
+    pub fn details_mut(&mut self) -> &mut Vec<Any> {
+        &mut self.details
+    }
+
+    /// Returns the first error detail of the specified type.
+    pub fn get_detail<T: ErrorDetail>(&self) -> Option<T> {
+        self.details.iter().find_map(T::from_any)
+    }
+
     pub(crate) fn to_headers(&self) -> HeaderMap {
         let mut headers = HeaderMap::new();
 
@@ -329,6 +377,19 @@ impl Status {
             headers.insert(GRPC_STATUS_MESSAGE_HEADER, message);
         }
 
+        if !self.details.is_empty() {
+            let status = RpcStatus {
+                code: self.code.as_u16() as i32,
+                message: self.message.clone().unwrap_or_default(),
+                details: self.details.clone(),
+            };
+            headers.insert(
+                GRPC_STATUS_DETAILS_HEADER,
+                HeaderValue::from_maybe_shared(STANDARD_NO_PAD.encode(status.encode_to_vec()))
+                    .expect("valid value"),
+            );
+        }
+
         headers
     }
 
@@ -349,9 +410,81 @@ impl Status {
             {
                 status = status.with_message(message);
             }
+            if let Some(details) = headers
+                .get(GRPC_STATUS_DETAILS_HEADER)
+                .and_then(|value| BASE64_DECODER.decode(value.as_bytes()).ok())
+                .and_then(|data| RpcStatus::decode(data.as_slice()).ok())
+            {
+                status.details = details.details;
+            }
             Ok(Some(status))
         } else {
             Ok(None)
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::error_details::{BadRequest, ErrorInfo, QuotaFailure, RetryInfo};
+
+    #[test]
+    fn details() {
+        let status = Status::new(Code::InvalidArgument)
+            .with_message("invalid request")
+            .with_detail(BadRequest::new().add_field_violation("name", "must not be empty"))
+            .with_detail(ErrorInfo::new("EMPTY_NAME", "example.com").with_metadata("a", "1"))
+            .with_detail(RetryInfo::new(Duration::from_millis(1500)));
+        let headers = status.to_headers();
+        assert!(headers.contains_key(GRPC_STATUS_DETAILS_HEADER));
+
+        let status = Status::from_headers(&headers).unwrap().unwrap();
+        assert_eq!(status.code(), Code::InvalidArgument);
+        assert_eq!(status.message(), Some("invalid request"));
+        assert_eq!(status.details().len(), 3);
+        assert_eq!(
+            status.details()[0].type_url,
+            "type.googleapis.com/google.rpc.BadRequest"
+        );
+        assert_eq!(
+            status.get_detail::<BadRequest>().unwrap(),
+            BadRequest::new().add_field_violation("name", "must not be empty")
+        );
+        assert_eq!(
+            status.get_detail::<ErrorInfo>().unwrap().metadata.get("a"),
+            Some(&"1".to_string())
+        );
+        assert_eq!(
+            status.get_detail::<RetryInfo>().unwrap().delay(),
+            Some(Duration::from_millis(1500))
+        );
+        assert!(status.get_detail::<QuotaFailure>().is_none());
+
+        // padded base64
+        let mut headers = HeaderMap::new();
+        headers.insert(GRPC_STATUS_HEADER_CODE, HeaderValue::from_static("8"));
+        let data = RpcStatus {
+            code: 8,
+            message: String::new(),
+            details: vec![
+                QuotaFailure::new()
+                    .add_violation("user:1", "too many")
+                    .to_any(),
+            ],
+        }
+        .encode_to_vec();
+        headers.insert(
+            GRPC_STATUS_DETAILS_HEADER,
+            HeaderValue::from_maybe_shared(base64::engine::general_purpose::STANDARD.encode(data))
+                .unwrap(),
+        );
+        let status = Status::from_headers(&headers).unwrap().unwrap();
+        assert_eq!(
+            status.get_detail::<QuotaFailure>().unwrap().violations[0].subject,
+            "user:1"
+        );
+    }
+}
@@ -3,12 +3,36 @@ mod proto {
     include!(concat!(env!("OUT_DIR"), "/test_harness.rs"));
 }
 
+use std::{
+    sync::atomic::{AtomicI32, Ordering},
+    time::Duration,
+};
+
 use futures_util::TryStreamExt;
 use proto::{TestHarness, UnaryRequest, ValueRequest, ValueResponse};
 
-use crate::{Request, Response, Status, Streaming};
+use crate::{
+    Code, Request, Response, Status, Streaming,
+    error_details::{BadRequest, RetryInfo},
+};
+
+#[derive(Default)]
+pub(crate) struct TestHarnessService {
+    attempts: AtomicI32,
+}
 
-pub(crate) struct TestHarnessService;
+impl TestHarnessService {
+    /// Fails with `Unavailable` for the first `value` attempts, and returns
+    /// the number of attempts.
+    #[allow(clippy::result_large_err)]
+    fn flaky(&self, req: Request<ValueRequest>) -> Result<Response<ValueResponse>, Status> {
+        let attempts = self.attempts.fetch_add(1, Ordering::SeqCst) + 1;
+        if attempts <= req.value {
+            return Err(Status::new(Code::Unavailable).with_message("try again"));
+        }
+        Ok(Response::new(ValueResponse { value: attempts }))
+    }
+}
 
 impl TestHarness for TestHarnessService {
     async fn unary(&self, req: Request<UnaryRequest>) -> Result<Response<ValueResponse>, Status> {
@@ -71,6 +95,43 @@ impl TestHarness for TestHarnessService {
         }
         Ok(resp)
     }
+
+    async fn unary_deadline(
+        &self,
+        req: Request<ValueRequest>,
+    ) -> Result<Response<ValueResponse>, Status> {
+        tokio::time::sleep(Duration::from_millis(req.value as u64)).await;
+        Ok(Response::new(ValueResponse {
+            value: req
+                .remaining_time()
+                .map(|remaining| remaining.as_millis() as i32)
+                .unwrap_or(-1),
+        }))
+    }
+
+    async fn unary_error_details(
+        &self,
+        req: Request<ValueRequest>,
+    ) -> Result<Response<ValueResponse>, Status> {
+        Err(Status::new(Code::InvalidArgument)
+            .with_message("invalid value")
+            .with_detail(BadRequest::new().add_field_violation("value", "must be positive"))
+            .with_detail(RetryInfo::new(Duration::from_millis(req.value as u64))))
+    }
+
+    async fn unary_flaky(
+        &self,
+        req: Request<ValueRequest>,
+    ) -> Result<Response<ValueResponse>, Status> {
+        self.flaky(req)
+    }
+
+    async fn unary_flaky_non_idempotent(
+        &self,
+        req: Request<ValueRequest>,
+    ) -> Result<Response<ValueResponse>, Status> {
+        self.flaky(req)
+    }
 }
 
 #[cfg(test)]
@@ -79,10 +140,15 @@ mod tests {
     use proto::{TestHarnessClient, TestHarnessServer};
 
     use super::*;
-    use crate::RouteGrpc;
+    use base64::engine::{Engine, general_purpose::STANDARD};
+    use bytes::{Buf, BufMut, Bytes, BytesMut};
+    use poem::{Endpoint, EndpointExt, IntoEndpoint, http::Version};
+    use prost::Message;
+
+    use crate::{GrpcWeb, HedgingPolicy, RetryPolicy, RouteGrpc, grpc_web::decode_base64};
 
     fn create_cli() -> TestHarnessClient {
-        let server = TestHarnessServer::new(TestHarnessService);
+        let server = TestHarnessServer::new(TestHarnessService::default());
         let route = RouteGrpc::new().add_service(server);
         TestHarnessClient::from_endpoint(route)
     }
@@ -156,4 +222,288 @@ mod tests {
         assert_eq!(resp.metadata().get("mydata"), Some("abc"));
         assert_eq!(resp.into_inner(), ValueResponse { value: 30 });
     }
+
+    #[tokio::test]
+    async fn deadline() {
+        let mut cli = create_cli();
+
+        let resp = cli
+            .unary_deadline(Request::new(ValueRequest { value: 0 }))
+            .await
+            .unwrap();
+        assert_eq!(resp.into_inner().value, -1);
+
+        let mut req = Request::new(ValueRequest { value: 0 });
+        req.set_timeout(Duration::from_secs(10));
+        let value = cli.unary_deadline(req).await.unwrap().into_inner().value;
+        assert!(value > 9000 && value <= 10000);
+
+        // cancelled by the server
+        let mut req = Request::new(ValueRequest { value: 10000 });
+        req.metadata_mut().insert("grpc-timeout", "50m");
+        let err = cli.unary_deadline(req).await.unwrap_err();
+        assert_eq!(err.code(), Code::DeadlineExceeded);
+
+        let mut req = Request::new(ValueRequest { value: 10000 });
+        req.set_timeout(Duration::from_millis(50));
+        let err = cli.unary_deadline(req).await.unwrap_err();
+        assert_eq!(err.code(), Code::DeadlineExceeded);
+
+        cli.set_timeout(Duration::from_millis(50));
+        let err = cli
+            .unary_deadline(Request::new(ValueRequest { value: 10000 }))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::DeadlineExceeded);
+
+        let mut req = Request::new(ValueRequest { value: 0 });
+        req.set_timeout(Duration::from_secs(10));
+        let value = cli.unary_deadline(req).await.unwrap().into_inner().value;
+        assert!(value > 9000);
+    }
+
+    #[tokio::test]
+    async fn server_streaming_deadline() {
+        let cli = create_cli();
+        let mut req = Request::new(ValueRequest { value: 5 });
+        req.set_timeout(Duration::from_secs(10));
+        let resp = cli.server_streaming(req).await.unwrap();
+        assert_eq!(
+            resp.into_inner()
+                .map_ok(|resp| resp.value)
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap(),
+            vec![5, 4, 3, 2, 1, 0]
+        );
+    }
+
+    #[tokio::test]
+    async fn error_details() {
+        let cli = create_cli();
+        let err = cli
+            .unary_error_details(Request::new(ValueRequest { value: 100 }))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+        assert_eq!(err.message(), Some("invalid value"));
+        assert_eq!(
+            err.get_detail::<BadRequest>().unwrap().field_violations[0].field,
+            "value"
+        );
+        assert_eq!(
+            err.get_detail::<RetryInfo>().unwrap().delay(),
+            Some(Duration::from_millis(100))
+        );
+    }
+
+    #[tokio::test]
+    async fn max_message_size() {
+        let create_cli = |server: TestHarnessServer<TestHarnessService>| {
+            TestHarnessClient::from_endpoint(RouteGrpc::new().add_service(server))
+        };
+        let req = || Request::new(UnaryRequest { a: 10, b: 20 });
+
+        let cli = create_cli(
+            TestHarnessServer::new(TestHarnessService::default()).max_decoding_message_size(2),
+        );
+        let err = cli.unary(req()).await.unwrap_err();
+        assert_eq!(err.code(), Code::ResourceExhausted);
+
+        let cli = create_cli(
+            TestHarnessServer::new(TestHarnessService::default()).max_encoding_message_size(1),
+        );
+        let err = cli.unary(req()).await.unwrap_err();
+        assert_eq!(err.code(), Code::ResourceExhausted);
+
+        let mut cli = create_cli(TestHarnessServer::new(TestHarnessService::default()));
+        cli.set_max_encoding_message_size(2);
+        let err = cli.unary(req()).await.unwrap_err();
+        assert_eq!(err.code(), Code::ResourceExhausted);
+
+        let mut cli = create_cli(TestHarnessServer::new(TestHarnessService::default()));
+        cli.set_max_decoding_message_size(1);
+        let err = cli.unary(req()).await.unwrap_err();
+        assert_eq!(err.code(), Code::ResourceExhausted);
+
+        let resp = cli
+            .server_streaming(Request::new(ValueRequest { value: 5 }))
+            .await
+            .unwrap();
+        let err = resp.into_inner().try_next().await.unwrap_err();
+        assert_eq!(err.code(), Code::ResourceExhausted);
+
+        cli.set_max_decoding_message_size(2);
+        let resp = cli.unary(req()).await.unwrap();
+        assert_eq!(resp.into_inner(), ValueResponse { value: 30 });
+    }
+
+    #[tokio::test]
+    async fn retry() {
+        let mut cli = create_cli();
+        cli.set_retry_policy(RetryPolicy::new().initial_backoff(Duration::from_millis(1)));
+        let resp = cli
+            .unary_flaky(Request::new(ValueRequest { value: 2 }))
+            .await
+            .unwrap();
+        assert_eq!(resp.into_inner(), ValueResponse { value: 3 });
+
+        // the attempts are exhausted
+        let mut cli = create_cli();
+        cli.set_retry_policy(
+            RetryPolicy::new()
+                .max_attempts(2)
+                .initial_backoff(Duration::from_millis(1)),
+        );
+        let err = cli
+            .unary_flaky(Request::new(ValueRequest { value: 2 }))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::Unavailable);
+
+        // the methods which are not idempotent are not retried
+        let mut cli = create_cli();
+        cli.set_retry_policy(RetryPolicy::new().initial_backoff(Duration::from_millis(1)));
+        let err = cli
+            .unary_flaky_non_idempotent(Request::new(ValueRequest { value: 1 }))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::Unavailable);
+    }
+
+    #[tokio::test]
+    async fn hedging() {
+        let mut cli = create_cli();
+        cli.set_hedging_policy(
+            HedgingPolicy::new()
+                .max_attempts(3)
+                .hedging_delay(Duration::from_secs(10)),
+        );
+        let resp = cli
+            .unary_flaky(Request::new(ValueRequest { value: 2 }))
+            .await
+            .unwrap();
+        assert_eq!(resp.into_inner(), ValueResponse { value: 3 });
+    }
+
+    fn grpc_web_frame(message: impl Message) -> Bytes {
+        let data = message.encode_to_vec();
+        let mut buf = BytesMut::new();
+        buf.put_u8(0);
+        buf.put_u32(data.len() as u32);
+        buf.put_slice(&data);
+        buf.freeze()
+    }
+
+    /// Returns the messages and the trailers of the gRPC-Web response body.
+    fn parse_grpc_web_body(mut data: Bytes) -> (Vec<ValueResponse>, String) {
+        let mut messages = Vec::new();
+        let mut trailers = String::new();
+        while data.has_remaining() {
+            let flag = data.get_u8();
+            let len = data.get_u32() as usize;
+            let payload = data.split_to(len);
+            if flag == 0x80 {
+                trailers = String::from_utf8(payload.to_vec()).unwrap();
+            } else {
+                messages.push(ValueResponse::decode(payload).unwrap());
+            }
+        }
+        (messages, trailers)
+    }
+
+    #[tokio::test]
+    async fn grpc_web() {
+        let ep = RouteGrpc::new()
+            .add_service(TestHarnessServer::new(TestHarnessService::default()))
+            .with(GrpcWeb::new())
+            .into_endpoint();
+
+        let resp = ep
+            .call(
+                poem::Request::builder()
+                    .version(Version::HTTP_11)
+                    .method(poem::http::Method::POST)
+                    .uri_str("/test_harness.TestHarness/Unary")
+                    .content_type("application/grpc-web+proto")
+                    .header("origin", "http://example.com")
+                    .body(grpc_web_frame(UnaryRequest { a: 10, b: 20 })),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.content_type(), Some("application/grpc-web+proto"));
+        assert!(
+            resp.headers()
+                .get("access-control-expose-headers")
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .contains("grpc-status")
+        );
+        let (messages, trailers) =
+            parse_grpc_web_body(resp.into_body().into_bytes().await.unwrap());
+        assert_eq!(messages, vec![ValueResponse { value: 30 }]);
+        assert_eq!(trailers, "grpc-status:0\r\n");
+
+        let resp = ep
+            .call(
+                poem::Request::builder()
+                    .version(Version::HTTP_11)
+                    .method(poem::http::Method::POST)
+                    .uri_str("/test_harness.TestHarness/ServerStreaming")
+                    .content_type("application/grpc-web-text")
+                    .body(STANDARD.encode(grpc_web_frame(ValueRequest { value: 2 }))),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.content_type(), Some("application/grpc-web-text"));
+        assert!(!resp.headers().contains_key("access-control-expose-headers"));
+        let data = resp.into_body().into_bytes().await.unwrap();
+        let (messages, trailers) = parse_grpc_web_body(decode_base64(&data).unwrap());
+        assert_eq!(
+            messages,
+            vec![
+                ValueResponse { value: 2 },
+                ValueResponse { value: 1 },
+                ValueResponse { value: 0 }
+            ]
+        );
+        assert_eq!(trailers, "grpc-status:0\r\n");
+
+        let resp = ep
+            .call(
+                poem::Request::builder()
+                    .version(Version::HTTP_11)
+                    .method(poem::http::Method::POST)
+                    .uri_str("/test_harness.TestHarness/UnaryDeadline")
+                    .content_type("application/grpc-web")
+                    .header("grpc-timeout", "10m")
+                    .body(grpc_web_frame(ValueRequest { value: 10000 })),
+            )
+            .await
+            .unwrap();
+        // trailers-only response
+        assert_eq!(
+            resp.headers().get("grpc-status"),
+            Some(&poem::http::HeaderValue::from_static("4"))
+        );
+        assert!(resp.into_body().into_bytes().await.unwrap().is_empty());
+
+        // native GRPC requests over HTTP/1.1 are still rejected
+        let err = ep
+            .call(
+                poem::Request::builder()
+                    .version(Version::HTTP_11)
+                    .method(poem::http::Method::POST)
+                    .uri_str("/test_harness.TestHarness/Unary")
+                    .content_type("application/grpc")
+                    .body(grpc_web_frame(UnaryRequest { a: 10, b: 20 })),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.status(),
+            poem::http::StatusCode::HTTP_VERSION_NOT_SUPPORTED
+        );
+    }
 }
@@ -115,6 +115,7 @@
 //! | prost-wkt-types    | Integrate with the [`prost-wkt-types` crate](https://crates.io/crates/prost-wkt-types) |
 //! | static-files       | Support for static file response                                                       |
 //! | websocket          | Support for websocket                                                                  |
+//! | test               | Support for validating test responses against the schemas of the types                 |
 //! | sonic-rs           | Uses [`sonic-rs`](https://github.com/cloudwego/sonic-rs) instead of `serde_json`. Pls, checkout `sonic-rs` requirements to properly enable `sonic-rs` capabilities |
 
 #![doc(html_favicon_url = "https://raw.githubusercontent.com/poem-web/poem/master/favicon.ico")]
@@ -135,7 +136,10 @@ pub mod param;
 pub mod payload;
 #[doc(hidden)]
 pub mod registry;
-mod response;
+pub mod response;
+#[cfg(feature = "test")]
+#[cfg_attr(docsrs, doc(cfg(feature = "test")))]
+pub mod test;
 pub mod types;
 #[doc(hidden)]
 pub mod validation;
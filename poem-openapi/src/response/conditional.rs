@@ -0,0 +1,166 @@
+use std::time::SystemTime;
+
+use poem::{
+    Error, IntoResponse, Response,
+    web::headers::{ETag, HeaderMapExt, LastModified},
+};
+
+use super::{ETAG_DESCRIPTION, LAST_MODIFIED_DESCRIPTION};
+use crate::{
+    ApiResponse,
+    registry::{MetaHeader, MetaResponse, MetaResponses, Registry},
+    types::Type,
+};
+
+/// A response that carries the `ETag` and `Last-Modified` validators provided
+/// by the handler.
+///
+/// The validators are added to the successful responses, and the
+/// `304 Not Modified` and `412 Precondition Failed` responses are added to the
+/// document. The conditional requests are evaluated by the
+/// [`ETag`](poem::middleware::ETag) middleware, which uses these validators
+/// instead of hashing the response body.
+///
+/// # Example
+///
+/// ```
+/// use poem::{EndpointExt, middleware::ETag};
+/// use poem_openapi::{OpenApi, OpenApiService, payload::PlainText, response::Conditional};
+///
+/// struct Api;
+///
+/// #[OpenApi]
+/// impl Api {
+///     #[oai(path = "/document", method = "get")]
+///     async fn document(&self) -> Conditional<PlainText<String>> {
+///         Conditional::new(PlainText("hello".to_string())).etag("v1")
+///     }
+/// }
+///
+/// let app = OpenApiService::new(Api, "demo", "1.0").with(ETag::new());
+/// ```
+pub struct Conditional<T> {
+    inner: T,
+    etag: Option<ETag>,
+    last_modified: Option<SystemTime>,
+}
+
+impl<T> Conditional<T> {
+    /// Create a response without validators.
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            etag: None,
+            last_modified: None,
+        }
+    }
+
+    /// Sets the strong entity tag, without the quotes.
+    ///
+    /// The entity tag is ignored if it contains invalid characters.
+    #[must_use]
+    pub fn etag(self, tag: impl AsRef<str>) -> Self {
+        Self {
+            etag: format!("\"{}\"", tag.as_ref()).parse().ok(),
+            ..self
+        }
+    }
+
+    /// Sets the weak entity tag, without the `W/` prefix and the quotes.
+    ///
+    /// The entity tag is ignored if it contains invalid characters.
+    #[must_use]
+    pub fn weak_etag(self, tag: impl AsRef<str>) -> Self {
+        Self {
+            etag: format!("W/\"{}\"", tag.as_ref()).parse().ok(),
+            ..self
+        }
+    }
+
+    /// Sets the time when the resource was last modified.
+    #[must_use]
+    pub fn last_modified(self, last_modified: SystemTime) -> Self {
+        Self {
+            last_modified: Some(last_modified),
+            ..self
+        }
+    }
+
+    /// Consumes this object and returns the inner response.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: IntoResponse> IntoResponse for Conditional<T> {
+    fn into_response(self) -> Response {
+        let mut resp = self.inner.into_response();
+        if resp.status().is_success() {
+            if let Some(etag) = self.etag {
+                resp.headers_mut().typed_insert(etag);
+            }
+            if let Some(last_modified) = self.last_modified {
+                resp.headers_mut()
+                    .typed_insert(LastModified::from(last_modified));
+            }
+        }
+        resp
+    }
+}
+
+impl<T: ApiResponse> ApiResponse for Conditional<T> {
+    const BAD_REQUEST_HANDLER: bool = T::BAD_REQUEST_HANDLER;
+
+    fn meta() -> MetaResponses {
+        let mut meta = T::meta();
+
+        for resp in &mut meta.responses {
+            if !resp
+                .status
+                .is_some_and(|status| (200..300).contains(&status))
+            {
+                continue;
+            }
+            for (name, description) in [
+                ("etag", ETAG_DESCRIPTION),
+                ("last-modified", LAST_MODIFIED_DESCRIPTION),
+            ] {
+                if !resp.headers.iter().any(|header| header.name == name) {
+                    resp.headers.push(MetaHeader {
+                        name: name.to_string(),
+                        description: Some(description.to_string()),
+                        required: false,
+                        deprecated: false,
+                        schema: String::schema_ref(),
+                    });
+                }
+            }
+        }
+
+        for (status, description) in [(304, "Not modified"), (412, "Precondition failed")] {
+            if !meta
+                .responses
+                .iter()
+                .any(|resp| resp.status == Some(status))
+            {
+                meta.responses.push(MetaResponse {
+                    description,
+                    status: Some(status),
+                    status_range: None,
+                    content: vec![],
+                    headers: vec![],
+                });
+            }
+        }
+
+        meta
+    }
+
+    fn register(registry: &mut Registry) {
+        T::register(registry);
+    }
+
+    fn from_parse_request_error(err: Error) -> Self {
+        Self::new(T::from_parse_request_error(err))
+    }
+}
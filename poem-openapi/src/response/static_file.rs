@@ -1,5 +1,6 @@
 use poem::{Body, web::StaticFileResponse};
 
+use super::{ETAG_DESCRIPTION, LAST_MODIFIED_DESCRIPTION};
 use crate::{
     ApiResponse,
     payload::{Binary, Payload},
@@ -7,8 +8,6 @@ use crate::{
     types::Type,
 };
 
-const ETAG_DESCRIPTION: &str = r#"The ETag (or entity tag) HTTP response header is an identifier for a specific version of a resource. It lets caches be more efficient and save bandwidth, as a web server does not need to resend a full response if the content was not changed. Additionally, etags help to prevent simultaneous updates of a resource from overwriting each other ("mid-air collisions")."#;
-const LAST_MODIFIED_DESCRIPTION: &str = r#"The Last-Modified response HTTP header contains a date and time when the origin server believes the resource was last modified. It is used as a validator to determine if the resource is the same as the previously stored one. Less accurate than an ETag header, it is a fallback mechanism. Conditional requests containing If-Modified-Since or If-Unmodified-Since headers make use of this field."#;
 const CONTENT_TYPE_DESCRIPTION: &str = r#"The Content-Type representation header is used to indicate the original media type of the resource (prior to any content encoding applied for sending)."#;
 
 impl ApiResponse for StaticFileResponse {
@@ -0,0 +1,67 @@
+//! Test utilities for the OpenAPI types.
+
+use std::future::Future;
+
+use poem::test::TestResponse;
+use serde_json::Value;
+
+use crate::{registry::Registry, types::Type};
+
+/// Returns the JSON Schema of the type, with the registered schemas under
+/// `components/schemas` so that the `$ref`s of [`Type::schema_ref`] can be
+/// resolved.
+pub fn json_schema_of<T: Type>() -> Value {
+    let mut registry = Registry::new();
+    T::register(&mut registry);
+    serde_json::json!({
+        "allOf": [T::schema_ref()],
+        "components": {
+            "schemas": registry.schemas,
+        },
+    })
+}
+
+/// An extension trait for [`TestResponse`] to validate the response body
+/// against the schema of an OpenAPI type.
+///
+/// # Example
+///
+/// ```
+/// use poem::{handler, test::TestClient};
+/// use poem_openapi::{Object, payload::Json, test::TestResponseExt};
+///
+/// #[derive(Object)]
+/// struct Pet {
+///     id: i64,
+///     #[oai(validator(max_length = 32))]
+///     name: String,
+/// }
+///
+/// #[handler]
+/// fn index() -> Json<Pet> {
+///     Json(Pet {
+///         id: 1,
+///         name: "tom".to_string(),
+///     })
+/// }
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let cli = TestClient::new(index);
+/// cli.get("/").send().await.assert_json_schema_of::<Pet>().await;
+/// # });
+/// ```
+pub trait TestResponseExt {
+    /// Asserts that the response body is JSON and it is valid against the
+    /// schema of `T`.
+    ///
+    /// Note that an optional field is serialized as `null` unless it has
+    /// `#[oai(skip_serializing_if_is_none)]`, which is only valid if the field
+    /// is also marked as `nullable`.
+    fn assert_json_schema_of<T: Type>(self) -> impl Future<Output = ()> + Send;
+}
+
+impl TestResponseExt for TestResponse {
+    async fn assert_json_schema_of<T: Type>(self) {
+        self.assert_json_schema(json_schema_of::<T>()).await;
+    }
+}
@@ -23,6 +23,7 @@ mod ip;
 mod non_zero;
 mod optional;
 mod path_buf;
+mod problem_details;
 #[cfg(feature = "prost-wkt-types")]
 mod prost_wkt_types;
 mod regex;
@@ -0,0 +1,137 @@
+use std::borrow::Cow;
+
+use poem::web::ProblemDetails;
+use serde_json::Value;
+
+use crate::{
+    registry::{MetaSchema, MetaSchemaRef, Registry},
+    types::{ParseError, ParseFromJSON, ParseResult, ToJSON, Type},
+};
+
+impl Type for ProblemDetails {
+    const IS_REQUIRED: bool = true;
+
+    type RawValueType = Self;
+
+    type RawElementValueType = Self;
+
+    fn name() -> Cow<'static, str> {
+        "ProblemDetails".into()
+    }
+
+    fn schema_ref() -> MetaSchemaRef {
+        MetaSchemaRef::Reference(Self::name().into_owned())
+    }
+
+    fn register(registry: &mut Registry) {
+        registry.create_schema::<Self, _>(Self::name().into_owned(), |registry| {
+            String::register(registry);
+            u16::register(registry);
+            Value::register(registry);
+
+            let string_property = |description| {
+                MetaSchemaRef::Inline(Box::new(MetaSchema {
+                    description: Some(description),
+                    ..MetaSchema::new("string")
+                }))
+            };
+
+            MetaSchema {
+                description: Some("A problem details object as defined in RFC 9457."),
+                properties: vec![
+                    (
+                        "type",
+                        MetaSchemaRef::Inline(Box::new(MetaSchema {
+                            description: Some(
+                                "A URI reference that identifies the problem type.",
+                            ),
+                            default: Some(Value::String("about:blank".to_string())),
+                            ..MetaSchema::new_with_format("string", "uri-reference")
+                        })),
+                    ),
+                    (
+                        "title",
+                        string_property("A short, human-readable summary of the problem type."),
+                    ),
+                    (
+                        "status",
+                        MetaSchemaRef::Inline(Box::new(MetaSchema {
+                            description: Some("The HTTP status code."),
+                            ..MetaSchema::new_with_format("integer", "uint16")
+                        })),
+                    ),
+                    (
+                        "detail",
+                        string_property(
+                            "A human-readable explanation specific to this occurrence of the problem.",
+                        ),
+                    ),
+                    (
+                        "instance",
+                        MetaSchemaRef::Inline(Box::new(MetaSchema {
+                            description: Some(
+                                "A URI reference that identifies the specific occurrence of the problem.",
+                            ),
+                            ..MetaSchema::new_with_format("string", "uri-reference")
+                        })),
+                    ),
+                ],
+                additional_properties: Some(Box::new(Value::schema_ref())),
+                ..MetaSchema::new("object")
+            }
+        });
+    }
+
+    fn as_raw_value(&self) -> Option<&Self::RawValueType> {
+        Some(self)
+    }
+
+    fn raw_element_iter<'a>(
+        &'a self,
+    ) -> Box<dyn Iterator<Item = &'a Self::RawElementValueType> + 'a> {
+        Box::new(self.as_raw_value().into_iter())
+    }
+}
+
+impl ParseFromJSON for ProblemDetails {
+    fn parse_from_json(value: Option<Value>) -> ParseResult<Self> {
+        let value = value.unwrap_or_default();
+        match value {
+            Value::Object(_) => Ok(serde_json::from_value(value)?),
+            _ => Err(ParseError::expected_type(value)),
+        }
+    }
+}
+
+impl ToJSON for ProblemDetails {
+    fn to_json(&self) -> Option<Value> {
+        serde_json::to_value(self).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use poem::http::StatusCode;
+
+    use super::*;
+
+    #[test]
+    fn problem_details() {
+        let problem = ProblemDetails::new(StatusCode::NOT_FOUND)
+            .detail("user not found")
+            .extension("id", 1);
+        let value = serde_json::json!({
+            "type": "about:blank",
+            "title": "Not Found",
+            "status": 404,
+            "detail": "user not found",
+            "id": 1,
+        });
+
+        assert_eq!(problem.to_json(), Some(value.clone()));
+        assert_eq!(
+            ProblemDetails::parse_from_json(Some(value)).unwrap(),
+            problem
+        );
+    }
+}
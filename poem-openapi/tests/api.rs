@@ -1033,10 +1033,54 @@ async fn issue_489() {
 
     let ep = OpenApiService::new(Api, "test", "1.0");
     let cli = TestClient::new(ep);
-    cli.delete("/goodbye")
-        .send()
-        .await
-        .assert_status(StatusCode::METHOD_NOT_ALLOWED);
+    let resp = cli.delete("/goodbye").send().await;
+    resp.assert_status(StatusCode::METHOD_NOT_ALLOWED);
+    resp.assert_header("allow", "GET, HEAD, OPTIONS");
+
+    let resp = cli.options("/hello").send().await;
+    resp.assert_status(StatusCode::NO_CONTENT);
+    resp.assert_header("allow", "GET, HEAD, DELETE, OPTIONS");
+}
+
+#[test]
+fn describe_routes() {
+    struct Api;
+
+    #[OpenApi]
+    impl Api {
+        #[oai(path = "/hello", method = "get")]
+        async fn get_hello(&self) {}
+
+        #[oai(path = "/hello", method = "delete")]
+        async fn delete_hello(&self) {}
+
+        #[oai(path = "/users/:id", method = "get")]
+        async fn get_user(&self, _id: Path<i32>) {}
+    }
+
+    let app = poem::Route::new().nest("/api", OpenApiService::new(Api, "test", "1.0"));
+    let mut routes = app
+        .describe_routes()
+        .table()
+        .0
+        .into_iter()
+        .map(|route| {
+            let mut methods = route.methods;
+            methods.sort();
+            (route.path, methods)
+        })
+        .collect::<Vec<_>>();
+    routes.sort();
+    assert_eq!(
+        routes,
+        vec![
+            (
+                "/api/hello".to_string(),
+                vec!["DELETE".to_string(), "GET".to_string()]
+            ),
+            ("/api/users/:param0".to_string(), vec!["GET".to_string()]),
+        ]
+    );
 }
 
 #[tokio::test]
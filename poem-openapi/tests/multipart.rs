@@ -1,6 +1,6 @@
 use std::io::Write;
 
-use poem::{Request, RequestBody};
+use poem::{Request, RequestBody, http::StatusCode, web::MultipartConfig};
 use poem_openapi::{
     Enum, Multipart, Object,
     payload::{ParsePayload, Payload},
@@ -549,3 +549,42 @@ async fn deny_unknown_fields() {
     .unwrap_err();
     assert_eq!(err.to_string(), "parse multipart error: unknown field `c`");
 }
+
+#[tokio::test]
+async fn multipart_config() {
+    #[derive(Multipart, Debug)]
+    #[allow(dead_code)]
+    struct A {
+        name: String,
+        file: Upload,
+    }
+
+    let data =
+        create_multipart_payload(&[("name", None, b"abc"), ("file", Some("1.txt"), &[1, 2, 3])]);
+    let err = A::from_request(
+        &Request::builder()
+            .header("content-type", "multipart/form-data; boundary=X-BOUNDARY")
+            .extension(MultipartConfig::new().field_size("file", 2))
+            .finish(),
+        &mut RequestBody::new(data.into()),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "parse multipart error: failed to parse field `file`: failed to parse \"string_binary\": parse: field \"file\" exceeded the size limit: 2 bytes"
+    );
+
+    let data =
+        create_multipart_payload(&[("name", None, b"abc"), ("file", Some("1.txt"), &[1, 2, 3])]);
+    let err = A::from_request(
+        &Request::builder()
+            .header("content-type", "multipart/form-data; boundary=X-BOUNDARY")
+            .extension(MultipartConfig::new().allowed_content_types(["image/*"]))
+            .finish(),
+        &mut RequestBody::new(data.into()),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(err.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+}
@@ -1132,3 +1132,74 @@ fn deserialize_with() {
         Obj { a: 7 }
     );
 }
+
+#[cfg(feature = "test")]
+#[tokio::test]
+async fn assert_json_schema_of() {
+    use poem::{handler, test::TestClient, web::Json as PoemJson};
+    use poem_openapi::test::TestResponseExt;
+
+    #[derive(Object)]
+    struct Tag {
+        #[oai(validator(max_length = 3))]
+        name: String,
+    }
+
+    #[derive(Object)]
+    struct Pet {
+        id: i64,
+        #[oai(nullable)]
+        nickname: Option<String>,
+        #[oai(skip_serializing_if_is_none)]
+        owner: Option<String>,
+        tags: Vec<Tag>,
+    }
+
+    #[handler]
+    fn valid() -> PoemJson<serde_json::Value> {
+        PoemJson(
+            Pet {
+                id: 1,
+                nickname: None,
+                owner: None,
+                tags: vec![Tag {
+                    name: "cat".to_string(),
+                }],
+            }
+            .to_json()
+            .unwrap(),
+        )
+    }
+
+    #[handler]
+    fn invalid() -> PoemJson<serde_json::Value> {
+        PoemJson(json!({ "id": "1", "owner": null, "tags": [{ "name": "kitten" }] }))
+    }
+
+    let cli = TestClient::new(valid);
+    cli.get("/")
+        .send()
+        .await
+        .assert_json_schema_of::<Pet>()
+        .await;
+
+    let cli = TestClient::new(invalid);
+    let err = tokio::spawn(async move {
+        cli.get("/")
+            .send()
+            .await
+            .assert_json_schema_of::<Pet>()
+            .await;
+    })
+    .await
+    .unwrap_err()
+    .into_panic();
+    let msg = err.downcast_ref::<String>().unwrap();
+    for err in [
+        "/id: expect type `integer`, actual \"1\"",
+        "/owner: expect type `string`, actual null",
+        "/tags/0/name: the length 6 is greater than 3",
+    ] {
+        assert!(msg.contains(err), "{msg}");
+    }
+}
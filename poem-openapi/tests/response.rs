@@ -613,3 +613,59 @@ async fn status_range() {
     let resp = cli.get("/?error=server").send().await;
     resp.assert_status(StatusCode::INSUFFICIENT_STORAGE);
 }
+
+#[tokio::test]
+async fn conditional() {
+    use poem::{EndpointExt, http::header, middleware::ETag};
+    use poem_openapi::response::Conditional;
+
+    struct Api;
+
+    #[OpenApi]
+    impl Api {
+        #[oai(path = "/", method = "get")]
+        async fn get(&self) -> Conditional<PlainText<String>> {
+            Conditional::new(PlainText("hello".to_string())).etag("v1")
+        }
+
+        #[oai(path = "/", method = "put")]
+        async fn put(&self) -> PlainText<String> {
+            PlainText("updated".to_string())
+        }
+    }
+
+    let service = OpenApiService::new(Api, "test", "1.0");
+    let spec = serde_json::from_str::<serde_json::Value>(&service.spec()).unwrap();
+    let responses = &spec["paths"]["/"]["get"]["responses"];
+    assert!(responses["200"]["headers"]["etag"].is_object());
+    assert!(responses["200"]["headers"]["last-modified"].is_object());
+    assert_eq!(responses["304"], json!({ "description": "Not modified" }));
+    assert_eq!(
+        responses["412"],
+        json!({ "description": "Precondition failed" })
+    );
+
+    let cli = TestClient::new(service.with(ETag::new()));
+
+    let resp = cli.get("/").send().await;
+    resp.assert_status_is_ok();
+    resp.assert_header(header::ETAG, "\"v1\"");
+
+    cli.get("/")
+        .header(header::IF_NONE_MATCH, "\"v1\"")
+        .send()
+        .await
+        .assert_status(StatusCode::NOT_MODIFIED);
+
+    cli.put("/")
+        .header(header::IF_MATCH, "\"v0\"")
+        .send()
+        .await
+        .assert_status(StatusCode::PRECONDITION_FAILED);
+    cli.put("/")
+        .header(header::IF_MATCH, "\"v1\"")
+        .send()
+        .await
+        .assert_text("updated")
+        .await;
+}
@@ -1,6 +1,6 @@
 use std::future::Future;
 
-use crate::{Endpoint, IntoResponse, Request, Result};
+use crate::{Endpoint, IntoResponse, Request, Result, route::RouteNode};
 
 /// Endpoint for the [`and_then`](super::EndpointExt::and_then) method.
 pub struct AndThen<E, F> {
@@ -29,4 +29,8 @@ where
         let resp = self.inner.call(req).await?;
         (self.f)(resp).await
     }
+
+    fn describe_routes(&self) -> RouteNode {
+        self.inner.describe_routes()
+    }
 }
@@ -1,6 +1,6 @@
 use std::{future::Future, sync::Arc};
 
-use crate::{Endpoint, IntoResponse, Request, Result};
+use crate::{Endpoint, IntoResponse, Request, Result, route::RouteNode};
 
 /// Endpoint for the [`around`](super::EndpointExt::around) method.
 pub struct Around<E, F> {
@@ -30,4 +30,8 @@ where
     async fn call(&self, req: Request) -> Result<Self::Output> {
         (self.f)(self.inner.clone(), req).await
     }
+
+    fn describe_routes(&self) -> RouteNode {
+        self.inner.describe_routes()
+    }
 }
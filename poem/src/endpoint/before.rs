@@ -1,6 +1,6 @@
 use std::future::Future;
 
-use crate::{Endpoint, Request, Result};
+use crate::{Endpoint, Request, Result, route::RouteNode};
 
 /// Endpoint for the [`before`](super::EndpointExt::before) method.
 pub struct Before<E, F> {
@@ -26,4 +26,8 @@ where
     async fn call(&self, req: Request) -> Result<Self::Output> {
         self.inner.call((self.f)(req).await?).await
     }
+
+    fn describe_routes(&self) -> RouteNode {
+        self.inner.describe_routes()
+    }
 }
@@ -0,0 +1,245 @@
+use serde::Deserialize;
+
+use crate::{
+    Endpoint, Request, Response, Result,
+    http::{Method, StatusCode},
+};
+
+/// A `Content-Security-Policy` violation report.
+///
+/// Both the `application/csp-report` format of the `report-uri` directive and
+/// the `application/reports+json` format of the Reporting API are supported.
+#[cfg_attr(docsrs, doc(cfg(feature = "security-headers")))]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct CspReport {
+    /// The URL of the document in which the violation occurred.
+    #[serde(rename = "document-uri", alias = "documentURL")]
+    pub document_uri: String,
+
+    /// The referrer of the document.
+    pub referrer: Option<String>,
+
+    /// The URL of the resource that was blocked.
+    #[serde(rename = "blocked-uri", alias = "blockedURL")]
+    pub blocked_uri: Option<String>,
+
+    /// The directive whose enforcement caused the violation.
+    #[serde(rename = "effective-directive", alias = "effectiveDirective")]
+    pub effective_directive: Option<String>,
+
+    /// The directive that was violated, only reported by the `report-uri`
+    /// directive.
+    #[serde(rename = "violated-directive")]
+    pub violated_directive: Option<String>,
+
+    /// The policy that was violated.
+    #[serde(rename = "original-policy", alias = "originalPolicy")]
+    pub original_policy: Option<String>,
+
+    /// `enforce` or `report`.
+    pub disposition: Option<String>,
+
+    /// The HTTP status code of the document.
+    #[serde(rename = "status-code", alias = "statusCode")]
+    pub status_code: Option<u16>,
+
+    /// The URL of the script that caused the violation.
+    #[serde(rename = "source-file", alias = "sourceFile")]
+    pub source_file: Option<String>,
+
+    /// The line number in the source file.
+    #[serde(rename = "line-number", alias = "lineNumber")]
+    pub line_number: Option<u32>,
+
+    /// The column number in the source file.
+    #[serde(rename = "column-number", alias = "columnNumber")]
+    pub column_number: Option<u32>,
+
+    /// The first characters of the inline script or style that caused the
+    /// violation.
+    #[serde(rename = "script-sample", alias = "sample")]
+    pub sample: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Report {
+    #[serde(rename = "type")]
+    ty: String,
+    body: CspReport,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Reports {
+    Legacy {
+        #[serde(rename = "csp-report")]
+        csp_report: Box<CspReport>,
+    },
+    Reporting(Vec<Report>),
+}
+
+/// An endpoint that collects the `Content-Security-Policy` violation reports,
+/// which should be used as the URI of the `report-uri` directive or the
+/// reporting endpoint of the `report-to` directive.
+///
+/// It only accepts `POST` requests, and returns `204 No Content`.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     EndpointExt, Route,
+///     endpoint::{CspReport, CspReportEndpoint},
+///     middleware::{ContentSecurityPolicy, SecurityHeaders},
+/// };
+///
+/// let csp = ContentSecurityPolicy::new()
+///     .default_src(["'self'"])
+///     .report_uri("/csp-report");
+/// let app = Route::new()
+///     .at(
+///         "/csp-report",
+///         CspReportEndpoint::new(|report: CspReport| {
+///             tracing::warn!(
+///                 directive = ?report.effective_directive,
+///                 blocked_uri = ?report.blocked_uri,
+///                 "csp violation"
+///             );
+///         }),
+///     )
+///     .with(SecurityHeaders::new().content_security_policy_report_only(csp));
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "security-headers")))]
+pub struct CspReportEndpoint<F> {
+    f: F,
+}
+
+impl<F> CspReportEndpoint<F>
+where
+    F: Fn(CspReport) + Send + Sync,
+{
+    /// Create a `CspReportEndpoint` that calls `f` for each report.
+    pub fn new(f: F) -> Self {
+        Self { f }
+    }
+}
+
+impl<F> Endpoint for CspReportEndpoint<F>
+where
+    F: Fn(CspReport) + Send + Sync,
+{
+    type Output = Response;
+
+    async fn call(&self, mut req: Request) -> Result<Self::Output> {
+        if req.method() != Method::POST {
+            return Ok(StatusCode::METHOD_NOT_ALLOWED.into());
+        }
+
+        match req.take_body().into_json::<Reports>().await? {
+            Reports::Legacy { csp_report } => (self.f)(*csp_report),
+            Reports::Reporting(reports) => reports
+                .into_iter()
+                .filter(|report| report.ty == "csp-violation")
+                .for_each(|report| (self.f)(report.body)),
+        }
+        Ok(StatusCode::NO_CONTENT.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::test::TestClient;
+
+    #[tokio::test]
+    async fn collect_reports() {
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let cli = TestClient::new(CspReportEndpoint::new({
+            let reports = reports.clone();
+            move |report| reports.lock().unwrap().push(report)
+        }));
+
+        cli.post("/")
+            .content_type("application/csp-report")
+            .body_json(&json!({
+                "csp-report": {
+                    "document-uri": "https://example.com/",
+                    "blocked-uri": "inline",
+                    "violated-directive": "script-src",
+                    "effective-directive": "script-src",
+                    "original-policy": "script-src 'self'",
+                    "disposition": "report",
+                    "status-code": 200,
+                    "line-number": 10,
+                }
+            }))
+            .send()
+            .await
+            .assert_status(StatusCode::NO_CONTENT);
+
+        cli.post("/")
+            .content_type("application/reports+json")
+            .body_json(&json!([
+                {
+                    "type": "csp-violation",
+                    "url": "https://example.com/",
+                    "body": {
+                        "documentURL": "https://example.com/",
+                        "blockedURL": "https://evil.com/a.js",
+                        "effectiveDirective": "script-src-elem",
+                        "disposition": "enforce",
+                        "sample": "alert(1)",
+                    }
+                },
+                {
+                    "type": "deprecation",
+                    "url": "https://example.com/",
+                    "body": {}
+                }
+            ]))
+            .send()
+            .await
+            .assert_status(StatusCode::NO_CONTENT);
+
+        cli.post("/")
+            .body("invalid")
+            .send()
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+        cli.get("/")
+            .send()
+            .await
+            .assert_status(StatusCode::METHOD_NOT_ALLOWED);
+
+        let reports = reports.lock().unwrap();
+        assert_eq!(
+            *reports,
+            vec![
+                CspReport {
+                    document_uri: "https://example.com/".to_string(),
+                    blocked_uri: Some("inline".to_string()),
+                    effective_directive: Some("script-src".to_string()),
+                    violated_directive: Some("script-src".to_string()),
+                    original_policy: Some("script-src 'self'".to_string()),
+                    disposition: Some("report".to_string()),
+                    status_code: Some(200),
+                    line_number: Some(10),
+                    ..Default::default()
+                },
+                CspReport {
+                    document_uri: "https://example.com/".to_string(),
+                    blocked_uri: Some("https://evil.com/a.js".to_string()),
+                    effective_directive: Some("script-src-elem".to_string()),
+                    disposition: Some("enforce".to_string()),
+                    sample: Some("alert(1)".to_string()),
+                    ..Default::default()
+                },
+            ]
+        );
+    }
+}
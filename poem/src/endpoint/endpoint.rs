@@ -10,6 +10,7 @@ use crate::{
     Error, IntoResponse, Middleware, Request, Response, Result,
     error::IntoResult,
     middleware::{AddData, AddDataEndpoint},
+    route::RouteNode,
 };
 
 /// An HTTP request handler.
@@ -54,6 +55,18 @@ pub trait Endpoint: Send + Sync {
                 .unwrap_or_else(|err| err.into_response())
         }
     }
+
+    /// Returns the description of the routes of this endpoint.
+    ///
+    /// The routing objects describe their routes, and the endpoints that wrap
+    /// another endpoint, such as the endpoints created by [`EndpointExt`] and
+    /// the middlewares, return the routes of the inner endpoint. Other
+    /// endpoints return [`RouteNode::Endpoint`].
+    ///
+    /// See [`RouteTable`](crate::RouteTable) for an example.
+    fn describe_routes(&self) -> RouteNode {
+        RouteNode::Endpoint
+    }
 }
 
 struct SyncFnEndpoint<T, F> {
@@ -115,6 +128,13 @@ where
             EitherEndpoint::B(b) => b.call(req).await.map(IntoResponse::into_response),
         }
     }
+
+    fn describe_routes(&self) -> RouteNode {
+        match self {
+            EitherEndpoint::A(a) => a.describe_routes(),
+            EitherEndpoint::B(b) => b.describe_routes(),
+        }
+    }
 }
 
 /// Create an endpoint with a function.
@@ -184,6 +204,10 @@ impl<T: Endpoint + ?Sized> Endpoint for &T {
     async fn call(&self, req: Request) -> Result<Self::Output> {
         T::call(self, req).await
     }
+
+    fn describe_routes(&self) -> RouteNode {
+        T::describe_routes(self)
+    }
 }
 
 impl<T: Endpoint + ?Sized> Endpoint for Box<T> {
@@ -192,6 +216,10 @@ impl<T: Endpoint + ?Sized> Endpoint for Box<T> {
     async fn call(&self, req: Request) -> Result<Self::Output> {
         self.as_ref().call(req).await
     }
+
+    fn describe_routes(&self) -> RouteNode {
+        self.as_ref().describe_routes()
+    }
 }
 
 impl<T: Endpoint + ?Sized> Endpoint for Arc<T> {
@@ -200,6 +228,10 @@ impl<T: Endpoint + ?Sized> Endpoint for Arc<T> {
     async fn call(&self, req: Request) -> Result<Self::Output> {
         self.as_ref().call(req).await
     }
+
+    fn describe_routes(&self) -> RouteNode {
+        self.as_ref().describe_routes()
+    }
 }
 
 /// A `endpoint` that can be dynamically dispatched.
@@ -209,6 +241,11 @@ pub trait DynEndpoint: Send + Sync {
 
     /// Get the response to the request.
     fn call(&self, req: Request) -> BoxFuture<'_, Result<Self::Output>>;
+
+    /// Returns the description of the routes of this endpoint.
+    fn describe_routes(&self) -> RouteNode {
+        RouteNode::Endpoint
+    }
 }
 
 /// A [`Endpoint`] wrapper used to implement [`DynEndpoint`].
@@ -224,6 +261,11 @@ where
     fn call(&self, req: Request) -> BoxFuture<'_, Result<Self::Output>> {
         self.0.call(req).boxed()
     }
+
+    #[inline]
+    fn describe_routes(&self) -> RouteNode {
+        self.0.describe_routes()
+    }
 }
 
 impl<T> Endpoint for dyn DynEndpoint<Output = T> + '_
@@ -236,6 +278,11 @@ where
     async fn call(&self, req: Request) -> Result<Self::Output> {
         DynEndpoint::call(self, req).await
     }
+
+    #[inline]
+    fn describe_routes(&self) -> RouteNode {
+        DynEndpoint::describe_routes(self)
+    }
 }
 
 /// An owned dynamically typed `Endpoint` for use in cases where you can’t
@@ -1,4 +1,4 @@
-use crate::{Endpoint, Error, Request, Result};
+use crate::{Endpoint, Error, Request, Result, route::RouteNode};
 
 /// Endpoint for the [`inspect_all_err`](super::EndpointExt::inspect_all_err)
 /// method.
@@ -30,4 +30,8 @@ where
             }
         }
     }
+
+    fn describe_routes(&self) -> RouteNode {
+        self.inner.describe_routes()
+    }
 }
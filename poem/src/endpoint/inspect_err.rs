@@ -1,6 +1,6 @@
 use std::marker::PhantomData;
 
-use crate::{Endpoint, Request, Result};
+use crate::{Endpoint, Request, Result, route::RouteNode};
 
 /// Endpoint for the
 /// [`inspect_err`](super::EndpointExt::inspect_err) method.
@@ -39,4 +39,8 @@ where
             Err(err) => Err(err),
         }
     }
+
+    fn describe_routes(&self) -> RouteNode {
+        self.inner.describe_routes()
+    }
 }
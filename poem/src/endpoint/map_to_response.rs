@@ -1,4 +1,4 @@
-use crate::{Endpoint, IntoResponse, Request, Response, Result};
+use crate::{Endpoint, IntoResponse, Request, Response, Result, route::RouteNode};
 
 /// Endpoint for the [`map_to_response`](super::EndpointExt::map_to_response)
 /// method.
@@ -19,4 +19,8 @@ impl<E: Endpoint> Endpoint for MapToResponse<E> {
     async fn call(&self, req: Request) -> Result<Self::Output> {
         self.inner.call(req).await.map(IntoResponse::into_response)
     }
+
+    fn describe_routes(&self) -> RouteNode {
+        self.inner.describe_routes()
+    }
 }
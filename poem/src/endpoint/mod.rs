@@ -6,6 +6,8 @@ mod around;
 mod before;
 mod catch_all_error;
 mod catch_error;
+#[cfg(feature = "security-headers")]
+mod csp_report;
 #[cfg(feature = "embed")]
 mod embed;
 #[allow(clippy::module_inception)]
@@ -28,6 +30,8 @@ pub use around::Around;
 pub use before::Before;
 pub use catch_all_error::CatchAllError;
 pub use catch_error::CatchError;
+#[cfg(feature = "security-headers")]
+pub use csp_report::{CspReport, CspReportEndpoint};
 #[cfg(feature = "embed")]
 pub use embed::{EmbeddedFileEndpoint, EmbeddedFilesEndpoint};
 pub use endpoint::{
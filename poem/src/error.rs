@@ -8,7 +8,7 @@ use std::{
 };
 
 use headers::{ContentRange, HeaderMapExt};
-use http::{Extensions, Method};
+use http::{Extensions, HeaderValue, Method, header};
 
 use crate::{IntoResponse, Response, http::StatusCode};
 
@@ -451,6 +451,11 @@ impl Error {
             AsResponse::Fn(ref f, _) => f(&self),
             AsResponse::Response(resp) => *resp,
         };
+        if let Some(AllowedMethods(methods)) = self.extensions.get() {
+            if let Ok(allow) = HeaderValue::try_from(join_methods(methods)) {
+                resp.headers_mut().insert(header::ALLOW, allow);
+            }
+        }
         *resp.extensions_mut() = self.extensions;
         resp
     }
@@ -654,8 +659,56 @@ define_simple_errors!(
 
     /// Error occurred in the router.
     (MethodNotAllowedError, METHOD_NOT_ALLOWED, "method not allowed");
+
+    /// Error occurred in the `ETag` middleware when the `If-Match` or `If-Unmodified-Since` precondition fails.
+    (PreconditionFailedError, PRECONDITION_FAILED, "precondition failed");
 );
 
+/// The methods allowed by a route.
+///
+/// The [`MethodNotAllowedError`] returned by
+/// [`RouteMethod`](crate::RouteMethod) carries it as the data of the
+/// [`Error`], which is sent in the `Allow` header of the response.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     Endpoint, Request, get, handler,
+///     error::{AllowedMethods, MethodNotAllowedError},
+///     http::{Method, header},
+/// };
+///
+/// #[handler]
+/// fn index() {}
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let err = get(index)
+///     .call(Request::builder().method(Method::PUT).finish())
+///     .await
+///     .unwrap_err();
+/// assert!(err.is::<MethodNotAllowedError>());
+/// assert_eq!(
+///     err.data::<AllowedMethods>(),
+///     Some(&AllowedMethods(vec![Method::GET, Method::HEAD, Method::OPTIONS]))
+/// );
+/// assert_eq!(
+///     err.into_response().headers().get(header::ALLOW).unwrap(),
+///     "GET, HEAD, OPTIONS"
+/// );
+/// # });
+/// ```
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct AllowedMethods(pub Vec<Method>);
+
+pub(crate) fn join_methods(methods: &[Method]) -> String {
+    methods
+        .iter()
+        .map(Method::as_str)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 /// A possible error value when reading the body.
 #[derive(Debug, thiserror::Error)]
 pub enum ReadBodyError {
@@ -681,12 +734,32 @@ impl ResponseError for ReadBodyError {
         match self {
             ReadBodyError::BodyHasBeenTaken => StatusCode::INTERNAL_SERVER_ERROR,
             ReadBodyError::Utf8(_) => StatusCode::BAD_REQUEST,
-            ReadBodyError::Io(_) => StatusCode::BAD_REQUEST,
+            ReadBodyError::Io(err) => io_error_status(err),
             ReadBodyError::PayloadTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
         }
     }
 }
 
+/// Returns the status of the errors returned by the body readers of the
+/// middlewares, such as [`SizedLimitError`], which may be wrapped in other IO
+/// errors.
+fn io_error_status(err: &std::io::Error) -> StatusCode {
+    let mut inner = err.get_ref();
+    while let Some(err) = inner {
+        if let Some(err) = err.downcast_ref::<SizedLimitError>() {
+            return err.status();
+        }
+        #[cfg(feature = "compression")]
+        if let Some(err) = err.downcast_ref::<DecompressionError>() {
+            return err.status();
+        }
+        inner = err
+            .downcast_ref::<std::io::Error>()
+            .and_then(std::io::Error::get_ref);
+    }
+    StatusCode::BAD_REQUEST
+}
+
 /// A possible error value when parsing cookie.
 #[cfg(feature = "cookie")]
 #[cfg_attr(docsrs, doc(cfg(feature = "cookie")))]
@@ -878,6 +951,37 @@ pub enum ParseMultipartError {
     /// Io error
     #[error("io: {0}")]
     Io(#[from] std::io::Error),
+
+    /// A limit of the [`MultipartConfig`](crate::web::MultipartConfig) is
+    /// exceeded.
+    #[error(transparent)]
+    Limit(#[from] MultipartLimitError),
+}
+
+/// A limit of the [`MultipartConfig`](crate::web::MultipartConfig) is
+/// exceeded.
+#[cfg(feature = "multipart")]
+#[cfg_attr(docsrs, doc(cfg(feature = "multipart")))]
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum MultipartLimitError {
+    /// The number of fields exceeds the limit.
+    #[error("too many fields, limit: {0}")]
+    TooManyFields(usize),
+
+    /// The content type of a file field is not allowed.
+    #[error("content type `{0}` is not allowed")]
+    ContentTypeNotAllowed(String),
+}
+
+#[cfg(feature = "multipart")]
+impl ResponseError for MultipartLimitError {
+    fn status(&self) -> StatusCode {
+        match self {
+            MultipartLimitError::TooManyFields(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            MultipartLimitError::ContentTypeNotAllowed(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        }
+    }
 }
 
 #[cfg(feature = "multipart")]
@@ -886,9 +990,13 @@ impl ResponseError for ParseMultipartError {
         match self {
             ParseMultipartError::InvalidContentType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
             ParseMultipartError::ContentTypeRequired => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ParseMultipartError::Multipart(
+                multer::Error::FieldSizeExceeded { .. } | multer::Error::StreamSizeExceeded { .. },
+            ) => StatusCode::PAYLOAD_TOO_LARGE,
             ParseMultipartError::Multipart(_) => StatusCode::BAD_REQUEST,
             ParseMultipartError::Utf8(_) => StatusCode::BAD_REQUEST,
             ParseMultipartError::Io(_) => StatusCode::BAD_REQUEST,
+            ParseMultipartError::Limit(err) => err.status(),
         }
     }
 }
@@ -1036,6 +1144,132 @@ impl ResponseError for SizedLimitError {
     }
 }
 
+/// A possible error value occurred in the `Decompression` middleware.
+#[cfg(feature = "compression")]
+#[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+#[derive(Debug, thiserror::Error, Eq, PartialEq)]
+pub enum DecompressionError {
+    /// Unsupported `Content-Encoding`
+    #[error("unsupported content encoding: {0}")]
+    UnsupportedEncoding(String),
+
+    /// The decompressed payload is too large
+    #[error("decompressed payload too large")]
+    PayloadTooLarge,
+
+    /// The compression ratio is too large
+    #[error("compression ratio too large")]
+    RatioTooLarge,
+}
+
+#[cfg(feature = "compression")]
+impl ResponseError for DecompressionError {
+    fn status(&self) -> StatusCode {
+        match self {
+            DecompressionError::UnsupportedEncoding(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            DecompressionError::PayloadTooLarge | DecompressionError::RatioTooLarge => {
+                StatusCode::PAYLOAD_TOO_LARGE
+            }
+        }
+    }
+}
+
+/// A possible error value occurred in the `JwtAuth` middleware.
+#[cfg(feature = "jwt")]
+#[cfg_attr(docsrs, doc(cfg(feature = "jwt")))]
+#[derive(Debug, thiserror::Error, Clone, Eq, PartialEq)]
+pub enum JwtError {
+    /// Missing bearer token
+    #[error("missing bearer token")]
+    MissingToken,
+
+    /// The token is not a valid JSON Web Token
+    #[error("malformed token")]
+    Malformed,
+
+    /// Unsupported or disallowed signature algorithm
+    #[error("unsupported algorithm: {0}")]
+    UnsupportedAlgorithm(String),
+
+    /// Invalid decoding key
+    #[error("invalid key")]
+    InvalidKey,
+
+    /// No key matches the token
+    #[error("unknown key")]
+    UnknownKey,
+
+    /// Invalid signature
+    #[error("invalid signature")]
+    InvalidSignature,
+
+    /// The token has expired
+    #[error("token expired")]
+    Expired,
+
+    /// The token is not valid yet
+    #[error("token not yet valid")]
+    NotYetValid,
+
+    /// Invalid `aud` claim
+    #[error("invalid audience")]
+    InvalidAudience,
+
+    /// Invalid `iss` claim
+    #[error("invalid issuer")]
+    InvalidIssuer,
+
+    /// The claims cannot be deserialized
+    #[error("invalid claims: {0}")]
+    InvalidClaims(String),
+
+    /// Failed to fetch the JSON Web Key Set
+    #[error("failed to fetch jwks: {0}")]
+    FetchJwks(String),
+}
+
+#[cfg(feature = "jwt")]
+impl ResponseError for JwtError {
+    fn status(&self) -> StatusCode {
+        match self {
+            JwtError::InvalidKey => StatusCode::INTERNAL_SERVER_ERROR,
+            JwtError::FetchJwks(_) => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::UNAUTHORIZED,
+        }
+    }
+
+    fn as_response(&self) -> Response {
+        let mut resp = self.to_string().into_response();
+        resp.set_status(self.status());
+        // the description is fixed for each variant, because the details may
+        // come from the token
+        let description = match self {
+            JwtError::InvalidKey | JwtError::FetchJwks(_) => return resp,
+            JwtError::MissingToken => {
+                resp.headers_mut()
+                    .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+                return resp;
+            }
+            JwtError::Malformed => "malformed token",
+            JwtError::UnsupportedAlgorithm(_) => "unsupported algorithm",
+            JwtError::UnknownKey => "unknown key",
+            JwtError::InvalidSignature => "invalid signature",
+            JwtError::Expired => "token expired",
+            JwtError::NotYetValid => "token not yet valid",
+            JwtError::InvalidAudience => "invalid audience",
+            JwtError::InvalidIssuer => "invalid issuer",
+            JwtError::InvalidClaims(_) => "invalid claims",
+        };
+        let challenge =
+            format!("Bearer error=\"invalid_token\", error_description=\"{description}\"");
+        if let Ok(challenge) = HeaderValue::try_from(challenge) {
+            resp.headers_mut()
+                .insert(header::WWW_AUTHENTICATE, challenge);
+        }
+        resp
+    }
+}
+
 /// A possible error value occurred when adding a route.
 #[derive(Debug, thiserror::Error, Eq, PartialEq)]
 pub enum RouteError {
@@ -1056,6 +1290,20 @@ pub enum RouteError {
         /// Regex
         regex: String,
     },
+
+    /// Duplicate route name
+    #[error("duplicate route name: {0}")]
+    DuplicateName(String),
+
+    /// The path is shadowed by another path
+    #[error("path {path} is shadowed by {by}")]
+    Shadowed {
+        /// Path
+        path: String,
+
+        /// The path that shadows it
+        by: String,
+    },
 }
 
 impl ResponseError for RouteError {
@@ -1064,6 +1312,58 @@ impl ResponseError for RouteError {
     }
 }
 
+/// A possible error value occurred when generating a URL with
+/// [`UrlFor`](crate::route::UrlFor).
+#[derive(Debug, thiserror::Error, Eq, PartialEq)]
+pub enum UrlForError {
+    /// No route with this name
+    #[error("unknown route: {0}")]
+    UnknownRoute(String),
+
+    /// A parameter of the route was not provided
+    #[error("missing parameter `{param}` for route `{route}`")]
+    MissingParam {
+        /// Route name
+        route: String,
+
+        /// Parameter name
+        param: String,
+    },
+
+    /// The route has no parameter with this name
+    #[error("unknown parameter `{param}` for route `{route}`")]
+    UnknownParam {
+        /// Route name
+        route: String,
+
+        /// Parameter name
+        param: String,
+    },
+
+    /// A parameter does not match the route pattern
+    #[error("invalid value `{value}` for parameter `{param}` of route `{route}`")]
+    InvalidParam {
+        /// Route name
+        route: String,
+
+        /// Parameter name
+        param: String,
+
+        /// Parameter value
+        value: String,
+    },
+
+    /// The route contains a segment without name, which can't be generated
+    #[error("route `{0}` contains an unnamed segment")]
+    UnnamedSegment(String),
+}
+
+impl ResponseError for UrlForError {
+    fn status(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
+
 /// A possible error value occurred in the `Cors` middleware.
 #[derive(Debug, thiserror::Error, Eq, PartialEq)]
 pub enum CorsError {
@@ -0,0 +1,299 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt::{self, Display, Formatter},
+    path::PathBuf,
+};
+
+use fluent_syntax::ast::{
+    CallArguments, Entry, Expression, InlineExpression, Pattern, PatternElement,
+};
+use unic_langid::LanguageIdentifier;
+
+use crate::i18n::resources::Source;
+
+/// The kind of an [`I18NDiagnostic`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum I18NDiagnosticKind {
+    /// The FTL source has a syntax error.
+    SyntaxError,
+    /// A message of the default language is missing in another language.
+    MissingMessage,
+    /// An argument of the message in the default language is not used by the
+    /// translation.
+    UnusedArgument,
+    /// The translation uses an argument which is not used by the message in
+    /// the default language.
+    UnknownArgument,
+}
+
+/// A problem found by
+/// [`I18NResourcesBuilder::validate`](crate::i18n::I18NResourcesBuilder::validate).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct I18NDiagnostic {
+    /// The kind of the problem.
+    pub kind: I18NDiagnosticKind,
+    /// The language of the problem.
+    pub language: LanguageIdentifier,
+    /// The FTL file, or `None` if the resource was added by
+    /// [`add_ftl`](crate::i18n::I18NResourcesBuilder::add_ftl).
+    pub path: Option<PathBuf>,
+    /// The line number in the FTL source, starting from 1.
+    ///
+    /// For [`I18NDiagnosticKind::MissingMessage`], it is the location of the
+    /// message in the default language.
+    pub line: Option<usize>,
+    /// The description of the problem.
+    pub message: String,
+}
+
+impl Display for I18NDiagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "{}", path.display())?,
+            None => write!(f, "<{}>", self.language)?,
+        }
+        if let Some(line) = self.line {
+            write!(f, ":{line}")?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+struct MessageInfo<'a> {
+    path: Option<&'a PathBuf>,
+    line: Option<usize>,
+    arguments: Option<BTreeSet<&'a str>>,
+}
+
+pub(crate) fn validate(
+    sources: &[Source],
+    default_language: &LanguageIdentifier,
+) -> Vec<I18NDiagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut languages: HashMap<&LanguageIdentifier, BTreeMap<String, MessageInfo<'_>>> =
+        HashMap::new();
+
+    for source in sources {
+        let resource = match fluent_syntax::parser::parse(source.ftl.as_str()) {
+            Ok(resource) => resource,
+            Err((resource, errors)) => {
+                for err in errors {
+                    // Reports the start of the invalid entry, since the precise
+                    // position may be at the end of the source.
+                    let offset = err.slice.as_ref().unwrap_or(&err.pos).start;
+                    diagnostics.push(I18NDiagnostic {
+                        kind: I18NDiagnosticKind::SyntaxError,
+                        language: source.language.clone(),
+                        path: source.path.clone(),
+                        line: Some(line_of_offset(&source.ftl, offset)),
+                        message: err.kind.to_string(),
+                    });
+                }
+                resource
+            }
+        };
+
+        let messages = languages.entry(&source.language).or_default();
+        for entry in &resource.body {
+            let (id, arguments) = match entry {
+                Entry::Message(message) => {
+                    let mut arguments = BTreeSet::new();
+                    if let Some(value) = &message.value {
+                        collect_pattern(value, &mut arguments);
+                    }
+                    for attribute in &message.attributes {
+                        collect_pattern(&attribute.value, &mut arguments);
+                    }
+                    (message.id.name.to_string(), Some(arguments))
+                }
+                Entry::Term(term) => (format!("-{}", term.id.name), None),
+                _ => continue,
+            };
+            messages.insert(
+                id.clone(),
+                MessageInfo {
+                    path: source.path.as_ref(),
+                    line: line_of_message(&source.ftl, &id),
+                    arguments,
+                },
+            );
+        }
+    }
+
+    let Some(default_messages) = languages.get(default_language) else {
+        return diagnostics;
+    };
+    let mut other_languages = languages
+        .iter()
+        .filter(|(language, _)| **language != default_language)
+        .collect::<Vec<_>>();
+    other_languages.sort_by_key(|(language, _)| language.to_string());
+
+    for (language, messages) in other_languages {
+        for (id, default_message) in default_messages {
+            let Some(message) = messages.get(id) else {
+                diagnostics.push(I18NDiagnostic {
+                    kind: I18NDiagnosticKind::MissingMessage,
+                    language: (*language).clone(),
+                    path: default_message.path.cloned(),
+                    line: default_message.line,
+                    message: format!("message `{id}` is missing in `{language}`"),
+                });
+                continue;
+            };
+
+            let (Some(expected), Some(actual)) = (&default_message.arguments, &message.arguments)
+            else {
+                continue;
+            };
+            let mut push = |kind, message_text| {
+                diagnostics.push(I18NDiagnostic {
+                    kind,
+                    language: (*language).clone(),
+                    path: message.path.cloned(),
+                    line: message.line,
+                    message: message_text,
+                })
+            };
+            for argument in expected.difference(actual) {
+                push(
+                    I18NDiagnosticKind::UnusedArgument,
+                    format!("message `{id}` does not use the argument `${argument}`"),
+                );
+            }
+            for argument in actual.difference(expected) {
+                push(
+                    I18NDiagnosticKind::UnknownArgument,
+                    format!(
+                        "message `{id}` uses the argument `${argument}` which is not used in `{default_language}`"
+                    ),
+                );
+            }
+        }
+    }
+
+    diagnostics
+}
+
+fn line_of_offset(ftl: &str, offset: usize) -> usize {
+    ftl.as_bytes()[..offset.min(ftl.len())]
+        .iter()
+        .filter(|b| **b == b'\n')
+        .count()
+        + 1
+}
+
+fn line_of_message(ftl: &str, id: &str) -> Option<usize> {
+    ftl.lines()
+        .position(|line| {
+            line.strip_prefix(id)
+                .is_some_and(|rest| rest.trim_start().starts_with('='))
+        })
+        .map(|idx| idx + 1)
+}
+
+fn collect_pattern<'a>(pattern: &Pattern<&'a str>, arguments: &mut BTreeSet<&'a str>) {
+    for element in &pattern.elements {
+        if let PatternElement::Placeable { expression } = element {
+            collect_expression(expression, arguments);
+        }
+    }
+}
+
+fn collect_expression<'a>(expression: &Expression<&'a str>, arguments: &mut BTreeSet<&'a str>) {
+    match expression {
+        Expression::Select { selector, variants } => {
+            collect_inline_expression(selector, arguments);
+            for variant in variants {
+                collect_pattern(&variant.value, arguments);
+            }
+        }
+        Expression::Inline(expression) => collect_inline_expression(expression, arguments),
+    }
+}
+
+fn collect_inline_expression<'a>(
+    expression: &InlineExpression<&'a str>,
+    arguments: &mut BTreeSet<&'a str>,
+) {
+    match expression {
+        InlineExpression::VariableReference { id } => {
+            arguments.insert(id.name);
+        }
+        InlineExpression::FunctionReference {
+            arguments: call_arguments,
+            ..
+        } => collect_call_arguments(call_arguments, arguments),
+        InlineExpression::Placeable { expression } => collect_expression(expression, arguments),
+        _ => {}
+    }
+}
+
+fn collect_call_arguments<'a>(
+    call_arguments: &CallArguments<&'a str>,
+    arguments: &mut BTreeSet<&'a str>,
+) {
+    for expression in &call_arguments.positional {
+        collect_inline_expression(expression, arguments);
+    }
+    for argument in &call_arguments.named {
+        collect_inline_expression(&argument.value, arguments);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use unic_langid::langid;
+
+    use super::*;
+
+    fn source(language: LanguageIdentifier, path: &str, ftl: &str) -> Source {
+        Source {
+            language,
+            path: Some(PathBuf::from(path)),
+            ftl: ftl.to_string(),
+        }
+    }
+
+    #[test]
+    fn diagnostics() {
+        let sources = [
+            source(
+                langid!("en-US"),
+                "en-US/main.ftl",
+                "hello = Hello { $name }!\n\
+                 count = { $count ->\n    [one] One item\n   *[other] { NUMBER($count) } items\n}\n\
+                 bye = Bye!\n\
+                 -brand = Poem\n",
+            ),
+            source(
+                langid!("fr"),
+                "fr/main.ftl",
+                "hello = Bonjour { $user }!\ncount = { $count } éléments\n",
+            ),
+            source(
+                langid!("zh-CN"),
+                "zh-CN/main.ftl",
+                "hello = 你好 { $name }！\nbye = {\n",
+            ),
+        ];
+
+        let diagnostics = validate(&sources, &langid!("en-US"))
+            .into_iter()
+            .map(|diagnostic| diagnostic.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            diagnostics,
+            vec![
+                "zh-CN/main.ftl:2: Expected an inline expression",
+                "en-US/main.ftl:7: message `-brand` is missing in `fr`",
+                "en-US/main.ftl:6: message `bye` is missing in `fr`",
+                "fr/main.ftl:1: message `hello` does not use the argument `$name`",
+                "fr/main.ftl:1: message `hello` uses the argument `$user` which is not used in `en-US`",
+                "en-US/main.ftl:7: message `-brand` is missing in `zh-CN`",
+                "en-US/main.ftl:6: message `bye` is missing in `zh-CN`",
+                "en-US/main.ftl:2: message `count` is missing in `zh-CN`",
+            ]
+        );
+    }
+}
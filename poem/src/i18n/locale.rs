@@ -1,20 +1,23 @@
 use std::str::FromStr;
 
-use http::header;
 use smallvec::SmallVec;
 use unic_langid::LanguageIdentifier;
 
 use crate::{
     FromRequest, Request, RequestBody, Result,
     error::I18NError,
-    i18n::{I18NArgs, I18NBundle, I18NResources},
+    i18n::{I18NArgs, I18NBundle, I18NResources, LocaleSource},
 };
 
 type LanguageArray = SmallVec<[LanguageIdentifier; 8]>;
 
-/// An extractor that parses the `Accept-Language` header and negotiates
+/// An extractor that resolves the language of the request and negotiates
 /// language bundles.
 ///
+/// By default, the language is negotiated from the `Accept-Language` header,
+/// the sources can be configured with
+/// [`I18NResourcesBuilder::locale_resolver`](crate::i18n::I18NResourcesBuilder::locale_resolver).
+///
 /// # Example
 ///
 /// ```
@@ -61,9 +64,21 @@ type LanguageArray = SmallVec<[LanguageIdentifier; 8]>;
 /// ```
 pub struct Locale {
     bundle: I18NBundle,
+    language: LanguageIdentifier,
+    source: LocaleSource,
 }
 
 impl Locale {
+    /// Returns the resolved language.
+    pub fn language(&self) -> &LanguageIdentifier {
+        &self.language
+    }
+
+    /// Returns the source of the resolved language.
+    pub fn source(&self) -> LocaleSource {
+        self.source
+    }
+
     /// Gets the text with arguments.
     ///
     /// See also: [`I18NBundle::text_with_args`](I18NBundle::text_with_args)
@@ -90,20 +105,22 @@ impl<'a> FromRequest<'a> for Locale {
             .get::<I18NResources>()
             .expect("To use the `Locale` extractor, the `I18NResources` data is required.");
 
-        let accept_languages = req
-            .headers()
-            .get(header::ACCEPT_LANGUAGE)
-            .and_then(|value| value.to_str().ok())
-            .map(parse_accept_languages)
-            .unwrap_or_default();
-
-        Ok(Self {
-            bundle: resources.negotiate_languages(&accept_languages),
+        Ok(match resources.resolver().resolve(resources, req, false) {
+            Some((languages, language, source)) => Self {
+                bundle: resources.negotiate_languages(&languages),
+                language,
+                source,
+            },
+            None => Self {
+                bundle: resources.negotiate_languages(&[] as &[LanguageIdentifier]),
+                language: resources.default_language().clone(),
+                source: LocaleSource::Default,
+            },
         })
     }
 }
 
-fn parse_accept_languages(value: &str) -> LanguageArray {
+pub(crate) fn parse_accept_languages(value: &str) -> LanguageArray {
     let mut languages = SmallVec::<[_; 8]>::new();
 
     for s in value.split(',').map(str::trim) {
@@ -55,19 +55,33 @@
 //!     .unwrap();
 //! ```
 //!
+//! # Validate resources
+//!
+//! See also: [`crate::i18n::I18NResourcesBuilder::validate`]
+//!
 //! # Use extractor
 //!
 //! See also: [`crate::i18n::Locale`]
+//!
+//! # Resolve the language from the path, query or cookie
+//!
+//! See also: [`crate::i18n::LocaleResolver`] and
+//! [`crate::middleware::LocaleRedirect`]
 
 mod args;
+mod diagnostics;
 mod locale;
+mod resolver;
 mod resources;
 
 pub use fluent_langneg::NegotiationStrategy;
+pub(crate) use resolver::path_prefix;
 pub use unic_langid;
 
 pub use self::{
     args::I18NArgs,
+    diagnostics::{I18NDiagnostic, I18NDiagnosticKind},
     locale::Locale,
+    resolver::{LocaleResolver, LocaleSource},
     resources::{I18NBundle, I18NResources, I18NResourcesBuilder},
 };
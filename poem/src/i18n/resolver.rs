@@ -0,0 +1,297 @@
+use std::str::FromStr;
+
+use http::header;
+use unic_langid::LanguageIdentifier;
+
+use crate::{
+    Request,
+    i18n::{I18NResources, locale::parse_accept_languages},
+};
+
+/// The source of the language of a [`Locale`](crate::i18n::Locale).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LocaleSource {
+    /// The first segment of the request path, such as `/fr/about`.
+    PathPrefix,
+    /// A query parameter, such as `?lang=fr`.
+    Query,
+    /// A cookie, such as `lang=fr`.
+    Cookie,
+    /// The `Accept-Language` header.
+    AcceptLanguage,
+    /// The default language of the [`I18NResources`].
+    Default,
+}
+
+#[derive(Debug, Clone)]
+enum Step {
+    PathPrefix,
+    Query(String),
+    Cookie(String),
+    AcceptLanguage,
+}
+
+/// A chain of sources to resolve the language of a request.
+///
+/// The sources are tried in the order they were added, the first one which
+/// provides a language available in the resources wins, otherwise the
+/// default language of the resources is used.
+///
+/// The default resolver only uses the `Accept-Language` header.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     EndpointExt, Route, get, handler,
+///     http::header,
+///     i18n::{I18NResources, Locale, LocaleResolver, LocaleSource},
+///     test::TestClient,
+/// };
+///
+/// let resources = I18NResources::builder()
+///     .add_ftl("en-US", "hello-world = hello world!")
+///     .add_ftl("fr", "hello-world = bonjour le monde!")
+///     .locale_resolver(
+///         LocaleResolver::new()
+///             .query("lang")
+///             .cookie("lang")
+///             .accept_language(),
+///     )
+///     .build()
+///     .unwrap();
+///
+/// #[handler]
+/// async fn index(locale: Locale) -> String {
+///     assert_eq!(locale.source(), LocaleSource::Query);
+///     locale.text("hello-world").unwrap()
+/// }
+///
+/// let app = Route::new().at("/", get(index)).data(resources);
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli
+///     .get("/")
+///     .query("lang", &"fr")
+///     .header(header::ACCEPT_LANGUAGE, "en-US")
+///     .send()
+///     .await;
+/// resp.assert_text("bonjour le monde!").await;
+/// # });
+/// ```
+#[derive(Debug, Clone)]
+pub struct LocaleResolver {
+    steps: Vec<Step>,
+}
+
+impl Default for LocaleResolver {
+    fn default() -> Self {
+        Self::new().accept_language()
+    }
+}
+
+impl LocaleResolver {
+    /// Create an empty resolver, which always uses the default language.
+    pub fn new() -> Self {
+        Self { steps: Vec::new() }
+    }
+
+    /// Uses the first segment of the request path, such as `fr` in
+    /// `/fr/about`.
+    #[must_use]
+    pub fn path_prefix(mut self) -> Self {
+        self.steps.push(Step::PathPrefix);
+        self
+    }
+
+    /// Uses the query parameter with the specified name.
+    #[must_use]
+    pub fn query(mut self, name: impl Into<String>) -> Self {
+        self.steps.push(Step::Query(name.into()));
+        self
+    }
+
+    /// Uses the cookie with the specified name.
+    #[must_use]
+    pub fn cookie(mut self, name: impl Into<String>) -> Self {
+        self.steps.push(Step::Cookie(name.into()));
+        self
+    }
+
+    /// Uses the `Accept-Language` header.
+    #[must_use]
+    pub fn accept_language(mut self) -> Self {
+        self.steps.push(Step::AcceptLanguage);
+        self
+    }
+
+    /// Returns the request headers which the resolved language depends on,
+    /// for the `Vary` header of the responses.
+    pub(crate) fn vary_headers(&self) -> Vec<header::HeaderName> {
+        let mut headers = Vec::new();
+        for step in &self.steps {
+            let name = match step {
+                Step::Cookie(_) => header::COOKIE,
+                Step::AcceptLanguage => header::ACCEPT_LANGUAGE,
+                Step::PathPrefix | Step::Query(_) => continue,
+            };
+            if !headers.contains(&name) {
+                headers.push(name);
+            }
+        }
+        headers
+    }
+
+    /// Returns the requested languages, the best matched available language
+    /// and the source which provides it, or `None` if no source does.
+    pub(crate) fn resolve(
+        &self,
+        resources: &I18NResources,
+        req: &Request,
+        skip_path_prefix: bool,
+    ) -> Option<(Vec<LanguageIdentifier>, LanguageIdentifier, LocaleSource)> {
+        for step in &self.steps {
+            let (languages, source) = match step {
+                Step::PathPrefix if skip_path_prefix => continue,
+                Step::PathPrefix => (
+                    path_prefix(req.uri().path())
+                        .and_then(|prefix| LanguageIdentifier::from_str(prefix).ok())
+                        .into_iter()
+                        .collect(),
+                    LocaleSource::PathPrefix,
+                ),
+                Step::Query(name) => (
+                    req.uri()
+                        .query()
+                        .and_then(|query| {
+                            serde_urlencoded::from_str::<Vec<(String, String)>>(query).ok()
+                        })
+                        .and_then(|params| {
+                            params
+                                .into_iter()
+                                .find(|(key, _)| key == name)
+                                .and_then(|(_, value)| LanguageIdentifier::from_str(&value).ok())
+                        })
+                        .into_iter()
+                        .collect(),
+                    LocaleSource::Query,
+                ),
+                Step::Cookie(name) => (
+                    cookie_value(req, name)
+                        .and_then(|value| LanguageIdentifier::from_str(value).ok())
+                        .into_iter()
+                        .collect(),
+                    LocaleSource::Cookie,
+                ),
+                Step::AcceptLanguage => (
+                    req.headers()
+                        .get(header::ACCEPT_LANGUAGE)
+                        .and_then(|value| value.to_str().ok())
+                        .map(parse_accept_languages)
+                        .unwrap_or_default()
+                        .into_vec(),
+                    LocaleSource::AcceptLanguage,
+                ),
+            };
+
+            if let Some(language) = resources.matched_language(&languages) {
+                return Some((languages, language, source));
+            }
+        }
+
+        None
+    }
+}
+
+/// Returns the first segment of the path.
+pub(crate) fn path_prefix(path: &str) -> Option<&str> {
+    let prefix = path.strip_prefix('/')?.split('/').next()?;
+    (!prefix.is_empty()).then_some(prefix)
+}
+
+fn cookie_value<'a>(req: &'a Request, name: &str) -> Option<&'a str> {
+    req.headers()
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value.trim_matches('"'))
+}
+
+#[cfg(test)]
+mod tests {
+    use unic_langid::langid;
+
+    use super::*;
+
+    #[test]
+    fn resolve_chain() {
+        let resources = I18NResources::builder()
+            .add_ftl("en-US", "hello = hello")
+            .add_ftl("fr", "hello = bonjour")
+            .add_ftl("zh-CN", "hello = 你好")
+            .build()
+            .unwrap();
+        let resolver = LocaleResolver::new()
+            .path_prefix()
+            .query("lang")
+            .cookie("lang")
+            .accept_language();
+        let resolve = |req: Request| {
+            resolver
+                .resolve(&resources, &req, false)
+                .map(|(_, language, source)| (language, source))
+        };
+
+        assert_eq!(
+            resolve(
+                Request::builder()
+                    .uri_str("/fr/about?lang=zh-CN")
+                    .header(header::COOKIE, "lang=zh-CN")
+                    .finish()
+            ),
+            Some((langid!("fr"), LocaleSource::PathPrefix))
+        );
+        assert_eq!(
+            resolve(Request::builder().uri_str("/about?a=1&lang=zh-CN").finish()),
+            Some((langid!("zh-CN"), LocaleSource::Query))
+        );
+        assert_eq!(
+            resolve(
+                Request::builder()
+                    .uri_str("/de/about?lang=de")
+                    .header(header::COOKIE, "a=1; lang=fr")
+                    .header(header::ACCEPT_LANGUAGE, "zh-CN")
+                    .finish()
+            ),
+            Some((langid!("fr"), LocaleSource::Cookie))
+        );
+        assert_eq!(
+            resolve(
+                Request::builder()
+                    .header(header::ACCEPT_LANGUAGE, "de, zh-CN;q=0.5")
+                    .finish()
+            ),
+            Some((langid!("zh-CN"), LocaleSource::AcceptLanguage))
+        );
+        assert_eq!(
+            resolve(
+                Request::builder()
+                    .header(header::ACCEPT_LANGUAGE, "de")
+                    .finish()
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_path_prefix() {
+        assert_eq!(path_prefix("/fr/about"), Some("fr"));
+        assert_eq!(path_prefix("/fr"), Some("fr"));
+        assert_eq!(path_prefix("/"), None);
+        assert_eq!(path_prefix(""), None);
+    }
+}
@@ -2,11 +2,13 @@ use std::{
     collections::HashMap,
     path::{Path, PathBuf},
     str::FromStr,
-    sync::Arc,
+    sync::{Arc, Weak},
+    time::{Duration, SystemTime},
 };
 
 use fluent::{FluentMessage, FluentResource};
 use intl_memoizer::concurrent::IntlLangMemoizer;
+use parking_lot::RwLock;
 use smallvec::SmallVec;
 use unic_langid::{LanguageIdentifier, langid};
 
@@ -16,21 +18,34 @@ type FluentBundle = fluent::bundle::FluentBundle<FluentResource, IntlLangMemoize
 
 use fluent_langneg::NegotiationStrategy;
 
-use crate::i18n::I18NArgs;
+use crate::i18n::{I18NArgs, I18NDiagnostic, LocaleResolver, diagnostics};
 
 struct InnerResources {
     available_languages: Vec<LanguageIdentifier>,
     bundles: HashMap<LanguageIdentifier, Arc<FluentBundle>>,
-    default_language: LanguageIdentifier,
-    strategy: NegotiationStrategy,
+}
+
+struct SharedResources {
+    builder: I18NResourcesBuilder,
+    inner: RwLock<Arc<InnerResources>>,
+}
+
+/// A FTL source of a language.
+pub(crate) struct Source {
+    pub(crate) language: LanguageIdentifier,
+    pub(crate) path: Option<PathBuf>,
+    pub(crate) ftl: String,
 }
 
 /// I18N resources builder.
+#[derive(Clone)]
 pub struct I18NResourcesBuilder {
     paths: Vec<PathBuf>,
     resources: Vec<(String, String)>,
     default_language: LanguageIdentifier,
     strategy: NegotiationStrategy,
+    resolver: LocaleResolver,
+    watch_interval: Option<Duration>,
 }
 
 impl I18NResourcesBuilder {
@@ -106,42 +121,120 @@ impl I18NResourcesBuilder {
         self
     }
 
+    /// Sets the chain of sources used by the [`Locale`](crate::i18n::Locale)
+    /// extractor to resolve the language of a request.
+    ///
+    /// Default is [`LocaleResolver::default`], which only uses the
+    /// `Accept-Language` header.
+    #[must_use]
+    pub fn locale_resolver(mut self, resolver: LocaleResolver) -> Self {
+        self.resolver = resolver;
+        self
+    }
+
+    /// Watches the resource directories added by
+    /// [`add_path`](Self::add_path), and reloads the resources when the files
+    /// change.
+    ///
+    /// The directories are polled with the specified interval, this is
+    /// intended for development. The changed files are reloaded once they
+    /// are unchanged for one interval. If they fail to load, the error is
+    /// logged and the previous resources are kept.
+    #[must_use]
+    pub fn watch(mut self, interval: Duration) -> Self {
+        self.watch_interval = Some(interval);
+        self
+    }
+
+    /// Checks the resources and returns the diagnostics, such as syntax
+    /// errors, messages missing in non-default languages and arguments which
+    /// are not used by a translation.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use poem::i18n::{I18NDiagnosticKind, I18NResources};
+    ///
+    /// let diagnostics = I18NResources::builder()
+    ///     .add_ftl("en-US", "hello = Hello { $name }!\nbye = Bye!")
+    ///     .add_ftl("zh-CN", "hello = 你好！")
+    ///     .validate()
+    ///     .unwrap();
+    ///
+    /// let kinds = diagnostics.iter().map(|d| d.kind).collect::<Vec<_>>();
+    /// assert_eq!(
+    ///     kinds,
+    ///     [
+    ///         I18NDiagnosticKind::MissingMessage,
+    ///         I18NDiagnosticKind::UnusedArgument
+    ///     ]
+    /// );
+    /// ```
+    pub fn validate(&self) -> Result<Vec<I18NDiagnostic>, I18NError> {
+        Ok(diagnostics::validate(
+            &self.load_sources()?,
+            &self.default_language,
+        ))
+    }
+
     /// Consumes this builder and returns a [`I18NResources`] object.
     pub fn build(self) -> Result<I18NResources, I18NError> {
-        let mut bundles = HashMap::new();
+        let inner = self.load()?;
+        let watch_interval = self.watch_interval;
+        let shared = Arc::new(SharedResources {
+            builder: self,
+            inner: RwLock::new(Arc::new(inner)),
+        });
+        if let Some(interval) = watch_interval {
+            spawn_watcher(Arc::downgrade(&shared), interval);
+        }
+        Ok(I18NResources { shared })
+    }
+
+    fn load_sources(&self) -> Result<Vec<Source>, I18NError> {
+        let mut sources = Vec::new();
 
-        for path in self.paths {
-            load_resources_from_path(&mut bundles, path)?;
+        for path in &self.paths {
+            load_sources_from_path(&mut sources, path)?;
         }
 
-        for (language, ftl) in self.resources {
-            let language = LanguageIdentifier::from_str(&language)?;
-            let resource = FluentResource::try_new(ftl)
+        for (language, ftl) in &self.resources {
+            sources.push(Source {
+                language: LanguageIdentifier::from_str(language)?,
+                path: None,
+                ftl: ftl.clone(),
+            });
+        }
+
+        Ok(sources)
+    }
+
+    fn load(&self) -> Result<InnerResources, I18NError> {
+        let mut bundles = HashMap::new();
+
+        for source in self.load_sources()? {
+            let resource = FluentResource::try_new(source.ftl)
                 .map_err(|(_, errors)| I18NError::FluentParser(errors))?;
 
             bundles
-                .entry(language.clone())
-                .or_insert_with(|| FluentBundle::new_concurrent(vec![language]))
+                .entry(source.language.clone())
+                .or_insert_with(|| FluentBundle::new_concurrent(vec![source.language]))
                 .add_resource(resource)
                 .map_err(I18NError::Fluent)?;
         }
 
-        Ok(I18NResources {
-            inner: Arc::new(InnerResources {
-                available_languages: bundles.keys().cloned().collect(),
-                bundles: bundles
-                    .into_iter()
-                    .map(|(key, value)| (key, Arc::new(value)))
-                    .collect(),
-                default_language: self.default_language,
-                strategy: self.strategy,
-            }),
+        Ok(InnerResources {
+            available_languages: bundles.keys().cloned().collect(),
+            bundles: bundles
+                .into_iter()
+                .map(|(key, value)| (key, Arc::new(value)))
+                .collect(),
         })
     }
 }
 
-fn load_resources_from_path(
-    bundles: &mut HashMap<LanguageIdentifier, FluentBundle>,
+fn load_sources_from_path(
+    sources: &mut Vec<Source>,
     path: impl AsRef<Path>,
 ) -> Result<(), I18NError> {
     let path = path.as_ref();
@@ -167,24 +260,90 @@ fn load_resources_from_path(
 
             tracing::debug!(path = ?resource_path.path(), "load fluent resource");
 
-            let resource = FluentResource::try_new(std::fs::read_to_string(resource_path.path())?)
-                .map_err(|(_, errors)| I18NError::FluentParser(errors))?;
-
-            bundles
-                .entry(language.clone())
-                .or_insert_with(|| FluentBundle::new_concurrent(vec![language.clone()]))
-                .add_resource(resource)
-                .map_err(I18NError::Fluent)?;
+            sources.push(Source {
+                language: language.clone(),
+                ftl: std::fs::read_to_string(resource_path.path())?,
+                path: Some(resource_path.path()),
+            });
         }
     }
 
     Ok(())
 }
 
+/// Returns the modification times and sizes of the files in the resource
+/// directories.
+fn fingerprint(paths: &[PathBuf]) -> Vec<(PathBuf, Option<(SystemTime, u64)>)> {
+    let mut files = Vec::new();
+    let read_dir = |path: &Path| {
+        std::fs::read_dir(path)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+    };
+
+    for language_dir in paths.iter().flat_map(|path| read_dir(path)) {
+        for path in read_dir(&language_dir) {
+            let modified = std::fs::metadata(&path)
+                .and_then(|metadata| Ok((metadata.modified()?, metadata.len())))
+                .ok();
+            files.push((path, modified));
+        }
+        files.push((language_dir, None));
+    }
+
+    files.sort();
+    files
+}
+
+fn spawn_watcher(shared: Weak<SharedResources>, interval: Duration) {
+    let Some(paths) = shared.upgrade().map(|shared| shared.builder.paths.clone()) else {
+        return;
+    };
+    let mut loaded = fingerprint(&paths);
+    let mut pending = loaded.clone();
+
+    std::thread::spawn(move || {
+        loop {
+            std::thread::sleep(interval);
+            let Some(shared) = shared.upgrade() else {
+                break;
+            };
+
+            // the files are loaded after they are unchanged for one interval,
+            // and the load is discarded if they are changed while loading, so
+            // the files which are being written are not loaded
+            let current = fingerprint(&paths);
+            if current != pending {
+                pending = current;
+                continue;
+            }
+            if current == loaded {
+                continue;
+            }
+
+            let res = shared.builder.load();
+            if fingerprint(&paths) != current {
+                continue;
+            }
+            loaded = current;
+
+            match res {
+                Ok(inner) => {
+                    tracing::info!("fluent resources reloaded");
+                    *shared.inner.write() = Arc::new(inner);
+                }
+                Err(err) => tracing::error!(error = %err, "failed to reload fluent resources"),
+            }
+        }
+    });
+}
+
 /// A resource for translating natural language.
 #[derive(Clone)]
 pub struct I18NResources {
-    inner: Arc<InnerResources>,
+    shared: Arc<SharedResources>,
 }
 
 impl I18NResources {
@@ -195,23 +354,70 @@ impl I18NResources {
             resources: vec![],
             default_language: langid!("en-US"),
             strategy: NegotiationStrategy::Filtering,
+            resolver: LocaleResolver::default(),
+            watch_interval: None,
         }
     }
 
+    /// Reloads the resources from the files and strings.
+    ///
+    /// If the resources fail to load, the previous resources are kept.
+    pub fn reload(&self) -> Result<(), I18NError> {
+        let inner = self.shared.builder.load()?;
+        *self.shared.inner.write() = Arc::new(inner);
+        Ok(())
+    }
+
+    fn inner(&self) -> Arc<InnerResources> {
+        self.shared.inner.read().clone()
+    }
+
+    pub(crate) fn default_language(&self) -> &LanguageIdentifier {
+        &self.shared.builder.default_language
+    }
+
+    pub(crate) fn resolver(&self) -> &LocaleResolver {
+        &self.shared.builder.resolver
+    }
+
+    /// Returns the best available language for the input language id list,
+    /// without falling back to the default language.
+    pub(crate) fn matched_language(
+        &self,
+        languages: &[impl AsRef<LanguageIdentifier>],
+    ) -> Option<LanguageIdentifier> {
+        fluent_langneg::negotiate_languages(
+            languages,
+            &self.inner().available_languages,
+            None,
+            self.shared.builder.strategy,
+        )
+        .first()
+        .map(|language| (*language).clone())
+    }
+
     /// Negotiate the language according to the input language id list and
     /// return the [`I18NBundle`].
+    ///
+    /// The bundle of the default language is always included last, so the
+    /// messages missing in the negotiated languages fall back to it.
     pub fn negotiate_languages(&self, languages: &[impl AsRef<LanguageIdentifier>]) -> I18NBundle {
-        let resolved_languages = fluent_langneg::negotiate_languages(
+        let inner = self.inner();
+        let default_language = self.default_language();
+        let mut resolved_languages = fluent_langneg::negotiate_languages(
             languages,
-            &self.inner.available_languages,
-            Some(&self.inner.default_language),
-            self.inner.strategy,
+            &inner.available_languages,
+            Some(default_language),
+            self.shared.builder.strategy,
         );
+        if !resolved_languages.contains(&default_language) {
+            resolved_languages.push(default_language);
+        }
 
         I18NBundle(
             resolved_languages
                 .into_iter()
-                .filter_map(|language| self.inner.bundles.get(language))
+                .filter_map(|language| inner.bundles.get(language))
                 .cloned()
                 .collect(),
         )
@@ -299,3 +505,64 @@ impl I18NBundle {
         self.text_with_args(id, I18NArgs::default())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fallback_to_default_language() {
+        let resources = I18NResources::builder()
+            .add_ftl("en-US", "hello = hello\nbye = bye")
+            .add_ftl("fr", "hello = bonjour")
+            .negotiation_strategy(NegotiationStrategy::Lookup)
+            .build()
+            .unwrap();
+        let bundle = resources.negotiate_languages(&[langid!("fr")]);
+        assert_eq!(bundle.text("hello").unwrap(), "bonjour");
+        assert_eq!(bundle.text("bye").unwrap(), "bye");
+    }
+
+    #[test]
+    fn reload() {
+        let dir = std::env::temp_dir().join(format!("poem-i18n-reload-{}", std::process::id()));
+        let ftl = dir.join("en-US").join("main.ftl");
+        std::fs::create_dir_all(ftl.parent().unwrap()).unwrap();
+        std::fs::write(&ftl, "hello = hello").unwrap();
+
+        let resources = I18NResources::builder()
+            .add_path(&dir)
+            .watch(Duration::from_millis(10))
+            .build()
+            .unwrap();
+        let text = || {
+            resources
+                .negotiate_languages(&[langid!("en-US")])
+                .text("hello")
+                .unwrap()
+        };
+        assert_eq!(text(), "hello");
+
+        std::fs::write(&ftl, "hello = hello world").unwrap();
+        let mut reloaded = false;
+        for _ in 0..200 {
+            if text() == "hello world" {
+                reloaded = true;
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(reloaded);
+
+        // keeps the previous resources if the files fail to load
+        std::fs::write(&ftl, "hello = {").unwrap();
+        assert!(resources.reload().is_err());
+        assert_eq!(text(), "hello world");
+
+        std::fs::write(&ftl, "hello = hi").unwrap();
+        resources.reload().unwrap();
+        assert_eq!(text(), "hi");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
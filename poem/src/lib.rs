@@ -231,9 +231,11 @@
 //! |Feature           |Description                     |
 //! |------------------|--------------------------------|
 //! | server | Server and listener APIs(enable by default) |
+//! |cache             | Support for server-side HTTP response caching |
 //! |compression  | Support decompress request body and compress response body |
 //! |cookie            | Support for Cookie             |
 //! |csrf | Support for Cross-Site Request Forgery (CSRF) protection |
+//! |jwt               | Support for JSON Web Token (JWT) authentication |
 //! |multipart         | Support for Multipart          |
 //! |native-tls        | Support for HTTP server over TLS with [`native-tls`](https://crates.io/crates/native-tls)  |
 //! |openssl-tls        | Support for HTTP server over TLS with [`openssl-tls`](https://crates.io/crates/openssl)  |
@@ -241,10 +243,12 @@
 //! |prometheus        | Support for Prometheus       |
 //! |redis-session     | Support for RedisSession     |
 //! |rustls            | Support for HTTP server over TLS with [`rustls`](https://crates.io/crates/rustls)  |
+//! |security-headers  | Support for security headers, such as HSTS and CSP with nonces |
 //! |session           | Support for session    |
 //! |sse               | Support Server-Sent Events (SSE)       |
 //! |tempfile          | Support for [`tempfile`](https://crates.io/crates/tempfile) |
 //! |test              | Test utilities to test your endpoints. |
+//! |test-server       | Test utilities to test your endpoints with a real server. |
 //! |tower-compat      | Adapters for `tower::Layer` and `tower::Service`. |
 //! |websocket         | Support for WebSocket          |
 //! | anyhow        | Integrate with the [`anyhow`](https://crates.io/crates/anyhow) crate. |
@@ -303,8 +307,9 @@ pub use poem_derive::handler;
 pub use request::{OnUpgrade, Request, RequestBuilder, RequestParts, Upgraded};
 pub use response::{Response, ResponseBuilder, ResponseParts};
 pub use route::{
-    PathPattern, Route, RouteDomain, RouteMethod, RouteScheme, connect, delete, get, head, options,
-    patch, post, put, trace,
+    DomainRoute, MethodRoute, PathPattern, PathRoute, PathRouteKind, Route, RouteDomain, RouteInfo,
+    RouteMethod, RouteNode, RouteScheme, RouteTable, SchemeRoute, UrlFor, connect, delete, get,
+    head, options, patch, post, put, trace,
 };
 #[cfg(feature = "server")]
 pub use server::Server;
@@ -1,4 +1,4 @@
-use crate::{Endpoint, Middleware, Request, Result};
+use crate::{Endpoint, Middleware, Request, Result, route::RouteNode};
 
 /// Middleware for adding any data to a request.
 pub struct AddData<T> {
@@ -44,6 +44,10 @@ where
         req.extensions_mut().insert(self.value.clone());
         self.inner.call(req).await
     }
+
+    fn describe_routes(&self) -> RouteNode {
+        self.inner.describe_routes()
+    }
 }
 
 #[cfg(test)]
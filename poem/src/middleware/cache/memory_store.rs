@@ -0,0 +1,184 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+
+use super::{CacheStore, CachedResponse};
+use crate::Result;
+
+struct Entry {
+    resp: CachedResponse,
+    expires_at: Instant,
+    size: usize,
+    tick: u64,
+}
+
+#[derive(Default)]
+struct Inner {
+    entries: HashMap<String, Entry>,
+    /// Keys ordered from the least recently used.
+    lru: BTreeMap<u64, String>,
+    tick: u64,
+    size: usize,
+}
+
+impl Inner {
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.lru.remove(&entry.tick);
+            self.size -= entry.size;
+        }
+    }
+}
+
+/// A cache store using memory, which evicts the least recently used
+/// responses when the total size exceeds the budget.
+pub struct MemoryCacheStore {
+    max_size: usize,
+    inner: Mutex<Inner>,
+}
+
+impl MemoryCacheStore {
+    /// Create a `MemoryCacheStore` with the maximum total size of the
+    /// responses in bytes.
+    pub fn new(max_size: usize) -> Self {
+        Self {
+            max_size,
+            inner: Default::default(),
+        }
+    }
+
+    /// Returns the total size of the stored responses in bytes.
+    pub fn size(&self) -> usize {
+        self.inner.lock().size
+    }
+}
+
+impl CacheStore for MemoryCacheStore {
+    async fn get<'a>(&'a self, key: &'a str) -> Result<Option<CachedResponse>> {
+        let mut inner = self.inner.lock();
+        let tick = inner.next_tick();
+
+        let Some(entry) = inner.entries.get_mut(key) else {
+            return Ok(None);
+        };
+        if entry.expires_at <= Instant::now() {
+            inner.remove(key);
+            return Ok(None);
+        }
+        let old_tick = std::mem::replace(&mut entry.tick, tick);
+        let resp = entry.resp.clone();
+        inner.lru.remove(&old_tick);
+        inner.lru.insert(tick, key.to_string());
+        Ok(Some(resp))
+    }
+
+    async fn set<'a>(&'a self, key: &'a str, resp: CachedResponse, ttl: Duration) -> Result<()> {
+        let mut inner = self.inner.lock();
+        inner.remove(key);
+
+        let size = key.len() + resp.size();
+        if size > self.max_size {
+            return Ok(());
+        }
+        while inner.size + size > self.max_size {
+            match inner.lru.first_key_value() {
+                Some((_, key)) => {
+                    let key = key.clone();
+                    inner.remove(&key);
+                }
+                None => break,
+            }
+        }
+
+        let tick = inner.next_tick();
+        inner.entries.insert(
+            key.to_string(),
+            Entry {
+                resp,
+                expires_at: Instant::now() + ttl,
+                size,
+                tick,
+            },
+        );
+        inner.lru.insert(tick, key.to_string());
+        inner.size += size;
+        Ok(())
+    }
+
+    async fn remove<'a>(&'a self, key: &'a str) -> Result<()> {
+        self.inner.lock().remove(key);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::http::{HeaderMap, StatusCode};
+
+    fn response(body: &'static str) -> CachedResponse {
+        CachedResponse {
+            status: StatusCode::OK,
+            headers: HeaderMap::new(),
+            body: Bytes::from_static(body.as_bytes()),
+            created_at: SystemTime::now(),
+            max_age: Duration::from_secs(60),
+            stale_while_revalidate: Duration::ZERO,
+        }
+    }
+
+    #[tokio::test]
+    async fn evict_least_recently_used() {
+        let ttl = Duration::from_secs(60);
+        let store = MemoryCacheStore::new(30);
+
+        store.set("a", response("aaaaaaaaa"), ttl).await.unwrap();
+        store.set("b", response("bbbbbbbbb"), ttl).await.unwrap();
+        store.set("c", response("ccccccccc"), ttl).await.unwrap();
+        assert_eq!(store.size(), 30);
+
+        // touch `a`, so `b` is evicted
+        assert!(store.get("a").await.unwrap().is_some());
+        store.set("d", response("ddddddddd"), ttl).await.unwrap();
+        assert_eq!(store.size(), 30);
+        assert!(store.get("b").await.unwrap().is_none());
+        assert!(store.get("a").await.unwrap().is_some());
+        assert!(store.get("c").await.unwrap().is_some());
+        assert!(store.get("d").await.unwrap().is_some());
+
+        // larger than the budget
+        store
+            .set("e", response("eeeeeeeeeeeeeeeeeeeeeeeeeeeeee"), ttl)
+            .await
+            .unwrap();
+        assert!(store.get("e").await.unwrap().is_none());
+
+        store.remove("a").await.unwrap();
+        assert_eq!(store.size(), 20);
+    }
+
+    #[tokio::test]
+    async fn expires() {
+        let store = MemoryCacheStore::new(1024);
+        store
+            .set("a", response("a"), Duration::from_millis(50))
+            .await
+            .unwrap();
+        assert!(store.get("a").await.unwrap().is_some());
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(store.get("a").await.unwrap().is_none());
+        assert_eq!(store.size(), 0);
+    }
+}
@@ -0,0 +1,607 @@
+mod memory_store;
+mod store;
+
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use parking_lot::Mutex;
+use tokio::sync::broadcast;
+
+pub use self::{
+    memory_store::MemoryCacheStore,
+    store::{CacheStore, CachedResponse},
+};
+use crate::{
+    Body, Endpoint, IntoResponse, Middleware, Request, Response, Result,
+    http::{
+        HeaderMap, HeaderValue, Method,
+        header::{self, HeaderName},
+    },
+    route::RouteNode,
+};
+
+const X_CACHE: HeaderName = HeaderName::from_static("x-cache");
+
+/// The parsed directives of the `Cache-Control` header.
+struct CacheControl(HashMap<String, Option<String>>);
+
+impl CacheControl {
+    fn new(headers: &HeaderMap) -> Self {
+        let mut directives = HashMap::new();
+        for value in headers.get_all(header::CACHE_CONTROL) {
+            for item in value.to_str().unwrap_or_default().split(',') {
+                let (name, value) = match item.split_once('=') {
+                    Some((name, value)) => (name, Some(value.trim().trim_matches('"'))),
+                    None => (item, None),
+                };
+                let name = name.trim();
+                if !name.is_empty() {
+                    directives.insert(name.to_ascii_lowercase(), value.map(ToString::to_string));
+                }
+            }
+        }
+        Self(directives)
+    }
+
+    fn has(&self, name: &str) -> bool {
+        self.0.contains_key(name)
+    }
+
+    fn seconds(&self, name: &str) -> Option<Duration> {
+        let value = self.0.get(name)?.as_deref()?;
+        value.parse().ok().map(Duration::from_secs)
+    }
+}
+
+/// Middleware for caching responses on the server side.
+///
+/// Only the responses of `GET` requests are cached. The cache key is made of
+/// the path, the query string and the request headers specified by
+/// [`Cache::vary`], and responses that vary on other headers are not cached.
+///
+/// A response is stored if its status code is cacheable by default, its body
+/// is buffered in memory, it has no `Set-Cookie` header, and its
+/// `Cache-Control` header contains `max-age` or `s-maxage` (or
+/// [`Cache::default_ttl`] is set) without `no-store`, `no-cache` or
+/// `private`. Responses to requests with the `Authorization` header are only
+/// stored if they are marked as `public` or have `s-maxage`. The
+/// `stale-while-revalidate` directive allows a stale response to be served
+/// while it is revalidated in the background.
+///
+/// Requests with `Cache-Control: no-store` bypass the cache, and requests with
+/// `Cache-Control: no-cache` or `max-age=0` skip the lookup, but update the
+/// cache. Concurrent requests that miss the same key wait for the first one,
+/// instead of calling the endpoint at the same time.
+///
+/// Cached responses have the `Age` header, and all the responses of `GET`
+/// requests have the `X-Cache` header with the value `HIT`, `STALE` or `MISS`.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     EndpointExt, Response, Route, get, handler,
+///     http::header,
+///     middleware::{Cache, MemoryCacheStore},
+///     test::TestClient,
+/// };
+///
+/// #[handler]
+/// fn index() -> Response {
+///     Response::builder()
+///         .header(header::CACHE_CONTROL, "max-age=60, stale-while-revalidate=30")
+///         .body("hello")
+/// }
+///
+/// let store = MemoryCacheStore::new(64 * 1024 * 1024);
+/// let app = Route::new()
+///     .at("/", get(index))
+///     .with(Cache::new(store).vary(header::ACCEPT_LANGUAGE));
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/").send().await;
+/// resp.assert_header("x-cache", "MISS");
+///
+/// let resp = cli.get("/").send().await;
+/// resp.assert_header("x-cache", "HIT");
+/// resp.assert_header(header::AGE, "0");
+/// resp.assert_text("hello").await;
+/// # });
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "cache")))]
+pub struct Cache<S> {
+    store: Arc<S>,
+    vary: Vec<HeaderName>,
+    default_ttl: Option<Duration>,
+}
+
+impl<S> Clone for Cache<S> {
+    fn clone(&self) -> Self {
+        Self {
+            store: self.store.clone(),
+            vary: self.vary.clone(),
+            default_ttl: self.default_ttl,
+        }
+    }
+}
+
+impl<S: CacheStore> Cache<S> {
+    /// Create a `Cache` middleware with the store.
+    pub fn new(store: S) -> Self {
+        Self {
+            store: Arc::new(store),
+            vary: Vec::new(),
+            default_ttl: None,
+        }
+    }
+
+    /// Adds a request header to the cache key.
+    ///
+    /// Responses that vary on headers that are not added are not cached.
+    #[must_use]
+    pub fn vary<K: TryInto<HeaderName>>(mut self, name: K) -> Self {
+        if let Ok(name) = name.try_into() {
+            self.vary.push(name);
+        }
+        self
+    }
+
+    /// Sets how long responses without `max-age` or `s-maxage` are fresh.
+    /// (defaults to not caching them)
+    #[must_use]
+    pub fn default_ttl(self, ttl: Duration) -> Self {
+        Self {
+            default_ttl: Some(ttl),
+            ..self
+        }
+    }
+
+    fn key(&self, req: &Request) -> String {
+        let uri = req.uri();
+        let path = uri
+            .path_and_query()
+            .map(|path| path.as_str())
+            .unwrap_or_else(|| uri.path());
+        let mut key = format!("{} {}", req.method(), path);
+        for name in &self.vary {
+            key.push('\n');
+            key.push_str(name.as_str());
+            key.push(':');
+            let values = req
+                .headers()
+                .get_all(name)
+                .iter()
+                .map(|value| value.to_str().unwrap_or_default())
+                .collect::<Vec<_>>();
+            key.push_str(&values.join(","));
+        }
+        key
+    }
+
+    /// Returns `max-age` and `stale-while-revalidate` if the response can be
+    /// stored.
+    fn freshness(&self, resp: &Response, authorized: bool) -> Option<(Duration, Duration)> {
+        let cacheable_status = matches!(
+            resp.status().as_u16(),
+            200 | 203 | 204 | 300 | 301 | 308 | 404 | 405 | 410 | 414 | 501
+        );
+        if !cacheable_status || resp.headers().contains_key(header::SET_COOKIE) {
+            return None;
+        }
+
+        let cache_control = CacheControl::new(resp.headers());
+        if ["no-store", "no-cache", "private"]
+            .iter()
+            .any(|name| cache_control.has(name))
+        {
+            return None;
+        }
+        if authorized && !cache_control.has("public") && !cache_control.has("s-maxage") {
+            return None;
+        }
+
+        for value in resp.headers().get_all(header::VARY) {
+            for name in value.to_str().ok()?.split(',').map(str::trim) {
+                if !self
+                    .vary
+                    .iter()
+                    .any(|vary| vary.as_str().eq_ignore_ascii_case(name))
+                {
+                    return None;
+                }
+            }
+        }
+
+        let max_age = cache_control
+            .seconds("s-maxage")
+            .or_else(|| cache_control.seconds("max-age"))
+            .or(self.default_ttl)?;
+        let stale_while_revalidate = cache_control
+            .seconds("stale-while-revalidate")
+            .unwrap_or_default();
+        if max_age.is_zero() && stale_while_revalidate.is_zero() {
+            return None;
+        }
+
+        Some((max_age, stale_while_revalidate))
+    }
+}
+
+impl<E: Endpoint + 'static, S: CacheStore + 'static> Middleware<E> for Cache<S> {
+    type Output = CacheEndpoint<E, S>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        CacheEndpoint {
+            shared: Arc::new(Shared {
+                inner: ep,
+                config: self.clone(),
+                inflight: Default::default(),
+            }),
+        }
+    }
+}
+
+/// Endpoint for the Cache middleware.
+#[cfg_attr(docsrs, doc(cfg(feature = "cache")))]
+pub struct CacheEndpoint<E, S> {
+    shared: Arc<Shared<E, S>>,
+}
+
+type InflightMap = Mutex<HashMap<String, broadcast::Sender<Option<CachedResponse>>>>;
+
+struct Shared<E, S> {
+    inner: E,
+    config: Cache<S>,
+    inflight: Arc<InflightMap>,
+}
+
+/// Removes the key from the in-flight map when the fetch completes or is
+/// cancelled.
+struct Inflight {
+    map: Arc<InflightMap>,
+    key: String,
+    finished: bool,
+}
+
+impl Inflight {
+    /// Returns `None` if another request is fetching the key.
+    fn start(map: &Arc<InflightMap>, key: &str) -> Option<Self> {
+        let mut inflight = map.lock();
+        if inflight.contains_key(key) {
+            return None;
+        }
+        inflight.insert(key.to_string(), broadcast::channel(1).0);
+        Some(Self {
+            map: map.clone(),
+            key: key.to_string(),
+            finished: false,
+        })
+    }
+
+    fn finish(mut self, resp: Option<CachedResponse>) {
+        self.finished = true;
+        if let Some(tx) = self.map.lock().remove(&self.key) {
+            _ = tx.send(resp);
+        }
+    }
+}
+
+impl Drop for Inflight {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.map.lock().remove(&self.key);
+        }
+    }
+}
+
+fn cached_response(cached: CachedResponse, status: &'static str) -> Response {
+    let age = cached.age().as_secs();
+    let mut resp = Response::builder().status(cached.status).body(cached.body);
+    *resp.headers_mut() = cached.headers;
+    resp.headers_mut()
+        .insert(header::AGE, HeaderValue::from(age));
+    resp.headers_mut()
+        .insert(X_CACHE, HeaderValue::from_static(status));
+    resp
+}
+
+impl<E: Endpoint, S: CacheStore> Shared<E, S> {
+    /// Calls the endpoint and stores the response if it can be stored.
+    async fn fetch(&self, key: &str, req: Request) -> Result<(Response, Option<CachedResponse>)> {
+        let authorized = req.headers().contains_key(header::AUTHORIZATION);
+        let resp = self.inner.call(req).await?.into_response();
+        let Some((max_age, stale_while_revalidate)) = self.config.freshness(&resp, authorized)
+        else {
+            return Ok((resp, None));
+        };
+
+        let (parts, body) = resp.into_parts();
+        if body.exact_size().is_none() {
+            return Ok((Response::from_parts(parts, body), None));
+        }
+        let body = body.into_bytes().await?;
+        let cached = CachedResponse {
+            status: parts.status,
+            headers: parts.headers.clone(),
+            body: body.clone(),
+            created_at: SystemTime::now(),
+            max_age,
+            stale_while_revalidate,
+        };
+        let ttl = max_age + stale_while_revalidate;
+        if let Err(err) = self.config.store.set(key, cached.clone(), ttl).await {
+            tracing::warn!(error = %err, "failed to write the response cache");
+        }
+        Ok((
+            Response::from_parts(parts, Body::from_bytes(body)),
+            Some(cached),
+        ))
+    }
+
+    async fn lookup(&self, key: &str) -> Option<CachedResponse> {
+        match self.config.store.get(key).await {
+            Ok(cached) => cached,
+            Err(err) => {
+                tracing::warn!(error = %err, "failed to read the response cache");
+                None
+            }
+        }
+    }
+}
+
+impl<E: Endpoint + 'static, S: CacheStore + 'static> Endpoint for CacheEndpoint<E, S> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let shared = &self.shared;
+        if req.method() != Method::GET {
+            return Ok(shared.inner.call(req).await?.into_response());
+        }
+
+        let cache_control = CacheControl::new(req.headers());
+        if cache_control.has("no-store") {
+            return Ok(shared.inner.call(req).await?.into_response());
+        }
+
+        let key = shared.config.key(&req);
+        let revalidate = cache_control.has("no-cache")
+            || cache_control.seconds("max-age") == Some(Duration::ZERO);
+        if !revalidate {
+            if let Some(cached) = shared.lookup(&key).await {
+                let age = cached.age();
+                if age < cached.max_age {
+                    return Ok(cached_response(cached, "HIT"));
+                }
+                if age < cached.max_age + cached.stale_while_revalidate {
+                    if let Some(inflight) = Inflight::start(&shared.inflight, &key) {
+                        let shared = shared.clone();
+                        let req = req.clone_without_body();
+                        tokio::spawn(async move {
+                            if let Ok((_, cached)) = shared.fetch(&inflight.key, req).await {
+                                inflight.finish(cached);
+                            }
+                        });
+                    }
+                    return Ok(cached_response(cached, "STALE"));
+                }
+            }
+        }
+
+        let mut resp = match Inflight::start(&shared.inflight, &key) {
+            Some(inflight) => {
+                let (resp, cached) = shared.fetch(&key, req).await?;
+                inflight.finish(cached);
+                resp
+            }
+            None => {
+                // wait for the concurrent request that is fetching the same key
+                let rx = shared.inflight.lock().get(&key).map(|tx| tx.subscribe());
+                match rx {
+                    Some(mut rx) => match rx.recv().await {
+                        Ok(Some(cached)) => return Ok(cached_response(cached, "HIT")),
+                        _ => shared.fetch(&key, req).await?.0,
+                    },
+                    None => shared.fetch(&key, req).await?.0,
+                }
+            }
+        };
+        resp.headers_mut()
+            .insert(X_CACHE, HeaderValue::from_static("MISS"));
+        Ok(resp)
+    }
+
+    fn describe_routes(&self) -> RouteNode {
+        self.shared.inner.describe_routes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use futures_util::future::join_all;
+
+    use super::*;
+    use crate::{EndpointExt, endpoint::make, test::TestClient};
+
+    fn counter_endpoint(
+        cache_control: &'static str,
+    ) -> (Arc<AtomicUsize>, impl Endpoint<Output = Response>) {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let ep = make({
+            let counter = counter.clone();
+            move |req: Request| {
+                let counter = counter.clone();
+                async move {
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                    let n = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                    Response::builder()
+                        .header(header::CACHE_CONTROL, cache_control)
+                        .body(format!("{} {n}", req.uri().path()))
+                }
+            }
+        });
+        (counter, ep)
+    }
+
+    #[tokio::test]
+    async fn hit_and_miss() {
+        let (counter, ep) = counter_endpoint("max-age=60");
+        let cli = TestClient::new(ep.with(Cache::new(MemoryCacheStore::new(1024))));
+
+        let resp = cli.get("/a").send().await;
+        resp.assert_header(X_CACHE, "MISS");
+        resp.assert_text("/a 1").await;
+
+        let resp = cli.get("/a").send().await;
+        resp.assert_header(X_CACHE, "HIT");
+        resp.assert_header(header::AGE, "0");
+        resp.assert_header(header::CACHE_CONTROL, "max-age=60");
+        resp.assert_text("/a 1").await;
+
+        cli.get("/a?q=1").send().await.assert_text("/a 2").await;
+        cli.post("/a").send().await.assert_text("/a 3").await;
+
+        cli.get("/a")
+            .header(header::CACHE_CONTROL, "no-store")
+            .send()
+            .await
+            .assert_text("/a 4")
+            .await;
+        cli.get("/a").send().await.assert_text("/a 1").await;
+
+        cli.get("/a")
+            .header(header::CACHE_CONTROL, "no-cache")
+            .send()
+            .await
+            .assert_text("/a 5")
+            .await;
+        cli.get("/a").send().await.assert_text("/a 5").await;
+        assert_eq!(counter.load(Ordering::SeqCst), 5);
+    }
+
+    #[tokio::test]
+    async fn not_stored() {
+        for cache_control in ["no-store", "private, max-age=60", "no-cache", ""] {
+            let (counter, ep) = counter_endpoint(cache_control);
+            let cli = TestClient::new(ep.with(Cache::new(MemoryCacheStore::new(1024))));
+            cli.get("/").send().await.assert_header(X_CACHE, "MISS");
+            cli.get("/").send().await.assert_header(X_CACHE, "MISS");
+            assert_eq!(counter.load(Ordering::SeqCst), 2);
+        }
+
+        let (counter, ep) = counter_endpoint("max-age=60");
+        let cli = TestClient::new(ep.with(Cache::new(MemoryCacheStore::new(1024))));
+        for _ in 0..2 {
+            cli.get("/")
+                .header(header::AUTHORIZATION, "Bearer token")
+                .send()
+                .await
+                .assert_header(X_CACHE, "MISS");
+        }
+        assert_eq!(counter.load(Ordering::SeqCst), 2);
+
+        let (counter, ep) = counter_endpoint("");
+        let cache = Cache::new(MemoryCacheStore::new(1024)).default_ttl(Duration::from_secs(60));
+        let cli = TestClient::new(ep.with(cache));
+        cli.get("/").send().await.assert_header(X_CACHE, "MISS");
+        cli.get("/").send().await.assert_header(X_CACHE, "HIT");
+        assert_eq!(counter.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn vary() {
+        let ep = make(|req: Request| async move {
+            let lang = req
+                .header(header::ACCEPT_LANGUAGE)
+                .unwrap_or("en")
+                .to_string();
+            let vary = req
+                .header("x-vary")
+                .unwrap_or("accept-language")
+                .to_string();
+            Response::builder()
+                .header(header::CACHE_CONTROL, "max-age=60")
+                .header(header::VARY, vary)
+                .body(lang)
+        });
+        let cli = TestClient::new(
+            ep.with(Cache::new(MemoryCacheStore::new(1024)).vary(header::ACCEPT_LANGUAGE)),
+        );
+
+        for lang in ["en", "fr", "en"] {
+            let resp = cli
+                .get("/")
+                .header(header::ACCEPT_LANGUAGE, lang)
+                .send()
+                .await;
+            resp.assert_text(lang).await;
+        }
+        cli.get("/")
+            .header(header::ACCEPT_LANGUAGE, "fr")
+            .send()
+            .await
+            .assert_header(X_CACHE, "HIT");
+
+        // varies on a header that is not in the key
+        for _ in 0..2 {
+            cli.get("/other")
+                .header("x-vary", "accept-encoding")
+                .send()
+                .await
+                .assert_header(X_CACHE, "MISS");
+        }
+    }
+
+    #[tokio::test]
+    async fn stale_while_revalidate() {
+        let (counter, ep) = counter_endpoint("max-age=60, stale-while-revalidate=60");
+        let store = Arc::new(MemoryCacheStore::new(1024));
+        let cli = TestClient::new(ep.with(Cache::new(store.clone())));
+
+        let key = "GET /";
+        let mut stale = store.get(key).await.unwrap();
+        assert!(stale.is_none());
+        cli.get("/").send().await.assert_text("/ 1").await;
+        stale = store.get(key).await.unwrap();
+        let mut stale = stale.unwrap();
+        stale.created_at -= Duration::from_secs(90);
+        store
+            .set(key, stale, Duration::from_secs(60))
+            .await
+            .unwrap();
+
+        let resp = cli.get("/").send().await;
+        resp.assert_header(X_CACHE, "STALE");
+        resp.assert_header(header::AGE, "90");
+        resp.assert_text("/ 1").await;
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(counter.load(Ordering::SeqCst), 2);
+        let resp = cli.get("/").send().await;
+        resp.assert_header(X_CACHE, "HIT");
+        resp.assert_text("/ 2").await;
+    }
+
+    #[tokio::test]
+    async fn coalesce_concurrent_misses() {
+        let (counter, ep) = counter_endpoint("max-age=60");
+        let cli = TestClient::new(ep.with(Cache::new(MemoryCacheStore::new(1024))));
+
+        let resps = join_all((0..5).map(|_| cli.get("/").send())).await;
+        for resp in resps {
+            resp.assert_text("/ 1").await;
+        }
+        assert_eq!(counter.load(Ordering::SeqCst), 1);
+
+        // uncacheable responses are fetched by each request
+        let (counter, ep) = counter_endpoint("no-store");
+        let cli = TestClient::new(ep.with(Cache::new(MemoryCacheStore::new(1024))));
+        join_all((0..5).map(|_| cli.get("/").send())).await;
+        assert_eq!(counter.load(Ordering::SeqCst), 5);
+    }
+}
@@ -0,0 +1,96 @@
+use std::{
+    future::Future,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use bytes::Bytes;
+
+use crate::{
+    Result,
+    http::{HeaderMap, StatusCode},
+};
+
+/// A response stored by the [`Cache`](super::Cache) middleware.
+#[derive(Debug, Clone)]
+pub struct CachedResponse {
+    /// Status code.
+    pub status: StatusCode,
+
+    /// Response headers.
+    pub headers: HeaderMap,
+
+    /// Response body.
+    pub body: Bytes,
+
+    /// The time when the response was generated.
+    pub created_at: SystemTime,
+
+    /// How long the response is fresh.
+    pub max_age: Duration,
+
+    /// How long the response can be served after it becomes stale, while it
+    /// is revalidated in the background.
+    pub stale_while_revalidate: Duration,
+}
+
+impl CachedResponse {
+    /// Returns the time since the response was generated.
+    pub fn age(&self) -> Duration {
+        SystemTime::now()
+            .duration_since(self.created_at)
+            .unwrap_or_default()
+    }
+
+    /// Returns the approximate size of the response in bytes.
+    pub fn size(&self) -> usize {
+        let headers = self
+            .headers
+            .iter()
+            .map(|(name, value)| name.as_str().len() + value.len())
+            .sum::<usize>();
+        headers + self.body.len()
+    }
+}
+
+/// Represents a back-end storage of the [`Cache`](super::Cache) middleware.
+pub trait CacheStore: Send + Sync {
+    /// Load a response.
+    fn get<'a>(
+        &'a self,
+        key: &'a str,
+    ) -> impl Future<Output = Result<Option<CachedResponse>>> + Send + 'a;
+
+    /// Insert or replace a response, which can be removed after `ttl`.
+    fn set<'a>(
+        &'a self,
+        key: &'a str,
+        resp: CachedResponse,
+        ttl: Duration,
+    ) -> impl Future<Output = Result<()>> + Send + 'a;
+
+    /// Remove a response.
+    fn remove<'a>(&'a self, key: &'a str) -> impl Future<Output = Result<()>> + Send + 'a;
+}
+
+impl<T: CacheStore> CacheStore for Arc<T> {
+    fn get<'a>(
+        &'a self,
+        key: &'a str,
+    ) -> impl Future<Output = Result<Option<CachedResponse>>> + Send + 'a {
+        self.as_ref().get(key)
+    }
+
+    fn set<'a>(
+        &'a self,
+        key: &'a str,
+        resp: CachedResponse,
+        ttl: Duration,
+    ) -> impl Future<Output = Result<()>> + Send + 'a {
+        self.as_ref().set(key, resp, ttl)
+    }
+
+    fn remove<'a>(&'a self, key: &'a str) -> impl Future<Output = Result<()>> + Send + 'a {
+        self.as_ref().remove(key)
+    }
+}
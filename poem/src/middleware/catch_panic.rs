@@ -3,7 +3,7 @@ use std::{any::Any, panic::AssertUnwindSafe};
 use futures_util::FutureExt;
 use http::StatusCode;
 
-use crate::{Endpoint, IntoResponse, Middleware, Request, Response, Result};
+use crate::{Endpoint, IntoResponse, Middleware, Request, Response, Result, route::RouteNode};
 
 /// Panics handler
 pub trait PanicHandler: Clone + Sync + Send + 'static {
@@ -137,4 +137,8 @@ impl<E: Endpoint, H: PanicHandler> Endpoint for CatchPanicEndpoint<E, H> {
             Err(err) => Ok(self.panic_handler.get_response(err).into_response()),
         }
     }
+
+    fn describe_routes(&self) -> RouteNode {
+        self.inner.describe_routes()
+    }
 }
@@ -3,8 +3,10 @@ use std::{collections::HashSet, str::FromStr};
 use headers::HeaderMap;
 
 use crate::{
-    Body, Endpoint, IntoResponse, Middleware, Request, Response, Result,
+    Endpoint, IntoResponse, Middleware, Request, Response, Result,
     http::header,
+    middleware::Decompression,
+    route::RouteNode,
     web::{Compress, CompressionAlgo, CompressionLevel},
 };
 
@@ -77,11 +79,19 @@ fn parse_accept_encoding(
 /// The decompression algorithm is selected according to the request
 /// `Content-Encoding` header, and the compression algorithm is selected
 /// according to the request `Accept-Encoding` header.
+///
+/// The request body is decompressed with the [`Decompression`] policy, which
+/// can be set with [`Compression::decompression`].
+///
+/// # Errors
+///
+/// - [`DecompressionError`](crate::error::DecompressionError)
 #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
 #[derive(Default)]
 pub struct Compression {
     level: Option<CompressionLevel>,
     algorithms: HashSet<CompressionAlgo>,
+    decompression: Decompression,
 }
 
 impl Compression {
@@ -110,6 +120,16 @@ impl Compression {
             ..self
         }
     }
+
+    /// Specify the policy to decompress the request body, such as the limits
+    /// of the decompressed size.
+    #[must_use]
+    pub fn decompression(self, decompression: Decompression) -> Self {
+        Self {
+            decompression,
+            ..self
+        }
+    }
 }
 
 impl<E: Endpoint> Middleware<E> for Compression {
@@ -120,6 +140,7 @@ impl<E: Endpoint> Middleware<E> for Compression {
             ep,
             level: self.level,
             algorithms: self.algorithms.clone(),
+            decompression: self.decompression.clone(),
         }
     }
 }
@@ -130,6 +151,7 @@ pub struct CompressionEndpoint<E: Endpoint> {
     ep: E,
     level: Option<CompressionLevel>,
     algorithms: HashSet<CompressionAlgo>,
+    decompression: Decompression,
 }
 
 #[inline]
@@ -146,17 +168,9 @@ fn coding_priority(c: &ContentCoding) -> u8 {
 impl<E: Endpoint> Endpoint for CompressionEndpoint<E> {
     type Output = Response;
 
-    async fn call(&self, mut req: Request) -> Result<Self::Output> {
+    async fn call(&self, req: Request) -> Result<Self::Output> {
         // decompress request body
-        if let Some(algo) = req
-            .headers()
-            .get(header::CONTENT_ENCODING)
-            .and_then(|value| value.to_str().ok())
-            .and_then(|value| CompressionAlgo::from_str(value).ok())
-        {
-            let new_body = algo.decompress(req.take_body().into_async_read());
-            req.set_body(Body::from_async_read(new_body));
-        }
+        let req = self.decompression.decompress(req)?;
 
         // negotiate content-encoding
         let compress_algo =
@@ -179,6 +193,10 @@ impl<E: Endpoint> Endpoint for CompressionEndpoint<E> {
             None => Ok(resp.into_response()),
         }
     }
+
+    fn describe_routes(&self) -> RouteNode {
+        self.ep.describe_routes()
+    }
 }
 
 #[cfg(test)]
@@ -186,7 +204,7 @@ mod tests {
     use tokio::io::AsyncReadExt;
 
     use super::*;
-    use crate::{EndpointExt, handler, test::TestClient};
+    use crate::{Body, EndpointExt, handler, test::TestClient};
 
     const DATA: &str = "abcdefghijklmnopqrstuvwxyz1234567890";
     const DATA_REV: &str = "0987654321zyxwvutsrqponmlkjihgfedcba";
@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use crate::{
     Endpoint, IntoResponse, Middleware, Request, Response, Result,
+    route::RouteNode,
     web::cookie::{CookieJar, CookieKey},
 };
 
@@ -64,6 +65,10 @@ impl<E: Endpoint> Endpoint for CookieJarManagerEndpoint<E> {
             self.inner.call(req).await.map(IntoResponse::into_response)
         }
     }
+
+    fn describe_routes(&self) -> RouteNode {
+        self.inner.describe_routes()
+    }
 }
 
 #[cfg(test)]
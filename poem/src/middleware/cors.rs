@@ -16,6 +16,7 @@ use crate::{
     middleware::Middleware,
     request::Request,
     response::Response,
+    route::RouteNode,
 };
 
 /// Middleware for CORS
@@ -415,6 +416,10 @@ impl<E: Endpoint> Endpoint for CorsEndpoint<E> {
 
         Ok(resp)
     }
+
+    fn describe_routes(&self) -> RouteNode {
+        self.inner.describe_routes()
+    }
 }
 
 #[cfg(test)]
@@ -9,6 +9,7 @@ use libcsrf::{
 use crate::{
     Endpoint, Middleware, Request, Result,
     middleware::{CookieJarManager, CookieJarManagerEndpoint},
+    route::RouteNode,
     web::{
         CsrfToken, CsrfVerifier,
         cookie::{Cookie, SameSite},
@@ -255,6 +256,10 @@ impl<E: Endpoint> Endpoint for CsrfEndpoint<E> {
 
         self.inner.call(req).await
     }
+
+    fn describe_routes(&self) -> RouteNode {
+        self.inner.describe_routes()
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,375 @@
+use std::{
+    collections::HashSet,
+    io::{Error as IoError, Result as IoResult},
+    pin::Pin,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    task::{Context, Poll, ready},
+};
+
+use tokio::io::{AsyncRead, ReadBuf};
+
+use crate::{
+    Body, Endpoint, Middleware, Request, Result, error::DecompressionError, http::header,
+    middleware::size_limit::Decompressed, route::RouteNode, web::CompressionAlgo,
+};
+
+/// The ratio is only checked after the decompressed size exceeds this size, so
+/// that small and highly compressible payloads are not rejected.
+const MIN_RATIO_CHECK_SIZE: u64 = 64 * 1024;
+
+/// Middleware to decompress the request body according to the
+/// `Content-Encoding` header.
+///
+/// It can be used alone, or as the decompression policy of the
+/// [`Compression`](crate::middleware::Compression) middleware. Since a small
+/// compressed payload can be decompressed into a huge one, the decompressed
+/// size and the compression ratio should be limited.
+///
+/// The `Content-Encoding` and `Content-Length` headers are removed from the
+/// decompressed request, and [`SizeLimit`](crate::middleware::SizeLimit)
+/// counts the decompressed bytes when it is applied after this middleware.
+///
+/// # Errors
+///
+/// - [`DecompressionError`]
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     EndpointExt, handler,
+///     http::StatusCode,
+///     middleware::{Compression, Decompression},
+///     test::TestClient,
+/// };
+///
+/// #[handler]
+/// fn index(data: String) -> String {
+///     data
+/// }
+///
+/// let decompression = Decompression::new().max_size(1024 * 1024).max_ratio(100);
+/// let app = index.with(Compression::new().decompression(decompression));
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = TestClient::new(app)
+///     .post("/")
+///     .header("content-encoding", "compress")
+///     .body("abc")
+///     .send()
+///     .await;
+/// resp.assert_status(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+/// # });
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+#[derive(Debug, Clone, Default)]
+pub struct Decompression {
+    algorithms: HashSet<CompressionAlgo>,
+    max_size: Option<u64>,
+    max_ratio: Option<u64>,
+}
+
+impl Decompression {
+    /// Creates a new `Decompression` middleware.
+    ///
+    /// By default, all the algorithms are enabled and there are no limits.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Specify the enabled algorithms (defaults to all)
+    #[must_use]
+    pub fn algorithms(self, algorithms: impl IntoIterator<Item = CompressionAlgo>) -> Self {
+        Self {
+            algorithms: algorithms.into_iter().collect(),
+            ..self
+        }
+    }
+
+    /// Sets the maximum size of the decompressed body in bytes.
+    #[must_use]
+    pub fn max_size(self, max_size: u64) -> Self {
+        Self {
+            max_size: Some(max_size),
+            ..self
+        }
+    }
+
+    /// Sets the maximum ratio of the decompressed size to the compressed size.
+    ///
+    /// The ratio is checked after the decompressed size exceeds 64 KiB.
+    #[must_use]
+    pub fn max_ratio(self, max_ratio: u64) -> Self {
+        Self {
+            max_ratio: Some(max_ratio),
+            ..self
+        }
+    }
+
+    fn is_enabled(&self, algo: CompressionAlgo) -> bool {
+        self.algorithms.is_empty() || self.algorithms.contains(&algo)
+    }
+
+    pub(crate) fn decompress(&self, mut req: Request) -> Result<Request> {
+        let Some(content_encoding) = req.headers().get(header::CONTENT_ENCODING) else {
+            return Ok(req);
+        };
+        let content_encoding = content_encoding
+            .to_str()
+            .map_err(|_| DecompressionError::UnsupportedEncoding("<invalid>".to_string()))?;
+
+        // the codings are listed in the order in which they were applied
+        let mut algorithms = Vec::new();
+        for coding in content_encoding.split(',').map(str::trim) {
+            if coding.is_empty() || coding.eq_ignore_ascii_case("identity") {
+                continue;
+            }
+            match coding.to_ascii_lowercase().parse::<CompressionAlgo>() {
+                Ok(algo) if self.is_enabled(algo) => algorithms.push(algo),
+                _ => {
+                    let err = DecompressionError::UnsupportedEncoding(coding.to_string());
+                    return Err(err.into());
+                }
+            }
+        }
+
+        req.headers_mut().remove(header::CONTENT_ENCODING);
+        if algorithms.is_empty() {
+            return Ok(req);
+        }
+        req.headers_mut().remove(header::CONTENT_LENGTH);
+
+        let compressed = Arc::new(AtomicU64::new(0));
+        let mut reader: Pin<Box<dyn AsyncRead + Send>> = Box::pin(CountReader {
+            inner: req.take_body().into_async_read(),
+            count: compressed.clone(),
+        });
+        for algo in algorithms.into_iter().rev() {
+            reader = algo.decompress(reader);
+        }
+        req.set_body(Body::from_async_read(DecompressReader {
+            inner: reader,
+            compressed,
+            decompressed: 0,
+            max_size: self.max_size,
+            max_ratio: self.max_ratio,
+        }));
+        req.set_data(Decompressed);
+        Ok(req)
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for Decompression {
+    type Output = DecompressionEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        DecompressionEndpoint {
+            inner: ep,
+            config: self.clone(),
+        }
+    }
+}
+
+/// Endpoint for the Decompression middleware.
+#[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+pub struct DecompressionEndpoint<E> {
+    inner: E,
+    config: Decompression,
+}
+
+impl<E: Endpoint> Endpoint for DecompressionEndpoint<E> {
+    type Output = E::Output;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let req = self.config.decompress(req)?;
+        self.inner.call(req).await
+    }
+
+    fn describe_routes(&self) -> RouteNode {
+        self.inner.describe_routes()
+    }
+}
+
+/// Counts the bytes read from the compressed body.
+struct CountReader<R> {
+    inner: R,
+    count: Arc<AtomicU64>,
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for CountReader<R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<IoResult<()>> {
+        let filled = buf.filled().len();
+        ready!(Pin::new(&mut self.inner).poll_read(cx, buf))?;
+        let n = (buf.filled().len() - filled) as u64;
+        self.count.fetch_add(n, Ordering::Relaxed);
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Checks the limits while reading the decompressed body.
+struct DecompressReader<R> {
+    inner: R,
+    compressed: Arc<AtomicU64>,
+    decompressed: u64,
+    max_size: Option<u64>,
+    max_ratio: Option<u64>,
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for DecompressReader<R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<IoResult<()>> {
+        let filled = buf.filled().len();
+        ready!(Pin::new(&mut self.inner).poll_read(cx, buf))?;
+        self.decompressed += (buf.filled().len() - filled) as u64;
+
+        if self
+            .max_size
+            .is_some_and(|max_size| self.decompressed > max_size)
+        {
+            return Poll::Ready(Err(IoError::other(DecompressionError::PayloadTooLarge)));
+        }
+
+        if let Some(max_ratio) = self.max_ratio {
+            let compressed = self.compressed.load(Ordering::Relaxed).max(1);
+            if self.decompressed > MIN_RATIO_CHECK_SIZE
+                && self.decompressed / compressed > max_ratio
+            {
+                return Poll::Ready(Err(IoError::other(DecompressionError::RatioTooLarge)));
+            }
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::StatusCode;
+
+    use super::*;
+    use crate::{EndpointExt, handler, middleware::SizeLimit, test::TestClient};
+
+    #[handler(internal)]
+    async fn index(data: String) -> String {
+        data
+    }
+
+    fn gzip(data: &[u8]) -> Body {
+        let reader = std::io::Cursor::new(data.to_vec());
+        Body::from_async_read(CompressionAlgo::GZIP.compress(reader, None))
+    }
+
+    #[tokio::test]
+    async fn decompression() {
+        let cli = TestClient::new(index.with(Decompression::new()));
+
+        let resp = cli
+            .post("/")
+            .header("content-encoding", "gzip")
+            .body(gzip(b"abc"))
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_text("abc").await;
+
+        let body =
+            CompressionAlgo::BR.compress(CompressionAlgo::GZIP.compress(&b"abc"[..], None), None);
+        let body = Body::from_bytes(Body::from_async_read(body).into_bytes().await.unwrap());
+        let resp = cli
+            .post("/")
+            .header("content-encoding", "gzip, br")
+            .body(body)
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_text("abc").await;
+
+        let resp = cli
+            .post("/")
+            .header("content-encoding", "identity")
+            .body("abc")
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_text("abc").await;
+
+        cli.post("/")
+            .header("content-encoding", "compress")
+            .body("abc")
+            .send()
+            .await
+            .assert_status(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let cli =
+            TestClient::new(index.with(Decompression::new().algorithms([CompressionAlgo::BR])));
+        cli.post("/")
+            .header("content-encoding", "gzip")
+            .body(gzip(b"abc"))
+            .send()
+            .await
+            .assert_status(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+
+    #[tokio::test]
+    async fn decompression_limits() {
+        let data = vec![b'a'; 1024 * 1024];
+
+        let cli = TestClient::new(index.with(Decompression::new().max_size(1024)));
+        cli.post("/")
+            .header("content-encoding", "gzip")
+            .body(gzip(&data[..1024]))
+            .send()
+            .await
+            .assert_status_is_ok();
+        cli.post("/")
+            .header("content-encoding", "gzip")
+            .body(gzip(&data[..1025]))
+            .send()
+            .await
+            .assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+
+        let cli = TestClient::new(index.with(Decompression::new().max_ratio(100)));
+        cli.post("/")
+            .header("content-encoding", "gzip")
+            .body(gzip(&data))
+            .send()
+            .await
+            .assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+        cli.post("/")
+            .header("content-encoding", "gzip")
+            .body(gzip(&data[..1024]))
+            .send()
+            .await
+            .assert_status_is_ok();
+    }
+
+    #[tokio::test]
+    async fn size_limit_counts_decompressed_bytes() {
+        let data = vec![b'a'; 1024];
+        let cli = TestClient::new(index.with(SizeLimit::new(100)).with(Decompression::new()));
+
+        cli.post("/")
+            .header("content-encoding", "gzip")
+            .body(gzip(&data[..100]))
+            .send()
+            .await
+            .assert_status_is_ok();
+        cli.post("/")
+            .header("content-encoding", "gzip")
+            .body(gzip(&data))
+            .send()
+            .await
+            .assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
@@ -0,0 +1,668 @@
+use std::{fmt::Write, time::SystemTime};
+
+use headers::{
+    ETag as ETagHeader, HeaderMapExt, IfMatch, IfModifiedSince, IfNoneMatch, IfUnmodifiedSince,
+    LastModified,
+};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    Body, Endpoint, IntoResponse, Middleware, Request, Response, Result,
+    error::PreconditionFailedError,
+    http::{Method, StatusCode, header},
+    route::RouteNode,
+};
+
+/// The headers that are copied from the `200 OK` response to the
+/// `304 Not Modified` response.
+const NOT_MODIFIED_HEADERS: &[header::HeaderName] = &[
+    header::CACHE_CONTROL,
+    header::CONTENT_LOCATION,
+    header::DATE,
+    header::ETAG,
+    header::EXPIRES,
+    header::VARY,
+];
+
+/// Middleware to add the `ETag` header to responses and evaluate the
+/// conditional request headers.
+///
+/// For `GET` requests, the validator of a `200 OK` response is the `ETag`
+/// header set by the handler, or a SHA-256 hash of the body if the body is buffered in
+/// memory. Streaming bodies are not hashed. If `If-None-Match` or
+/// `If-Modified-Since` (compared with the `Last-Modified` header) shows that
+/// the client has the current representation, `304 Not Modified` is returned.
+///
+/// The body of a `HEAD` response is empty, so only the `ETag` and
+/// `Last-Modified` headers set by the handler are used for `HEAD` requests.
+///
+/// The preconditions of `PUT`, `PATCH` and `DELETE` requests are passed to the
+/// endpoint unchanged, unless [`ETag::probe_with_get`] is enabled.
+///
+/// Note that `If-Match` uses the strong comparison, so weak validators never
+/// match it.
+///
+/// # Errors
+///
+/// - [`PreconditionFailedError`]
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     EndpointExt, Route, get, handler,
+///     http::{StatusCode, header},
+///     middleware::ETag,
+///     test::TestClient,
+/// };
+///
+/// #[handler]
+/// fn index() -> &'static str {
+///     "hello"
+/// }
+///
+/// let app = Route::new().at("/", get(index)).with(ETag::new());
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/").send().await;
+/// resp.assert_status_is_ok();
+/// let etag = resp.0.headers().get(header::ETAG).unwrap().clone();
+///
+/// cli.get("/")
+///     .header(header::IF_NONE_MATCH, etag)
+///     .send()
+///     .await
+///     .assert_status(StatusCode::NOT_MODIFIED);
+/// # });
+/// ```
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ETag {
+    weak: bool,
+    probe_with_get: bool,
+}
+
+impl ETag {
+    /// Creates a new `ETag` middleware.
+    #[must_use]
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Generates weak validators for the hashed bodies. (defaults to `false`)
+    #[must_use]
+    pub fn weak(self, weak: bool) -> Self {
+        Self { weak, ..self }
+    }
+
+    /// Evaluates the `If-Match`, `If-Unmodified-Since` and `If-None-Match`
+    /// headers of `PUT`, `PATCH` and `DELETE` requests by first calling the
+    /// endpoint with a `GET` request for the same URI, and returns
+    /// `412 Precondition Failed` without calling the endpoint if the
+    /// precondition fails. (defaults to `false`)
+    ///
+    /// This runs the `GET` handler, including its side effects, before each
+    /// conditional request. The check is **not atomic**: the resource can be
+    /// changed by another request between the `GET` and the real call, so
+    /// handlers which need strict optimistic concurrency control must compare
+    /// the validators themselves while holding the resource.
+    ///
+    /// If the `GET` request fails with `404 Not Found`, `405 Method Not
+    /// Allowed` or `410 Gone`, there is no current representation, other
+    /// errors are returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use poem::{
+    ///     EndpointExt, Route, get, handler,
+    ///     http::{StatusCode, header},
+    ///     middleware::ETag,
+    ///     test::TestClient,
+    /// };
+    ///
+    /// #[handler]
+    /// fn index() -> &'static str {
+    ///     "hello"
+    /// }
+    ///
+    /// let app = Route::new()
+    ///     .at("/", get(index).put(index))
+    ///     .with(ETag::new().probe_with_get(true));
+    /// let cli = TestClient::new(app);
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// cli.put("/")
+    ///     .header(header::IF_MATCH, "\"outdated\"")
+    ///     .send()
+    ///     .await
+    ///     .assert_status(StatusCode::PRECONDITION_FAILED);
+    /// # });
+    /// ```
+    #[must_use]
+    pub fn probe_with_get(self, enable: bool) -> Self {
+        Self {
+            probe_with_get: enable,
+            ..self
+        }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for ETag {
+    type Output = ETagEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        ETagEndpoint {
+            inner: ep,
+            weak: self.weak,
+            probe_with_get: self.probe_with_get,
+        }
+    }
+}
+
+/// Endpoint for the ETag middleware.
+pub struct ETagEndpoint<E> {
+    inner: E,
+    weak: bool,
+    probe_with_get: bool,
+}
+
+/// The validators of the current representation.
+struct Validators {
+    etag: Option<ETagHeader>,
+    last_modified: Option<SystemTime>,
+}
+
+struct Conditions {
+    if_match: Option<IfMatch>,
+    if_unmodified_since: Option<IfUnmodifiedSince>,
+    if_none_match: Option<IfNoneMatch>,
+    if_modified_since: Option<IfModifiedSince>,
+}
+
+enum Outcome {
+    Pass,
+    NotModified,
+    PreconditionFailed,
+}
+
+impl Conditions {
+    fn new(req: &Request) -> Self {
+        Self {
+            if_match: req.headers().typed_get(),
+            if_unmodified_since: req.headers().typed_get(),
+            if_none_match: req.headers().typed_get(),
+            if_modified_since: req.headers().typed_get(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.if_match.is_none()
+            && self.if_unmodified_since.is_none()
+            && self.if_none_match.is_none()
+    }
+
+    /// Evaluates the preconditions in the order of RFC 9110, section 13.2.2.
+    ///
+    /// `current` is `None` if there is no current representation.
+    fn evaluate(&self, method: &Method, current: Option<&Validators>) -> Outcome {
+        if let Some(if_match) = &self.if_match {
+            let passes = current.is_some_and(|current| {
+                if_match.is_any()
+                    || current
+                        .etag
+                        .as_ref()
+                        .is_some_and(|etag| if_match.precondition_passes(etag))
+            });
+            if !passes {
+                return Outcome::PreconditionFailed;
+            }
+        } else if let Some(if_unmodified_since) = &self.if_unmodified_since {
+            let passes = current
+                .and_then(|current| current.last_modified)
+                .is_some_and(|modified| if_unmodified_since.precondition_passes(modified));
+            if !passes {
+                return Outcome::PreconditionFailed;
+            }
+        }
+
+        let is_get = matches!(*method, Method::GET | Method::HEAD);
+        if let Some(if_none_match) = &self.if_none_match {
+            let matches = current.is_some_and(|current| {
+                if_none_match == &IfNoneMatch::any()
+                    || current
+                        .etag
+                        .as_ref()
+                        .is_some_and(|etag| !if_none_match.precondition_passes(etag))
+            });
+            if matches {
+                return match is_get {
+                    true => Outcome::NotModified,
+                    false => Outcome::PreconditionFailed,
+                };
+            }
+        } else if let (true, Some(if_modified_since)) = (is_get, &self.if_modified_since) {
+            let modified = current.and_then(|current| current.last_modified);
+            if modified.is_some_and(|modified| !if_modified_since.is_modified(modified)) {
+                return Outcome::NotModified;
+            }
+        }
+
+        Outcome::Pass
+    }
+}
+
+/// Returns the hex encoded SHA-256 digest of the data, which is collision
+/// resistant, so it can be used as a strong validator.
+fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .fold(String::with_capacity(64), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        })
+}
+
+impl<E: Endpoint> ETagEndpoint<E> {
+    /// Returns the validators of the response, and hashes the body if the
+    /// handler did not provide an `ETag` header and `hash_body` is `true`.
+    async fn validators(&self, resp: Response, hash_body: bool) -> Result<(Response, Validators)> {
+        let last_modified = resp
+            .headers()
+            .typed_get::<LastModified>()
+            .map(SystemTime::from);
+        if let Some(etag) = resp.headers().typed_get::<ETagHeader>() {
+            let validators = Validators {
+                etag: Some(etag),
+                last_modified,
+            };
+            return Ok((resp, validators));
+        }
+
+        let (mut parts, body) = resp.into_parts();
+        if !hash_body || body.exact_size().is_none() {
+            let validators = Validators {
+                etag: None,
+                last_modified,
+            };
+            return Ok((Response::from_parts(parts, body), validators));
+        }
+
+        let data = body.into_bytes().await?;
+        let tag = format!(
+            "{}\"{:x}-{}\"",
+            if self.weak { "W/" } else { "" },
+            data.len(),
+            sha256_hex(&data)
+        );
+        let etag = tag.parse::<ETagHeader>().ok();
+        if let Some(etag) = &etag {
+            parts.headers.typed_insert(etag.clone());
+        }
+        let resp = Response::from_parts(parts, Body::from_bytes(data));
+        Ok((
+            resp,
+            Validators {
+                etag,
+                last_modified,
+            },
+        ))
+    }
+}
+
+fn not_modified(resp: &Response) -> Response {
+    let mut not_modified = Response::builder()
+        .status(StatusCode::NOT_MODIFIED)
+        .finish();
+    for name in NOT_MODIFIED_HEADERS {
+        for value in resp.headers().get_all(name) {
+            not_modified.headers_mut().append(name, value.clone());
+        }
+    }
+    not_modified
+}
+
+impl<E: Endpoint> Endpoint for ETagEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let conditions = Conditions::new(&req);
+        let method = req.method().clone();
+
+        match method {
+            Method::GET | Method::HEAD => {
+                let resp = self.inner.call(req).await?.into_response();
+                if resp.status() != StatusCode::OK {
+                    return Ok(resp);
+                }
+                // the body of a HEAD response is empty, hashing it would give
+                // a different tag from the GET response
+                let (resp, validators) = self.validators(resp, method == Method::GET).await?;
+                match conditions.evaluate(&method, Some(&validators)) {
+                    Outcome::Pass => Ok(resp),
+                    Outcome::NotModified => Ok(not_modified(&resp)),
+                    Outcome::PreconditionFailed => Err(PreconditionFailedError.into()),
+                }
+            }
+            Method::PUT | Method::PATCH | Method::DELETE
+                if self.probe_with_get && !conditions.is_empty() =>
+            {
+                let mut probe = req.clone_without_body();
+                probe.set_method(Method::GET);
+                for name in [
+                    header::IF_MATCH,
+                    header::IF_UNMODIFIED_SINCE,
+                    header::IF_NONE_MATCH,
+                    header::IF_MODIFIED_SINCE,
+                    header::CONTENT_TYPE,
+                    header::CONTENT_LENGTH,
+                    header::CONTENT_ENCODING,
+                ] {
+                    probe.headers_mut().remove(name);
+                }
+
+                let current = match self.inner.call(probe).await {
+                    Ok(resp) => {
+                        let resp = resp.into_response();
+                        match resp.status() {
+                            StatusCode::OK => Some(self.validators(resp, true).await?.1),
+                            _ => None,
+                        }
+                    }
+                    Err(err)
+                        if matches!(
+                            err.status(),
+                            StatusCode::NOT_FOUND
+                                | StatusCode::METHOD_NOT_ALLOWED
+                                | StatusCode::GONE
+                        ) =>
+                    {
+                        None
+                    }
+                    Err(err) => return Err(err),
+                };
+                match conditions.evaluate(&method, current.as_ref()) {
+                    Outcome::PreconditionFailed => Err(PreconditionFailedError.into()),
+                    _ => Ok(self.inner.call(req).await?.into_response()),
+                }
+            }
+            _ => Ok(self.inner.call(req).await?.into_response()),
+        }
+    }
+
+    fn describe_routes(&self) -> RouteNode {
+        self.inner.describe_routes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures_util::stream;
+
+    use super::*;
+    use crate::{
+        EndpointExt, Route, get, handler,
+        test::TestClient,
+        web::{Path, headers::HeaderValue},
+    };
+
+    #[handler(internal)]
+    fn index() -> &'static str {
+        "hello"
+    }
+
+    fn etag_of(resp: &crate::test::TestResponse) -> HeaderValue {
+        resp.0.headers().get(header::ETAG).unwrap().clone()
+    }
+
+    #[tokio::test]
+    async fn hashed_etag() {
+        let cli = TestClient::new(index.with(ETag::new()));
+
+        let resp = cli.get("/").send().await;
+        resp.assert_status_is_ok();
+        let etag = etag_of(&resp);
+        assert_eq!(
+            etag,
+            "\"5-2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824\""
+        );
+        resp.assert_text("hello").await;
+
+        let resp = cli
+            .get("/")
+            .header(header::IF_NONE_MATCH, etag.clone())
+            .send()
+            .await;
+        resp.assert_status(StatusCode::NOT_MODIFIED);
+        resp.assert_header(header::ETAG, etag.to_str().unwrap());
+        resp.assert_text("").await;
+
+        cli.get("/")
+            .header(header::IF_NONE_MATCH, "\"other\"")
+            .send()
+            .await
+            .assert_status_is_ok();
+        cli.get("/")
+            .header(header::IF_NONE_MATCH, "*")
+            .send()
+            .await
+            .assert_status(StatusCode::NOT_MODIFIED);
+        cli.get("/")
+            .header(header::IF_MATCH, "\"other\"")
+            .send()
+            .await
+            .assert_status(StatusCode::PRECONDITION_FAILED);
+
+        let cli = TestClient::new(index.with(ETag::new().weak(true)));
+        let resp = cli.get("/").send().await;
+        let weak_etag = etag_of(&resp);
+        assert_eq!(weak_etag, format!("W/{}", etag.to_str().unwrap()));
+        cli.get("/")
+            .header(header::IF_NONE_MATCH, etag)
+            .send()
+            .await
+            .assert_status(StatusCode::NOT_MODIFIED);
+    }
+
+    #[tokio::test]
+    async fn handler_provided_validators() {
+        let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+
+        #[handler(internal)]
+        fn index() -> Response {
+            Response::builder()
+                .header(header::ETAG, "\"v1\"")
+                .header(header::LAST_MODIFIED, "Sun, 09 Sep 2001 01:46:40 GMT")
+                .header(header::CACHE_CONTROL, "no-cache")
+                .body("hello")
+        }
+
+        let cli = TestClient::new(index.with(ETag::new()));
+
+        let resp = cli.get("/").send().await;
+        resp.assert_header(header::ETAG, "\"v1\"");
+
+        let resp = cli
+            .get("/")
+            .header(header::IF_NONE_MATCH, "\"v0\", \"v1\"")
+            .send()
+            .await;
+        resp.assert_status(StatusCode::NOT_MODIFIED);
+        resp.assert_header(header::CACHE_CONTROL, "no-cache");
+        resp.assert_header_is_not_exist(header::LAST_MODIFIED);
+
+        cli.get("/")
+            .typed_header(IfModifiedSince::from(modified))
+            .send()
+            .await
+            .assert_status(StatusCode::NOT_MODIFIED);
+        cli.get("/")
+            .typed_header(IfModifiedSince::from(modified - Duration::from_secs(1)))
+            .send()
+            .await
+            .assert_status_is_ok();
+        cli.get("/")
+            .typed_header(IfUnmodifiedSince::from(modified - Duration::from_secs(1)))
+            .send()
+            .await
+            .assert_status(StatusCode::PRECONDITION_FAILED);
+    }
+
+    #[tokio::test]
+    async fn streaming_body() {
+        #[handler(internal)]
+        fn index() -> Body {
+            Body::from_bytes_stream(stream::iter([Ok::<_, std::io::Error>("hello")]))
+        }
+
+        let cli = TestClient::new(index.with(ETag::new()));
+        let resp = cli.get("/").send().await;
+        resp.assert_status_is_ok();
+        resp.assert_header_is_not_exist(header::ETAG);
+        resp.assert_text("hello").await;
+    }
+
+    #[tokio::test]
+    async fn optimistic_concurrency() {
+        use std::sync::{Arc, Mutex};
+
+        #[handler(internal)]
+        fn get_value(value: crate::web::Data<&Arc<Mutex<String>>>) -> String {
+            value.lock().unwrap().clone()
+        }
+
+        #[handler(internal)]
+        fn put_value(
+            Path(new_value): Path<String>,
+            value: crate::web::Data<&Arc<Mutex<String>>>,
+        ) -> String {
+            *value.lock().unwrap() = new_value.clone();
+            new_value
+        }
+
+        let value = Arc::new(Mutex::new("a".to_string()));
+        let app = Route::new()
+            .at("/:value", get(get_value).put(put_value).patch(put_value))
+            .with(ETag::new().probe_with_get(true))
+            .data(value.clone());
+        let cli = TestClient::new(app);
+
+        let resp = cli.get("/x").send().await;
+        let etag = etag_of(&resp);
+
+        cli.put("/b")
+            .header(header::IF_MATCH, etag.clone())
+            .send()
+            .await
+            .assert_text("b")
+            .await;
+        assert_eq!(*value.lock().unwrap(), "b");
+
+        // the representation has changed
+        cli.patch("/c")
+            .header(header::IF_MATCH, etag)
+            .send()
+            .await
+            .assert_status(StatusCode::PRECONDITION_FAILED);
+        assert_eq!(*value.lock().unwrap(), "b");
+
+        // create only if the resource does not exist
+        cli.put("/c")
+            .header(header::IF_NONE_MATCH, "*")
+            .send()
+            .await
+            .assert_status(StatusCode::PRECONDITION_FAILED);
+
+        // unconditional requests are not checked
+        cli.put("/c").send().await.assert_text("c").await;
+    }
+
+    #[tokio::test]
+    async fn probe_disabled() {
+        let cli = TestClient::new(
+            Route::new()
+                .at("/", get(index).put(index))
+                .with(ETag::new()),
+        );
+        cli.put("/")
+            .header(header::IF_MATCH, "\"outdated\"")
+            .send()
+            .await
+            .assert_status_is_ok();
+    }
+
+    #[tokio::test]
+    async fn probe_error() {
+        #[handler(internal)]
+        fn fail() -> Result<()> {
+            Err(crate::Error::from_status(StatusCode::SERVICE_UNAVAILABLE))
+        }
+
+        let cli = TestClient::new(
+            Route::new()
+                .at("/", get(fail).put(index))
+                .with(ETag::new().probe_with_get(true)),
+        );
+        cli.put("/")
+            .header(header::IF_MATCH, "*")
+            .send()
+            .await
+            .assert_status(StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn head() {
+        let cli = TestClient::new(Route::new().at("/", get(index)).with(ETag::new()));
+        let etag = etag_of(&cli.get("/").send().await);
+
+        // the empty body of the HEAD response is not hashed
+        let resp = cli.head("/").send().await;
+        resp.assert_status_is_ok();
+        resp.assert_header_is_not_exist(header::ETAG);
+        cli.head("/")
+            .header(header::IF_NONE_MATCH, etag)
+            .send()
+            .await
+            .assert_status_is_ok();
+
+        #[handler(internal)]
+        fn tagged() -> Response {
+            Response::builder()
+                .header(header::ETAG, "\"v1\"")
+                .body("hello")
+        }
+
+        let cli = TestClient::new(Route::new().at("/", get(tagged)).with(ETag::new()));
+        let resp = cli.head("/").send().await;
+        resp.assert_header(header::ETAG, "\"v1\"");
+        cli.head("/")
+            .header(header::IF_NONE_MATCH, "\"v1\"")
+            .send()
+            .await
+            .assert_status(StatusCode::NOT_MODIFIED);
+    }
+
+    #[tokio::test]
+    async fn missing_resource() {
+        #[handler(internal)]
+        fn put() {}
+
+        let cli = TestClient::new(
+            Route::new()
+                .at("/", crate::put(put))
+                .with(ETag::new().probe_with_get(true)),
+        );
+        cli.put("/")
+            .header(header::IF_MATCH, "*")
+            .send()
+            .await
+            .assert_status(StatusCode::PRECONDITION_FAILED);
+        cli.put("/")
+            .header(header::IF_NONE_MATCH, "*")
+            .send()
+            .await
+            .assert_status_is_ok();
+    }
+}
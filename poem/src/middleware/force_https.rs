@@ -2,7 +2,9 @@ use std::{borrow::Cow, sync::Arc};
 
 use http::{Uri, header, uri::Scheme};
 
-use crate::{Endpoint, IntoResponse, Middleware, Request, Response, Result, web::Redirect};
+use crate::{
+    Endpoint, IntoResponse, Middleware, Request, Response, Result, route::RouteNode, web::Redirect,
+};
 
 type FilterFn = Arc<dyn Fn(&Request) -> bool + Send + Sync>;
 
@@ -86,6 +88,10 @@ where
 
         self.inner.call(req).await.map(IntoResponse::into_response)
     }
+
+    fn describe_routes(&self) -> RouteNode {
+        self.inner.describe_routes()
+    }
 }
 
 fn redirect_host(host: &str, https_port: Option<u16>) -> Cow<'_, str> {
@@ -0,0 +1,201 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use parking_lot::RwLock;
+use reqwest::Client;
+use serde::Deserialize;
+
+use super::{
+    KeyEntry,
+    key::{DecodingKey, Jwk},
+};
+use crate::error::JwtError;
+
+fn build_client(connect_timeout: Duration, timeout: Duration) -> Client {
+    Client::builder()
+        .connect_timeout(connect_timeout)
+        .timeout(timeout)
+        .build()
+        .expect("create the http client")
+}
+
+#[derive(Deserialize)]
+struct JwkSet {
+    keys: Vec<Jwk>,
+}
+
+#[derive(Default)]
+struct State {
+    keys: Arc<Vec<KeyEntry>>,
+    fetched_at: Option<Instant>,
+    attempted_at: Option<Instant>,
+}
+
+/// A JSON Web Key Set fetched from a URL, such as the `jwks_uri` of an OpenID
+/// Connect provider.
+///
+/// The keys are cached for [`cache_ttl`](Jwks::cache_ttl), and refetched
+/// earlier when a token references an unknown `kid`, so that rotated keys
+/// are picked up. Refetching is rate limited by
+/// [`min_refresh_interval`](Jwks::min_refresh_interval), and the cached keys
+/// are kept if a refetch fails.
+///
+/// The tokens with an unknown `kid` wait for the running refetch, so the
+/// requests to the URL are bounded by
+/// [`connect_timeout`](Jwks::connect_timeout) and
+/// [`timeout`](Jwks::timeout).
+#[cfg_attr(docsrs, doc(cfg(feature = "jwt")))]
+pub struct Jwks {
+    url: String,
+    client: Client,
+    connect_timeout: Duration,
+    timeout: Duration,
+    cache_ttl: Duration,
+    min_refresh_interval: Duration,
+    state: RwLock<State>,
+    refresh_lock: tokio::sync::Mutex<()>,
+}
+
+impl Jwks {
+    /// Create a `Jwks` that fetches the keys from the URL.
+    pub fn new(url: impl Into<String>) -> Self {
+        let connect_timeout = Duration::from_secs(5);
+        let timeout = Duration::from_secs(10);
+        Self {
+            url: url.into(),
+            client: build_client(connect_timeout, timeout),
+            connect_timeout,
+            timeout,
+            cache_ttl: Duration::from_secs(60 * 60),
+            min_refresh_interval: Duration::from_secs(30),
+            state: Default::default(),
+            refresh_lock: Default::default(),
+        }
+    }
+
+    /// Sets the timeout for connecting to the URL, default is 5 seconds.
+    #[must_use]
+    pub fn connect_timeout(self, timeout: Duration) -> Self {
+        Self {
+            client: build_client(timeout, self.timeout),
+            connect_timeout: timeout,
+            ..self
+        }
+    }
+
+    /// Sets the timeout of fetching the keys, from connecting until the
+    /// response body is read, default is 10 seconds.
+    #[must_use]
+    pub fn timeout(self, timeout: Duration) -> Self {
+        Self {
+            client: build_client(self.connect_timeout, timeout),
+            timeout,
+            ..self
+        }
+    }
+
+    /// Sets how long the fetched keys are cached, default is 1 hour.
+    #[must_use]
+    pub fn cache_ttl(self, ttl: Duration) -> Self {
+        Self {
+            cache_ttl: ttl,
+            ..self
+        }
+    }
+
+    /// Sets the minimum interval between two fetches, default is 30 seconds.
+    #[must_use]
+    pub fn min_refresh_interval(self, interval: Duration) -> Self {
+        Self {
+            min_refresh_interval: interval,
+            ..self
+        }
+    }
+
+    /// Returns the keys, refetching them if they have expired or none of them
+    /// has the `kid`.
+    pub(crate) async fn keys(&self, kid: Option<&str>) -> Result<Arc<Vec<KeyEntry>>, JwtError> {
+        if let Some(keys) = self.cached_keys(kid, false) {
+            return Ok(keys);
+        }
+
+        let _guard = self.refresh_lock.lock().await;
+        if let Some(keys) = self.cached_keys(kid, true) {
+            return Ok(keys);
+        }
+
+        self.state.write().attempted_at = Some(Instant::now());
+        match self.fetch().await {
+            Ok(keys) => {
+                let keys = Arc::new(keys);
+                let mut state = self.state.write();
+                state.keys = keys.clone();
+                state.fetched_at = Some(Instant::now());
+                Ok(keys)
+            }
+            Err(err) => {
+                let state = self.state.read();
+                if state.fetched_at.is_none() {
+                    return Err(err);
+                }
+                tracing::warn!(url = %self.url, error = %err, "failed to refresh jwks");
+                Ok(state.keys.clone())
+            }
+        }
+    }
+
+    /// Returns the cached keys if they can be used without a fetch.
+    ///
+    /// When `rate_limited` is `true`, the keys are also returned if the last
+    /// fetch is within the minimum refresh interval.
+    fn cached_keys(&self, kid: Option<&str>, rate_limited: bool) -> Option<Arc<Vec<KeyEntry>>> {
+        let state = self.state.read();
+        let fetched_at = state.fetched_at?;
+        let fresh = fetched_at.elapsed() < self.cache_ttl
+            && kid.is_none_or(|kid| state.keys.iter().any(|key| key.kid.as_deref() == Some(kid)));
+        let throttled = rate_limited
+            && state
+                .attempted_at
+                .is_some_and(|attempted_at| attempted_at.elapsed() < self.min_refresh_interval);
+        (fresh || throttled).then(|| state.keys.clone())
+    }
+
+    async fn fetch(&self) -> Result<Vec<KeyEntry>, JwtError> {
+        let set = self
+            .client
+            .get(&self.url)
+            .send()
+            .await
+            .and_then(|resp| resp.error_for_status())
+            .map_err(|err| JwtError::FetchJwks(err.to_string()))?
+            .json::<JwkSet>()
+            .await
+            .map_err(|err| JwtError::FetchJwks(err.to_string()))?;
+
+        Ok(set
+            .keys
+            .iter()
+            .filter(|jwk| {
+                jwk.key_use
+                    .as_deref()
+                    .is_none_or(|key_use| key_use == "sig")
+            })
+            .filter_map(|jwk| {
+                let key = DecodingKey::from_jwk(jwk)
+                    .inspect_err(|_| tracing::debug!(kid = ?jwk.kid, "skip invalid jwk"))
+                    .ok()?;
+                let alg = match jwk.alg.as_deref() {
+                    Some(alg) => Some(alg.parse().ok()?),
+                    None => None,
+                };
+                Some(KeyEntry {
+                    kid: jwk.kid.clone(),
+                    alg,
+                    key,
+                })
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,349 @@
+use std::{fmt::Debug, str::FromStr};
+
+use base64::{
+    Engine,
+    engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+};
+use ring::{hmac, signature};
+use serde::Deserialize;
+
+use crate::error::JwtError;
+
+/// The signature algorithms of JSON Web Tokens.
+#[cfg_attr(docsrs, doc(cfg(feature = "jwt")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[allow(missing_docs)]
+pub enum Algorithm {
+    HS256,
+    HS384,
+    HS512,
+    RS256,
+    RS384,
+    RS512,
+    PS256,
+    PS384,
+    PS512,
+    ES256,
+    ES384,
+}
+
+impl Algorithm {
+    /// All the supported algorithms.
+    pub const ALL: &'static [Algorithm] = &[
+        Algorithm::HS256,
+        Algorithm::HS384,
+        Algorithm::HS512,
+        Algorithm::RS256,
+        Algorithm::RS384,
+        Algorithm::RS512,
+        Algorithm::PS256,
+        Algorithm::PS384,
+        Algorithm::PS512,
+        Algorithm::ES256,
+        Algorithm::ES384,
+    ];
+}
+
+impl FromStr for Algorithm {
+    type Err = JwtError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Algorithm::ALL
+            .iter()
+            .find(|alg| format!("{alg:?}") == s)
+            .copied()
+            .ok_or_else(|| JwtError::UnsupportedAlgorithm(s.to_string()))
+    }
+}
+
+#[derive(Clone)]
+enum KeyKind {
+    Hmac(Vec<u8>),
+    Rsa { n: Vec<u8>, e: Vec<u8> },
+    EcP256(Vec<u8>),
+    EcP384(Vec<u8>),
+}
+
+/// A key to verify the signatures of JSON Web Tokens.
+#[cfg_attr(docsrs, doc(cfg(feature = "jwt")))]
+#[derive(Clone)]
+pub struct DecodingKey(KeyKind);
+
+impl Debug for DecodingKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match &self.0 {
+            KeyKind::Hmac(_) => "hmac",
+            KeyKind::Rsa { .. } => "rsa",
+            KeyKind::EcP256(_) => "ec-p256",
+            KeyKind::EcP384(_) => "ec-p384",
+        };
+        f.debug_tuple("DecodingKey").field(&kind).finish()
+    }
+}
+
+impl DecodingKey {
+    /// Create a key for the `HS*` algorithms from the shared secret.
+    pub fn from_secret(secret: impl AsRef<[u8]>) -> Self {
+        Self(KeyKind::Hmac(secret.as_ref().to_vec()))
+    }
+
+    /// Create a key for the `RS*` and `PS*` algorithms from the big-endian
+    /// modulus and exponent.
+    pub fn from_rsa_components(n: impl AsRef<[u8]>, e: impl AsRef<[u8]>) -> Self {
+        Self(KeyKind::Rsa {
+            n: n.as_ref().to_vec(),
+            e: e.as_ref().to_vec(),
+        })
+    }
+
+    /// Create a key for the `ES*` algorithms from the big-endian coordinates
+    /// of the public point.
+    pub fn from_ec_components(x: impl AsRef<[u8]>, y: impl AsRef<[u8]>) -> Result<Self, JwtError> {
+        let (x, y) = (x.as_ref(), y.as_ref());
+        let mut point = Vec::with_capacity(1 + x.len() + y.len());
+        point.push(4);
+        point.extend_from_slice(x);
+        point.extend_from_slice(y);
+        Self::from_ec_point(point)
+    }
+
+    fn from_ec_point(point: Vec<u8>) -> Result<Self, JwtError> {
+        match point.len() {
+            65 => Ok(Self(KeyKind::EcP256(point))),
+            97 => Ok(Self(KeyKind::EcP384(point))),
+            _ => Err(JwtError::InvalidKey),
+        }
+    }
+
+    /// Create a key for the `RS*` and `PS*` algorithms from a PEM encoded
+    /// `PUBLIC KEY` or `RSA PUBLIC KEY`.
+    pub fn from_rsa_pem(pem: impl AsRef<[u8]>) -> Result<Self, JwtError> {
+        let (label, der) = decode_pem(pem.as_ref())?;
+        let der = match label.as_str() {
+            "PUBLIC KEY" => spki_public_key(&der)?,
+            "RSA PUBLIC KEY" => &der,
+            _ => return Err(JwtError::InvalidKey),
+        };
+
+        // RSAPublicKey ::= SEQUENCE { modulus INTEGER, publicExponent INTEGER }
+        let (seq, _) = der_read(der, 0x30)?;
+        let (n, rest) = der_read(seq, 0x02)?;
+        let (e, _) = der_read(rest, 0x02)?;
+        let strip = |int: &[u8]| int.iter().position(|b| *b != 0).unwrap_or_default();
+        Ok(Self::from_rsa_components(&n[strip(n)..], &e[strip(e)..]))
+    }
+
+    /// Create a key for the `ES*` algorithms from a PEM encoded `PUBLIC KEY`.
+    pub fn from_ec_pem(pem: impl AsRef<[u8]>) -> Result<Self, JwtError> {
+        let (label, der) = decode_pem(pem.as_ref())?;
+        if label != "PUBLIC KEY" {
+            return Err(JwtError::InvalidKey);
+        }
+        Self::from_ec_point(spki_public_key(&der)?.to_vec())
+    }
+
+    /// Create a key from a JSON Web Key.
+    pub(crate) fn from_jwk(jwk: &Jwk) -> Result<Self, JwtError> {
+        let decode = |value: &Option<String>| {
+            value
+                .as_deref()
+                .and_then(|value| URL_SAFE_NO_PAD.decode(value).ok())
+                .ok_or(JwtError::InvalidKey)
+        };
+        match jwk.kty.as_str() {
+            "oct" => Ok(Self::from_secret(decode(&jwk.k)?)),
+            "RSA" => Ok(Self::from_rsa_components(decode(&jwk.n)?, decode(&jwk.e)?)),
+            "EC" => Self::from_ec_components(decode(&jwk.x)?, decode(&jwk.y)?),
+            _ => Err(JwtError::InvalidKey),
+        }
+    }
+
+    /// Returns `true` if the key can be used with the algorithm.
+    pub(crate) fn supports(&self, alg: Algorithm) -> bool {
+        use Algorithm::*;
+
+        matches!(
+            (&self.0, alg),
+            (KeyKind::Hmac(_), HS256 | HS384 | HS512)
+                | (
+                    KeyKind::Rsa { .. },
+                    RS256 | RS384 | RS512 | PS256 | PS384 | PS512
+                )
+                | (KeyKind::EcP256(_), ES256)
+                | (KeyKind::EcP384(_), ES384)
+        )
+    }
+
+    pub(crate) fn verify(&self, alg: Algorithm, message: &[u8], sig: &[u8]) -> bool {
+        use Algorithm::*;
+
+        match (&self.0, alg) {
+            (KeyKind::Hmac(secret), HS256 | HS384 | HS512) => {
+                let alg = match alg {
+                    HS256 => hmac::HMAC_SHA256,
+                    HS384 => hmac::HMAC_SHA384,
+                    _ => hmac::HMAC_SHA512,
+                };
+                hmac::verify(&hmac::Key::new(alg, secret), message, sig).is_ok()
+            }
+            (KeyKind::Rsa { n, e }, RS256 | RS384 | RS512 | PS256 | PS384 | PS512) => {
+                let params: &signature::RsaParameters = match alg {
+                    RS256 => &signature::RSA_PKCS1_2048_8192_SHA256,
+                    RS384 => &signature::RSA_PKCS1_2048_8192_SHA384,
+                    RS512 => &signature::RSA_PKCS1_2048_8192_SHA512,
+                    PS256 => &signature::RSA_PSS_2048_8192_SHA256,
+                    PS384 => &signature::RSA_PSS_2048_8192_SHA384,
+                    _ => &signature::RSA_PSS_2048_8192_SHA512,
+                };
+                signature::RsaPublicKeyComponents { n, e }
+                    .verify(params, message, sig)
+                    .is_ok()
+            }
+            (KeyKind::EcP256(point), ES256) => {
+                signature::UnparsedPublicKey::new(&signature::ECDSA_P256_SHA256_FIXED, point)
+                    .verify(message, sig)
+                    .is_ok()
+            }
+            (KeyKind::EcP384(point), ES384) => {
+                signature::UnparsedPublicKey::new(&signature::ECDSA_P384_SHA384_FIXED, point)
+                    .verify(message, sig)
+                    .is_ok()
+            }
+            _ => false,
+        }
+    }
+}
+
+/// A JSON Web Key.
+#[derive(Debug, Deserialize)]
+pub(crate) struct Jwk {
+    pub(crate) kty: String,
+    pub(crate) kid: Option<String>,
+    pub(crate) alg: Option<String>,
+    #[serde(rename = "use")]
+    pub(crate) key_use: Option<String>,
+    k: Option<String>,
+    n: Option<String>,
+    e: Option<String>,
+    x: Option<String>,
+    y: Option<String>,
+}
+
+/// Returns the label and the DER data of the first PEM block.
+fn decode_pem(pem: &[u8]) -> Result<(String, Vec<u8>), JwtError> {
+    let pem = std::str::from_utf8(pem).map_err(|_| JwtError::InvalidKey)?;
+    let mut lines = pem
+        .lines()
+        .map(str::trim)
+        .skip_while(|line| line.is_empty());
+    let label = lines
+        .next()
+        .and_then(|line| line.strip_prefix("-----BEGIN "))
+        .and_then(|line| line.strip_suffix("-----"))
+        .ok_or(JwtError::InvalidKey)?;
+    let data = lines
+        .take_while(|line| !line.starts_with("-----END "))
+        .collect::<String>();
+    let der = STANDARD.decode(data).map_err(|_| JwtError::InvalidKey)?;
+    Ok((label.to_string(), der))
+}
+
+/// Reads a DER element with the tag, and returns its content and the
+/// remaining data.
+fn der_read(data: &[u8], tag: u8) -> Result<(&[u8], &[u8]), JwtError> {
+    let (&actual_tag, data) = data.split_first().ok_or(JwtError::InvalidKey)?;
+    let (&len, mut data) = data.split_first().ok_or(JwtError::InvalidKey)?;
+    if actual_tag != tag {
+        return Err(JwtError::InvalidKey);
+    }
+    let len = match len {
+        0..=0x7f => len as usize,
+        0x81..=0x84 => {
+            let num_bytes = (len & 0x7f) as usize;
+            if data.len() < num_bytes {
+                return Err(JwtError::InvalidKey);
+            }
+            let (len_bytes, rest) = data.split_at(num_bytes);
+            data = rest;
+            len_bytes
+                .iter()
+                .fold(0usize, |len, byte| (len << 8) | *byte as usize)
+        }
+        _ => return Err(JwtError::InvalidKey),
+    };
+    if data.len() < len {
+        return Err(JwtError::InvalidKey);
+    }
+    Ok(data.split_at(len))
+}
+
+/// Returns the public key of a `SubjectPublicKeyInfo`.
+fn spki_public_key(der: &[u8]) -> Result<&[u8], JwtError> {
+    // SubjectPublicKeyInfo ::= SEQUENCE {
+    //     algorithm AlgorithmIdentifier,
+    //     subjectPublicKey BIT STRING
+    // }
+    let (spki, _) = der_read(der, 0x30)?;
+    let (_, rest) = der_read(spki, 0x30)?;
+    let (bits, _) = der_read(rest, 0x03)?;
+    match bits.split_first() {
+        Some((0, key)) => Ok(key),
+        _ => Err(JwtError::InvalidKey),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ring::{
+        rand::SystemRandom,
+        signature::{EcdsaKeyPair, KeyPair},
+    };
+
+    use super::*;
+
+    /// Returns the PEM encoded public key of a generated P-256 key pair.
+    fn ec_public_pem() -> String {
+        const SPKI_PREFIX: &[u8] = &[
+            0x30, 0x59, 0x30, 0x13, 0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01, 0x06,
+            0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07, 0x03, 0x42, 0x00,
+        ];
+
+        let rng = SystemRandom::new();
+        let alg = &signature::ECDSA_P256_SHA256_FIXED_SIGNING;
+        let pkcs8 = EcdsaKeyPair::generate_pkcs8(alg, &rng).unwrap();
+        let key_pair = EcdsaKeyPair::from_pkcs8(alg, pkcs8.as_ref(), &rng).unwrap();
+        let der = [SPKI_PREFIX, key_pair.public_key().as_ref()].concat();
+        format!(
+            "-----BEGIN PUBLIC KEY-----\n{}\n-----END PUBLIC KEY-----\n",
+            base64::engine::general_purpose::STANDARD.encode(der)
+        )
+    }
+
+    #[test]
+    fn parse_pem() {
+        let key =
+            DecodingKey::from_rsa_pem(include_str!("../../../testdata/jwt_auth/rsa_public.pem"))
+                .unwrap();
+        assert!(matches!(&key.0, KeyKind::Rsa { n, e } if n.len() == 256 && e == &[1, 0, 1]));
+        assert!(key.supports(Algorithm::RS256));
+        assert!(key.supports(Algorithm::PS512));
+        assert!(!key.supports(Algorithm::HS256));
+
+        let key = DecodingKey::from_ec_pem(ec_public_pem()).unwrap();
+        assert!(matches!(&key.0, KeyKind::EcP256(_)));
+        assert!(key.supports(Algorithm::ES256));
+        assert!(!key.supports(Algorithm::ES384));
+
+        assert!(
+            DecodingKey::from_rsa_pem(include_str!("../../../testdata/jwt_auth/rsa_private.pem"))
+                .is_err()
+        );
+        assert!(DecodingKey::from_ec_pem("invalid").is_err());
+    }
+
+    #[test]
+    fn parse_algorithm() {
+        assert_eq!("RS256".parse::<Algorithm>().unwrap(), Algorithm::RS256);
+        assert!("none".parse::<Algorithm>().is_err());
+    }
+}
@@ -0,0 +1,773 @@
+mod jwks;
+mod key;
+
+use std::{
+    marker::PhantomData,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use serde::{Deserialize, de::DeserializeOwned};
+
+pub use self::{
+    jwks::Jwks,
+    key::{Algorithm, DecodingKey},
+};
+use crate::{
+    Endpoint, Middleware, Request, Result, error::JwtError, http::header, route::RouteNode,
+    web::JwtClaims,
+};
+
+pub(crate) struct KeyEntry {
+    kid: Option<String>,
+    alg: Option<Algorithm>,
+    key: DecodingKey,
+}
+
+enum KeySource {
+    Static(Arc<Vec<KeyEntry>>),
+    Jwks(Jwks),
+}
+
+#[derive(Deserialize)]
+struct Header {
+    alg: String,
+    kid: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Audience {
+    Single(String),
+    Multiple(Vec<String>),
+}
+
+#[derive(Deserialize)]
+struct RegisteredClaims {
+    exp: Option<f64>,
+    nbf: Option<f64>,
+    iss: Option<String>,
+    aud: Option<Audience>,
+}
+
+#[derive(Clone)]
+struct Config {
+    keys: Arc<KeySource>,
+    algorithms: Vec<Algorithm>,
+    audience: Vec<String>,
+    issuer: Vec<String>,
+    leeway: Duration,
+    require_exp: bool,
+}
+
+/// Middleware for authenticating requests with JSON Web Tokens in the
+/// `Authorization: Bearer` header.
+///
+/// It verifies the `HS*`, `RS*`, `PS*` and `ES*` signatures, the `exp`, `nbf`,
+/// `aud` and `iss` claims with a [`leeway`](JwtAuth::leeway) for the clock
+/// skew, and then puts the claims deserialized as `T` into the request
+/// extensions, which can be extracted with [`JwtClaims`].
+///
+/// Requests without a token are rejected with
+/// [`JwtError::MissingToken`], unless the middleware is
+/// [`optional`](JwtAuth::optional).
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     EndpointExt, Route, get, handler,
+///     middleware::{DecodingKey, JwtAuth},
+///     web::JwtClaims,
+/// };
+/// use serde::Deserialize;
+///
+/// #[derive(Debug, Clone, Deserialize)]
+/// struct Claims {
+///     sub: String,
+/// }
+///
+/// #[handler]
+/// fn index(claims: JwtClaims<Claims>) -> String {
+///     format!("hello {}", claims.sub)
+/// }
+///
+/// let app = Route::new().at("/", get(index)).with(
+///     JwtAuth::<Claims>::new(DecodingKey::from_secret("secret"))
+///         .audience(["my-api"])
+///         .issuer(["https://auth.example.com"]),
+/// );
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "jwt")))]
+pub struct JwtAuth<T> {
+    config: Config,
+    optional: bool,
+    _mark: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for JwtAuth<T> {
+    fn clone(&self) -> Self {
+        Self {
+            config: self.config.clone(),
+            optional: self.optional,
+            _mark: PhantomData,
+        }
+    }
+}
+
+impl<T> JwtAuth<T> {
+    fn with_keys(keys: KeySource) -> Self {
+        Self {
+            config: Config {
+                keys: Arc::new(keys),
+                algorithms: Algorithm::ALL.to_vec(),
+                audience: Vec::new(),
+                issuer: Vec::new(),
+                leeway: Duration::from_secs(60),
+                require_exp: true,
+            },
+            optional: false,
+            _mark: PhantomData,
+        }
+    }
+
+    /// Create a `JwtAuth` that verifies the tokens with the key.
+    pub fn new(key: DecodingKey) -> Self {
+        Self::with_keys(KeySource::Static(Arc::new(vec![KeyEntry {
+            kid: None,
+            alg: None,
+            key,
+        }])))
+    }
+
+    /// Create a `JwtAuth` that verifies the tokens with the keys, which are
+    /// selected by the `kid` header of the tokens.
+    pub fn with_key_ids(keys: impl IntoIterator<Item = (impl Into<String>, DecodingKey)>) -> Self {
+        Self::with_keys(KeySource::Static(Arc::new(
+            keys.into_iter()
+                .map(|(kid, key)| KeyEntry {
+                    kid: Some(kid.into()),
+                    alg: None,
+                    key,
+                })
+                .collect(),
+        )))
+    }
+
+    /// Create a `JwtAuth` that verifies the tokens with the keys of the JSON
+    /// Web Key Set.
+    pub fn from_jwks(jwks: Jwks) -> Self {
+        Self::with_keys(KeySource::Jwks(jwks))
+    }
+
+    /// Sets the allowed algorithms, default is all the supported algorithms.
+    ///
+    /// An algorithm is only accepted if the key is of the same kind, so an
+    /// `HS256` token can never be verified with an RSA public key.
+    #[must_use]
+    pub fn algorithms(mut self, algorithms: impl IntoIterator<Item = Algorithm>) -> Self {
+        self.config.algorithms = algorithms.into_iter().collect();
+        self
+    }
+
+    /// Sets the accepted audiences, the `aud` claim must contain one of them.
+    ///
+    /// The `aud` claim is not checked if no audience is set.
+    #[must_use]
+    pub fn audience(mut self, audience: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.config.audience = audience.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets the accepted issuers, the `iss` claim must be one of them.
+    ///
+    /// The `iss` claim is not checked if no issuer is set.
+    #[must_use]
+    pub fn issuer(mut self, issuer: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.config.issuer = issuer.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets the leeway for the clock skew when checking the `exp` and `nbf`
+    /// claims, default is 60 seconds.
+    #[must_use]
+    pub fn leeway(mut self, leeway: Duration) -> Self {
+        self.config.leeway = leeway;
+        self
+    }
+
+    /// Sets whether the `exp` claim is required, default is `true`.
+    #[must_use]
+    pub fn require_exp(mut self, require_exp: bool) -> Self {
+        self.config.require_exp = require_exp;
+        self
+    }
+
+    /// Sets whether the requests without a token are allowed, default is
+    /// `false`.
+    ///
+    /// The requests with an invalid token are always rejected.
+    #[must_use]
+    pub fn optional(self, optional: bool) -> Self {
+        Self { optional, ..self }
+    }
+}
+
+impl<T: DeserializeOwned> JwtAuth<T> {
+    /// Validates the token and returns the claims.
+    ///
+    /// This can be used to reuse the validation outside of the middleware,
+    /// such as in the checker of a `poem-openapi` security scheme.
+    pub async fn validate(&self, token: &str) -> Result<T, JwtError> {
+        let config = &self.config;
+        let mut parts = token.split('.');
+        let (Some(header), Some(payload), Some(signature), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(JwtError::Malformed);
+        };
+        let decode = |data: &str| {
+            URL_SAFE_NO_PAD
+                .decode(data)
+                .map_err(|_| JwtError::Malformed)
+        };
+
+        let header: Header =
+            serde_json::from_slice(&decode(header)?).map_err(|_| JwtError::Malformed)?;
+        let alg = header.alg.parse::<Algorithm>()?;
+        if !config.algorithms.contains(&alg) {
+            return Err(JwtError::UnsupportedAlgorithm(header.alg));
+        }
+
+        let keys = match &*config.keys {
+            KeySource::Static(keys) => keys.clone(),
+            KeySource::Jwks(jwks) => jwks.keys(header.kid.as_deref()).await?,
+        };
+        let mut candidates = keys
+            .iter()
+            .filter(|entry| {
+                entry.key.supports(alg)
+                    && entry.alg.is_none_or(|key_alg| key_alg == alg)
+                    && (entry.kid.is_none() || entry.kid == header.kid)
+            })
+            .peekable();
+        if candidates.peek().is_none() {
+            return Err(JwtError::UnknownKey);
+        }
+        let message = &token[..token.len() - signature.len() - 1];
+        let signature = decode(signature)?;
+        if !candidates.any(|entry| entry.key.verify(alg, message.as_bytes(), &signature)) {
+            return Err(JwtError::InvalidSignature);
+        }
+
+        let payload = decode(payload)?;
+        let registered: RegisteredClaims = serde_json::from_slice(&payload)
+            .map_err(|err| JwtError::InvalidClaims(err.to_string()))?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+        let leeway = config.leeway.as_secs_f64();
+        match registered.exp {
+            Some(exp) if now - leeway >= exp => return Err(JwtError::Expired),
+            None if config.require_exp => {
+                return Err(JwtError::InvalidClaims("missing `exp`".to_string()));
+            }
+            _ => {}
+        }
+        if registered.nbf.is_some_and(|nbf| now + leeway < nbf) {
+            return Err(JwtError::NotYetValid);
+        }
+        if !config.issuer.is_empty()
+            && !registered
+                .iss
+                .is_some_and(|iss| config.issuer.contains(&iss))
+        {
+            return Err(JwtError::InvalidIssuer);
+        }
+        if !config.audience.is_empty() {
+            let matched = match &registered.aud {
+                Some(Audience::Single(aud)) => config.audience.contains(aud),
+                Some(Audience::Multiple(auds)) => {
+                    auds.iter().any(|aud| config.audience.contains(aud))
+                }
+                None => false,
+            };
+            if !matched {
+                return Err(JwtError::InvalidAudience);
+            }
+        }
+
+        serde_json::from_slice(&payload).map_err(|err| JwtError::InvalidClaims(err.to_string()))
+    }
+}
+
+impl<E, T> Middleware<E> for JwtAuth<T>
+where
+    E: Endpoint,
+    T: DeserializeOwned + Clone + Send + Sync + 'static,
+{
+    type Output = JwtAuthEndpoint<E, T>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        JwtAuthEndpoint {
+            inner: ep,
+            auth: self.clone(),
+        }
+    }
+}
+
+/// Endpoint for the JwtAuth middleware.
+#[cfg_attr(docsrs, doc(cfg(feature = "jwt")))]
+pub struct JwtAuthEndpoint<E, T> {
+    inner: E,
+    auth: JwtAuth<T>,
+}
+
+impl<E, T> Endpoint for JwtAuthEndpoint<E, T>
+where
+    E: Endpoint,
+    T: DeserializeOwned + Clone + Send + Sync + 'static,
+{
+    type Output = E::Output;
+
+    async fn call(&self, mut req: Request) -> Result<Self::Output> {
+        let token = req
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| {
+                let (scheme, token) = value.split_once(' ')?;
+                scheme
+                    .eq_ignore_ascii_case("bearer")
+                    .then(|| token.trim().to_string())
+            });
+
+        match token {
+            Some(token) => {
+                let claims = self.auth.validate(&token).await?;
+                req.extensions_mut().insert(JwtClaims(claims));
+            }
+            None if self.auth.optional => {}
+            None => return Err(JwtError::MissingToken.into()),
+        }
+        self.inner.call(req).await
+    }
+
+    fn describe_routes(&self) -> RouteNode {
+        self.inner.describe_routes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::OnceLock, time::Instant};
+
+    use ring::{
+        hmac,
+        rand::{SecureRandom, SystemRandom},
+        signature::{self, EcdsaKeyPair, KeyPair, RsaKeyPair},
+    };
+    use serde_json::{Value, json};
+
+    use super::*;
+    use crate::{
+        EndpointExt, Route, Server, get, handler,
+        http::StatusCode,
+        listener::{Acceptor, Listener, TcpListener},
+        test::TestClient,
+    };
+
+    #[derive(Debug, Clone, Deserialize, PartialEq)]
+    struct Claims {
+        sub: String,
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    fn pkcs8(pem: &str) -> Vec<u8> {
+        let data = pem
+            .lines()
+            .filter(|line| !line.starts_with("-----"))
+            .collect::<String>();
+        base64::engine::general_purpose::STANDARD
+            .decode(data)
+            .unwrap()
+    }
+
+    /// The RSA key pair is loaded from the test data because `ring` can not
+    /// generate it, the other keys are generated for each test run.
+    fn rsa_key_pair() -> RsaKeyPair {
+        RsaKeyPair::from_pkcs8(&pkcs8(include_str!(
+            "../../../testdata/jwt_auth/rsa_private.pem"
+        )))
+        .unwrap()
+    }
+
+    fn secret() -> &'static [u8] {
+        static SECRET: OnceLock<[u8; 32]> = OnceLock::new();
+        SECRET.get_or_init(|| {
+            let mut secret = [0; 32];
+            SystemRandom::new().fill(&mut secret).unwrap();
+            secret
+        })
+    }
+
+    fn ec_key_pair() -> &'static EcdsaKeyPair {
+        static KEY_PAIR: OnceLock<EcdsaKeyPair> = OnceLock::new();
+        KEY_PAIR.get_or_init(|| {
+            let rng = SystemRandom::new();
+            let alg = &signature::ECDSA_P256_SHA256_FIXED_SIGNING;
+            let pkcs8 = EcdsaKeyPair::generate_pkcs8(alg, &rng).unwrap();
+            EcdsaKeyPair::from_pkcs8(alg, pkcs8.as_ref(), &rng).unwrap()
+        })
+    }
+
+    fn ec_public_key() -> DecodingKey {
+        let point = ec_key_pair().public_key().as_ref();
+        DecodingKey::from_ec_components(&point[1..33], &point[33..]).unwrap()
+    }
+
+    fn sign(alg: Algorithm, kid: Option<&str>, claims: &Value) -> String {
+        let header = json!({ "alg": format!("{alg:?}"), "typ": "JWT", "kid": kid });
+        let message = format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(header.to_string()),
+            URL_SAFE_NO_PAD.encode(claims.to_string())
+        );
+        let rng = SystemRandom::new();
+        let signature = match alg {
+            Algorithm::HS256 => hmac::sign(
+                &hmac::Key::new(hmac::HMAC_SHA256, secret()),
+                message.as_bytes(),
+            )
+            .as_ref()
+            .to_vec(),
+            Algorithm::RS256 | Algorithm::PS256 => {
+                let key_pair = rsa_key_pair();
+                let padding: &dyn signature::RsaEncoding = match alg {
+                    Algorithm::RS256 => &signature::RSA_PKCS1_SHA256,
+                    _ => &signature::RSA_PSS_SHA256,
+                };
+                let mut signature = vec![0; key_pair.public().modulus_len()];
+                key_pair
+                    .sign(padding, &rng, message.as_bytes(), &mut signature)
+                    .unwrap();
+                signature
+            }
+            Algorithm::ES256 => ec_key_pair()
+                .sign(&rng, message.as_bytes())
+                .unwrap()
+                .as_ref()
+                .to_vec(),
+            _ => panic!("unsupported algorithm {alg:?}"),
+        };
+        format!("{message}.{}", URL_SAFE_NO_PAD.encode(signature))
+    }
+
+    #[tokio::test]
+    async fn verify_signatures() {
+        let claims = json!({ "sub": "alice", "exp": now() + 60 });
+        let expected = Claims {
+            sub: "alice".to_string(),
+        };
+
+        let auth = JwtAuth::<Claims>::new(DecodingKey::from_secret(secret()));
+        let token = sign(Algorithm::HS256, None, &claims);
+        assert_eq!(auth.validate(&token).await.unwrap(), expected);
+        let last = if token.ends_with('A') { 'B' } else { 'A' };
+        let tampered = format!("{}{last}", &token[..token.len() - 1]);
+        assert!(matches!(
+            auth.validate(&tampered).await,
+            Err(JwtError::InvalidSignature | JwtError::Malformed)
+        ));
+        assert_eq!(auth.validate("a.b").await.unwrap_err(), JwtError::Malformed);
+
+        let rsa =
+            DecodingKey::from_rsa_pem(include_str!("../../../testdata/jwt_auth/rsa_public.pem"))
+                .unwrap();
+        let auth = JwtAuth::<Claims>::new(rsa.clone());
+        for alg in [Algorithm::RS256, Algorithm::PS256] {
+            let token = sign(alg, None, &claims);
+            assert_eq!(auth.validate(&token).await.unwrap(), expected);
+        }
+        assert_eq!(
+            auth.validate(&sign(Algorithm::HS256, None, &claims))
+                .await
+                .unwrap_err(),
+            JwtError::UnknownKey
+        );
+        let auth = JwtAuth::<Claims>::new(rsa).algorithms([Algorithm::RS256]);
+        assert_eq!(
+            auth.validate(&sign(Algorithm::PS256, None, &claims))
+                .await
+                .unwrap_err(),
+            JwtError::UnsupportedAlgorithm("PS256".to_string())
+        );
+
+        let auth = JwtAuth::<Claims>::with_key_ids([
+            ("hmac", DecodingKey::from_secret(secret())),
+            ("ec", ec_public_key()),
+        ]);
+        let token = sign(Algorithm::ES256, Some("ec"), &claims);
+        assert_eq!(auth.validate(&token).await.unwrap(), expected);
+        let token = sign(Algorithm::ES256, Some("other"), &claims);
+        assert_eq!(
+            auth.validate(&token).await.unwrap_err(),
+            JwtError::UnknownKey
+        );
+    }
+
+    #[tokio::test]
+    async fn validate_claims() {
+        let auth = JwtAuth::<Claims>::new(DecodingKey::from_secret(secret()))
+            .audience(["api"])
+            .issuer(["https://auth.example.com"])
+            .leeway(Duration::from_secs(10));
+        let validate = |claims: Value| {
+            let auth = auth.clone();
+            async move { auth.validate(&sign(Algorithm::HS256, None, &claims)).await }
+        };
+        let now = now();
+
+        assert!(
+            validate(json!({
+                "sub": "alice",
+                "exp": now + 60,
+                "nbf": now - 60,
+                "aud": ["other", "api"],
+                "iss": "https://auth.example.com",
+            }))
+            .await
+            .is_ok()
+        );
+        assert!(
+            validate(json!({
+                "sub": "alice",
+                "exp": now - 5,
+                "nbf": now + 5,
+                "aud": "api",
+                "iss": "https://auth.example.com",
+            }))
+            .await
+            .is_ok()
+        );
+
+        let claims = |exp: u64, nbf: u64, aud: &str, iss: &str| json!({ "sub": "alice", "exp": exp, "nbf": nbf, "aud": aud, "iss": iss });
+        let iss = "https://auth.example.com";
+        assert_eq!(
+            validate(claims(now - 20, now, "api", iss)).await,
+            Err(JwtError::Expired)
+        );
+        assert_eq!(
+            validate(claims(now + 60, now + 20, "api", iss)).await,
+            Err(JwtError::NotYetValid)
+        );
+        assert_eq!(
+            validate(claims(now + 60, now, "other", iss)).await,
+            Err(JwtError::InvalidAudience)
+        );
+        assert_eq!(
+            validate(claims(now + 60, now, "api", "https://evil.com")).await,
+            Err(JwtError::InvalidIssuer)
+        );
+        assert!(matches!(
+            validate(json!({ "sub": "alice", "aud": "api", "iss": iss })).await,
+            Err(JwtError::InvalidClaims(_))
+        ));
+        assert!(matches!(
+            validate(json!({ "exp": now + 60, "aud": "api", "iss": iss })).await,
+            Err(JwtError::InvalidClaims(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn middleware() {
+        #[handler(internal)]
+        fn index(claims: Option<JwtClaims<Claims>>) -> String {
+            claims
+                .map(|claims| claims.0.sub)
+                .unwrap_or_else(|| "anonymous".to_string())
+        }
+
+        let auth = JwtAuth::<Claims>::new(DecodingKey::from_secret(secret()));
+        let token = sign(
+            Algorithm::HS256,
+            None,
+            &json!({ "sub": "alice", "exp": now() + 60 }),
+        );
+
+        let cli = TestClient::new(index.with(auth.clone()));
+        let resp = cli
+            .get("/")
+            .header(header::AUTHORIZATION, format!("Bearer {token}"))
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_text("alice").await;
+
+        let resp = cli.get("/").send().await;
+        resp.assert_status(StatusCode::UNAUTHORIZED);
+        resp.assert_header(header::WWW_AUTHENTICATE, "Bearer");
+
+        let resp = cli
+            .get("/")
+            .header(header::AUTHORIZATION, "Bearer invalid")
+            .send()
+            .await;
+        resp.assert_status(StatusCode::UNAUTHORIZED);
+        resp.assert_header(
+            header::WWW_AUTHENTICATE,
+            r#"Bearer error="invalid_token", error_description="malformed token""#,
+        );
+
+        // the details from the token are not written to the challenge
+        let token = format!(
+            "{}.{}.AA",
+            URL_SAFE_NO_PAD.encode(json!({ "alg": "x\", realm=\"evil" }).to_string()),
+            URL_SAFE_NO_PAD.encode(json!({ "sub": "alice" }).to_string())
+        );
+        let resp = cli
+            .get("/")
+            .header(header::AUTHORIZATION, format!("Bearer {token}"))
+            .send()
+            .await;
+        resp.assert_status(StatusCode::UNAUTHORIZED);
+        resp.assert_header(
+            header::WWW_AUTHENTICATE,
+            r#"Bearer error="invalid_token", error_description="unsupported algorithm""#,
+        );
+
+        let cli = TestClient::new(index.with(auth.optional(true)));
+        let resp = cli.get("/").send().await;
+        resp.assert_status_is_ok();
+        resp.assert_text("anonymous").await;
+        cli.get("/")
+            .header(header::AUTHORIZATION, "Bearer invalid")
+            .send()
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn jwks_rotation() {
+        let rsa_key_pair = rsa_key_pair();
+        let rsa_public = ring::rsa::PublicKeyComponents::<Vec<u8>>::from(rsa_key_pair.public());
+        let rsa_jwk = json!({
+            "kty": "RSA",
+            "kid": "rsa",
+            "use": "sig",
+            "n": URL_SAFE_NO_PAD.encode(&rsa_public.n),
+            "e": URL_SAFE_NO_PAD.encode(&rsa_public.e),
+        });
+        let ec_point = ec_key_pair().public_key().as_ref();
+        let ec_jwk = json!({
+            "kty": "EC",
+            "kid": "ec",
+            "crv": "P-256",
+            "alg": "ES256",
+            "x": URL_SAFE_NO_PAD.encode(&ec_point[1..33]),
+            "y": URL_SAFE_NO_PAD.encode(&ec_point[33..]),
+        });
+        let enc_jwk = json!({ "kty": "oct", "kid": "enc", "use": "enc", "k": URL_SAFE_NO_PAD.encode(secret()) });
+
+        let jwks = Arc::new(parking_lot::Mutex::new(
+            json!({ "keys": [rsa_jwk, enc_jwk] }),
+        ));
+        let fetches = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let app = Route::new()
+            .at(
+                "/jwks",
+                get({
+                    let jwks = jwks.clone();
+                    let fetches = fetches.clone();
+                    crate::endpoint::make_sync(move |_| {
+                        fetches.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                        crate::web::Json(jwks.lock().clone())
+                    })
+                }),
+            )
+            .at(
+                "/slow",
+                get(crate::endpoint::make(|_| async {
+                    tokio::time::sleep(Duration::from_secs(10)).await;
+                })),
+            );
+        let acceptor = TcpListener::bind("127.0.0.1:0")
+            .into_acceptor()
+            .await
+            .unwrap();
+        let addr = acceptor.local_addr().remove(0);
+        tokio::spawn(Server::new_with_acceptor(acceptor).run(app));
+
+        let auth = JwtAuth::<Claims>::from_jwks(
+            Jwks::new(format!("http://{}/jwks", addr.as_socket_addr().unwrap()))
+                .min_refresh_interval(Duration::ZERO),
+        );
+        let claims = json!({ "sub": "alice", "exp": now() + 60 });
+        let fetches = || fetches.load(std::sync::atomic::Ordering::SeqCst);
+
+        let token = sign(Algorithm::RS256, Some("rsa"), &claims);
+        assert!(auth.validate(&token).await.is_ok());
+        assert!(auth.validate(&token).await.is_ok());
+        assert_eq!(fetches(), 1);
+
+        // keys with `use` other than `sig` are ignored
+        let token = sign(Algorithm::HS256, Some("enc"), &claims);
+        assert_eq!(
+            auth.validate(&token).await.unwrap_err(),
+            JwtError::UnknownKey
+        );
+        assert_eq!(fetches(), 2);
+
+        // the rotated key is fetched on demand
+        jwks.lock()["keys"].as_array_mut().unwrap().push(ec_jwk);
+        let token = sign(Algorithm::ES256, Some("ec"), &claims);
+        assert!(auth.validate(&token).await.is_ok());
+        assert_eq!(fetches(), 3);
+
+        // refetching is rate limited
+        let auth = JwtAuth::<Claims>::from_jwks(Jwks::new(format!(
+            "http://{}/jwks",
+            addr.as_socket_addr().unwrap()
+        )));
+        let token = sign(Algorithm::RS256, Some("rsa"), &claims);
+        assert!(auth.validate(&token).await.is_ok());
+        let token = sign(Algorithm::ES256, Some("unknown"), &claims);
+        assert_eq!(
+            auth.validate(&token).await.unwrap_err(),
+            JwtError::UnknownKey
+        );
+        assert_eq!(
+            auth.validate(&token).await.unwrap_err(),
+            JwtError::UnknownKey
+        );
+        assert_eq!(fetches(), 4);
+
+        let auth = JwtAuth::<Claims>::from_jwks(Jwks::new(format!(
+            "http://{}/missing",
+            addr.as_socket_addr().unwrap()
+        )));
+        assert!(matches!(
+            auth.validate(&token).await,
+            Err(JwtError::FetchJwks(_))
+        ));
+
+        let auth = JwtAuth::<Claims>::from_jwks(
+            Jwks::new(format!("http://{}/slow", addr.as_socket_addr().unwrap()))
+                .timeout(Duration::from_millis(100)),
+        );
+        let start = Instant::now();
+        assert!(matches!(
+            auth.validate(&token).await,
+            Err(JwtError::FetchJwks(_))
+        ));
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,278 @@
+use std::str::FromStr;
+
+use http::{HeaderValue, Uri, header};
+use unic_langid::LanguageIdentifier;
+
+use crate::{
+    Endpoint, IntoResponse, Middleware, Request, Response, Result,
+    i18n::{I18NResources, path_prefix},
+    route::RouteNode,
+    web::Redirect,
+};
+
+/// Middleware which redirects the requests to the canonical URL with the
+/// language path prefix, such as `/about` to `/fr/about`.
+///
+/// If the first segment of the path is an available language in the
+/// [`I18NResources`], but not in its canonical form, such as `/FR/about` or
+/// `/fr-fr/about` when `fr-FR` is available, the request is redirected to
+/// `/fr-FR/about`. Otherwise, the language is resolved by the other sources of
+/// the [`LocaleResolver`](crate::i18n::LocaleResolver), and the request is
+/// redirected to the URL prefixed with it.
+///
+/// The target of the redirect depends on the `Cookie` and `Accept-Language`
+/// headers if the resolver uses them, so they are listed in the `Vary` header
+/// of the redirect response to prevent shared caches from serving it to other
+/// users.
+///
+/// The [`I18NResources`] data is required.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     EndpointExt, Route, get, handler,
+///     http::{StatusCode, header},
+///     i18n::{I18NResources, Locale, LocaleResolver},
+///     middleware::LocaleRedirect,
+///     test::TestClient,
+/// };
+///
+/// let resources = I18NResources::builder()
+///     .add_ftl("en-US", "hello-world = hello world!")
+///     .add_ftl("fr", "hello-world = bonjour le monde!")
+///     .locale_resolver(LocaleResolver::new().path_prefix().accept_language())
+///     .build()
+///     .unwrap();
+///
+/// #[handler]
+/// async fn index(locale: Locale) -> String {
+///     locale.text("hello-world").unwrap()
+/// }
+///
+/// let app = Route::new()
+///     .at("/:lang/hello", get(index))
+///     .with(LocaleRedirect::new())
+///     .data(resources);
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli
+///     .get("/hello")
+///     .header(header::ACCEPT_LANGUAGE, "fr")
+///     .send()
+///     .await;
+/// resp.assert_status(StatusCode::TEMPORARY_REDIRECT);
+/// resp.assert_header(header::LOCATION, "/fr/hello");
+///
+/// let resp = cli.get("/fr/hello").send().await;
+/// resp.assert_text("bonjour le monde!").await;
+/// # });
+/// ```
+#[derive(Default)]
+pub struct LocaleRedirect {
+    excludes: Vec<String>,
+}
+
+impl LocaleRedirect {
+    /// Create a new `LocaleRedirect` middleware.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Does not redirect the requests whose path starts with the prefix, such
+    /// as `/api` or `/static`.
+    #[must_use]
+    pub fn exclude(mut self, prefix: impl Into<String>) -> Self {
+        self.excludes.push(prefix.into());
+        self
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for LocaleRedirect {
+    type Output = LocaleRedirectEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        LocaleRedirectEndpoint {
+            inner: ep,
+            excludes: self.excludes.clone(),
+        }
+    }
+}
+
+/// Endpoint for the LocaleRedirect middleware.
+pub struct LocaleRedirectEndpoint<E> {
+    inner: E,
+    excludes: Vec<String>,
+}
+
+impl<E: Endpoint> LocaleRedirectEndpoint<E> {
+    fn is_excluded(&self, path: &str) -> bool {
+        self.excludes.iter().any(|prefix| {
+            path.strip_prefix(prefix.trim_end_matches('/'))
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        })
+    }
+
+    fn redirect_uri(&self, req: &Request) -> Option<String> {
+        let resources = req.extensions().get::<I18NResources>().expect(
+            "To use the `LocaleRedirect` middleware, the `I18NResources` data is required.",
+        );
+        let path = req.uri().path();
+        if self.is_excluded(path) {
+            return None;
+        }
+
+        let prefix = path_prefix(path);
+        let (language, rest) = match prefix
+            .and_then(|prefix| LanguageIdentifier::from_str(prefix).ok())
+            .and_then(|language| resources.matched_language(&[language]))
+        {
+            Some(language) => {
+                let language = language.to_string();
+                if prefix == Some(language.as_str()) {
+                    return None;
+                }
+                (
+                    language,
+                    &path[1 + prefix.map(str::len).unwrap_or_default()..],
+                )
+            }
+            None => {
+                let language = resources
+                    .resolver()
+                    .resolve(resources, req, true)
+                    .map(|(_, language, _)| language)
+                    .unwrap_or_else(|| resources.default_language().clone());
+                (language.to_string(), path)
+            }
+        };
+
+        let rest = if rest == "/" { "" } else { rest };
+        Some(match req.uri().query() {
+            Some(query) => format!("/{language}{rest}?{query}"),
+            None => format!("/{language}{rest}"),
+        })
+    }
+}
+
+impl<E: Endpoint> Endpoint for LocaleRedirectEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        if let Some(uri) = self
+            .redirect_uri(&req)
+            .and_then(|uri| Uri::from_str(&uri).ok())
+        {
+            let mut resp = Redirect::temporary(uri).into_response();
+            let vary = req
+                .extensions()
+                .get::<I18NResources>()
+                .map(|resources| resources.resolver().vary_headers())
+                .unwrap_or_default();
+            for name in vary {
+                resp.headers_mut()
+                    .append(header::VARY, HeaderValue::from_name(name));
+            }
+            return Ok(resp);
+        }
+        self.inner.call(req).await.map(IntoResponse::into_response)
+    }
+
+    fn describe_routes(&self) -> RouteNode {
+        self.inner.describe_routes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::StatusCode;
+
+    use super::*;
+    use crate::{
+        EndpointExt, Route, get, handler,
+        i18n::{Locale, LocaleResolver},
+        test::TestClient,
+    };
+
+    #[handler(internal)]
+    fn index(locale: Locale) -> String {
+        format!("{}:{:?}", locale.language(), locale.source())
+    }
+
+    #[tokio::test]
+    async fn redirect() {
+        let resources = I18NResources::builder()
+            .add_ftl("en-US", "hello = hello")
+            .add_ftl("fr-FR", "hello = bonjour")
+            .locale_resolver(
+                LocaleResolver::new()
+                    .path_prefix()
+                    .cookie("lang")
+                    .accept_language(),
+            )
+            .build()
+            .unwrap();
+        let app = Route::new()
+            .at("/api/hello", get(index))
+            .at("/:lang", get(index))
+            .at("/:lang/hello", get(index))
+            .with(LocaleRedirect::new().exclude("/api"))
+            .data(resources);
+        let cli = TestClient::new(app);
+
+        for (path, cookie, location) in [
+            ("/", None, "/en-US"),
+            ("/hello?a=1", None, "/en-US/hello?a=1"),
+            ("/hello", Some("lang=fr-FR"), "/fr-FR/hello"),
+            ("/fr/hello", None, "/fr-FR/hello"),
+            ("/en-us/hello", Some("lang=fr-FR"), "/en-US/hello"),
+        ] {
+            let mut req = cli.get(path);
+            if let Some(cookie) = cookie {
+                req = req.header(header::COOKIE, cookie);
+            }
+            let resp = req.send().await;
+            resp.assert_status(StatusCode::TEMPORARY_REDIRECT);
+            resp.assert_header(header::LOCATION, location);
+            resp.assert_header_all(header::VARY, ["cookie", "accept-language"]);
+        }
+
+        cli.get("/fr-FR/hello")
+            .header(header::ACCEPT_LANGUAGE, "en-US")
+            .send()
+            .await
+            .assert_text("fr-FR:PathPrefix")
+            .await;
+        cli.get("/fr-FR")
+            .send()
+            .await
+            .assert_text("fr-FR:PathPrefix")
+            .await;
+        cli.get("/api/hello")
+            .send()
+            .await
+            .assert_text("en-US:Default")
+            .await;
+    }
+
+    #[tokio::test]
+    async fn vary() {
+        let resources = I18NResources::builder()
+            .add_ftl("en-US", "hello = hello")
+            .add_ftl("fr-FR", "hello = bonjour")
+            .locale_resolver(LocaleResolver::new().path_prefix().query("lang"))
+            .build()
+            .unwrap();
+        let app = Route::new()
+            .at("/:lang/hello", get(index))
+            .with(LocaleRedirect::new())
+            .data(resources);
+        let cli = TestClient::new(app);
+
+        // the query string is a part of the cache key
+        let resp = cli.get("/hello?lang=fr-FR").send().await;
+        resp.assert_header(header::LOCATION, "/fr-FR/hello?lang=fr-FR");
+        resp.assert_header_is_not_exist(header::VARY);
+    }
+}
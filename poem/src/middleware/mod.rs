@@ -1,6 +1,8 @@
 //! Commonly used middleware.
 
 mod add_data;
+#[cfg(feature = "cache")]
+mod cache;
 mod catch_panic;
 #[cfg(feature = "compression")]
 mod compression;
@@ -9,15 +11,25 @@ mod cookie_jar_manager;
 mod cors;
 #[cfg(feature = "csrf")]
 mod csrf;
+#[cfg(feature = "compression")]
+mod decompression;
+mod etag;
 mod force_https;
+#[cfg(feature = "jwt")]
+mod jwt_auth;
+#[cfg(feature = "i18n")]
+mod locale_redirect;
 mod normalize_path;
 #[cfg(feature = "opentelemetry")]
 mod opentelemetry_metrics;
 #[cfg(feature = "opentelemetry")]
 mod opentelemetry_tracing;
+mod problem_json;
 mod propagate_header;
 #[cfg(feature = "requestid")]
 mod requestid;
+#[cfg(feature = "security-headers")]
+mod security_headers;
 mod sensitive_header;
 mod set_header;
 mod size_limit;
@@ -29,18 +41,31 @@ mod tracing_mw;
 
 use std::marker::PhantomData;
 
+#[cfg(feature = "cache")]
+pub use self::cache::{Cache, CacheEndpoint, CacheStore, CachedResponse, MemoryCacheStore};
 #[cfg(feature = "compression")]
 pub use self::compression::{Compression, CompressionEndpoint};
 #[cfg(feature = "cookie")]
 pub use self::cookie_jar_manager::{CookieJarManager, CookieJarManagerEndpoint};
 #[cfg(feature = "csrf")]
 pub use self::csrf::{Csrf, CsrfEndpoint};
+#[cfg(feature = "compression")]
+pub use self::decompression::{Decompression, DecompressionEndpoint};
+#[cfg(feature = "jwt")]
+pub use self::jwt_auth::{Algorithm, DecodingKey, Jwks, JwtAuth, JwtAuthEndpoint};
+#[cfg(feature = "i18n")]
+pub use self::locale_redirect::{LocaleRedirect, LocaleRedirectEndpoint};
 #[cfg(feature = "opentelemetry")]
 pub use self::opentelemetry_metrics::{OpenTelemetryMetrics, OpenTelemetryMetricsEndpoint};
 #[cfg(feature = "opentelemetry")]
 pub use self::opentelemetry_tracing::{OpenTelemetryTracing, OpenTelemetryTracingEndpoint};
 #[cfg(feature = "requestid")]
 pub use self::requestid::{ReqId, RequestId, RequestIdEndpoint, ReuseId};
+#[cfg(feature = "security-headers")]
+pub use self::security_headers::{
+    ContentSecurityPolicy, CrossOriginEmbedderPolicy, CrossOriginOpenerPolicy, FrameOptions, Hsts,
+    PermissionsPolicy, ReferrerPolicy, SecurityHeaders, SecurityHeadersEndpoint,
+};
 #[cfg(feature = "tokio-metrics")]
 pub use self::tokio_metrics_mw::{TokioMetrics, TokioMetricsEndpoint};
 #[cfg(feature = "tower-compat")]
@@ -49,8 +74,10 @@ pub use self::{
     add_data::{AddData, AddDataEndpoint},
     catch_panic::{CatchPanic, CatchPanicEndpoint, PanicHandler},
     cors::{Cors, CorsEndpoint},
+    etag::{ETag, ETagEndpoint},
     force_https::ForceHttps,
     normalize_path::{NormalizePath, NormalizePathEndpoint, TrailingSlash},
+    problem_json::{ProblemJson, ProblemJsonEndpoint},
     propagate_header::{PropagateHeader, PropagateHeaderEndpoint},
     sensitive_header::{SensitiveHeader, SensitiveHeaderEndpoint},
     set_header::{SetHeader, SetHeaderEndpoint},
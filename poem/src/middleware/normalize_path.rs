@@ -3,7 +3,7 @@ use std::str::FromStr;
 use http::{Uri, uri::PathAndQuery};
 use regex::Regex;
 
-use crate::{Endpoint, Middleware, Request, Result};
+use crate::{Endpoint, Middleware, Request, Result, route::RouteNode};
 
 /// Determines the behavior of the [`NormalizePath`] middleware.
 #[derive(Debug, Clone, Copy, Default)]
@@ -116,6 +116,10 @@ impl<E: Endpoint> Endpoint for NormalizePathEndpoint<E> {
 
         self.inner.call(req).await
     }
+
+    fn describe_routes(&self) -> RouteNode {
+        self.inner.describe_routes()
+    }
 }
 
 #[cfg(test)]
@@ -10,6 +10,7 @@ use opentelemetry_semantic_conventions::attribute;
 use crate::{
     Endpoint, FromRequest, IntoResponse, Middleware, Request, Response, Result,
     route::PathPattern,
+    route::RouteNode,
     web::{RealIp, headers::HeaderMapExt},
 };
 
@@ -165,4 +166,8 @@ where
         .with_context(Context::current_with_span(span))
         .await
     }
+
+    fn describe_routes(&self) -> RouteNode {
+        self.inner.describe_routes()
+    }
 }
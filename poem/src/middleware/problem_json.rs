@@ -0,0 +1,318 @@
+use std::sync::Arc;
+
+use crate::{
+    Endpoint, Error, IntoResponse, Middleware, Request, Response, Result,
+    error::{
+        CorsError, MethodNotAllowedError, NotFoundError, ParseFormError, ParseJsonError,
+        ParsePathError, ParseQueryError, ParseTypedHeaderError, PreconditionFailedError,
+        ReadBodyError, SizedLimitError,
+    },
+    http::header,
+    route::RouteNode,
+    web::ProblemDetails,
+};
+
+type Extend = Arc<dyn Fn(&Error, ProblemDetails) -> ProblemDetails + Send + Sync>;
+
+/// Returns the problem type name of the built-in errors.
+fn builtin_type(err: &Error) -> Option<&'static str> {
+    macro_rules! builtin_types {
+        ($($(#[$meta:meta])* $ty:ty => $name:literal,)*) => {
+            $(
+            $(#[$meta])*
+            if err.is::<$ty>() {
+                return Some($name);
+            }
+            )*
+        };
+    }
+
+    builtin_types!(
+        NotFoundError => "not-found",
+        MethodNotAllowedError => "method-not-allowed",
+        ParsePathError => "invalid-path",
+        ParseQueryError => "invalid-query",
+        ParseFormError => "invalid-form",
+        ParseJsonError => "invalid-json",
+        ParseTypedHeaderError => "invalid-header",
+        ReadBodyError => "invalid-body",
+        SizedLimitError => "payload-too-large",
+        PreconditionFailedError => "precondition-failed",
+        CorsError => "cors",
+        #[cfg(feature = "compression")]
+        crate::error::DecompressionError => "invalid-content-encoding",
+        #[cfg(feature = "cookie")]
+        crate::error::ParseCookieError => "invalid-cookie",
+        #[cfg(feature = "xml")]
+        crate::error::ParseXmlError => "invalid-xml",
+        #[cfg(feature = "yaml")]
+        crate::error::ParseYamlError => "invalid-yaml",
+        #[cfg(feature = "multipart")]
+        crate::error::ParseMultipartError => "invalid-multipart",
+        #[cfg(feature = "websocket")]
+        crate::error::WebSocketError => "invalid-websocket",
+        #[cfg(feature = "jwt")]
+        crate::error::JwtError => "invalid-token",
+    );
+
+    None
+}
+
+/// Middleware that renders errors as
+/// [RFC 9457](https://www.rfc-editor.org/rfc/rfc9457) problem details with
+/// the `application/problem+json` content type.
+///
+/// The `type` of the built-in errors is the type prefix followed by a stable
+/// name, such as `urn:poem:error:not-found` for
+/// [`NotFoundError`](crate::error::NotFoundError), and `about:blank` for the
+/// other errors. Errors which are already [`ProblemDetails`] are rendered
+/// as they are, and errors created from a response are not changed.
+///
+/// The problem details are returned as an error created from the response, so
+/// the outer middlewares still see the request as failed.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     EndpointExt, Route, get, handler, http::StatusCode, middleware::ProblemJson,
+///     test::TestClient,
+/// };
+///
+/// #[handler]
+/// fn index() {}
+///
+/// let app = Route::new().at("/", get(index)).with(ProblemJson::new());
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = TestClient::new(app).get("/missing").send().await;
+/// resp.assert_status(StatusCode::NOT_FOUND);
+/// resp.assert_content_type("application/problem+json");
+/// resp.assert_json(serde_json::json!({
+///     "type": "urn:poem:error:not-found",
+///     "title": "Not Found",
+///     "status": 404,
+///     "detail": "not found",
+///     "instance": "/missing",
+/// }))
+/// .await;
+/// # });
+/// ```
+#[derive(Clone)]
+pub struct ProblemJson {
+    type_prefix: String,
+    instance: bool,
+    extend: Option<Extend>,
+}
+
+impl Default for ProblemJson {
+    fn default() -> Self {
+        Self {
+            type_prefix: "urn:poem:error:".to_string(),
+            instance: true,
+            extend: None,
+        }
+    }
+}
+
+impl ProblemJson {
+    /// Create new `ProblemJson` middleware.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets the prefix of the `type` URI of the built-in errors.
+    ///
+    /// Default is `urn:poem:error:`.
+    #[must_use]
+    pub fn type_prefix(self, prefix: impl Into<String>) -> Self {
+        Self {
+            type_prefix: prefix.into(),
+            ..self
+        }
+    }
+
+    /// Sets whether to use the request path as the `instance` member.
+    ///
+    /// Default is `true`.
+    #[must_use]
+    pub fn instance(self, instance: bool) -> Self {
+        Self { instance, ..self }
+    }
+
+    /// Sets a function that can modify the problem details of each error,
+    /// for example to add extension members.
+    #[must_use]
+    pub fn extend<F>(self, f: F) -> Self
+    where
+        F: Fn(&Error, ProblemDetails) -> ProblemDetails + Send + Sync + 'static,
+    {
+        Self {
+            extend: Some(Arc::new(f)),
+            ..self
+        }
+    }
+
+    fn problem_details(&self, err: &Error) -> ProblemDetails {
+        if let Some(problem) = err.downcast_ref::<ProblemDetails>() {
+            return problem.clone();
+        }
+
+        let mut problem = ProblemDetails::new(err.status()).detail(err.to_string());
+        if let Some(name) = builtin_type(err) {
+            problem = problem.ty(format!("{}{}", self.type_prefix, name));
+        }
+        problem
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for ProblemJson {
+    type Output = ProblemJsonEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        ProblemJsonEndpoint {
+            inner: ep,
+            config: self.clone(),
+        }
+    }
+}
+
+/// Endpoint for the `ProblemJson` middleware.
+pub struct ProblemJsonEndpoint<E> {
+    inner: E,
+    config: ProblemJson,
+}
+
+impl<E: Endpoint> Endpoint for ProblemJsonEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let path = self.config.instance.then(|| req.uri().path().to_string());
+
+        let err = match self.inner.call(req).await {
+            Ok(resp) => return Ok(resp.into_response()),
+            Err(err) if err.is_from_response() => return Err(err),
+            Err(err) => err,
+        };
+
+        let mut problem = self.config.problem_details(&err);
+        if problem.instance.is_none() {
+            problem.instance = path;
+        }
+        if let Some(extend) = &self.config.extend {
+            problem = extend(&err, problem);
+        }
+
+        // keep the headers and extensions of the original response, such as
+        // the `Allow` header of `MethodNotAllowedError`
+        let mut resp = err.into_response();
+        let problem_resp = problem.into_response();
+        resp.set_status(problem_resp.status());
+        if let Some(content_type) = problem_resp.headers().get(header::CONTENT_TYPE) {
+            resp.headers_mut()
+                .insert(header::CONTENT_TYPE, content_type.clone());
+        }
+        resp.headers_mut().remove(header::CONTENT_LENGTH);
+        resp.set_body(problem_resp.into_body());
+        Err(Error::from_response(resp))
+    }
+
+    fn describe_routes(&self) -> RouteNode {
+        self.inner.describe_routes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        EndpointExt, Route, get, handler,
+        http::StatusCode,
+        test::TestClient,
+        web::{Json, headers::ContentType},
+    };
+
+    #[tokio::test]
+    async fn problem_json() {
+        #[handler(internal)]
+        fn index(_: Json<i32>) {}
+
+        #[handler(internal)]
+        fn problem() -> Result<()> {
+            Err(ProblemDetails::new(StatusCode::CONFLICT)
+                .ty("https://example.com/conflict")
+                .extension("id", 1)
+                .into())
+        }
+
+        #[handler(internal)]
+        fn custom() -> Result<()> {
+            Err(Error::from_string("custom", StatusCode::BAD_GATEWAY))
+        }
+
+        let app = Route::new()
+            .at("/", get(index))
+            .at("/problem", problem)
+            .at("/custom", custom)
+            .with(ProblemJson::new().extend(|_, details| details.extension("trace", "abc")));
+
+        let err = app
+            .call(Request::builder().uri_str("/custom").finish())
+            .await
+            .unwrap_err();
+        assert!(err.is_from_response());
+        assert_eq!(err.status(), StatusCode::BAD_GATEWAY);
+
+        let cli = TestClient::new(app);
+
+        let resp = cli.post("/").send().await;
+        resp.assert_status(StatusCode::METHOD_NOT_ALLOWED);
+        resp.assert_content_type("application/problem+json");
+        resp.assert_header(header::ALLOW, "GET, HEAD, OPTIONS");
+        resp.assert_json(serde_json::json!({
+            "type": "urn:poem:error:method-not-allowed",
+            "title": "Method Not Allowed",
+            "status": 405,
+            "detail": "method not allowed",
+            "instance": "/",
+            "trace": "abc",
+        }))
+        .await;
+
+        let resp = cli.get("/").typed_header(ContentType::text()).send().await;
+        resp.assert_status(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        resp.assert_json(serde_json::json!({
+            "type": "urn:poem:error:invalid-json",
+            "title": "Unsupported Media Type",
+            "status": 415,
+            "detail": "invalid content type `text/plain`, expect: `application/json`",
+            "instance": "/",
+            "trace": "abc",
+        }))
+        .await;
+
+        let resp = cli.get("/problem").send().await;
+        resp.assert_status(StatusCode::CONFLICT);
+        resp.assert_json(serde_json::json!({
+            "type": "https://example.com/conflict",
+            "title": "Conflict",
+            "status": 409,
+            "instance": "/problem",
+            "id": 1,
+            "trace": "abc",
+        }))
+        .await;
+
+        let resp = cli.get("/custom").send().await;
+        resp.assert_status(StatusCode::BAD_GATEWAY);
+        resp.assert_json(serde_json::json!({
+            "type": "about:blank",
+            "title": "Bad Gateway",
+            "status": 502,
+            "detail": "custom",
+            "instance": "/custom",
+            "trace": "abc",
+        }))
+        .await;
+    }
+}
@@ -2,7 +2,7 @@ use std::collections::HashSet;
 
 use http::{HeaderMap, header::HeaderName};
 
-use crate::{Endpoint, IntoResponse, Middleware, Request, Response, Result};
+use crate::{Endpoint, IntoResponse, Middleware, Request, Response, Result, route::RouteNode};
 
 /// Middleware to propagate a header from the request to the response.
 #[derive(Default)]
@@ -63,6 +63,10 @@ impl<E: Endpoint> Endpoint for PropagateHeaderEndpoint<E> {
         resp.headers_mut().extend(headers);
         Ok(resp)
     }
+
+    fn describe_routes(&self) -> RouteNode {
+        self.inner.describe_routes()
+    }
 }
 
 #[cfg(test)]
@@ -3,7 +3,7 @@ use uuid::Uuid;
 
 use crate::{
     Endpoint, Error, FromRequest, IntoResponse, Middleware, Request, Response, Result,
-    http::StatusCode,
+    http::StatusCode, route::RouteNode,
 };
 
 const X_REQUEST_ID: &str = "x-request-id";
@@ -101,6 +101,10 @@ impl<E: Endpoint> Endpoint for RequestIdEndpoint<E> {
             Err(e) => Err(e),
         }
     }
+
+    fn describe_routes(&self) -> RouteNode {
+        self.next.describe_routes()
+    }
 }
 
 /// A request ID which can be extracted in handler functions.
@@ -0,0 +1,787 @@
+use std::{fmt::Write, time::Duration};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use rand::{Rng, rng};
+
+use crate::{
+    Endpoint, IntoResponse, Middleware, Request, Response, Result,
+    http::{
+        HeaderValue,
+        header::{self, HeaderName},
+    },
+    route::RouteNode,
+    web::CspNonce,
+};
+
+/// The `Strict-Transport-Security` policy.
+#[cfg_attr(docsrs, doc(cfg(feature = "security-headers")))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hsts {
+    max_age: Duration,
+    include_subdomains: bool,
+    preload: bool,
+}
+
+impl Default for Hsts {
+    fn default() -> Self {
+        Self {
+            max_age: Duration::from_secs(365 * 24 * 60 * 60),
+            include_subdomains: true,
+            preload: false,
+        }
+    }
+}
+
+impl Hsts {
+    /// Create a policy with `max-age` of one year and `includeSubDomains`.
+    #[must_use]
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets how long the browser should only use HTTPS.
+    #[must_use]
+    pub fn max_age(self, max_age: Duration) -> Self {
+        Self { max_age, ..self }
+    }
+
+    /// Applies the policy to the subdomains. (defaults to `true`)
+    #[must_use]
+    pub fn include_subdomains(self, include_subdomains: bool) -> Self {
+        Self {
+            include_subdomains,
+            ..self
+        }
+    }
+
+    /// Requests the inclusion in the HSTS preload list of the browsers.
+    /// (defaults to `false`)
+    #[must_use]
+    pub fn preload(self, preload: bool) -> Self {
+        Self { preload, ..self }
+    }
+
+    fn value(&self) -> String {
+        let mut value = format!("max-age={}", self.max_age.as_secs());
+        if self.include_subdomains {
+            value.push_str("; includeSubDomains");
+        }
+        if self.preload {
+            value.push_str("; preload");
+        }
+        value
+    }
+}
+
+/// The `Content-Security-Policy` policy.
+///
+/// The sources are written as they appear in the header, so the keywords must
+/// be quoted, e.g. `'self'`.
+#[cfg_attr(docsrs, doc(cfg(feature = "security-headers")))]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContentSecurityPolicy {
+    directives: Vec<(String, Vec<String>)>,
+    nonce_directives: Vec<String>,
+}
+
+macro_rules! define_csp_directives {
+    ($($(#[$docs:meta])* ($method:ident, $name:literal);)*) => {
+        $(
+        $(#[$docs])*
+        #[must_use]
+        pub fn $method<I, T>(self, sources: I) -> Self
+        where
+            I: IntoIterator<Item = T>,
+            T: Into<String>,
+        {
+            self.directive($name, sources)
+        }
+        )*
+    };
+}
+
+impl ContentSecurityPolicy {
+    /// Create an empty policy.
+    #[must_use]
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets a directive, replacing the previous sources of the same directive.
+    #[must_use]
+    pub fn directive<I, T>(mut self, name: impl Into<String>, sources: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        let name = name.into();
+        let sources = sources.into_iter().map(Into::into).collect();
+        match self.directives.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, values)) => *values = sources,
+            None => self.directives.push((name, sources)),
+        }
+        self
+    }
+
+    define_csp_directives!(
+        /// Sets the `default-src` directive.
+        (default_src, "default-src");
+        /// Sets the `script-src` directive.
+        (script_src, "script-src");
+        /// Sets the `style-src` directive.
+        (style_src, "style-src");
+        /// Sets the `img-src` directive.
+        (img_src, "img-src");
+        /// Sets the `connect-src` directive.
+        (connect_src, "connect-src");
+        /// Sets the `font-src` directive.
+        (font_src, "font-src");
+        /// Sets the `object-src` directive.
+        (object_src, "object-src");
+        /// Sets the `media-src` directive.
+        (media_src, "media-src");
+        /// Sets the `frame-src` directive.
+        (frame_src, "frame-src");
+        /// Sets the `frame-ancestors` directive.
+        (frame_ancestors, "frame-ancestors");
+        /// Sets the `base-uri` directive.
+        (base_uri, "base-uri");
+        /// Sets the `form-action` directive.
+        (form_action, "form-action");
+    );
+
+    /// Sets the `upgrade-insecure-requests` directive.
+    #[must_use]
+    pub fn upgrade_insecure_requests(self) -> Self {
+        self.directive("upgrade-insecure-requests", Vec::<String>::new())
+    }
+
+    /// Sets the `report-uri` directive, which is the URI where the browser
+    /// sends the violation reports.
+    ///
+    /// See also [`CspReportEndpoint`](crate::endpoint::CspReportEndpoint).
+    #[must_use]
+    pub fn report_uri(self, uri: impl Into<String>) -> Self {
+        self.directive("report-uri", [uri])
+    }
+
+    /// Sets the `report-to` directive, which is the name of the reporting
+    /// endpoint group.
+    #[must_use]
+    pub fn report_to(self, group: impl Into<String>) -> Self {
+        self.directive("report-to", [group])
+    }
+
+    /// Adds the per-request nonce to the sources of the directive, e.g.
+    /// `script-src`.
+    ///
+    /// The nonce can be obtained with the [`CspNonce`] extractor.
+    #[must_use]
+    pub fn nonce(mut self, directive: impl Into<String>) -> Self {
+        let directive = directive.into();
+        if !self.nonce_directives.contains(&directive) {
+            self.nonce_directives.push(directive);
+        }
+        self
+    }
+
+    fn value(&self, nonce: Option<&str>) -> String {
+        let mut directives = self.directives.clone();
+        if let Some(nonce) = nonce {
+            for name in &self.nonce_directives {
+                let source = format!("'nonce-{nonce}'");
+                match directives.iter_mut().find(|(n, _)| n == name) {
+                    Some((_, sources)) => sources.push(source),
+                    None => directives.push((name.clone(), vec![source])),
+                }
+            }
+        }
+
+        let mut value = String::new();
+        for (name, sources) in directives {
+            if !value.is_empty() {
+                value.push_str("; ");
+            }
+            value.push_str(&name);
+            for source in sources {
+                _ = write!(value, " {source}");
+            }
+        }
+        value
+    }
+}
+
+/// The `X-Frame-Options` policy.
+#[cfg_attr(docsrs, doc(cfg(feature = "security-headers")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameOptions {
+    /// The page cannot be displayed in a frame.
+    Deny,
+    /// The page can only be displayed in a frame on the same origin.
+    SameOrigin,
+}
+
+/// The `Referrer-Policy` policy.
+#[cfg_attr(docsrs, doc(cfg(feature = "security-headers")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferrerPolicy {
+    /// `no-referrer`
+    NoReferrer,
+    /// `no-referrer-when-downgrade`
+    NoReferrerWhenDowngrade,
+    /// `origin`
+    Origin,
+    /// `origin-when-cross-origin`
+    OriginWhenCrossOrigin,
+    /// `same-origin`
+    SameOrigin,
+    /// `strict-origin`
+    StrictOrigin,
+    /// `strict-origin-when-cross-origin`
+    StrictOriginWhenCrossOrigin,
+    /// `unsafe-url`
+    UnsafeUrl,
+}
+
+impl ReferrerPolicy {
+    fn as_str(self) -> &'static str {
+        match self {
+            ReferrerPolicy::NoReferrer => "no-referrer",
+            ReferrerPolicy::NoReferrerWhenDowngrade => "no-referrer-when-downgrade",
+            ReferrerPolicy::Origin => "origin",
+            ReferrerPolicy::OriginWhenCrossOrigin => "origin-when-cross-origin",
+            ReferrerPolicy::SameOrigin => "same-origin",
+            ReferrerPolicy::StrictOrigin => "strict-origin",
+            ReferrerPolicy::StrictOriginWhenCrossOrigin => "strict-origin-when-cross-origin",
+            ReferrerPolicy::UnsafeUrl => "unsafe-url",
+        }
+    }
+}
+
+/// The `Permissions-Policy` policy.
+#[cfg_attr(docsrs, doc(cfg(feature = "security-headers")))]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PermissionsPolicy {
+    features: Vec<(String, Vec<String>)>,
+}
+
+impl PermissionsPolicy {
+    /// Create an empty policy.
+    #[must_use]
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    fn feature(mut self, feature: impl Into<String>, allowlist: Vec<String>) -> Self {
+        let feature = feature.into();
+        match self.features.iter_mut().find(|(f, _)| *f == feature) {
+            Some((_, values)) => *values = allowlist,
+            None => self.features.push((feature, allowlist)),
+        }
+        self
+    }
+
+    /// Disables the feature, e.g. `camera`.
+    #[must_use]
+    pub fn deny(self, feature: impl Into<String>) -> Self {
+        self.feature(feature, Vec::new())
+    }
+
+    /// Allows the feature for the same origin.
+    #[must_use]
+    pub fn allow_self(self, feature: impl Into<String>) -> Self {
+        self.feature(feature, vec!["self".to_string()])
+    }
+
+    /// Allows the feature for the same origin and the specified origins.
+    #[must_use]
+    pub fn allow<I, T>(self, feature: impl Into<String>, origins: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>,
+    {
+        let allowlist = std::iter::once("self".to_string())
+            .chain(
+                origins
+                    .into_iter()
+                    .map(|origin| format!("\"{}\"", origin.as_ref())),
+            )
+            .collect();
+        self.feature(feature, allowlist)
+    }
+
+    fn value(&self) -> String {
+        self.features
+            .iter()
+            .map(|(feature, allowlist)| format!("{feature}=({})", allowlist.join(" ")))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// The `Cross-Origin-Opener-Policy` policy.
+#[cfg_attr(docsrs, doc(cfg(feature = "security-headers")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrossOriginOpenerPolicy {
+    /// `same-origin`
+    SameOrigin,
+    /// `same-origin-allow-popups`
+    SameOriginAllowPopups,
+    /// `unsafe-none`
+    UnsafeNone,
+}
+
+/// The `Cross-Origin-Embedder-Policy` policy.
+#[cfg_attr(docsrs, doc(cfg(feature = "security-headers")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrossOriginEmbedderPolicy {
+    /// `require-corp`
+    RequireCorp,
+    /// `credentialless`
+    Credentialless,
+    /// `unsafe-none`
+    UnsafeNone,
+}
+
+/// Middleware to add the security related headers to responses.
+///
+/// The defaults are:
+///
+/// - `Strict-Transport-Security: max-age=31536000; includeSubDomains`
+/// - `Content-Security-Policy: default-src 'self'; object-src 'none';
+///   base-uri 'self'; frame-ancestors 'self'`
+/// - `X-Content-Type-Options: nosniff`
+/// - `X-Frame-Options: SAMEORIGIN`
+/// - `Referrer-Policy: strict-origin-when-cross-origin`
+/// - `Cross-Origin-Opener-Policy: same-origin`
+///
+/// The headers set by the handler are not overridden.
+///
+/// If the policy uses [`ContentSecurityPolicy::nonce`], a nonce is generated
+/// for each request, and the handler can get it with the [`CspNonce`]
+/// extractor.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     EndpointExt, Route, get, handler,
+///     http::header,
+///     middleware::{ContentSecurityPolicy, SecurityHeaders},
+///     test::TestClient,
+///     web::{CspNonce, Html},
+/// };
+///
+/// #[handler]
+/// fn index(nonce: &CspNonce) -> Html<String> {
+///     Html(format!(r#"<script nonce="{}">alert(1)</script>"#, nonce.0))
+/// }
+///
+/// let csp = ContentSecurityPolicy::new()
+///     .default_src(["'self'"])
+///     .nonce("script-src");
+/// let app = Route::new()
+///     .at("/", get(index))
+///     .with(SecurityHeaders::new().content_security_policy(csp));
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = TestClient::new(app).get("/").send().await;
+/// resp.assert_status_is_ok();
+/// resp.assert_header(header::X_CONTENT_TYPE_OPTIONS, "nosniff");
+/// # });
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "security-headers")))]
+#[derive(Debug, Clone)]
+pub struct SecurityHeaders {
+    hsts: Option<Hsts>,
+    csp: Option<ContentSecurityPolicy>,
+    csp_report_only: Option<ContentSecurityPolicy>,
+    content_type_options: bool,
+    frame_options: Option<FrameOptions>,
+    referrer_policy: Option<ReferrerPolicy>,
+    permissions_policy: Option<PermissionsPolicy>,
+    coop: Option<CrossOriginOpenerPolicy>,
+    coep: Option<CrossOriginEmbedderPolicy>,
+}
+
+impl Default for SecurityHeaders {
+    fn default() -> Self {
+        Self {
+            hsts: Some(Hsts::new()),
+            csp: Some(
+                ContentSecurityPolicy::new()
+                    .default_src(["'self'"])
+                    .object_src(["'none'"])
+                    .base_uri(["'self'"])
+                    .frame_ancestors(["'self'"]),
+            ),
+            csp_report_only: None,
+            content_type_options: true,
+            frame_options: Some(FrameOptions::SameOrigin),
+            referrer_policy: Some(ReferrerPolicy::StrictOriginWhenCrossOrigin),
+            permissions_policy: None,
+            coop: Some(CrossOriginOpenerPolicy::SameOrigin),
+            coep: None,
+        }
+    }
+}
+
+impl SecurityHeaders {
+    /// Create a `SecurityHeaders` middleware with the secure defaults.
+    #[must_use]
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets the `Strict-Transport-Security` policy, or `None` to disable it.
+    #[must_use]
+    pub fn hsts(self, hsts: impl Into<Option<Hsts>>) -> Self {
+        Self {
+            hsts: hsts.into(),
+            ..self
+        }
+    }
+
+    /// Sets the `Content-Security-Policy` policy, or `None` to disable it.
+    #[must_use]
+    pub fn content_security_policy(self, csp: impl Into<Option<ContentSecurityPolicy>>) -> Self {
+        Self {
+            csp: csp.into(),
+            ..self
+        }
+    }
+
+    /// Sets the `Content-Security-Policy-Report-Only` policy, which is
+    /// reported but not enforced by the browser. (defaults to `None`)
+    ///
+    /// It can be used together with an enforced policy, to test a new policy
+    /// before enforcing it.
+    #[must_use]
+    pub fn content_security_policy_report_only(
+        self,
+        csp: impl Into<Option<ContentSecurityPolicy>>,
+    ) -> Self {
+        Self {
+            csp_report_only: csp.into(),
+            ..self
+        }
+    }
+
+    /// Sets `X-Content-Type-Options: nosniff`. (defaults to `true`)
+    #[must_use]
+    pub fn content_type_options(self, enable: bool) -> Self {
+        Self {
+            content_type_options: enable,
+            ..self
+        }
+    }
+
+    /// Sets the `X-Frame-Options` policy, or `None` to disable it.
+    #[must_use]
+    pub fn frame_options(self, frame_options: impl Into<Option<FrameOptions>>) -> Self {
+        Self {
+            frame_options: frame_options.into(),
+            ..self
+        }
+    }
+
+    /// Sets the `Referrer-Policy` policy, or `None` to disable it.
+    #[must_use]
+    pub fn referrer_policy(self, referrer_policy: impl Into<Option<ReferrerPolicy>>) -> Self {
+        Self {
+            referrer_policy: referrer_policy.into(),
+            ..self
+        }
+    }
+
+    /// Sets the `Permissions-Policy` policy. (defaults to `None`)
+    #[must_use]
+    pub fn permissions_policy(
+        self,
+        permissions_policy: impl Into<Option<PermissionsPolicy>>,
+    ) -> Self {
+        Self {
+            permissions_policy: permissions_policy.into(),
+            ..self
+        }
+    }
+
+    /// Sets the `Cross-Origin-Opener-Policy` policy, or `None` to disable it.
+    #[must_use]
+    pub fn cross_origin_opener_policy(
+        self,
+        coop: impl Into<Option<CrossOriginOpenerPolicy>>,
+    ) -> Self {
+        Self {
+            coop: coop.into(),
+            ..self
+        }
+    }
+
+    /// Sets the `Cross-Origin-Embedder-Policy` policy. (defaults to `None`)
+    #[must_use]
+    pub fn cross_origin_embedder_policy(
+        self,
+        coep: impl Into<Option<CrossOriginEmbedderPolicy>>,
+    ) -> Self {
+        Self {
+            coep: coep.into(),
+            ..self
+        }
+    }
+
+    /// Returns the headers that do not depend on the request.
+    fn static_headers(&self) -> Vec<(HeaderName, HeaderValue)> {
+        let mut headers = Vec::new();
+        let mut push = |name: HeaderName, value: &str| {
+            if let Ok(value) = HeaderValue::from_str(value) {
+                headers.push((name, value));
+            }
+        };
+
+        if let Some(hsts) = &self.hsts {
+            push(header::STRICT_TRANSPORT_SECURITY, &hsts.value());
+        }
+        if self.content_type_options {
+            push(header::X_CONTENT_TYPE_OPTIONS, "nosniff");
+        }
+        if let Some(frame_options) = self.frame_options {
+            let value = match frame_options {
+                FrameOptions::Deny => "DENY",
+                FrameOptions::SameOrigin => "SAMEORIGIN",
+            };
+            push(header::X_FRAME_OPTIONS, value);
+        }
+        if let Some(referrer_policy) = self.referrer_policy {
+            push(header::REFERRER_POLICY, referrer_policy.as_str());
+        }
+        if let Some(permissions_policy) = &self.permissions_policy {
+            push(
+                HeaderName::from_static("permissions-policy"),
+                &permissions_policy.value(),
+            );
+        }
+        if let Some(coop) = self.coop {
+            let value = match coop {
+                CrossOriginOpenerPolicy::SameOrigin => "same-origin",
+                CrossOriginOpenerPolicy::SameOriginAllowPopups => "same-origin-allow-popups",
+                CrossOriginOpenerPolicy::UnsafeNone => "unsafe-none",
+            };
+            push(HeaderName::from_static("cross-origin-opener-policy"), value);
+        }
+        if let Some(coep) = self.coep {
+            let value = match coep {
+                CrossOriginEmbedderPolicy::RequireCorp => "require-corp",
+                CrossOriginEmbedderPolicy::Credentialless => "credentialless",
+                CrossOriginEmbedderPolicy::UnsafeNone => "unsafe-none",
+            };
+            push(
+                HeaderName::from_static("cross-origin-embedder-policy"),
+                value,
+            );
+        }
+        headers
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for SecurityHeaders {
+    type Output = SecurityHeadersEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        let csp = [
+            (header::CONTENT_SECURITY_POLICY, &self.csp),
+            (
+                header::CONTENT_SECURITY_POLICY_REPORT_ONLY,
+                &self.csp_report_only,
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(name, csp)| Some((name, csp.clone()?)))
+        .collect::<Vec<_>>();
+
+        SecurityHeadersEndpoint {
+            inner: ep,
+            use_nonce: csp.iter().any(|(_, csp)| !csp.nonce_directives.is_empty()),
+            headers: self.static_headers(),
+            csp,
+        }
+    }
+}
+
+/// Endpoint for the SecurityHeaders middleware.
+#[cfg_attr(docsrs, doc(cfg(feature = "security-headers")))]
+pub struct SecurityHeadersEndpoint<E> {
+    inner: E,
+    headers: Vec<(HeaderName, HeaderValue)>,
+    csp: Vec<(HeaderName, ContentSecurityPolicy)>,
+    use_nonce: bool,
+}
+
+fn generate_nonce() -> String {
+    STANDARD.encode(rng().random::<[u8; 16]>())
+}
+
+impl<E: Endpoint> Endpoint for SecurityHeadersEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, mut req: Request) -> Result<Self::Output> {
+        let nonce = self.use_nonce.then(generate_nonce);
+        if let Some(nonce) = &nonce {
+            req.extensions_mut().insert(CspNonce(nonce.clone()));
+        }
+
+        let mut resp = self.inner.call(req).await?.into_response();
+        let headers = resp.headers_mut();
+        for (name, value) in &self.headers {
+            if !headers.contains_key(name) {
+                headers.insert(name, value.clone());
+            }
+        }
+        for (name, csp) in &self.csp {
+            if !headers.contains_key(name) {
+                if let Ok(value) = HeaderValue::from_str(&csp.value(nonce.as_deref())) {
+                    headers.insert(name, value);
+                }
+            }
+        }
+        Ok(resp)
+    }
+
+    fn describe_routes(&self) -> RouteNode {
+        self.inner.describe_routes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EndpointExt, handler, test::TestClient};
+
+    #[handler(internal)]
+    fn index() -> &'static str {
+        "hello"
+    }
+
+    #[tokio::test]
+    async fn defaults() {
+        let resp = TestClient::new(index.with(SecurityHeaders::new()))
+            .get("/")
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_header(
+            header::STRICT_TRANSPORT_SECURITY,
+            "max-age=31536000; includeSubDomains",
+        );
+        resp.assert_header(
+            header::CONTENT_SECURITY_POLICY,
+            "default-src 'self'; object-src 'none'; base-uri 'self'; frame-ancestors 'self'",
+        );
+        resp.assert_header(header::X_CONTENT_TYPE_OPTIONS, "nosniff");
+        resp.assert_header(header::X_FRAME_OPTIONS, "SAMEORIGIN");
+        resp.assert_header(header::REFERRER_POLICY, "strict-origin-when-cross-origin");
+        resp.assert_header("cross-origin-opener-policy", "same-origin");
+        resp.assert_header_is_not_exist("cross-origin-embedder-policy");
+        resp.assert_header_is_not_exist("permissions-policy");
+        resp.assert_header_is_not_exist(header::CONTENT_SECURITY_POLICY_REPORT_ONLY);
+    }
+
+    #[tokio::test]
+    async fn custom_policies() {
+        let security_headers = SecurityHeaders::new()
+            .hsts(
+                Hsts::new()
+                    .max_age(Duration::from_secs(60))
+                    .include_subdomains(false)
+                    .preload(true),
+            )
+            .content_security_policy(None)
+            .content_type_options(false)
+            .frame_options(FrameOptions::Deny)
+            .referrer_policy(ReferrerPolicy::NoReferrer)
+            .permissions_policy(
+                PermissionsPolicy::new()
+                    .deny("camera")
+                    .allow_self("fullscreen")
+                    .allow("geolocation", ["https://example.com"]),
+            )
+            .cross_origin_opener_policy(None)
+            .cross_origin_embedder_policy(CrossOriginEmbedderPolicy::RequireCorp);
+
+        let resp = TestClient::new(index.with(security_headers))
+            .get("/")
+            .send()
+            .await;
+        resp.assert_header(header::STRICT_TRANSPORT_SECURITY, "max-age=60; preload");
+        resp.assert_header_is_not_exist(header::CONTENT_SECURITY_POLICY);
+        resp.assert_header_is_not_exist(header::X_CONTENT_TYPE_OPTIONS);
+        resp.assert_header(header::X_FRAME_OPTIONS, "DENY");
+        resp.assert_header(header::REFERRER_POLICY, "no-referrer");
+        resp.assert_header(
+            "permissions-policy",
+            r#"camera=(), fullscreen=(self), geolocation=(self "https://example.com")"#,
+        );
+        resp.assert_header_is_not_exist("cross-origin-opener-policy");
+        resp.assert_header("cross-origin-embedder-policy", "require-corp");
+    }
+
+    #[tokio::test]
+    async fn handler_headers_are_kept() {
+        #[handler(internal)]
+        fn index() -> Response {
+            Response::builder()
+                .header(header::X_FRAME_OPTIONS, "DENY")
+                .header(header::CONTENT_SECURITY_POLICY, "default-src *")
+                .finish()
+        }
+
+        let resp = TestClient::new(index.with(SecurityHeaders::new()))
+            .get("/")
+            .send()
+            .await;
+        resp.assert_header(header::X_FRAME_OPTIONS, "DENY");
+        resp.assert_header(header::CONTENT_SECURITY_POLICY, "default-src *");
+    }
+
+    #[tokio::test]
+    async fn nonce() {
+        #[handler(internal)]
+        fn index(nonce: &CspNonce) -> String {
+            nonce.0.clone()
+        }
+
+        let csp = ContentSecurityPolicy::new()
+            .default_src(["'self'"])
+            .script_src(["'self'"])
+            .nonce("script-src")
+            .nonce("style-src")
+            .report_uri("/csp-report");
+        let cli = TestClient::new(
+            index.with(SecurityHeaders::new().content_security_policy_report_only(csp)),
+        );
+
+        let mut nonces = Vec::new();
+        for _ in 0..2 {
+            let resp = cli.get("/").send().await;
+            let csp = resp
+                .0
+                .headers()
+                .get(header::CONTENT_SECURITY_POLICY_REPORT_ONLY)
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .to_string();
+            let nonce = resp.0.into_body().into_string().await.unwrap();
+            assert_eq!(nonce.len(), 24);
+            assert_eq!(
+                csp,
+                format!(
+                    "default-src 'self'; script-src 'self' 'nonce-{nonce}'; report-uri \
+                     /csp-report; style-src 'nonce-{nonce}'"
+                )
+            );
+            nonces.push(nonce);
+        }
+        assert_ne!(nonces[0], nonces[1]);
+    }
+}
@@ -2,7 +2,7 @@ use std::collections::HashSet;
 
 use http::{HeaderMap, header::HeaderName};
 
-use crate::{Endpoint, IntoResponse, Middleware, Request, Response, Result};
+use crate::{Endpoint, IntoResponse, Middleware, Request, Response, Result, route::RouteNode};
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
 enum AppliedTo {
@@ -107,6 +107,10 @@ impl<E: Endpoint> Endpoint for SensitiveHeaderEndpoint<E> {
 
         Ok(resp)
     }
+
+    fn describe_routes(&self) -> RouteNode {
+        self.inner.describe_routes()
+    }
 }
 
 #[allow(clippy::mutable_key_type)]
@@ -1,6 +1,7 @@
 use crate::{
     Endpoint, IntoResponse, Middleware, Request, Response, Result,
     http::{HeaderValue, header::HeaderName},
+    route::RouteNode,
 };
 
 #[derive(Debug, Clone)]
@@ -126,6 +127,10 @@ impl<E: Endpoint> Endpoint for SetHeaderEndpoint<E> {
 
         Ok(resp)
     }
+
+    fn describe_routes(&self) -> RouteNode {
+        self.inner.describe_routes()
+    }
 }
 
 #[cfg(test)]
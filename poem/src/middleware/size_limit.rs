@@ -1,12 +1,30 @@
+use std::{
+    io::{Error as IoError, Result as IoResult},
+    pin::Pin,
+    task::{Context, Poll, ready},
+};
+
+use tokio::io::{AsyncRead, ReadBuf};
+
 use crate::{
-    Endpoint, Middleware, Request, Result, error::SizedLimitError, web::headers::HeaderMapExt,
+    Body, Endpoint, Middleware, Request, Result, error::SizedLimitError, route::RouteNode,
+    web::headers::HeaderMapExt,
 };
 
+/// A marker inserted into the request data when the request body has been
+/// decompressed, so the size of the body is unknown.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Decompressed;
+
 /// Middleware to limit the request payload size.
 ///
 /// If the incoming request does not contain the `Content-Length` header, the
 /// middleware will return the `LENGTH_REQUIRED` status code.
 ///
+/// If the request body has been decompressed by the
+/// [`Decompression`](crate::middleware::Decompression) middleware, the
+/// decompressed bytes are counted while reading the body instead.
+///
 /// # Errors
 ///
 /// - [`SizedLimitError`]
@@ -41,7 +59,16 @@ pub struct SizeLimitEndpoint<E> {
 impl<E: Endpoint> Endpoint for SizeLimitEndpoint<E> {
     type Output = E::Output;
 
-    async fn call(&self, req: Request) -> Result<Self::Output> {
+    async fn call(&self, mut req: Request) -> Result<Self::Output> {
+        if req.data::<Decompressed>().is_some() {
+            let body = req.take_body().into_async_read();
+            req.set_body(Body::from_async_read(LimitReader {
+                inner: body,
+                remaining: self.max_size,
+            }));
+            return self.inner.call(req).await;
+        }
+
         let content_length = req
             .headers()
             .typed_get::<headers::ContentLength>()
@@ -53,6 +80,34 @@ impl<E: Endpoint> Endpoint for SizeLimitEndpoint<E> {
 
         self.inner.call(req).await
     }
+
+    fn describe_routes(&self) -> RouteNode {
+        self.inner.describe_routes()
+    }
+}
+
+struct LimitReader<R> {
+    inner: R,
+    remaining: usize,
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for LimitReader<R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<IoResult<()>> {
+        let filled = buf.filled().len();
+        ready!(Pin::new(&mut self.inner).poll_read(cx, buf))?;
+        let n = buf.filled().len() - filled;
+        match self.remaining.checked_sub(n) {
+            Some(remaining) => {
+                self.remaining = remaining;
+                Poll::Ready(Ok(()))
+            }
+            None => Poll::Ready(Err(IoError::other(SizedLimitError::PayloadTooLarge))),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -5,7 +5,8 @@ use serde::Serialize;
 use tokio_metrics::{TaskMetrics, TaskMonitor};
 
 use crate::{
-    Endpoint, IntoResponse, Middleware, Request, Response, Result, RouteMethod, endpoint::make_sync,
+    Endpoint, IntoResponse, Middleware, Request, Response, Result, RouteMethod,
+    endpoint::make_sync, route::RouteNode,
 };
 
 /// Middleware for metrics with [`tokio-metrics`](https://crates.io/crates/tokio-metrics) crate.
@@ -96,6 +97,10 @@ impl<E: Endpoint> Endpoint for TokioMetricsEndpoint<E> {
             .await?
             .into_response())
     }
+
+    fn describe_routes(&self) -> RouteNode {
+        self.inner.describe_routes()
+    }
 }
 
 #[derive(Serialize, Default)]
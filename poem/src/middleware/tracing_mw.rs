@@ -4,7 +4,7 @@ use tracing::{Instrument, Level};
 
 use crate::{
     Endpoint, FromRequest, IntoResponse, Middleware, Request, Response, Result, route::PathPattern,
-    web::RealIp,
+    route::RouteNode, web::RealIp,
 };
 
 /// Middleware for [`tracing`](https://crates.io/crates/tracing).
@@ -109,4 +109,8 @@ impl<E: Endpoint> Endpoint for TracingEndpoint<E> {
         .instrument(span)
         .await
     }
+
+    fn describe_routes(&self) -> RouteNode {
+        self.inner.describe_routes()
+    }
 }
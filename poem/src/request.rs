@@ -7,9 +7,10 @@ use std::{
     task::{Context, Poll},
 };
 
+use futures_util::{FutureExt, TryFutureExt, future::BoxFuture};
 use http::uri::Scheme;
 use http_body_util::BodyExt;
-use hyper::{body::Incoming, rt::Write as _};
+use hyper::body::Incoming;
 use hyper_util::rt::TokioIo;
 use parking_lot::Mutex;
 use serde::de::DeserializeOwned;
@@ -90,7 +91,7 @@ impl From<(http::request::Parts, LocalAddr, RemoteAddr, Scheme)> for RequestPart
             parts
                 .extensions
                 .remove::<hyper::upgrade::OnUpgrade>()
-                .map(|fut| OnUpgrade { fut }),
+                .map(OnUpgrade::new),
         );
 
         Self {
@@ -160,7 +161,7 @@ impl From<(http::Request<Incoming>, LocalAddr, RemoteAddr, Scheme)> for Request
             parts
                 .extensions
                 .remove::<hyper::upgrade::OnUpgrade>()
-                .map(|fut| OnUpgrade { fut }),
+                .map(OnUpgrade::new),
         );
 
         Self {
@@ -461,6 +462,29 @@ impl Request {
         &mut self.state
     }
 
+    /// Creates a request with the same head and state as this request, and an
+    /// empty body.
+    pub(crate) fn clone_without_body(&self) -> Request {
+        Request {
+            method: self.method.clone(),
+            uri: self.uri.clone(),
+            version: self.version,
+            headers: self.headers.clone(),
+            extensions: self.extensions.clone(),
+            body: Body::empty(),
+            state: RequestState {
+                local_addr: self.state.local_addr.clone(),
+                remote_addr: self.state.remote_addr.clone(),
+                scheme: self.state.scheme.clone(),
+                original_uri: self.state.original_uri.clone(),
+                match_params: self.state.match_params.clone(),
+                #[cfg(feature = "cookie")]
+                cookie_jar: self.state.cookie_jar.clone(),
+                on_upgrade: Default::default(),
+            },
+        }
+    }
+
     /// Returns the parameters used by the extractor.
     pub fn split(mut self) -> (Request, RequestBody) {
         let body = self.take_body();
@@ -492,10 +516,33 @@ impl Request {
     }
 }
 
-pin_project_lite::pin_project! {
-    /// A future for a possible HTTP upgrade.
-    pub struct OnUpgrade {
-        #[pin] fut: hyper::upgrade::OnUpgrade,
+/// A future for a possible HTTP upgrade.
+pub struct OnUpgrade {
+    fut: BoxFuture<'static, Result<Upgraded, UpgradeError>>,
+}
+
+impl OnUpgrade {
+    fn new(fut: hyper::upgrade::OnUpgrade) -> Self {
+        Self {
+            fut: fut
+                .map_ok(|stream| Upgraded::new(TokioIo::new(stream)))
+                .map_err(|err| UpgradeError::Other(err.to_string()))
+                .boxed(),
+        }
+    }
+
+    /// Create an upgrade that resolves immediately to the specified stream.
+    ///
+    /// This is used to upgrade requests that are not served by a real
+    /// connection, such as those sent by [`TestClient`](crate::test::TestClient).
+    #[cfg(all(feature = "test", feature = "websocket"))]
+    pub(crate) fn from_io<T>(io: T) -> Self
+    where
+        T: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+    {
+        Self {
+            fut: futures_util::future::ready(Ok(Upgraded::new(io))).boxed(),
+        }
     }
 }
 
@@ -503,51 +550,60 @@ impl Future for OnUpgrade {
     type Output = Result<Upgraded, UpgradeError>;
 
     #[inline]
-    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        let this = self.project();
-        this.fut
-            .poll(cx)
-            .map_ok(|stream| Upgraded { stream })
-            .map_err(|err| UpgradeError::Other(err.to_string()))
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.fut.poll_unpin(cx)
     }
 }
 
-pin_project_lite::pin_project! {
-    /// An upgraded HTTP connection.
-    pub struct Upgraded {
-        #[pin] stream: hyper::upgrade::Upgraded,
+trait UpgradedIo: AsyncRead + AsyncWrite + Send + Unpin {}
+
+impl<T: AsyncRead + AsyncWrite + Send + Unpin> UpgradedIo for T {}
+
+/// An upgraded HTTP connection.
+pub struct Upgraded {
+    stream: Box<dyn UpgradedIo>,
+}
+
+impl Upgraded {
+    fn new<T>(stream: T) -> Self
+    where
+        T: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+    {
+        Self {
+            stream: Box::new(stream),
+        }
     }
 }
 
 impl AsyncRead for Upgraded {
     #[inline]
     fn poll_read(
-        self: Pin<&mut Self>,
+        mut self: Pin<&mut Self>,
         cx: &mut Context<'_>,
         buf: &mut ReadBuf<'_>,
     ) -> Poll<std::io::Result<()>> {
-        Pin::new(&mut TokioIo::new(self.project().stream)).poll_read(cx, buf)
+        Pin::new(&mut self.stream).poll_read(cx, buf)
     }
 }
 
 impl AsyncWrite for Upgraded {
     #[inline]
     fn poll_write(
-        self: Pin<&mut Self>,
+        mut self: Pin<&mut Self>,
         cx: &mut Context<'_>,
         buf: &[u8],
     ) -> Poll<Result<usize, Error>> {
-        self.project().stream.poll_write(cx, buf)
+        Pin::new(&mut self.stream).poll_write(cx, buf)
     }
 
     #[inline]
-    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
-        self.project().stream.poll_flush(cx)
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        Pin::new(&mut self.stream).poll_flush(cx)
     }
 
     #[inline]
-    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
-        self.project().stream.poll_shutdown(cx)
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        Pin::new(&mut self.stream).poll_shutdown(cx)
     }
 }
 
@@ -13,7 +13,7 @@ fn longest_common_prefix(a: &[u8], b: &[u8]) -> usize {
 }
 
 #[derive(Debug, Eq, PartialEq)]
-enum RawSegment<'a> {
+pub(crate) enum RawSegment<'a> {
     Static(&'a [u8]),
     Param(&'a [u8]),
     CatchAll(Option<&'a [u8]>),
@@ -36,7 +36,7 @@ fn find_slash(path: &[u8]) -> Option<usize> {
     None
 }
 
-fn parse_path_segments(path: &[u8]) -> Result<Vec<RawSegment<'_>>, ()> {
+pub(crate) fn parse_path_segments(path: &[u8]) -> Result<Vec<RawSegment<'_>>, ()> {
     fn parse_static<'a>(path: &'a [u8], i: &mut usize) -> &'a [u8] {
         let s = *i;
         while *i < path.len() {
@@ -237,7 +237,7 @@ impl<T> Node<T> {
                         re: None,
                         param_children: ::std::mem::take(&mut child.param_children),
                         catch_all_child: child.catch_all_child.take(),
-                        regex_children: vec![],
+                        regex_children: std::mem::take(&mut child.regex_children),
                         data: child.data.take(),
                     };
 
@@ -1236,6 +1236,18 @@ mod tests {
         assert_eq!(matches.params[0].1, "def");
     }
 
+    #[test]
+    fn test_split_node_with_regex_child() {
+        let mut tree = RadixTree::default();
+        tree.add("/a/:id<\\d+>", 1).unwrap();
+        tree.add("/nest", 2).unwrap();
+
+        let matches = tree.matches("/a/10").unwrap();
+        assert_eq!(matches.data.data, 1);
+        assert_eq!(matches.params, vec![("id".to_string(), "10".to_string())]);
+        assert_eq!(tree.matches("/nest").unwrap().data.data, 2);
+    }
+
     #[test]
     fn test_percent_decoded() {
         let mut tree = RadixTree::default();
@@ -0,0 +1,309 @@
+use std::fmt::{self, Display, Formatter};
+
+use serde::Serialize;
+
+use crate::{IntoResponse, Response, http::header};
+
+/// The description of the routes of an endpoint, returned by
+/// [`Endpoint::describe_routes`](crate::Endpoint::describe_routes).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RouteNode {
+    /// The routes of a [`Route`](crate::Route).
+    Path {
+        /// Routes, in the order they were added.
+        routes: Vec<PathRoute>,
+    },
+
+    /// The routes of a [`RouteDomain`](crate::RouteDomain).
+    Domain {
+        /// Routes, in the order they were added.
+        routes: Vec<DomainRoute>,
+    },
+
+    /// The routes of a [`RouteScheme`](crate::RouteScheme).
+    Scheme {
+        /// Routes, in the order they were added.
+        routes: Vec<SchemeRoute>,
+
+        /// The fallback endpoint.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        fallback: Option<Box<RouteNode>>,
+    },
+
+    /// The routes of a [`RouteMethod`](crate::RouteMethod).
+    Method {
+        /// Routes, in the order they were added.
+        routes: Vec<MethodRoute>,
+    },
+
+    /// An endpoint that is not a routing object.
+    Endpoint,
+}
+
+/// How a [`PathRoute`] was added.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PathRouteKind {
+    /// Added with [`Route::at`](crate::Route::at).
+    At,
+
+    /// Added with [`Route::nest`](crate::Route::nest).
+    Nest,
+
+    /// Added with [`Route::nest_no_strip`](crate::Route::nest_no_strip).
+    NestNoStrip,
+}
+
+/// A route of a [`Route`](crate::Route).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PathRoute {
+    /// Path pattern.
+    pub pattern: String,
+
+    /// Route name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// How the route was added.
+    pub kind: PathRouteKind,
+
+    /// The routes of the endpoint.
+    pub node: RouteNode,
+}
+
+/// A route of a [`RouteDomain`](crate::RouteDomain).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DomainRoute {
+    /// Domain pattern.
+    pub pattern: String,
+
+    /// The routes of the endpoint.
+    pub node: RouteNode,
+}
+
+/// A route of a [`RouteScheme`](crate::RouteScheme).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SchemeRoute {
+    /// Scheme.
+    pub scheme: String,
+
+    /// The routes of the endpoint.
+    pub node: RouteNode,
+}
+
+/// A route of a [`RouteMethod`](crate::RouteMethod).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MethodRoute {
+    /// HTTP method.
+    pub method: String,
+
+    /// The routes of the endpoint.
+    pub node: RouteNode,
+}
+
+/// A row of [`RouteTable`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RouteInfo {
+    /// HTTP methods, empty if all the methods are accepted.
+    pub methods: Vec<String>,
+
+    /// Full path pattern.
+    pub path: String,
+
+    /// Route name.
+    pub name: Option<String>,
+
+    /// Domain pattern.
+    pub domain: Option<String>,
+
+    /// Scheme.
+    pub scheme: Option<String>,
+}
+
+/// A flat list of the routes of an endpoint, which is displayed as a table
+/// and serialized as a JSON array.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     Endpoint, Route, endpoint::make_sync, get, handler,
+///     test::TestClient, web::Json,
+/// };
+///
+/// #[handler]
+/// fn index() {}
+///
+/// let app = Route::new().nest(
+///     "/api",
+///     Route::new()
+///         .at_named("users", "/users", get(index).post(index))
+///         .at("/users/:id", get(index)),
+/// );
+///
+/// let table = app.describe_routes().table();
+/// assert_eq!(
+///     table.to_string(),
+///     "METHODS    PATH            NAME   DOMAIN  SCHEME\n\
+///      GET, POST  /api/users      users  -       -\n\
+///      GET        /api/users/:id  -      -       -\n"
+/// );
+///
+/// // serves the route table for debugging
+/// let routes = app.describe_routes();
+/// let app = app
+///     .at("/_routes", get(make_sync(move |_| table.clone())))
+///     .at("/_routes.json", get(make_sync(move |_| Json(routes.clone()))));
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = TestClient::new(app).get("/_routes").send().await;
+/// resp.assert_status_is_ok();
+/// resp.assert_content_type("text/plain; charset=utf-8");
+/// # });
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct RouteTable(pub Vec<RouteInfo>);
+
+#[derive(Clone, Default)]
+struct Scope {
+    prefix: String,
+    rest: Option<String>,
+    domain: Option<String>,
+    scheme: Option<String>,
+    name: Option<String>,
+}
+
+fn join_path(prefix: &str, pattern: &str) -> String {
+    match pattern {
+        "/" if !prefix.is_empty() => prefix.to_string(),
+        _ => format!("{prefix}{pattern}"),
+    }
+}
+
+impl RouteNode {
+    /// Returns the flat list of the routes.
+    pub fn table(&self) -> RouteTable {
+        let mut rows = Vec::new();
+        self.collect(Scope::default(), &mut rows);
+        RouteTable(rows)
+    }
+
+    fn collect(&self, scope: Scope, rows: &mut Vec<RouteInfo>) {
+        let push = |rows: &mut Vec<RouteInfo>, scope: Scope, methods| {
+            let path = match scope.rest {
+                Some(rest) => rest,
+                None if scope.prefix.is_empty() => "/*".to_string(),
+                None => scope.prefix,
+            };
+            rows.push(RouteInfo {
+                methods,
+                path,
+                name: scope.name,
+                domain: scope.domain,
+                scheme: scope.scheme,
+            });
+        };
+
+        match self {
+            RouteNode::Path { routes } => {
+                for route in routes {
+                    let rest = format!("{}{}/*", scope.prefix, route.pattern);
+                    let (prefix, rest) = match route.kind {
+                        PathRouteKind::At => (join_path(&scope.prefix, &route.pattern), None),
+                        PathRouteKind::Nest => {
+                            (format!("{}{}", scope.prefix, route.pattern), Some(rest))
+                        }
+                        PathRouteKind::NestNoStrip => (scope.prefix.clone(), Some(rest)),
+                    };
+                    let child = Scope {
+                        prefix,
+                        rest,
+                        name: route.name.clone(),
+                        ..scope.clone()
+                    };
+                    route.node.collect(child, rows);
+                }
+            }
+            RouteNode::Domain { routes } => {
+                for route in routes {
+                    let child = Scope {
+                        domain: Some(route.pattern.clone()),
+                        ..scope.clone()
+                    };
+                    route.node.collect(child, rows);
+                }
+            }
+            RouteNode::Scheme { routes, fallback } => {
+                for route in routes {
+                    let child = Scope {
+                        scheme: Some(route.scheme.clone()),
+                        ..scope.clone()
+                    };
+                    route.node.collect(child, rows);
+                }
+                if let Some(fallback) = fallback {
+                    fallback.collect(scope, rows);
+                }
+            }
+            RouteNode::Method { routes } => {
+                let methods = routes.iter().map(|route| route.method.clone()).collect();
+                push(rows, scope, methods);
+            }
+            RouteNode::Endpoint => push(rows, scope, Vec::new()),
+        }
+    }
+}
+
+impl Display for RouteTable {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        const HEADER: [&str; 5] = ["METHODS", "PATH", "NAME", "DOMAIN", "SCHEME"];
+
+        let or_dash = |value: &Option<String>| value.clone().unwrap_or_else(|| "-".to_string());
+        let rows = self
+            .0
+            .iter()
+            .map(|route| {
+                let methods = match route.methods.is_empty() {
+                    true => "*".to_string(),
+                    false => route.methods.join(", "),
+                };
+                [
+                    methods,
+                    route.path.clone(),
+                    or_dash(&route.name),
+                    or_dash(&route.domain),
+                    or_dash(&route.scheme),
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        let mut widths = HEADER.map(str::len);
+        for row in &rows {
+            for (width, value) in widths.iter_mut().zip(row) {
+                *width = (*width).max(value.len());
+            }
+        }
+
+        for row in std::iter::once(HEADER.map(ToString::to_string)).chain(rows) {
+            let line = row
+                .iter()
+                .zip(widths)
+                .map(|(value, width)| format!("{value:width$}"))
+                .collect::<Vec<_>>()
+                .join("  ");
+            writeln!(f, "{}", line.trim_end())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl IntoResponse for RouteTable {
+    fn into_response(self) -> Response {
+        Response::builder()
+            .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+            .body(self.to_string())
+    }
+}
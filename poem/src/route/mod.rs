@@ -1,12 +1,18 @@
 //! Route object and DSL
 
 mod internal;
+mod introspect;
 mod router;
 mod router_domain;
 mod router_method;
 mod router_scheme;
+mod url_for;
 
 pub(crate) use internal::radix_tree::PathParams;
+pub use introspect::{
+    DomainRoute, MethodRoute, PathRoute, PathRouteKind, RouteInfo, RouteNode, RouteTable,
+    SchemeRoute,
+};
 pub use router::{PathPattern, Route};
 #[allow(unreachable_pub)]
 pub use router_domain::RouteDomain;
@@ -16,6 +22,7 @@ pub use router_method::{
 };
 #[allow(unreachable_pub)]
 pub use router_scheme::RouteScheme;
+pub use url_for::UrlFor;
 
 use crate::error::RouteError;
 
@@ -27,5 +34,7 @@ pub(crate) fn check_result<T>(res: Result<T, RouteError>) -> T {
         Err(RouteError::InvalidRegex { path, regex }) => {
             panic!("invalid regex in path: {path} `{regex}`")
         }
+        Err(RouteError::DuplicateName(name)) => panic!("duplicate route name: {name}"),
+        Err(RouteError::Shadowed { path, by }) => panic!("path {path} is shadowed by {by}"),
     }
 }
@@ -1,5 +1,6 @@
-use std::{str::FromStr, sync::Arc};
+use std::{collections::HashMap, str::FromStr, sync::Arc};
 
+use parking_lot::Mutex;
 use regex::Regex;
 
 use crate::{
@@ -7,7 +8,11 @@ use crate::{
     endpoint::BoxEndpoint,
     error::{NotFoundError, ParsePathError, RouteError},
     http::{Uri, uri::PathAndQuery},
-    route::{check_result, internal::radix_tree::RadixTree},
+    route::{
+        PathRoute, PathRouteKind, RouteNode, UrlFor, check_result,
+        internal::radix_tree::{RadixTree, RawSegment, parse_path_segments},
+        url_for::{NamedRoute, NamedRoutes},
+    },
 };
 
 #[derive(Debug, Clone, Copy)]
@@ -128,6 +133,19 @@ struct PathPrefix(usize);
 #[derive(Default)]
 pub struct Route {
     tree: RadixTree<BoxEndpoint<'static>>,
+    names: Arc<NamedRoutes>,
+    routes: Vec<PathRoute>,
+    shapes: HashMap<String, String>,
+    url_for_cache: Mutex<Option<UrlForCache>>,
+}
+
+/// The [`UrlFor`] of a route nested through an endpoint which does not
+/// describe its routes, and the request data it is built from.
+struct UrlForCache {
+    parent: Option<UrlFor>,
+    prefix: Option<PathPattern>,
+    strip: Option<usize>,
+    url_for: UrlFor,
 }
 
 impl Route {
@@ -140,7 +158,9 @@ impl Route {
     ///
     /// # Panics
     ///
-    /// Panic when there are duplicates in the routing table.
+    /// Panic when there are duplicates in the routing table, or the path is
+    /// shadowed by another path that only differs in the names of the
+    /// parameters, such as `/users/:id` and `/users/:name`.
     #[must_use]
     pub fn at<E>(self, path: impl AsRef<str>, ep: E) -> Self
     where
@@ -151,16 +171,89 @@ impl Route {
     }
 
     /// Attempts to add an [Endpoint] to the specified path.
-    pub fn try_at<E>(mut self, path: impl AsRef<str>, ep: E) -> Result<Self, RouteError>
+    pub fn try_at<E>(self, path: impl AsRef<str>, ep: E) -> Result<Self, RouteError>
+    where
+        E: IntoEndpoint,
+        E::Endpoint: 'static,
+    {
+        self.add(normalize_path(path.as_ref()), None, ep)
+    }
+
+    /// Add an [Endpoint] to the specified path, and name the route so that
+    /// its path can be generated with [`UrlFor`].
+    ///
+    /// # Panics
+    ///
+    /// Panic when there are duplicates in the routing table or the name is
+    /// already used.
+    #[must_use]
+    pub fn at_named<E>(self, name: impl Into<String>, path: impl AsRef<str>, ep: E) -> Self
+    where
+        E: IntoEndpoint,
+        E::Endpoint: 'static,
+    {
+        check_result(self.try_at_named(name, path, ep))
+    }
+
+    /// Attempts to add an [Endpoint] to the specified path, and name the
+    /// route.
+    pub fn try_at_named<E>(
+        self,
+        name: impl Into<String>,
+        path: impl AsRef<str>,
+        ep: E,
+    ) -> Result<Self, RouteError>
     where
         E: IntoEndpoint,
         E::Endpoint: 'static,
     {
-        self.tree
-            .add(&normalize_path(path.as_ref()), ep.map_to_response().boxed())?;
+        let path = normalize_path(path.as_ref());
+        let route = NamedRoute::new(&path)?;
+        let name = name.into();
+        if self.names.contains_key(&name) {
+            return Err(RouteError::DuplicateName(name));
+        }
+        let mut this = self.add(path, Some(name.clone()), ep)?;
+        Arc::make_mut(&mut this.names).insert(name, route);
+        Ok(this)
+    }
+
+    fn add<E>(mut self, path: String, name: Option<String>, ep: E) -> Result<Self, RouteError>
+    where
+        E: IntoEndpoint,
+        E::Endpoint: 'static,
+    {
+        let shape = path_shape(&path)?;
+        self.check_shadowed(&shape, &path)?;
+
+        let ep = ep.into_endpoint();
+        let node = ep.describe_routes();
+        self.tree.add(&path, ep.map_to_response().boxed())?;
+        self.shapes.insert(shape, path.clone());
+        self.routes.push(PathRoute {
+            pattern: path,
+            name,
+            kind: PathRouteKind::At,
+            node,
+        });
         Ok(self)
     }
 
+    /// Returns an error if another path with the same shape has been added.
+    ///
+    /// The paths of the nested endpoints are the nest paths, so a nested
+    /// endpoint and the same nest path are reported as duplicates by the
+    /// radix tree instead.
+    fn check_shadowed(&self, shape: &str, path: &str) -> Result<(), RouteError> {
+        match self.shapes.get(shape) {
+            Some(by) if by != path => Err(RouteError::Shadowed {
+                path: path.to_string(),
+                by: by.clone(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
     /// Add an [Endpoint] to the `/` path.
     ///
     /// Same as `self.at("/", ep)`.
@@ -179,7 +272,8 @@ impl Route {
     ///
     /// # Panics
     ///
-    /// Panic when there are duplicates in the routing table.
+    /// Panic when there are duplicates in the routing table, or the path is
+    /// shadowed by another path, such as `/users` and `/users/*path`.
     #[must_use]
     pub fn nest<E>(self, path: impl AsRef<str>, ep: E) -> Self
     where
@@ -203,7 +297,8 @@ impl Route {
     ///
     /// # Panics
     ///
-    /// Panic when there are duplicates in the routing table.
+    /// Panic when there are duplicates in the routing table, or the path is
+    /// shadowed by another path, such as `/users` and `/users/*path`.
     #[must_use]
     pub fn nest_no_strip<E>(self, path: impl AsRef<str>, ep: E) -> Self
     where
@@ -229,11 +324,22 @@ impl Route {
         E::Endpoint: 'static,
     {
         let ep = Arc::new(ep.into_endpoint());
+        let node = ep.describe_routes();
         let mut path = path.to_string();
         if !path.ends_with('/') {
             path.push('/');
         }
 
+        // named routes of a nested `Route` are visible from this route, also
+        // when it is wrapped by other endpoints which describe its routes
+        if let RouteNode::Path { routes } = &node {
+            let prefix = match strip {
+                true => &path[..path.len() - 1],
+                false => "",
+            };
+            collect_named_routes(Arc::make_mut(&mut self.names), prefix, routes)?;
+        }
+
         struct Nest<T> {
             inner: T,
             root: bool,
@@ -286,6 +392,13 @@ impl Route {
             "regexs are not allowed in the nest path."
         );
 
+        let nest_path = &path[..path.len() - 1];
+        let rest_path = format!("{path}*--poem-rest");
+        let shapes = [path_shape(nest_path)?, path_shape(&rest_path)?];
+        for shape in &shapes {
+            self.check_shadowed(shape, nest_path)?;
+        }
+
         let prefix_len = match strip {
             false => 0,
             true => path.len() - 1,
@@ -296,7 +409,7 @@ impl Route {
         };
 
         self.tree.add(
-            &format!("{path}*--poem-rest"),
+            &rest_path,
             Nest {
                 inner: ep.clone(),
                 root: false,
@@ -317,6 +430,18 @@ impl Route {
             .boxed(),
         )?;
 
+        for shape in shapes {
+            self.shapes.insert(shape, nest_path.to_string());
+        }
+        self.routes.push(PathRoute {
+            pattern: nest_path.to_string(),
+            name: None,
+            kind: match strip {
+                true => PathRouteKind::Nest,
+                false => PathRouteKind::NestNoStrip,
+            },
+            node,
+        });
         Ok(self)
     }
 }
@@ -329,6 +454,11 @@ impl Endpoint for Route {
     type Output = Response;
 
     async fn call(&self, mut req: Request) -> Result<Self::Output> {
+        if !self.names.is_empty() {
+            let url_for = self.url_for(&req);
+            req.set_data(url_for);
+        }
+
         match self.tree.matches(req.uri().path()) {
             Some(matches) => {
                 req.state_mut().match_params.extend(matches.params);
@@ -370,6 +500,136 @@ impl Endpoint for Route {
             None => Err(NotFoundError.into()),
         }
     }
+
+    fn describe_routes(&self) -> RouteNode {
+        RouteNode::Path {
+            routes: self.routes.clone(),
+        }
+    }
+}
+
+impl Route {
+    /// Merges the named routes into the [`UrlFor`] of the parent routes.
+    ///
+    /// The merged routes are cached, since the parent routes and the prefix
+    /// are the same for all the requests unless the route is nested at more
+    /// than one place.
+    fn url_for(&self, req: &Request) -> UrlFor {
+        let parent = req.data::<UrlFor>();
+        if let Some(parent) = parent {
+            if self.names.keys().all(|name| parent.0.contains_key(name)) {
+                return parent.clone();
+            }
+        }
+
+        let prefix_pattern = req.data::<PathPattern>();
+        let strip = req.data::<PathPrefix>().map(|prefix| prefix.0);
+        let mut cache = self.url_for_cache.lock();
+        if let Some(cache) = &*cache {
+            if cache
+                .parent
+                .as_ref()
+                .map(|parent| &parent.0)
+                .map(Arc::as_ptr)
+                == parent.map(|parent| Arc::as_ptr(&parent.0))
+                && cache.prefix.as_ref().map(|prefix| &prefix.0)
+                    == prefix_pattern.map(|prefix| &prefix.0)
+                && cache.strip == strip
+            {
+                return cache.url_for.clone();
+            }
+        }
+
+        let url_for = self.merge_url_for(parent, prefix_pattern, strip);
+        *cache = Some(UrlForCache {
+            parent: parent.cloned(),
+            prefix: prefix_pattern.cloned(),
+            strip,
+            url_for: url_for.clone(),
+        });
+        url_for
+    }
+
+    fn merge_url_for(
+        &self,
+        parent: Option<&UrlFor>,
+        prefix: Option<&PathPattern>,
+        strip: Option<usize>,
+    ) -> UrlFor {
+        let prefix = prefix.map(|pattern| &*pattern.0);
+        let names = self.names.iter().map(|(name, route)| {
+            let route = match (prefix, strip) {
+                (None, None) => route.clone(),
+                (prefix, strip) => {
+                    route.rebase(prefix.unwrap_or_default(), strip.unwrap_or_default())
+                }
+            };
+            (name.clone(), route)
+        });
+
+        match parent {
+            Some(parent) => {
+                let mut routes = (*parent.0).clone();
+                for (name, route) in names {
+                    routes.entry(name).or_insert(route);
+                }
+                UrlFor(Arc::new(routes))
+            }
+            None if prefix.is_none() && strip.is_none() => UrlFor(self.names.clone()),
+            None => UrlFor(Arc::new(names.collect())),
+        }
+    }
+}
+
+/// Adds the named routes described by `routes`, which are nested at `prefix`.
+fn collect_named_routes(
+    names: &mut NamedRoutes,
+    prefix: &str,
+    routes: &[PathRoute],
+) -> Result<(), RouteError> {
+    for route in routes {
+        match (route.kind, &route.node) {
+            (PathRouteKind::At, _) => {
+                let Some(name) = &route.name else {
+                    continue;
+                };
+                if names.contains_key(name) {
+                    return Err(RouteError::DuplicateName(name.clone()));
+                }
+                let named_route = NamedRoute::new(&format!("{prefix}{}", route.pattern))?;
+                names.insert(name.clone(), named_route);
+            }
+            (PathRouteKind::Nest, RouteNode::Path { routes }) => {
+                collect_named_routes(names, &format!("{prefix}{}", route.pattern), routes)?;
+            }
+            (PathRouteKind::NestNoStrip, RouteNode::Path { routes }) => {
+                collect_named_routes(names, prefix, routes)?;
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Returns the path with the names of the parameters removed, so that the
+/// paths which match the same requests have the same shape.
+fn path_shape(path: &str) -> Result<String, RouteError> {
+    let segments = parse_path_segments(path.as_bytes())
+        .map_err(|_| RouteError::InvalidPath(path.to_string()))?;
+    let mut shape = String::new();
+    for segment in segments {
+        match segment {
+            RawSegment::Static(value) => shape.push_str(&String::from_utf8_lossy(value)),
+            RawSegment::Param(_) => shape.push(':'),
+            RawSegment::CatchAll(_) => shape.push('*'),
+            RawSegment::Regex(_, re) => {
+                shape.push('<');
+                shape.push_str(&String::from_utf8_lossy(re));
+                shape.push('>');
+            }
+        }
+    }
+    Ok(shape)
 }
 
 fn normalize_path(path: &str) -> String {
@@ -387,7 +647,10 @@ mod tests {
     use http::StatusCode;
 
     use super::*;
-    use crate::{Error, endpoint::make_sync, handler, test::TestClient};
+    use crate::{
+        Error, RouteDomain, RouteScheme, endpoint::make_sync, error::UrlForError, handler,
+        test::TestClient,
+    };
 
     #[test]
     fn test_normalize_path() {
@@ -781,4 +1044,242 @@ mod tests {
             "/nest_no_strip1/nest_no_strip2/:id"
         );
     }
+
+    #[handler(internal)]
+    fn url(url_for: UrlFor, uri: &Uri) -> String {
+        let id = uri.path().rsplit('/').next().unwrap();
+        let mut urls = vec![
+            url_for.url_for("a", [("id", id)]).unwrap(),
+            url_for.url_for("b", [("path", "x/y z")]).unwrap(),
+        ];
+        if url_for.contains("c") {
+            urls.push(url_for.url_for("c", [("id", id)]).unwrap());
+        }
+        urls.join(",")
+    }
+
+    #[tokio::test]
+    async fn url_for() {
+        let r = Route::new()
+            .at_named("a", "/a/:id<\\d+>", url)
+            .nest(
+                "/nest",
+                Route::new()
+                    .at_named("b", "/b/*path", url)
+                    .nest_no_strip("/c", Route::new().at_named("c", "/c/:id", url)),
+            )
+            .nest(
+                "/wrapped",
+                Route::new().at_named("d", "/d/:id", url).map_to_response(),
+            );
+
+        assert_eq!(get(&r, "/a/1").await, "/a/1,/nest/b/x/y%20z,/nest/c/1");
+        assert_eq!(get(&r, "/nest/b/2").await, "/a/2,/nest/b/x/y%20z,/nest/c/2");
+        assert_eq!(get(&r, "/nest/c/3").await, "/a/3,/nest/b/x/y%20z,/nest/c/3");
+
+        // routes nested through other endpoints which describe their routes are
+        // collected when the route is built
+        assert_eq!(
+            UrlFor(r.names.clone()).url_for("d", [("id", 5)]).unwrap(),
+            "/wrapped/d/5"
+        );
+        let resp = r
+            .call(
+                Request::builder()
+                    .uri(Uri::from_static("/wrapped/d/4"))
+                    .finish(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            resp.into_body().into_string().await.unwrap(),
+            "/a/4,/nest/b/x/y%20z,/nest/c/4"
+        );
+
+        // the routes nested through other endpoints are merged once
+        let hidden = Route::new().at_named("e", "/e", url);
+        let mut req = Request::builder().finish();
+        req.set_data(PathPattern("/hidden".into()));
+        req.set_data(PathPrefix(0));
+        let url_for = hidden.url_for(&req);
+        assert_eq!(
+            url_for
+                .url_for("e", Vec::<(String, String)>::new())
+                .unwrap(),
+            "/hidden/e"
+        );
+        assert!(Arc::ptr_eq(&url_for.0, &hidden.url_for(&req).0));
+    }
+
+    #[test]
+    fn url_for_errors() {
+        let r = Route::new()
+            .at_named("a", "/a/:id<\\d+>", h)
+            .at_named("b", "/b/<\\d+>", h);
+        let url_for = UrlFor(r.names.clone());
+
+        assert_eq!(
+            url_for.url_for("c", [("id", 1)]),
+            Err(UrlForError::UnknownRoute("c".to_string()))
+        );
+        assert_eq!(
+            url_for.url_for("a", None::<(&str, i32)>),
+            Err(UrlForError::MissingParam {
+                route: "a".to_string(),
+                param: "id".to_string()
+            })
+        );
+        assert_eq!(
+            url_for.url_for("a", [("id", "x")]),
+            Err(UrlForError::InvalidParam {
+                route: "a".to_string(),
+                param: "id".to_string(),
+                value: "x".to_string(),
+            })
+        );
+        assert_eq!(
+            url_for.url_for("a", [("id", "1"), ("name", "x")]),
+            Err(UrlForError::UnknownParam {
+                route: "a".to_string(),
+                param: "name".to_string()
+            })
+        );
+        assert_eq!(
+            url_for.url_for("b", None::<(&str, i32)>),
+            Err(UrlForError::UnnamedSegment("b".to_string()))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "duplicate route name: a")]
+    fn duplicate_name() {
+        let _ = Route::new()
+            .at_named("a", "/a", h)
+            .nest("/b", Route::new().at_named("a", "/a", h));
+    }
+
+    #[test]
+    fn describe_routes() {
+        let app = Route::new()
+            .at_named("index", "/", crate::get(h))
+            .nest(
+                "/api",
+                Route::new()
+                    .at("/users", crate::get(h).post(h))
+                    .at("/users/:id", h)
+                    .with(crate::middleware::Tracing),
+            )
+            .nest_no_strip("/static", h)
+            .nest(
+                "/site",
+                RouteDomain::new()
+                    .at("example.com", Route::new().at("/a", crate::get(h)))
+                    .at("*", RouteScheme::new().https(h).fallback(h)),
+            );
+
+        let node = app.describe_routes();
+        let RouteNode::Path { routes } = &node else {
+            panic!("expect path routes");
+        };
+        assert_eq!(
+            routes
+                .iter()
+                .map(|route| (route.pattern.as_str(), route.kind))
+                .collect::<Vec<_>>(),
+            vec![
+                ("/", PathRouteKind::At),
+                ("/api", PathRouteKind::Nest),
+                ("/static", PathRouteKind::NestNoStrip),
+                ("/site", PathRouteKind::Nest),
+            ]
+        );
+
+        let table = node.table();
+        assert_eq!(
+            table.to_string(),
+            "\
+METHODS    PATH            NAME   DOMAIN       SCHEME
+GET        /               index  -            -
+GET, POST  /api/users      -      -            -
+*          /api/users/:id  -      -            -
+*          /static/*       -      -            -
+GET        /site/a         -      example.com  -
+*          /site/*         -      *            https
+*          /site/*         -      *            -
+"
+        );
+
+        assert_eq!(
+            serde_json::to_value(&table.0[1]).unwrap(),
+            serde_json::json!({
+                "methods": ["GET", "POST"],
+                "path": "/api/users",
+                "name": null,
+                "domain": null,
+                "scheme": null,
+            })
+        );
+    }
+
+    #[test]
+    fn shadowed() {
+        assert_eq!(
+            Route::new()
+                .at("/users/:id", h)
+                .try_at("/users/:name", h)
+                .err(),
+            Some(RouteError::Shadowed {
+                path: "/users/:name".to_string(),
+                by: "/users/:id".to_string(),
+            })
+        );
+        assert_eq!(
+            Route::new()
+                .at("/a/:id<\\d+>/b", h)
+                .try_at("/a/<\\d+>/b", h)
+                .err(),
+            Some(RouteError::Shadowed {
+                path: "/a/<\\d+>/b".to_string(),
+                by: "/a/:id<\\d+>/b".to_string(),
+            })
+        );
+        assert!(
+            Route::new()
+                .at("/users/:id", h)
+                .try_at("/users/:id/posts", h)
+                .is_ok()
+        );
+        assert_eq!(
+            Route::new()
+                .at("/users/*path", h)
+                .try_nest("/users", h)
+                .err(),
+            Some(RouteError::Shadowed {
+                path: "/users".to_string(),
+                by: "/users/*path".to_string(),
+            })
+        );
+        assert_eq!(
+            Route::new()
+                .nest_no_strip("/users", h)
+                .try_at("/users/*path", h)
+                .err(),
+            Some(RouteError::Shadowed {
+                path: "/users/*path".to_string(),
+                by: "/users".to_string(),
+            })
+        );
+        assert!(
+            Route::new()
+                .nest("/users", h)
+                .try_at("/users/:id", h)
+                .is_ok()
+        );
+        assert!(
+            Route::new()
+                .at("/users/:id", h)
+                .try_at("/users/:id<\\d+>", h)
+                .is_ok()
+        );
+    }
 }
@@ -3,7 +3,7 @@ use crate::{
     endpoint::BoxEndpoint,
     error::{NotFoundError, RouteError},
     http::header,
-    route::{check_result, internal::trie::Trie},
+    route::{DomainRoute, RouteNode, check_result, internal::trie::Trie},
 };
 
 /// Routing object for `HOST` header
@@ -51,6 +51,7 @@ use crate::{
 #[derive(Default)]
 pub struct RouteDomain {
     tree: Trie<BoxEndpoint<'static>>,
+    routes: Vec<DomainRoute>,
 }
 
 impl RouteDomain {
@@ -79,10 +80,14 @@ impl RouteDomain {
         E: IntoEndpoint,
         E::Endpoint: 'static,
     {
-        self.tree.add(
-            pattern.as_ref(),
-            ep.into_endpoint().map_to_response().boxed(),
-        )?;
+        let ep = ep.into_endpoint();
+        let node = ep.describe_routes();
+        self.tree
+            .add(pattern.as_ref(), ep.map_to_response().boxed())?;
+        self.routes.push(DomainRoute {
+            pattern: pattern.as_ref().to_string(),
+            node,
+        });
         Ok(self)
     }
 }
@@ -101,6 +106,12 @@ impl Endpoint for RouteDomain {
             None => Err(NotFoundError.into()),
         }
     }
+
+    fn describe_routes(&self) -> RouteNode {
+        RouteNode::Domain {
+            routes: self.routes.clone(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -3,12 +3,19 @@ use std::future::Future;
 use futures_util::{FutureExt, future::Either};
 
 use crate::{
-    Endpoint, EndpointExt, IntoEndpoint, Request, Response, Result, endpoint::BoxEndpoint,
-    error::MethodNotAllowedError, http::Method,
+    Endpoint, EndpointExt, Error, IntoEndpoint, Request, Response, Result,
+    endpoint::BoxEndpoint,
+    error::{AllowedMethods, MethodNotAllowedError, join_methods},
+    http::{Method, StatusCode, header},
+    route::{MethodRoute, RouteNode},
 };
 
 /// Routing object for HTTP methods
 ///
+/// `HEAD` requests are handled by the `GET` endpoint if there is no `HEAD`
+/// endpoint, and `OPTIONS` requests are answered with the `Allow` header if
+/// there is no `OPTIONS` endpoint.
+///
 /// # Errors
 ///
 /// - [`MethodNotAllowedError`]
@@ -50,6 +57,7 @@ use crate::{
 ///     .get_response(Request::builder().method(Method::PUT).finish())
 ///     .await;
 /// assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+/// assert_eq!(resp.headers()["allow"], "GET, HEAD, POST, OPTIONS");
 /// # });
 /// ```
 #[derive(Default)]
@@ -164,6 +172,38 @@ impl RouteMethod {
     {
         self.method(Method::TRACE, ep)
     }
+
+    /// Returns the methods allowed by this object, including the methods that
+    /// are handled automatically.
+    fn allowed_methods(&self) -> Vec<Method> {
+        const ORDER: [Method; 9] = [
+            Method::GET,
+            Method::HEAD,
+            Method::POST,
+            Method::PUT,
+            Method::DELETE,
+            Method::CONNECT,
+            Method::OPTIONS,
+            Method::TRACE,
+            Method::PATCH,
+        ];
+
+        let mut allow = self
+            .methods
+            .iter()
+            .map(|(method, _)| method.clone())
+            .chain([Method::OPTIONS])
+            .collect::<Vec<_>>();
+        if allow.contains(&Method::GET) {
+            allow.push(Method::HEAD);
+        }
+        allow.sort_by_key(|method| {
+            let idx = ORDER.iter().position(|m| m == method);
+            (idx.unwrap_or(ORDER.len()), method.to_string())
+        });
+        allow.dedup();
+        allow
+    }
 }
 
 impl Endpoint for RouteMethod {
@@ -188,12 +228,33 @@ impl Endpoint for RouteMethod {
                         }
                         .boxed(),
                     ))
+                } else if req.method() == Method::OPTIONS {
+                    let resp = Response::builder()
+                        .status(StatusCode::NO_CONTENT)
+                        .header(header::ALLOW, join_methods(&self.allowed_methods()))
+                        .finish();
+                    Either::Right(Either::Right(futures_util::future::ok(resp)))
                 } else {
-                    Either::Right(Either::Right(async { Err(MethodNotAllowedError.into()) }))
+                    let mut err = Error::from(MethodNotAllowedError);
+                    err.set_data(AllowedMethods(self.allowed_methods()));
+                    Either::Right(Either::Right(futures_util::future::err(err)))
                 }
             }
         }
     }
+
+    fn describe_routes(&self) -> RouteNode {
+        RouteNode::Method {
+            routes: self
+                .methods
+                .iter()
+                .map(|(method, ep)| MethodRoute {
+                    method: method.to_string(),
+                    node: ep.describe_routes(),
+                })
+                .collect(),
+        }
+    }
 }
 
 /// A helper function, similar to `RouteMethod::new().get(ep)`.
@@ -339,6 +400,33 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn allow_header() {
+        #[handler(internal)]
+        fn index() -> &'static str {
+            "hello"
+        }
+
+        let cli = TestClient::new(RouteMethod::new().get(index).post(index));
+        let resp = cli.put("/").send().await;
+        resp.assert_status(StatusCode::METHOD_NOT_ALLOWED);
+        resp.assert_header(header::ALLOW, "GET, HEAD, POST, OPTIONS");
+
+        let resp = cli.options("/").send().await;
+        resp.assert_status(StatusCode::NO_CONTENT);
+        resp.assert_header(header::ALLOW, "GET, HEAD, POST, OPTIONS");
+
+        // explicit endpoints take precedence
+        let cli = TestClient::new(RouteMethod::new().options(index).head(index));
+        let resp = cli.options("/").send().await;
+        resp.assert_status_is_ok();
+        resp.assert_text("hello").await;
+
+        let resp = cli.get("/").send().await;
+        resp.assert_status(StatusCode::METHOD_NOT_ALLOWED);
+        resp.assert_header(header::ALLOW, "HEAD, OPTIONS");
+    }
+
     #[tokio::test]
     async fn head_method() {
         #[handler(internal)]
@@ -1,8 +1,10 @@
 use http::uri::Scheme;
 
 use crate::{
-    Endpoint, EndpointExt, IntoEndpoint, Request, Response, endpoint::BoxEndpoint,
+    Endpoint, EndpointExt, IntoEndpoint, Request, Response,
+    endpoint::BoxEndpoint,
     error::NotFoundError,
+    route::{RouteNode, SchemeRoute},
 };
 
 /// Routing object for request scheme
@@ -89,4 +91,21 @@ impl Endpoint for RouteScheme {
             },
         }
     }
+
+    fn describe_routes(&self) -> RouteNode {
+        RouteNode::Scheme {
+            routes: self
+                .schemes
+                .iter()
+                .map(|(scheme, ep)| SchemeRoute {
+                    scheme: scheme.to_string(),
+                    node: ep.describe_routes(),
+                })
+                .collect(),
+            fallback: self
+                .fallback
+                .as_ref()
+                .map(|ep| Box::new(ep.describe_routes())),
+        }
+    }
 }
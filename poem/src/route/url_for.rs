@@ -0,0 +1,204 @@
+use std::{collections::HashMap, fmt::Display, sync::Arc};
+
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC, utf8_percent_encode};
+use regex::Regex;
+
+use crate::{
+    FromRequest, Request, RequestBody, Result,
+    error::{RouteError, UrlForError},
+    route::internal::radix_tree::{RawSegment, parse_path_segments},
+};
+
+const SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+const TAIL: &AsciiSet = &SEGMENT.remove(b'/');
+
+#[derive(Debug, Clone)]
+enum UrlSegment {
+    Static(String),
+    Param(String),
+    CatchAll(Option<String>),
+    Regex(Option<String>, Regex),
+}
+
+/// The path pattern of a named route.
+#[derive(Debug, Clone)]
+pub(crate) struct NamedRoute {
+    segments: Vec<UrlSegment>,
+}
+
+impl NamedRoute {
+    pub(crate) fn new(path: &str) -> Result<Self, RouteError> {
+        let raw_segments = parse_path_segments(path.as_bytes())
+            .map_err(|_| RouteError::InvalidPath(path.to_string()))?;
+        let to_string = |value: &[u8]| String::from_utf8_lossy(value).into_owned();
+
+        let mut segments = Vec::with_capacity(raw_segments.len());
+        for raw_segment in raw_segments {
+            segments.push(match raw_segment {
+                RawSegment::Static(value) => UrlSegment::Static(to_string(value)),
+                RawSegment::Param(name) => UrlSegment::Param(to_string(name)),
+                RawSegment::CatchAll(name) => UrlSegment::CatchAll(name.map(to_string)),
+                RawSegment::Regex(name, re) => {
+                    let re = to_string(re);
+                    let regex = Regex::new(&format!("^(?:{re})$")).map_err(|_| {
+                        RouteError::InvalidRegex {
+                            path: path.to_string(),
+                            regex: re,
+                        }
+                    })?;
+                    UrlSegment::Regex(name.map(to_string), regex)
+                }
+            });
+        }
+
+        Ok(Self { segments })
+    }
+
+    /// Returns the route as seen from a parent route, which replaces the first
+    /// `strip` bytes of the path with `prefix`.
+    pub(crate) fn rebase(&self, prefix: &str, strip: usize) -> Self {
+        let mut segments = self.segments.clone();
+        match segments.first_mut() {
+            Some(UrlSegment::Static(value)) => {
+                *value = format!("{prefix}{}", value.get(strip..).unwrap_or_default());
+            }
+            _ => segments.insert(0, UrlSegment::Static(prefix.to_string())),
+        }
+        Self { segments }
+    }
+
+    fn generate(
+        &self,
+        name: &str,
+        mut params: HashMap<String, String>,
+    ) -> Result<String, UrlForError> {
+        let mut path = String::new();
+
+        for segment in &self.segments {
+            let (param, regex, tail) = match segment {
+                UrlSegment::Static(value) => {
+                    path.push_str(value);
+                    continue;
+                }
+                UrlSegment::Param(param) => (Some(param), None, false),
+                UrlSegment::CatchAll(param) => (param.as_ref(), None, true),
+                UrlSegment::Regex(param, regex) => (param.as_ref(), Some(regex), false),
+            };
+
+            let param = param.ok_or_else(|| UrlForError::UnnamedSegment(name.to_string()))?;
+            let value = params
+                .remove(param)
+                .ok_or_else(|| UrlForError::MissingParam {
+                    route: name.to_string(),
+                    param: param.clone(),
+                })?;
+
+            let is_valid = match regex {
+                Some(regex) => regex.is_match(&value),
+                None => tail || !value.is_empty(),
+            };
+            if !is_valid {
+                return Err(UrlForError::InvalidParam {
+                    route: name.to_string(),
+                    param: param.clone(),
+                    value,
+                });
+            }
+
+            let set = if tail { TAIL } else { SEGMENT };
+            path.extend(utf8_percent_encode(&value, set));
+        }
+
+        if let Some(param) = params.into_keys().next() {
+            return Err(UrlForError::UnknownParam {
+                route: name.to_string(),
+                param,
+            });
+        }
+
+        Ok(path)
+    }
+}
+
+pub(crate) type NamedRoutes = HashMap<String, NamedRoute>;
+
+/// An extractor that generates the path of a named route.
+///
+/// Routes are named with [`Route::at_named`](crate::Route::at_named). The
+/// generated path includes the prefixes of [`Route::nest`](crate::Route::nest),
+/// and the parameters are validated against the regular expressions of the
+/// route.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     Route, UrlFor, get, handler,
+///     http::{StatusCode, header},
+///     test::TestClient,
+///     web::{Path, Redirect},
+/// };
+///
+/// #[handler]
+/// fn user(Path(id): Path<u32>) -> String {
+///     format!("user {id}")
+/// }
+///
+/// #[handler]
+/// fn me(url_for: UrlFor) -> poem::Result<Redirect> {
+///     let path = url_for.url_for("user", [("id", 42)])?;
+///     Ok(Redirect::see_other(path))
+/// }
+///
+/// let app = Route::new().nest(
+///     "/api",
+///     Route::new()
+///         .at_named("user", "/users/:id<\\d+>", get(user))
+///         .at("/me", get(me)),
+/// );
+/// let cli = TestClient::new(app);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/api/me").send().await;
+/// resp.assert_status(StatusCode::SEE_OTHER);
+/// resp.assert_header(header::LOCATION, "/api/users/42");
+/// # });
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct UrlFor(pub(crate) Arc<NamedRoutes>);
+
+impl UrlFor {
+    /// Generates the path of the route with the specified name.
+    pub fn url_for<I, K, V>(&self, name: &str, params: I) -> Result<String, UrlForError>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Display,
+    {
+        let route = self
+            .0
+            .get(name)
+            .ok_or_else(|| UrlForError::UnknownRoute(name.to_string()))?;
+        let params = params
+            .into_iter()
+            .map(|(name, value)| (name.into(), value.to_string()))
+            .collect();
+        route.generate(name, params)
+    }
+
+    /// Returns `true` if there is a route with the specified name.
+    pub fn contains(&self, name: &str) -> bool {
+        self.0.contains_key(name)
+    }
+}
+
+impl<'a> FromRequest<'a> for UrlFor {
+    async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
+        Ok(req.data::<UrlFor>().cloned().unwrap_or_default())
+    }
+}
@@ -5,6 +5,7 @@ use serde_json::Value;
 use crate::{
     Endpoint, Middleware, Request, Result,
     middleware::{CookieJarManager, CookieJarManagerEndpoint},
+    route::RouteNode,
     session::{CookieConfig, Session, SessionStatus},
 };
 
@@ -88,6 +89,10 @@ impl<E: Endpoint> Endpoint for CookieSessionEndpoint<E> {
 
         Ok(resp)
     }
+
+    fn describe_routes(&self) -> RouteNode {
+        self.inner.describe_routes()
+    }
 }
 
 #[cfg(test)]
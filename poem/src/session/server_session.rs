@@ -6,6 +6,7 @@ use rand::{Rng, rng};
 use crate::{
     Endpoint, Middleware, Request, Result,
     middleware::{CookieJarManager, CookieJarManagerEndpoint},
+    route::RouteNode,
     session::{CookieConfig, Session, SessionStatus, session_storage::SessionStorage},
 };
 
@@ -113,4 +114,8 @@ where
 
         Ok(resp)
     }
+
+    fn describe_routes(&self) -> RouteNode {
+        self.inner.describe_routes()
+    }
 }
@@ -1,6 +1,8 @@
 use http::{HeaderMap, HeaderValue, Method, header, header::HeaderName};
 
-use crate::{Endpoint, IntoEndpoint, test::TestRequestBuilder};
+use crate::{Endpoint, IntoEndpoint, Request, Response, test::TestRequestBuilder};
+#[cfg(feature = "cookie")]
+use crate::{test::cookie_store::CookieStore, web::cookie::Cookie};
 
 macro_rules! impl_methods {
     ($($(#[$docs:meta])* ($name:ident, $method:ident)),*) => {
@@ -17,6 +19,9 @@ macro_rules! impl_methods {
 pub struct TestClient<E> {
     pub(crate) ep: E,
     pub(crate) default_headers: HeaderMap,
+    #[cfg(feature = "cookie")]
+    pub(crate) cookie_store: Option<parking_lot::Mutex<CookieStore>>,
+    pub(crate) max_redirects: usize,
 }
 
 impl<E: Endpoint> TestClient<E> {
@@ -28,6 +33,9 @@ impl<E: Endpoint> TestClient<E> {
         TestClient {
             ep: ep.into_endpoint(),
             default_headers: Default::default(),
+            #[cfg(feature = "cookie")]
+            cookie_store: None,
+            max_redirects: 0,
         }
     }
 
@@ -123,6 +131,171 @@ impl<E: Endpoint> TestClient<E> {
         self.default_header(header::CONTENT_TYPE, content_type.as_ref())
     }
 
+    /// Enables the cookie store, which stores the cookies of the responses
+    /// and sends them with the subsequent requests, following the domain,
+    /// path and expiry rules of
+    /// [RFC 6265](https://www.rfc-editor.org/rfc/rfc6265).
+    ///
+    /// The requests without an authority in the URI are considered to be
+    /// sent to `localhost` over HTTPS, so the `Secure` cookies are sent
+    /// unless the URI uses the `http` scheme.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poem::{
+    ///     Route, get, handler,
+    ///     test::TestClient,
+    ///     web::cookie::{Cookie, CookieJar},
+    ///     EndpointExt,
+    ///     middleware::CookieJarManager,
+    /// };
+    ///
+    /// #[handler]
+    /// fn login(cookie_jar: &CookieJar) {
+    ///     cookie_jar.add(Cookie::new_with_str("user", "alice"));
+    /// }
+    ///
+    /// #[handler]
+    /// fn profile(cookie_jar: &CookieJar) -> String {
+    ///     cookie_jar
+    ///         .get("user")
+    ///         .map(|cookie| cookie.value_str().to_string())
+    ///         .unwrap_or_default()
+    /// }
+    ///
+    /// let app = Route::new()
+    ///     .at("/login", get(login))
+    ///     .at("/profile", get(profile))
+    ///     .with(CookieJarManager::new());
+    /// let cli = TestClient::new(app).cookie_store(true);
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// cli.get("/login").send().await.assert_status_is_ok();
+    /// let resp = cli.get("/profile").send().await;
+    /// resp.assert_text("alice").await;
+    /// assert_eq!(cli.cookie("user").unwrap().value_str(), "alice");
+    /// # });
+    /// ```
+    #[cfg(feature = "cookie")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cookie")))]
+    #[must_use]
+    pub fn cookie_store(self, enable: bool) -> Self {
+        Self {
+            cookie_store: enable.then(Default::default),
+            ..self
+        }
+    }
+
+    /// Returns the cookie with the name in the cookie store.
+    ///
+    /// Returns `None` if the cookie store is not enabled.
+    #[cfg(feature = "cookie")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cookie")))]
+    pub fn cookie(&self, name: &str) -> Option<Cookie> {
+        self.cookie_store.as_ref()?.lock().get(name)
+    }
+
+    /// Removes all the cookies in the cookie store.
+    #[cfg(feature = "cookie")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cookie")))]
+    pub fn clear_cookies(&self) {
+        if let Some(cookie_store) = &self.cookie_store {
+            cookie_store.lock().clear();
+        }
+    }
+
+    /// Follows the redirects up to `max_redirects` hops, default is `0`,
+    /// which means the redirects are not followed.
+    ///
+    /// `303` redirects, as well as `301` and `302` redirects of `POST`
+    /// requests, are followed with a `GET` request without a body, and the
+    /// other redirects keep the original method and body.
+    ///
+    /// # Panics
+    ///
+    /// Sending a request panics if there are more than `max_redirects`
+    /// redirects.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poem::{Route, get, handler, test::TestClient, web::Redirect};
+    ///
+    /// #[handler]
+    /// fn old() -> Redirect {
+    ///     Redirect::see_other("/new")
+    /// }
+    ///
+    /// #[handler]
+    /// fn new() -> &'static str {
+    ///     "new"
+    /// }
+    ///
+    /// let app = Route::new().at("/old", get(old)).at("/new", get(new));
+    /// let cli = TestClient::new(app).follow_redirects(5);
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let resp = cli.get("/old").send().await;
+    /// resp.assert_status_is_ok();
+    /// resp.assert_text("new").await;
+    /// # });
+    /// ```
+    #[must_use]
+    pub fn follow_redirects(self, max_redirects: usize) -> Self {
+        Self {
+            max_redirects,
+            ..self
+        }
+    }
+
+    /// Adds the cookies in the cookie store to the request.
+    pub(crate) fn add_cookies(&self, req: &mut Request) {
+        #[cfg(feature = "cookie")]
+        if let Some(cookie_store) = &self.cookie_store {
+            let Some(value) = cookie_store.lock().header_value(req.uri(), req.headers()) else {
+                return;
+            };
+            let value = match req
+                .headers()
+                .get(header::COOKIE)
+                .and_then(|existing| existing.to_str().ok())
+            {
+                Some(existing) => HeaderValue::try_from(format!(
+                    "{existing}; {}",
+                    value.to_str().unwrap_or_default()
+                ))
+                .unwrap_or(value),
+                None => value,
+            };
+            req.headers_mut().insert(header::COOKIE, value);
+        }
+        #[cfg(not(feature = "cookie"))]
+        let _ = req;
+    }
+
+    /// Sends the request to the endpoint with the cookies in the cookie
+    /// store, and stores the cookies of the response.
+    pub(crate) async fn call(&self, mut req: Request) -> Response
+    where
+        E: Endpoint,
+    {
+        self.add_cookies(&mut req);
+        #[cfg(feature = "cookie")]
+        let parts = self
+            .cookie_store
+            .is_some()
+            .then(|| (req.uri().clone(), req.headers().clone()));
+
+        let resp = self.ep.get_response(req).await;
+
+        #[cfg(feature = "cookie")]
+        if let (Some(cookie_store), Some((uri, headers))) = (&self.cookie_store, parts) {
+            cookie_store.lock().store(&uri, &headers, resp.headers());
+        }
+        resp
+    }
+
     /// Create a [`TestRequestBuilder`].
     pub fn request(&self, method: Method, uri: impl Into<String>) -> TestRequestBuilder<'_, E> {
         TestRequestBuilder::new(self, method, uri.into())
@@ -149,3 +322,119 @@ impl<E: Endpoint> TestClient<E> {
         (trace, TRACE)
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Route, endpoint::make_sync, handler, http::Uri, web::Redirect};
+
+    #[cfg(feature = "cookie")]
+    #[tokio::test]
+    async fn cookie_store() {
+        use crate::{
+            EndpointExt, get,
+            http::StatusCode,
+            middleware::CookieJarManager,
+            post,
+            web::cookie::{Cookie, CookieJar},
+        };
+
+        #[handler(internal)]
+        fn login(cookie_jar: &CookieJar) -> Redirect {
+            let mut cookie = Cookie::new_with_str("session", "alice");
+            cookie.set_http_only(true);
+            cookie.set_secure(true);
+            cookie.set_path("/");
+            cookie_jar.add(cookie);
+            Redirect::see_other("/me")
+        }
+
+        #[handler(internal)]
+        fn me(cookie_jar: &CookieJar) -> String {
+            cookie_jar
+                .get("session")
+                .map(|cookie| cookie.value_str().to_string())
+                .unwrap_or_else(|| "anonymous".to_string())
+        }
+
+        #[handler(internal)]
+        fn logout(cookie_jar: &CookieJar) {
+            let mut cookie = Cookie::named("session");
+            cookie.set_path("/");
+            cookie.make_removal();
+            cookie_jar.add(cookie);
+        }
+
+        let app = Route::new()
+            .at("/login", post(login))
+            .at("/me", get(me))
+            .at("/logout", post(logout))
+            .with(CookieJarManager::new());
+
+        let cli = TestClient::new(&app).cookie_store(true).follow_redirects(1);
+        let resp = cli.post("/login").send().await;
+        resp.assert_status_is_ok();
+        resp.assert_text("alice").await;
+        assert_eq!(cli.cookie("session").unwrap().value_str(), "alice");
+
+        // secure cookies are not sent over http
+        cli.get("http://localhost/me")
+            .send()
+            .await
+            .assert_text("anonymous")
+            .await;
+
+        let resp = cli.post("/logout").send().await;
+        resp.assert_cookie_removed("session");
+        cli.get("/me").send().await.assert_text("anonymous").await;
+        assert!(cli.cookie("session").is_none());
+
+        // the cookie store is disabled by default
+        let cli = TestClient::new(&app).follow_redirects(1);
+        let resp = cli.post("/login").send().await;
+        resp.assert_text("anonymous").await;
+        let resp = TestClient::new(&app).post("/login").send().await;
+        resp.assert_status(StatusCode::SEE_OTHER);
+        let cookie = resp.assert_cookie("session", "alice");
+        assert!(cookie.http_only());
+        assert!(cookie.secure());
+    }
+
+    #[tokio::test]
+    async fn follow_redirects() {
+        #[handler(internal)]
+        fn echo(method: Method, uri: &Uri, body: String) -> String {
+            format!("{method} {uri} {body}")
+        }
+
+        let app = Route::new()
+            .at("/found", make_sync(|_| Redirect::see_other("a/echo")))
+            .at("/temporary", make_sync(|_| Redirect::temporary("/a/echo")))
+            .at("/a/echo", echo);
+        let cli = TestClient::new(app).follow_redirects(2);
+
+        cli.post("/found")
+            .body("data")
+            .send()
+            .await
+            .assert_text("GET /a/echo ")
+            .await;
+        cli.put("/temporary")
+            .body("data")
+            .send()
+            .await
+            .assert_text("PUT /a/echo data")
+            .await;
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "too many redirects")]
+    async fn too_many_redirects() {
+        let app = Route::new().at("/", make_sync(|_| Redirect::temporary("/")));
+        TestClient::new(app)
+            .follow_redirects(3)
+            .get("/")
+            .send()
+            .await;
+    }
+}
@@ -0,0 +1,237 @@
+use http::{HeaderMap, HeaderValue, Uri, header};
+use time::OffsetDateTime;
+
+use crate::web::cookie::Cookie;
+
+/// The host of the requests without an authority in the URI and a `Host`
+/// header.
+const DEFAULT_HOST: &str = "localhost";
+
+struct StoredCookie {
+    name: String,
+    value: String,
+    domain: String,
+    host_only: bool,
+    path: String,
+    expires: Option<OffsetDateTime>,
+    secure: bool,
+}
+
+impl StoredCookie {
+    fn is_expired(&self, now: OffsetDateTime) -> bool {
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+
+    fn matches(&self, host: &str, path: &str, secure: bool) -> bool {
+        let domain_matches = if self.host_only {
+            host == self.domain
+        } else {
+            domain_match(host, &self.domain)
+        };
+        domain_matches && path_match(path, &self.path) && (secure || !self.secure)
+    }
+}
+
+/// The cookies stored by a [`TestClient`](super::TestClient), following the
+/// domain, path and expiry rules of
+/// [RFC 6265](https://www.rfc-editor.org/rfc/rfc6265#section-5.3).
+#[derive(Default)]
+pub(crate) struct CookieStore {
+    cookies: Vec<StoredCookie>,
+}
+
+impl CookieStore {
+    /// Stores the cookies of the `Set-Cookie` headers of a response to the
+    /// request.
+    pub(crate) fn store(&mut self, uri: &Uri, req_headers: &HeaderMap, resp_headers: &HeaderMap) {
+        let host = request_host(uri, req_headers);
+        let now = OffsetDateTime::now_utc();
+
+        for value in resp_headers.get_all(header::SET_COOKIE) {
+            let Some(cookie) = value
+                .to_str()
+                .ok()
+                .and_then(|value| libcookie::Cookie::parse(value).ok())
+            else {
+                continue;
+            };
+
+            let (domain, host_only) = match cookie.domain() {
+                Some(domain) => {
+                    let domain = domain.trim_start_matches('.').to_ascii_lowercase();
+                    if !domain_match(&host, &domain) {
+                        continue;
+                    }
+                    (domain, false)
+                }
+                None => (host.clone(), true),
+            };
+            let path = match cookie.path() {
+                Some(path) if path.starts_with('/') => path.to_string(),
+                _ => default_path(uri.path()),
+            };
+            let expires = match cookie.max_age() {
+                Some(max_age) => Some(now + max_age),
+                None => cookie.expires_datetime(),
+            };
+
+            self.cookies.retain(|stored| {
+                !(stored.name == cookie.name() && stored.domain == domain && stored.path == path)
+            });
+            let stored = StoredCookie {
+                name: cookie.name().to_string(),
+                value: cookie.value().to_string(),
+                domain,
+                host_only,
+                path,
+                expires,
+                secure: cookie.secure().unwrap_or_default(),
+            };
+            if !stored.is_expired(now) {
+                self.cookies.push(stored);
+            }
+        }
+    }
+
+    /// Returns the value of the `Cookie` header for the request.
+    pub(crate) fn header_value(&mut self, uri: &Uri, headers: &HeaderMap) -> Option<HeaderValue> {
+        let now = OffsetDateTime::now_utc();
+        self.cookies.retain(|cookie| !cookie.is_expired(now));
+
+        let host = request_host(uri, headers);
+        let secure = uri.scheme_str() != Some("http");
+        let mut cookies = self
+            .cookies
+            .iter()
+            .filter(|cookie| cookie.matches(&host, uri.path(), secure))
+            .collect::<Vec<_>>();
+        if cookies.is_empty() {
+            return None;
+        }
+
+        // RFC 6265, Section 5.4, cookies with longer paths are listed first.
+        cookies.sort_by_key(|cookie| std::cmp::Reverse(cookie.path.len()));
+        let value = cookies
+            .iter()
+            .map(|cookie| format!("{}={}", cookie.name, cookie.value))
+            .collect::<Vec<_>>()
+            .join("; ");
+        HeaderValue::try_from(value).ok()
+    }
+
+    /// Returns the cookie with the name.
+    pub(crate) fn get(&self, name: &str) -> Option<Cookie> {
+        let now = OffsetDateTime::now_utc();
+        self.cookies
+            .iter()
+            .find(|cookie| cookie.name == name && !cookie.is_expired(now))
+            .and_then(|cookie| Cookie::parse(format!("{}={}", cookie.name, cookie.value)).ok())
+    }
+
+    /// Removes all the cookies.
+    pub(crate) fn clear(&mut self) {
+        self.cookies.clear();
+    }
+}
+
+fn request_host(uri: &Uri, headers: &HeaderMap) -> String {
+    uri.host()
+        .or_else(|| {
+            headers
+                .get(header::HOST)
+                .and_then(|value| value.to_str().ok())
+                .map(|host| host.split(':').next().unwrap_or(host))
+        })
+        .unwrap_or(DEFAULT_HOST)
+        .to_ascii_lowercase()
+}
+
+fn domain_match(host: &str, domain: &str) -> bool {
+    host == domain || (host.ends_with(domain) && host[..host.len() - domain.len()].ends_with('.'))
+}
+
+fn path_match(path: &str, cookie_path: &str) -> bool {
+    path == cookie_path
+        || (path.starts_with(cookie_path)
+            && (cookie_path.ends_with('/') || path[cookie_path.len()..].starts_with('/')))
+}
+
+fn default_path(path: &str) -> String {
+    match path.rfind('/') {
+        Some(0) | None => "/".to_string(),
+        Some(idx) => path[..idx].to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store(store: &mut CookieStore, uri: &str, set_cookies: &[&str]) {
+        let mut headers = HeaderMap::new();
+        for value in set_cookies {
+            headers.append(header::SET_COOKIE, HeaderValue::from_str(value).unwrap());
+        }
+        store.store(&uri.parse().unwrap(), &HeaderMap::new(), &headers);
+    }
+
+    fn cookies(store: &mut CookieStore, uri: &str) -> Option<String> {
+        store
+            .header_value(&uri.parse().unwrap(), &HeaderMap::new())
+            .map(|value| value.to_str().unwrap().to_string())
+    }
+
+    #[test]
+    fn domain_and_path() {
+        let mut jar = CookieStore::default();
+        store(
+            &mut jar,
+            "http://www.example.com/account/login",
+            &[
+                "a=1",
+                "b=2; Path=/",
+                "c=3; Domain=example.com; Path=/",
+                "d=4; Domain=other.com",
+                "e=5; Path=/account/settings",
+            ],
+        );
+
+        assert_eq!(
+            cookies(&mut jar, "http://www.example.com/account/settings/x").as_deref(),
+            Some("e=5; a=1; b=2; c=3")
+        );
+        assert_eq!(
+            cookies(&mut jar, "http://www.example.com/accounts").as_deref(),
+            Some("b=2; c=3")
+        );
+        assert_eq!(
+            cookies(&mut jar, "http://api.example.com/").as_deref(),
+            Some("c=3")
+        );
+        assert_eq!(cookies(&mut jar, "http://other.com/"), None);
+    }
+
+    #[test]
+    fn secure_and_expiry() {
+        let mut jar = CookieStore::default();
+        store(&mut jar, "/", &["a=1; Secure", "b=2; Max-Age=60", "c=3"]);
+        assert_eq!(cookies(&mut jar, "/").as_deref(), Some("a=1; b=2; c=3"));
+        assert_eq!(
+            cookies(&mut jar, "http://localhost/").as_deref(),
+            Some("b=2; c=3")
+        );
+
+        store(
+            &mut jar,
+            "/",
+            &[
+                "b=; Max-Age=0",
+                "c=; Expires=Thu, 01 Jan 1970 00:00:00 GMT",
+                "a=10; Secure",
+            ],
+        );
+        assert_eq!(cookies(&mut jar, "/").as_deref(), Some("a=10"));
+        assert_eq!(jar.get("a").unwrap().value_str(), "10");
+        assert!(jar.get("b").is_none());
+    }
+}
@@ -0,0 +1,390 @@
+use std::{fmt::Write, path::PathBuf};
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// The environment variable which makes
+/// [`TestResponse::assert_json_snapshot`](super::TestResponse::assert_json_snapshot)
+/// write the snapshot files instead of comparing them.
+pub(crate) const UPDATE_SNAPSHOTS_ENV: &str = "POEM_UPDATE_SNAPSHOTS";
+
+/// Returns `true` if the string placeholder matches the value, or `None` if
+/// the string is not a placeholder.
+fn match_placeholder(placeholder: &str, value: &Value) -> Option<bool> {
+    let matched = match placeholder {
+        "{{any}}" => true,
+        "{{string}}" => value.is_string(),
+        "{{number}}" => value.is_number(),
+        "{{integer}}" => value.is_i64() || value.is_u64(),
+        "{{bool}}" => value.is_boolean(),
+        "{{array}}" => value.is_array(),
+        "{{object}}" => value.is_object(),
+        "{{uuid}}" => value.as_str().is_some_and(is_uuid),
+        "{{datetime}}" => value.as_str().is_some_and(is_datetime),
+        _ => return None,
+    };
+    Some(matched)
+}
+
+fn is_uuid(s: &str) -> bool {
+    let bytes = s.as_bytes();
+    bytes.len() == 36
+        && bytes.iter().enumerate().all(|(idx, b)| match idx {
+            8 | 13 | 18 | 23 => *b == b'-',
+            _ => b.is_ascii_hexdigit(),
+        })
+}
+
+fn is_datetime(s: &str) -> bool {
+    static RE: std::sync::LazyLock<regex::Regex> = std::sync::LazyLock::new(|| {
+        regex::Regex::new(
+            r"^\d{4}-\d{2}-\d{2}[Tt ]\d{2}:\d{2}:\d{2}(\.\d+)?([Zz]|[+-]\d{2}:\d{2})$",
+        )
+        .unwrap()
+    });
+    RE.is_match(s)
+}
+
+/// Returns `true` if the JSON pointer matches the pattern, where `*` matches
+/// any segment.
+fn path_matches(pattern: &str, path: &str) -> bool {
+    let mut pattern = pattern.split('/');
+    let mut path = path.split('/');
+    loop {
+        match (pattern.next(), path.next()) {
+            (Some(a), Some(b)) if a == "*" || a == b => {}
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
+}
+
+/// A matcher to check a JSON value against an expected value with partial
+/// matching, ignored paths and placeholders.
+///
+/// The following string placeholders in the expected value match any value
+/// of the type:
+///
+/// | Placeholder    | Matches                         |
+/// |----------------|---------------------------------|
+/// | `{{any}}`      | Any value                       |
+/// | `{{string}}`   | Strings                         |
+/// | `{{number}}`   | Numbers                         |
+/// | `{{integer}}`  | Integers                        |
+/// | `{{bool}}`     | Booleans                        |
+/// | `{{array}}`    | Arrays                          |
+/// | `{{object}}`   | Objects                         |
+/// | `{{uuid}}`     | UUID strings                    |
+/// | `{{datetime}}` | RFC 3339 date-time strings      |
+///
+/// # Example
+///
+/// ```
+/// use poem::test::TestJsonMatcher;
+/// use serde_json::json;
+///
+/// TestJsonMatcher::new(json!({
+///     "id": "{{uuid}}",
+///     "name": "alice",
+///     "tags": [{ "name": "a" }],
+/// }))
+/// .ignore("/tags/*/created_at")
+/// .assert_matches(&json!({
+///     "id": "67e55044-10b1-426f-9247-bb680e5fe0c8",
+///     "name": "alice",
+///     "age": 18,
+///     "tags": [{ "name": "a", "created_at": 1700000000 }],
+/// }));
+/// ```
+#[derive(Debug, Clone)]
+pub struct TestJsonMatcher {
+    expected: Value,
+    partial: bool,
+    ignored: Vec<String>,
+}
+
+impl TestJsonMatcher {
+    /// Create a matcher for the expected value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the expected value cannot be serialized to JSON.
+    pub fn new(expected: impl Serialize) -> Self {
+        Self {
+            expected: serde_json::to_value(expected).expect("valid json"),
+            partial: true,
+            ignored: Vec::new(),
+        }
+    }
+
+    /// Sets whether the objects of the actual value may have more fields than
+    /// the expected value, default is `true`.
+    #[must_use]
+    pub fn partial(self, partial: bool) -> Self {
+        Self { partial, ..self }
+    }
+
+    /// Ignores the value at the [JSON pointer](https://www.rfc-editor.org/rfc/rfc6901),
+    /// where the `*` segment matches any field or array index, such as
+    /// `/items/*/id`.
+    #[must_use]
+    pub fn ignore(mut self, path: impl Into<String>) -> Self {
+        self.ignored.push(path.into());
+        self
+    }
+
+    /// Asserts that the actual value matches the expected value.
+    #[track_caller]
+    pub fn assert_matches(&self, actual: &Value) {
+        let mut errors = Vec::new();
+        self.check(&self.expected, actual, "", &mut errors);
+        if !errors.is_empty() {
+            let mut msg = String::from("json does not match:\n");
+            for err in &errors {
+                let _ = writeln!(msg, "  {err}");
+            }
+            let _ = write!(
+                msg,
+                "actual:\n{}",
+                serde_json::to_string_pretty(actual).unwrap_or_default()
+            );
+            panic!("{msg}");
+        }
+    }
+
+    fn check(&self, expected: &Value, actual: &Value, path: &str, errors: &mut Vec<String>) {
+        if self.is_ignored(path) {
+            return;
+        }
+
+        let display_path = if path.is_empty() { "/" } else { path };
+        if let Some(matched) = expected
+            .as_str()
+            .and_then(|placeholder| match_placeholder(placeholder, actual))
+        {
+            if !matched {
+                errors.push(format!(
+                    "{display_path}: expect {expected}, actual {actual}"
+                ));
+            }
+            return;
+        }
+
+        match (expected, actual) {
+            (Value::Object(expected), Value::Object(actual)) => {
+                for (key, expected) in expected {
+                    let path = format!("{path}/{}", escape_pointer(key));
+                    match actual.get(key) {
+                        Some(actual) => self.check(expected, actual, &path, errors),
+                        None if self.is_ignored(&path) => {}
+                        None => errors.push(format!("{path}: missing field")),
+                    }
+                }
+                if !self.partial {
+                    for key in actual.keys().filter(|key| !expected.contains_key(*key)) {
+                        let path = format!("{path}/{}", escape_pointer(key));
+                        if !self.is_ignored(&path) {
+                            errors.push(format!("{path}: unexpected field"));
+                        }
+                    }
+                }
+            }
+            (Value::Array(expected), Value::Array(actual)) => {
+                if expected.len() != actual.len() {
+                    errors.push(format!(
+                        "{display_path}: expect {} items, actual {} items",
+                        expected.len(),
+                        actual.len()
+                    ));
+                    return;
+                }
+                for (idx, (expected, actual)) in expected.iter().zip(actual).enumerate() {
+                    self.check(expected, actual, &format!("{path}/{idx}"), errors);
+                }
+            }
+            (Value::Number(a), Value::Number(b)) if a.as_f64() == b.as_f64() => {}
+            _ if expected == actual => {}
+            _ => errors.push(format!(
+                "{display_path}: expect {expected}, actual {actual}"
+            )),
+        }
+    }
+
+    fn is_ignored(&self, path: &str) -> bool {
+        self.ignored
+            .iter()
+            .any(|pattern| path_matches(pattern, path))
+    }
+}
+
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// Returns the path of the snapshot file.
+pub(crate) fn snapshot_path(name: &str) -> PathBuf {
+    let dir = std::env::var_os("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_default();
+    dir.join("tests")
+        .join("snapshots")
+        .join(format!("{name}.json"))
+}
+
+/// Returns the new snapshot, which keeps the placeholders of the old
+/// snapshot that still match.
+pub(crate) fn merge_snapshot(old: &Value, new: &Value) -> Value {
+    match (old, new) {
+        (Value::String(placeholder), _) if match_placeholder(placeholder, new) == Some(true) => {
+            old.clone()
+        }
+        (Value::Object(old), Value::Object(new)) => Value::Object(
+            new.iter()
+                .map(|(key, value)| {
+                    let value = match old.get(key) {
+                        Some(old) => merge_snapshot(old, value),
+                        None => value.clone(),
+                    };
+                    (key.clone(), value)
+                })
+                .collect(),
+        ),
+        (Value::Array(old), Value::Array(new)) if old.len() == new.len() => Value::Array(
+            old.iter()
+                .zip(new)
+                .map(|(old, new)| merge_snapshot(old, new))
+                .collect(),
+        ),
+        _ => new.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn errors(matcher: &TestJsonMatcher, actual: Value) -> Vec<String> {
+        let mut errors = Vec::new();
+        matcher.check(&matcher.expected, &actual, "", &mut errors);
+        errors
+    }
+
+    #[test]
+    fn partial_match() {
+        let matcher = TestJsonMatcher::new(json!({ "a": 1, "b": { "c": [1, 2.0] } }));
+        assert!(
+            errors(
+                &matcher,
+                json!({ "a": 1, "b": { "c": [1, 2], "d": 3 }, "e": 4 })
+            )
+            .is_empty()
+        );
+        assert_eq!(
+            errors(&matcher, json!({ "b": { "c": [1] } })),
+            vec!["/a: missing field", "/b/c: expect 2 items, actual 1 items"]
+        );
+
+        let matcher = matcher.partial(false);
+        assert_eq!(
+            errors(&matcher, json!({ "a": 1, "b": { "c": [1, 2], "d": 3 } })),
+            vec!["/b/d: unexpected field"]
+        );
+    }
+
+    #[test]
+    fn placeholders_and_ignored_paths() {
+        let matcher = TestJsonMatcher::new(json!({
+            "id": "{{uuid}}",
+            "created_at": "{{datetime}}",
+            "count": "{{integer}}",
+            "items": [{ "name": "{{string}}" }, { "name": "b" }],
+        }))
+        .partial(false)
+        .ignore("/items/*/id")
+        .ignore("/etag");
+
+        assert!(
+            errors(
+                &matcher,
+                json!({
+                    "id": "67e55044-10b1-426f-9247-bb680e5fe0c8",
+                    "created_at": "2024-01-01T00:00:00.123Z",
+                    "count": 10,
+                    "items": [{ "id": 1, "name": "a" }, { "id": 2, "name": "b" }],
+                    "etag": "abc",
+                })
+            )
+            .is_empty()
+        );
+        assert_eq!(
+            errors(
+                &matcher,
+                json!({
+                    "id": "1",
+                    "created_at": "yesterday",
+                    "count": 1.5,
+                    "items": [{ "name": 1 }, { "name": "c" }],
+                })
+            ),
+            vec![
+                r#"/count: expect "{{integer}}", actual 1.5"#,
+                r#"/created_at: expect "{{datetime}}", actual "yesterday""#,
+                r#"/id: expect "{{uuid}}", actual "1""#,
+                r#"/items/0/name: expect "{{string}}", actual 1"#,
+                r#"/items/1/name: expect "b", actual "c""#,
+            ]
+        );
+    }
+
+    #[test]
+    fn merge_placeholders() {
+        let old = json!({ "id": "{{uuid}}", "name": "a", "count": "{{integer}}" });
+        let new =
+            json!({ "id": "67e55044-10b1-426f-9247-bb680e5fe0c8", "name": "b", "count": "x" });
+        assert_eq!(
+            merge_snapshot(&old, &new),
+            json!({ "id": "{{uuid}}", "name": "b", "count": "x" })
+        );
+    }
+
+    #[tokio::test]
+    async fn assert_response() {
+        use crate::{handler, test::TestClient, web::Json};
+
+        #[handler(internal)]
+        fn index() -> Json<Value> {
+            Json(json!({
+                "id": "67e55044-10b1-426f-9247-bb680e5fe0c8",
+                "name": "alice",
+                "created_at": "2024-01-01T00:00:00Z",
+                "tags": ["a", "b"],
+            }))
+        }
+
+        let cli = TestClient::new(index);
+        cli.get("/")
+            .send()
+            .await
+            .assert_json_matches(json!({ "id": "{{uuid}}", "tags": ["a", "{{string}}"] }))
+            .await;
+        cli.get("/")
+            .send()
+            .await
+            .assert_json_schema(json!({
+                "type": "object",
+                "required": ["id", "name"],
+                "properties": {
+                    "name": { "type": "string" },
+                    "tags": { "type": "array", "items": { "type": "string" } },
+                },
+            }))
+            .await;
+        cli.get("/")
+            .send()
+            .await
+            .assert_json_snapshot("test_json_snapshot")
+            .await;
+    }
+}
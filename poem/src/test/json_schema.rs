@@ -0,0 +1,473 @@
+use std::collections::HashSet;
+
+use regex::Regex;
+use serde_json::{Map, Value};
+
+/// The maximum depth of `$ref` resolution, to stop on recursive schemas
+/// that never consume the instance.
+const MAX_DEPTH: usize = 64;
+
+/// Validates the instance against a JSON Schema, and returns the errors.
+///
+/// The commonly used keywords of JSON Schema and of the OpenAPI 3.0 schema
+/// object are supported, including `nullable` and the boolean form of
+/// `exclusiveMinimum` and `exclusiveMaximum`. `$ref` is resolved as a JSON
+/// pointer into the root schema, such as `#/components/schemas/Pet` or
+/// `#/$defs/Pet`. `format` is treated as an annotation.
+pub(crate) fn validate(schema: &Value, instance: &Value) -> Vec<String> {
+    let mut validator = Validator {
+        root: schema,
+        errors: Vec::new(),
+    };
+    validator.validate(schema, instance, "", 0);
+    validator.errors
+}
+
+struct Validator<'a> {
+    root: &'a Value,
+    errors: Vec<String>,
+}
+
+fn type_matches(ty: &str, instance: &Value) -> bool {
+    match ty {
+        "null" => instance.is_null(),
+        "boolean" => instance.is_boolean(),
+        "object" => instance.is_object(),
+        "array" => instance.is_array(),
+        "string" => instance.is_string(),
+        "number" => instance.is_number(),
+        "integer" => {
+            instance.is_i64()
+                || instance.is_u64()
+                || instance.as_f64().is_some_and(|n| n.fract() == 0.0)
+        }
+        _ => true,
+    }
+}
+
+fn json_eq(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64() == b.as_f64(),
+        (Value::Array(a), Value::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| json_eq(a, b))
+        }
+        (Value::Object(a), Value::Object(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(key, a)| b.get(key).is_some_and(|b| json_eq(a, b)))
+        }
+        _ => a == b,
+    }
+}
+
+impl<'a> Validator<'a> {
+    fn error(&mut self, path: &str, msg: impl AsRef<str>) {
+        let path = if path.is_empty() { "/" } else { path };
+        self.errors.push(format!("{path}: {}", msg.as_ref()));
+    }
+
+    /// Returns `true` if the instance is valid, without recording the errors.
+    fn is_valid(&mut self, schema: &'a Value, instance: &Value, path: &str, depth: usize) -> bool {
+        let errors = std::mem::take(&mut self.errors);
+        self.validate(schema, instance, path, depth);
+        let valid = self.errors.is_empty();
+        self.errors = errors;
+        valid
+    }
+
+    fn resolve(&mut self, reference: &str, path: &str) -> Option<&'a Value> {
+        let schema = reference
+            .strip_prefix('#')
+            .and_then(|pointer| self.root.pointer(pointer));
+        if schema.is_none() {
+            self.error(path, format!("unresolved reference `{reference}`"));
+        }
+        schema
+    }
+
+    fn validate(&mut self, schema: &'a Value, instance: &Value, path: &str, depth: usize) {
+        if depth > MAX_DEPTH {
+            self.error(path, "schema is too deep");
+            return;
+        }
+
+        let schema = match schema {
+            Value::Bool(true) => return,
+            Value::Bool(false) => {
+                self.error(path, "no value is allowed");
+                return;
+            }
+            Value::Object(schema) => schema,
+            _ => return,
+        };
+
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            if let Some(schema) = self.resolve(reference, path) {
+                self.validate(schema, instance, path, depth + 1);
+            }
+        }
+
+        if instance.is_null() && schema.get("nullable") == Some(&Value::Bool(true)) {
+            return;
+        }
+
+        self.validate_generic(schema, instance, path, depth);
+        match instance {
+            Value::Number(_) => self.validate_number(schema, instance, path),
+            Value::String(s) => self.validate_string(schema, s, path),
+            Value::Array(items) => self.validate_array(schema, items, path, depth),
+            Value::Object(object) => self.validate_object(schema, object, path, depth),
+            _ => {}
+        }
+    }
+
+    fn validate_generic(
+        &mut self,
+        schema: &'a Map<String, Value>,
+        instance: &Value,
+        path: &str,
+        depth: usize,
+    ) {
+        match schema.get("type") {
+            Some(Value::String(ty)) if !type_matches(ty, instance) => {
+                self.error(path, format!("expect type `{ty}`, actual {instance}"));
+            }
+            Some(Value::Array(types))
+                if !types
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .any(|ty| type_matches(ty, instance)) =>
+            {
+                self.error(
+                    path,
+                    format!(
+                        "expect type {}, actual {instance}",
+                        Value::Array(types.clone())
+                    ),
+                );
+            }
+            _ => {}
+        }
+
+        if let Some(Value::Array(items)) = schema.get("enum") {
+            if !items.iter().any(|item| json_eq(item, instance)) {
+                self.error(
+                    path,
+                    format!("{instance} is not one of {}", Value::Array(items.clone())),
+                );
+            }
+        }
+        if let Some(value) = schema.get("const") {
+            if !json_eq(value, instance) {
+                self.error(path, format!("expect {value}, actual {instance}"));
+            }
+        }
+
+        if let Some(Value::Array(schemas)) = schema.get("allOf") {
+            for schema in schemas {
+                self.validate(schema, instance, path, depth + 1);
+            }
+        }
+        if let Some(Value::Array(schemas)) = schema.get("anyOf") {
+            if !schemas
+                .iter()
+                .any(|schema| self.is_valid(schema, instance, path, depth + 1))
+            {
+                self.error(path, "does not match any schema of `anyOf`");
+            }
+        }
+        if let Some(Value::Array(schemas)) = schema.get("oneOf") {
+            let matched = schemas
+                .iter()
+                .filter(|schema| self.is_valid(schema, instance, path, depth + 1))
+                .count();
+            if matched != 1 {
+                self.error(
+                    path,
+                    format!("expect to match exactly one schema of `oneOf`, matched {matched}"),
+                );
+            }
+        }
+        if let Some(schema) = schema.get("not") {
+            if self.is_valid(schema, instance, path, depth + 1) {
+                self.error(path, "must not match the schema of `not`");
+            }
+        }
+    }
+
+    fn validate_number(&mut self, schema: &Map<String, Value>, instance: &Value, path: &str) {
+        let Some(n) = instance.as_f64() else {
+            return;
+        };
+        let get = |key| schema.get(key).and_then(Value::as_f64);
+
+        if let Some(max) = get("maximum") {
+            let exclusive = schema.get("exclusiveMaximum") == Some(&Value::Bool(true));
+            if n > max || (exclusive && n == max) {
+                self.error(path, format!("{n} is greater than the maximum {max}"));
+            }
+        }
+        if let Some(max) = get("exclusiveMaximum") {
+            if n >= max {
+                self.error(path, format!("{n} is not less than {max}"));
+            }
+        }
+        if let Some(min) = get("minimum") {
+            let exclusive = schema.get("exclusiveMinimum") == Some(&Value::Bool(true));
+            if n < min || (exclusive && n == min) {
+                self.error(path, format!("{n} is less than the minimum {min}"));
+            }
+        }
+        if let Some(min) = get("exclusiveMinimum") {
+            if n <= min {
+                self.error(path, format!("{n} is not greater than {min}"));
+            }
+        }
+        if let Some(multiple_of) = get("multipleOf") {
+            if multiple_of > 0.0 && (n / multiple_of).fract().abs() > f64::EPSILON {
+                self.error(path, format!("{n} is not a multiple of {multiple_of}"));
+            }
+        }
+    }
+
+    fn validate_string(&mut self, schema: &Map<String, Value>, s: &str, path: &str) {
+        let len = s.chars().count() as u64;
+        if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+            if len > max {
+                self.error(path, format!("the length {len} is greater than {max}"));
+            }
+        }
+        if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+            if len < min {
+                self.error(path, format!("the length {len} is less than {min}"));
+            }
+        }
+        if let Some(pattern) = schema.get("pattern").and_then(Value::as_str) {
+            match Regex::new(pattern) {
+                Ok(re) if !re.is_match(s) => {
+                    self.error(
+                        path,
+                        format!("{s:?} does not match the pattern `{pattern}`"),
+                    );
+                }
+                Err(_) => self.error(path, format!("invalid pattern `{pattern}`")),
+                _ => {}
+            }
+        }
+    }
+
+    fn validate_array(
+        &mut self,
+        schema: &'a Map<String, Value>,
+        items: &[Value],
+        path: &str,
+        depth: usize,
+    ) {
+        let len = items.len() as u64;
+        if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+            if len > max {
+                self.error(path, format!("{len} items are more than {max}"));
+            }
+        }
+        if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+            if len < min {
+                self.error(path, format!("{len} items are less than {min}"));
+            }
+        }
+        if schema.get("uniqueItems") == Some(&Value::Bool(true)) {
+            let has_duplicates = items
+                .iter()
+                .enumerate()
+                .any(|(idx, a)| items[idx + 1..].iter().any(|b| json_eq(a, b)));
+            if has_duplicates {
+                self.error(path, "the items are not unique");
+            }
+        }
+
+        let prefix_items = match (schema.get("prefixItems"), schema.get("items")) {
+            (Some(Value::Array(prefix_items)), _) | (None, Some(Value::Array(prefix_items))) => {
+                prefix_items.as_slice()
+            }
+            _ => &[],
+        };
+        for (idx, (schema, item)) in prefix_items.iter().zip(items).enumerate() {
+            self.validate(schema, item, &format!("{path}/{idx}"), depth + 1);
+        }
+        let rest_schema = match schema.get("items") {
+            Some(Value::Array(_)) => schema.get("additionalItems"),
+            items => items,
+        };
+        if let Some(rest_schema) = rest_schema {
+            for (idx, item) in items.iter().enumerate().skip(prefix_items.len()) {
+                self.validate(rest_schema, item, &format!("{path}/{idx}"), depth + 1);
+            }
+        }
+    }
+
+    fn validate_object(
+        &mut self,
+        schema: &'a Map<String, Value>,
+        object: &Map<String, Value>,
+        path: &str,
+        depth: usize,
+    ) {
+        let len = object.len() as u64;
+        if let Some(max) = schema.get("maxProperties").and_then(Value::as_u64) {
+            if len > max {
+                self.error(path, format!("{len} properties are more than {max}"));
+            }
+        }
+        if let Some(min) = schema.get("minProperties").and_then(Value::as_u64) {
+            if len < min {
+                self.error(path, format!("{len} properties are less than {min}"));
+            }
+        }
+        if let Some(Value::Array(required)) = schema.get("required") {
+            for name in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(name) {
+                    self.error(path, format!("missing required property `{name}`"));
+                }
+            }
+        }
+
+        let properties = schema.get("properties").and_then(Value::as_object);
+        let pattern_properties = schema
+            .get("patternProperties")
+            .and_then(Value::as_object)
+            .map(|patterns| {
+                patterns
+                    .iter()
+                    .filter_map(|(pattern, schema)| Some((Regex::new(pattern).ok()?, schema)))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        let mut additional = HashSet::new();
+
+        for (name, value) in object {
+            let value_path = format!("{path}/{}", name.replace('~', "~0").replace('/', "~1"));
+            let mut matched = false;
+            if let Some(schema) = properties.and_then(|properties| properties.get(name)) {
+                matched = true;
+                self.validate(schema, value, &value_path, depth + 1);
+            }
+            for (re, schema) in &pattern_properties {
+                if re.is_match(name) {
+                    matched = true;
+                    self.validate(schema, value, &value_path, depth + 1);
+                }
+            }
+            if !matched {
+                additional.insert(name.as_str());
+            }
+        }
+
+        match schema.get("additionalProperties") {
+            Some(Value::Bool(false)) => {
+                let mut additional = additional.into_iter().collect::<Vec<_>>();
+                additional.sort_unstable();
+                for name in additional {
+                    self.error(path, format!("unexpected property `{name}`"));
+                }
+            }
+            Some(schema @ Value::Object(_)) => {
+                for (name, value) in object
+                    .iter()
+                    .filter(|(name, _)| additional.contains(name.as_str()))
+                {
+                    self.validate(schema, value, &format!("{path}/{name}"), depth + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn validate_schema() {
+        let schema = json!({
+            "$ref": "#/$defs/Pet",
+            "$defs": {
+                "Pet": {
+                    "type": "object",
+                    "required": ["id", "name"],
+                    "additionalProperties": false,
+                    "properties": {
+                        "id": { "type": "integer", "minimum": 1 },
+                        "name": { "type": "string", "minLength": 1, "pattern": "^[a-z]+$" },
+                        "tag": { "type": "string", "nullable": true },
+                        "kind": { "enum": ["cat", "dog"] },
+                        "weight": { "type": "number", "maximum": 10, "exclusiveMaximum": true },
+                        "tags": {
+                            "type": "array",
+                            "items": { "$ref": "#/$defs/Tag" },
+                            "uniqueItems": true,
+                            "maxItems": 2,
+                        },
+                        "owner": { "oneOf": [{ "type": "string" }, { "type": "integer" }] },
+                    }
+                },
+                "Tag": { "type": "string" },
+            }
+        });
+
+        assert!(
+            validate(
+                &schema,
+                &json!({
+                    "id": 1,
+                    "name": "tom",
+                    "tag": null,
+                    "kind": "cat",
+                    "weight": 9.5,
+                    "tags": ["a", "b"],
+                    "owner": 1,
+                })
+            )
+            .is_empty()
+        );
+
+        assert_eq!(
+            validate(
+                &schema,
+                &json!({
+                    "id": 0,
+                    "name": "Tom",
+                    "kind": "bird",
+                    "weight": 10,
+                    "tags": ["a", "a", 1],
+                    "owner": true,
+                    "color": "black",
+                })
+            ),
+            vec![
+                "/id: 0 is less than the minimum 1",
+                r#"/kind: "bird" is not one of ["cat","dog"]"#,
+                r#"/name: "Tom" does not match the pattern `^[a-z]+$`"#,
+                "/owner: expect to match exactly one schema of `oneOf`, matched 0",
+                "/tags: 3 items are more than 2",
+                "/tags: the items are not unique",
+                "/tags/2: expect type `string`, actual 1",
+                "/weight: 10 is greater than the maximum 10",
+                "/: unexpected property `color`",
+            ]
+        );
+
+        assert_eq!(
+            validate(&schema, &json!({ "id": 1.5 })),
+            vec![
+                "/: missing required property `name`",
+                "/id: expect type `integer`, actual 1.5",
+            ]
+        );
+        assert_eq!(
+            validate(&json!({ "$ref": "#/missing" }), &json!(1)),
+            vec!["/: unresolved reference `#/missing`"]
+        );
+    }
+}
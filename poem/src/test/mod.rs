@@ -110,13 +110,22 @@
 //! ```
 
 mod client;
+#[cfg(feature = "cookie")]
+mod cookie_store;
 mod form;
 mod json;
+mod json_match;
+mod json_schema;
 mod request_builder;
 mod response;
+#[cfg(feature = "test-server")]
+mod server;
 
 pub use client::TestClient;
 pub use form::{TestForm, TestFormField};
 pub use json::{TestJson, TestJsonArray, TestJsonObject, TestJsonValue};
+pub use json_match::TestJsonMatcher;
 pub use request_builder::TestRequestBuilder;
 pub use response::TestResponse;
+#[cfg(feature = "test-server")]
+pub use server::{TestServer, TestServerBuilder};
@@ -1,8 +1,13 @@
+use bytes::Bytes;
 use headers::{Header, HeaderMapExt};
-use http::{Extensions, HeaderMap, HeaderValue, Method, header, header::HeaderName};
+use http::{
+    Extensions, HeaderMap, HeaderValue, Method, StatusCode, Uri, header, header::HeaderName,
+};
 use serde::Serialize;
 use serde_json::Value;
 
+#[cfg(feature = "websocket")]
+use crate::web::websocket::{DeflateConfig, WebSocketClient, WebSocketStream};
 use crate::{
     Body, Endpoint, Request,
     test::{TestClient, TestForm, TestResponse},
@@ -211,9 +216,171 @@ impl<'a, E> TestRequestBuilder<'a, E> {
     where
         E: Endpoint,
     {
+        let cli = self.cli;
+        let mut req = self.make_request();
+        if cli.max_redirects == 0 {
+            return TestResponse::new(cli.call(req).await);
+        }
+
+        let mut body = req
+            .take_body()
+            .into_bytes()
+            .await
+            .expect("valid request body");
+        let mut redirects = 0;
+        loop {
+            let mut next = Request::builder()
+                .method(req.method().clone())
+                .uri(req.uri().clone())
+                .finish();
+            *next.headers_mut() = req.headers().clone();
+            *next.extensions_mut() = req.extensions().clone();
+            // the cookies of the next request are taken from the cookie store
+            #[cfg(feature = "cookie")]
+            if cli.cookie_store.is_some() {
+                next.headers_mut().remove(header::COOKIE);
+            }
+
+            req.set_body(body.clone());
+            let resp = cli.call(req).await;
+            let Some(location) = resp
+                .status()
+                .is_redirection()
+                .then(|| resp.headers().get(header::LOCATION))
+                .flatten()
+                .and_then(|value| value.to_str().ok())
+            else {
+                return TestResponse::new(resp);
+            };
+
+            redirects += 1;
+            assert!(
+                redirects <= cli.max_redirects,
+                "too many redirects, the limit is {}",
+                cli.max_redirects
+            );
+
+            let status = resp.status();
+            if status == StatusCode::SEE_OTHER && next.method() != Method::HEAD
+                || matches!(status, StatusCode::MOVED_PERMANENTLY | StatusCode::FOUND)
+                    && next.method() == Method::POST
+            {
+                next.set_method(Method::GET);
+                body = Bytes::new();
+                for name in [
+                    header::CONTENT_TYPE,
+                    header::CONTENT_LENGTH,
+                    header::CONTENT_ENCODING,
+                    header::CONTENT_LANGUAGE,
+                    header::TRANSFER_ENCODING,
+                ] {
+                    next.headers_mut().remove(name);
+                }
+            }
+            *next.uri_mut() = resolve_location(next.uri(), location);
+            req = next;
+        }
+    }
+
+    /// Performs a WebSocket handshake with the endpoint and returns the
+    /// client side of the connection.
+    ///
+    /// The `Sec-WebSocket-Protocol` and `Sec-WebSocket-Extensions` headers set
+    /// on this request are offered to the endpoint, so subprotocol and
+    /// `permessage-deflate` negotiation can be tested as well. The client
+    /// honours the parameters of the first `permessage-deflate` offer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the endpoint does not accept the upgrade, or the
+    /// `permessage-deflate` offer has parameters that the client can not
+    /// honour.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use futures_util::{SinkExt, StreamExt};
+    /// use poem::{
+    ///     IntoResponse, Route, get, handler,
+    ///     test::TestClient,
+    ///     web::websocket::{Message, WebSocket},
+    /// };
+    ///
+    /// #[handler]
+    /// async fn index(ws: WebSocket) -> impl IntoResponse {
+    ///     ws.on_upgrade(|mut socket| async move {
+    ///         if let Some(Ok(Message::Text(text))) = socket.next().await {
+    ///             let _ = socket.send(Message::Text(text)).await;
+    ///         }
+    ///     })
+    /// }
+    ///
+    /// let app = Route::new().at("/", get(index));
+    /// let cli = TestClient::new(app);
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let mut stream = cli.get("/").websocket().await;
+    /// stream.send(Message::text("hello")).await.unwrap();
+    /// assert_eq!(stream.next().await.unwrap().unwrap(), Message::text("hello"));
+    /// # });
+    /// ```
+    #[cfg(feature = "websocket")]
+    pub async fn websocket(self) -> WebSocketStream
+    where
+        E: Endpoint,
+    {
+        let header_values = |name| {
+            self.headers
+                .get_all(name)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .flat_map(|value| value.split(','))
+                .map(|value| value.trim().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let mut client =
+            WebSocketClient::new().protocols(header_values(header::SEC_WEBSOCKET_PROTOCOL));
+        if let Some(offer) = header_values(header::SEC_WEBSOCKET_EXTENSIONS)
+            .iter()
+            .find(|value| value.starts_with("permessage-deflate"))
+        {
+            let config = DeflateConfig::from_offer(offer)
+                .unwrap_or_else(|| panic!("unsupported `permessage-deflate` offer: {offer}"));
+            client = client.deflate(config);
+        }
+
         let ep = &self.cli.ep;
-        let req = self.make_request();
+        let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+        let cli = self.cli;
+        let mut req = self.make_request();
+        cli.add_cookies(&mut req);
+        let key = client.prepare_headers(req.headers_mut());
+        *req.state_mut().on_upgrade.get_mut() = Some(crate::OnUpgrade::from_io(server_io));
+
         let resp = ep.get_response(req).await;
-        TestResponse::new(resp)
+        client
+            .finish(&key, resp.status(), resp.headers(), client_io)
+            .await
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+/// Resolves the `Location` header of a redirect against the URI of the
+/// request.
+fn resolve_location(base: &Uri, location: &str) -> Uri {
+    let location = match location.parse::<Uri>() {
+        Ok(uri) if uri.scheme().is_some() => return uri,
+        _ if location.starts_with('/') => location.to_string(),
+        _ => {
+            let dir = &base.path()[..base.path().rfind('/').map_or(0, |idx| idx + 1)];
+            format!("{}{location}", if dir.is_empty() { "/" } else { dir })
+        }
+    };
+    match (base.scheme(), base.authority()) {
+        (Some(scheme), Some(authority)) => format!("{scheme}://{authority}{location}"),
+        _ => location,
     }
+    .parse()
+    .expect("valid location")
 }
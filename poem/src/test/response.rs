@@ -6,7 +6,18 @@ use serde::{Serialize, de::DeserializeOwned};
 use serde_json::Value;
 use tokio_util::compat::TokioAsyncReadCompatExt;
 
-use crate::{Response, test::json::TestJson, web::sse::Event};
+#[cfg(feature = "cookie")]
+use crate::web::cookie::Cookie;
+use crate::{
+    Response,
+    test::{
+        TestJsonMatcher,
+        json::TestJson,
+        json_match::{UPDATE_SNAPSHOTS_ENV, merge_snapshot, snapshot_path},
+        json_schema,
+    },
+    web::sse::Event,
+};
 
 /// A response object for testing.
 pub struct TestResponse(pub Response);
@@ -138,6 +149,76 @@ impl TestResponse {
         self.assert_header(header::CONTENT_TYPE, content_type);
     }
 
+    /// Returns the cookie with the name in the `Set-Cookie` headers.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use poem::{
+    ///     Route, get, handler,
+    ///     test::TestClient,
+    ///     web::cookie::{Cookie, CookieJar},
+    ///     EndpointExt,
+    ///     middleware::CookieJarManager,
+    /// };
+    ///
+    /// #[handler]
+    /// fn index(cookie_jar: &CookieJar) {
+    ///     let mut cookie = Cookie::new_with_str("session", "abc");
+    ///     cookie.set_http_only(true);
+    ///     cookie_jar.add(cookie);
+    /// }
+    ///
+    /// let cli = TestClient::new(Route::new().at("/", get(index)).with(CookieJarManager::new()));
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let resp = cli.get("/").send().await;
+    /// let cookie = resp.assert_cookie("session", "abc");
+    /// assert!(cookie.http_only());
+    /// assert!(!cookie.secure());
+    /// # });
+    /// ```
+    #[cfg(feature = "cookie")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cookie")))]
+    pub fn cookie(&self, name: &str) -> Option<Cookie> {
+        self.0
+            .headers()
+            .get_all(header::SET_COOKIE)
+            .iter()
+            .rev()
+            .filter_map(|value| value.to_str().ok())
+            .filter_map(|value| Cookie::parse(value).ok())
+            .find(|cookie| cookie.name() == name)
+    }
+
+    /// Asserts that the response sets the cookie `name` to `value`, and
+    /// returns the cookie to check its attributes.
+    #[cfg(feature = "cookie")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cookie")))]
+    #[track_caller]
+    pub fn assert_cookie(&self, name: &str, value: &str) -> Cookie {
+        let cookie = self
+            .cookie(name)
+            .unwrap_or_else(|| panic!("expect cookie `{name}`"));
+        assert_eq!(cookie.value_str(), value);
+        cookie
+    }
+
+    /// Asserts that the response removes the cookie `name`.
+    #[cfg(feature = "cookie")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cookie")))]
+    #[track_caller]
+    pub fn assert_cookie_removed(&self, name: &str) {
+        let cookie = self
+            .cookie(name)
+            .unwrap_or_else(|| panic!("expect cookie `{name}`"));
+        let removed = cookie.max_age().is_some_and(|max_age| max_age.is_zero())
+            || cookie
+                .expires()
+                .is_some_and(|expires| expires <= chrono::Utc::now());
+        assert!(removed, "expect cookie `{name}` to be removed");
+    }
+
     /// Asserts that the response body is utf8 string and it equals to `text`.
     pub async fn assert_text(self, text: impl AsRef<str>) {
         assert_eq!(
@@ -166,6 +247,115 @@ impl TestResponse {
         );
     }
 
+    /// Asserts that the response body is JSON and it matches `expected`.
+    ///
+    /// The objects of the body may have more fields than `expected`, and the
+    /// placeholders such as `"{{uuid}}"` and `"{{datetime}}"` match any value
+    /// of the type. See [`TestJsonMatcher`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use poem::{handler, test::TestClient, web::Json};
+    /// use serde_json::{Value, json};
+    ///
+    /// #[handler]
+    /// fn index() -> Json<Value> {
+    ///     Json(json!({
+    ///         "id": "67e55044-10b1-426f-9247-bb680e5fe0c8",
+    ///         "name": "alice",
+    ///         "created_at": "2024-01-01T00:00:00Z",
+    ///     }))
+    /// }
+    ///
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+    /// let cli = TestClient::new(index);
+    /// cli.get("/")
+    ///     .send()
+    ///     .await
+    ///     .assert_json_matches(json!({ "id": "{{uuid}}", "name": "alice" }))
+    ///     .await;
+    /// # });
+    /// ```
+    pub async fn assert_json_matches(self, expected: impl Serialize) {
+        self.assert_json_matches_with(TestJsonMatcher::new(expected))
+            .await;
+    }
+
+    /// Asserts that the response body is JSON and it matches the
+    /// [`TestJsonMatcher`].
+    pub async fn assert_json_matches_with(self, matcher: TestJsonMatcher) {
+        let actual = self.into_json_value().await;
+        matcher.assert_matches(&actual);
+    }
+
+    /// Asserts that the response body is JSON and it is valid against the
+    /// [JSON Schema](https://json-schema.org).
+    ///
+    /// `$ref` is resolved against the schema itself, so the schemas of an
+    /// OpenAPI document can be used by placing them under
+    /// `components/schemas`.
+    pub async fn assert_json_schema(self, schema: impl Serialize) {
+        let schema = serde_json::to_value(schema).expect("valid json");
+        let actual = self.into_json_value().await;
+        let errors = json_schema::validate(&schema, &actual);
+        if !errors.is_empty() {
+            panic!(
+                "json does not match the schema:\n  {}\nactual:\n{}",
+                errors.join("\n  "),
+                serde_json::to_string_pretty(&actual).unwrap_or_default()
+            );
+        }
+    }
+
+    /// Asserts that the response body is JSON and it equals to the snapshot
+    /// file `tests/snapshots/{name}.json` in the package directory.
+    ///
+    /// The snapshot may contain the placeholders of [`TestJsonMatcher`]. If
+    /// the `POEM_UPDATE_SNAPSHOTS` environment variable is set, the snapshot
+    /// file is written with the response body instead, keeping the
+    /// placeholders that still match.
+    pub async fn assert_json_snapshot(self, name: &str) {
+        let actual = self.into_json_value().await;
+        let path = snapshot_path(name);
+
+        if std::env::var_os(UPDATE_SNAPSHOTS_ENV).is_some() {
+            let snapshot = match std::fs::read(&path) {
+                Ok(data) => match serde_json::from_slice(&data) {
+                    Ok(old) => merge_snapshot(&old, &actual),
+                    Err(_) => actual,
+                },
+                Err(_) => actual,
+            };
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir).expect("create the snapshot directory");
+            }
+            let mut data = serde_json::to_string_pretty(&snapshot).expect("valid json");
+            data.push('\n');
+            std::fs::write(&path, data).expect("write the snapshot");
+            return;
+        }
+
+        let data = std::fs::read(&path).unwrap_or_else(|_| {
+            panic!(
+                "snapshot `{}` does not exist, set `{UPDATE_SNAPSHOTS_ENV}=1` to create it",
+                path.display()
+            )
+        });
+        let expected: Value = serde_json::from_slice(&data).expect("valid snapshot");
+        TestJsonMatcher::new(expected)
+            .partial(false)
+            .assert_matches(&actual);
+    }
+
+    async fn into_json_value(self) -> Value {
+        self.0
+            .into_body()
+            .into_json::<Value>()
+            .await
+            .expect("expect body")
+    }
+
     /// Asserts that the response body is XML and it equals to `xml`.
     #[cfg(feature = "xml")]
     pub async fn assert_xml(self, xml: impl Serialize) {
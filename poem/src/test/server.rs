@@ -0,0 +1,293 @@
+use std::{convert::Infallible, io::Result as IoResult, net::SocketAddr, time::Duration};
+
+use reqwest::Client;
+use tokio::{sync::oneshot, task::JoinHandle};
+
+use crate::{
+    IntoEndpoint, Server,
+    listener::{Acceptor, BoxAcceptor, Listener, RustlsCertificate, RustlsConfig, TcpListener},
+};
+
+type ConfigureServer =
+    Box<dyn FnOnce(Server<Infallible, BoxAcceptor>) -> Server<Infallible, BoxAcceptor> + Send>;
+
+/// A builder for [`TestServer`].
+pub struct TestServerBuilder {
+    tls: bool,
+    http2_prior_knowledge: bool,
+    graceful_shutdown_timeout: Option<Duration>,
+    configure_server: Option<ConfigureServer>,
+}
+
+impl TestServerBuilder {
+    /// Serves over TLS with a generated self-signed certificate for
+    /// `127.0.0.1` and `localhost`, which is trusted by the client.
+    ///
+    /// The server supports both HTTP/1.1 and HTTP/2 via ALPN.
+    #[must_use]
+    pub fn tls(self, tls: bool) -> Self {
+        Self { tls, ..self }
+    }
+
+    /// Makes the client use HTTP/2 without negotiation, which is needed to
+    /// test HTTP/2 over plain TCP.
+    #[must_use]
+    pub fn http2_prior_knowledge(self, enable: bool) -> Self {
+        Self {
+            http2_prior_knowledge: enable,
+            ..self
+        }
+    }
+
+    /// Sets the timeout of the graceful shutdown, default is no timeout.
+    #[must_use]
+    pub fn graceful_shutdown_timeout(self, timeout: Duration) -> Self {
+        Self {
+            graceful_shutdown_timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    /// Configures the [`Server`], such as its
+    /// [`idle_timeout`](Server::idle_timeout).
+    #[must_use]
+    pub fn configure_server<F>(self, f: F) -> Self
+    where
+        F: FnOnce(Server<Infallible, BoxAcceptor>) -> Server<Infallible, BoxAcceptor>
+            + Send
+            + 'static,
+    {
+        Self {
+            configure_server: Some(Box::new(f)),
+            ..self
+        }
+    }
+
+    /// Binds an ephemeral loopback port and starts the server.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the port cannot be bound.
+    pub async fn start<E>(self, ep: E) -> TestServer
+    where
+        E: IntoEndpoint + Send + 'static,
+        E::Endpoint: 'static,
+    {
+        let mut client = Client::builder();
+        if self.http2_prior_knowledge {
+            client = client.http2_prior_knowledge();
+        }
+        let listener = TcpListener::bind("127.0.0.1:0");
+        let (listener, certificate) = if self.tls {
+            let cert = rcgen::generate_simple_self_signed(vec![
+                "127.0.0.1".to_string(),
+                "localhost".to_string(),
+            ])
+            .expect("generate a self-signed certificate");
+            let cert_pem = cert.serialize_pem().expect("serialize the certificate");
+            let config = RustlsConfig::new().fallback(
+                RustlsCertificate::new()
+                    .cert(cert_pem.clone())
+                    .key(cert.serialize_private_key_pem()),
+            );
+            client = client.add_root_certificate(
+                reqwest::Certificate::from_pem(cert_pem.as_bytes()).expect("valid certificate"),
+            );
+            (listener.rustls(config).boxed(), Some(cert_pem))
+        } else {
+            (listener.boxed(), None)
+        };
+        let acceptor = listener
+            .into_acceptor()
+            .await
+            .expect("bind a loopback port");
+        let addr = *acceptor
+            .local_addr()
+            .remove(0)
+            .as_socket_addr()
+            .expect("socket address");
+        let scheme = if self.tls { "https" } else { "http" };
+
+        let mut server = Server::new_with_acceptor(acceptor);
+        if let Some(configure_server) = self.configure_server {
+            server = configure_server(server);
+        }
+        let (tx, rx) = oneshot::channel();
+        let handle = tokio::spawn(server.run_with_graceful_shutdown(
+            ep,
+            async move {
+                let _ = rx.await;
+            },
+            self.graceful_shutdown_timeout,
+        ));
+
+        TestServer {
+            addr,
+            base_url: format!("{scheme}://{addr}"),
+            client: client.build().expect("build the client"),
+            certificate,
+            shutdown: Some(tx),
+            handle: Some(handle),
+        }
+    }
+}
+
+/// A server for testing, which runs a real [`Server`] on an ephemeral
+/// loopback port.
+///
+/// Unlike [`TestClient`](super::TestClient), requests go through the
+/// listener, TLS, the HTTP/1.1 and HTTP/2 protocol implementations and the
+/// server options. The server is gracefully shut down when it is dropped, or
+/// when [`TestServer::shutdown`] is called.
+///
+/// # Example
+///
+/// ```
+/// use poem::{Route, get, handler, test::TestServer};
+///
+/// #[handler]
+/// fn index() -> &'static str {
+///     "hello"
+/// }
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let server = TestServer::start(Route::new().at("/", get(index))).await;
+/// let resp = server.client().get(server.url("/")).send().await.unwrap();
+/// assert_eq!(resp.text().await.unwrap(), "hello");
+/// server.shutdown().await.unwrap();
+/// # });
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "test-server")))]
+pub struct TestServer {
+    addr: SocketAddr,
+    base_url: String,
+    client: Client,
+    certificate: Option<String>,
+    shutdown: Option<oneshot::Sender<()>>,
+    handle: Option<JoinHandle<IoResult<()>>>,
+}
+
+impl TestServer {
+    /// Starts a server over plain TCP for the endpoint.
+    pub async fn start<E>(ep: E) -> Self
+    where
+        E: IntoEndpoint + Send + 'static,
+        E::Endpoint: 'static,
+    {
+        Self::builder().start(ep).await
+    }
+
+    /// Create a [`TestServerBuilder`].
+    pub fn builder() -> TestServerBuilder {
+        TestServerBuilder {
+            tls: false,
+            http2_prior_knowledge: false,
+            graceful_shutdown_timeout: None,
+            configure_server: None,
+        }
+    }
+
+    /// Returns the address of the server.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Returns the base URL of the server, such as `http://127.0.0.1:12345`.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Returns the URL of the path on the server.
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{path}", self.base_url)
+    }
+
+    /// Returns the client which trusts the certificate of the server.
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    /// Returns the PEM encoded self-signed certificate if the server uses TLS.
+    pub fn certificate_pem(&self) -> Option<&str> {
+        self.certificate.as_deref()
+    }
+
+    /// Gracefully shuts down the server and waits for it to stop.
+    pub async fn shutdown(mut self) -> IoResult<()> {
+        if let Some(tx) = self.shutdown.take() {
+            let _ = tx.send(());
+        }
+        match self.handle.take() {
+            Some(handle) => handle.await.map_err(std::io::Error::other)?,
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        if let Some(tx) = self.shutdown.take() {
+            let _ = tx.send(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Request, Route, get, handler, http::Version};
+
+    #[handler(internal)]
+    fn version(req: &Request) -> String {
+        format!("{:?}", req.version())
+    }
+
+    #[tokio::test]
+    async fn http1_and_http2() {
+        let server = TestServer::start(Route::new().at("/", get(version))).await;
+        assert!(server.base_url().starts_with("http://127.0.0.1:"));
+        let resp = server.client().get(server.url("/")).send().await.unwrap();
+        assert_eq!(resp.version(), Version::HTTP_11);
+        assert_eq!(resp.text().await.unwrap(), "HTTP/1.1");
+        server.shutdown().await.unwrap();
+
+        let server = TestServer::builder()
+            .http2_prior_knowledge(true)
+            .start(Route::new().at("/", get(version)))
+            .await;
+        let resp = server.client().get(server.url("/")).send().await.unwrap();
+        assert_eq!(resp.text().await.unwrap(), "HTTP/2.0");
+    }
+
+    #[tokio::test]
+    async fn tls() {
+        let server = TestServer::builder()
+            .tls(true)
+            .start(Route::new().at("/", get(version)))
+            .await;
+        assert!(server.base_url().starts_with("https://"));
+        assert!(server.certificate_pem().is_some());
+        let resp = server.client().get(server.url("/")).send().await.unwrap();
+        assert_eq!(resp.text().await.unwrap(), "HTTP/2.0");
+    }
+
+    #[tokio::test]
+    async fn shutdown_on_drop() {
+        let server = TestServer::builder()
+            .configure_server(|server| server.idle_timeout(Duration::from_secs(1)))
+            .start(Route::new().at("/", get(version)))
+            .await;
+        let addr = server.local_addr();
+        drop(server);
+
+        let mut stopped = false;
+        for _ in 0..50 {
+            if tokio::net::TcpStream::connect(addr).await.is_err() {
+                stopped = true;
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(stopped);
+    }
+}
@@ -0,0 +1,29 @@
+use std::ops::Deref;
+
+use crate::{FromRequest, Request, RequestBody, Result};
+
+/// The `Content-Security-Policy` nonce of the current request, which can be
+/// used in the `nonce` attribute of the inline scripts and styles.
+///
+/// See also [`SecurityHeaders`](crate::middleware::SecurityHeaders) and
+/// [`ContentSecurityPolicy::nonce`](crate::middleware::ContentSecurityPolicy::nonce).
+#[cfg_attr(docsrs, doc(cfg(feature = "security-headers")))]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CspNonce(pub String);
+
+impl Deref for CspNonce {
+    type Target = String;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'a> FromRequest<'a> for &'a CspNonce {
+    async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
+        Ok(req.extensions().get::<CspNonce>().expect(
+            "To use the `CspNonce` extractor, the `SecurityHeaders` middleware with a \
+             `ContentSecurityPolicy` that uses nonces is required.",
+        ))
+    }
+}
@@ -0,0 +1,519 @@
+//! A publish/subscribe hub to fan out messages to WebSocket and SSE
+//! connections.
+//!
+//! Messages published to a topic are delivered to every
+//! [`Subscription`] of that topic. A subscription is a [`Stream`], so it can be
+//! turned into an SSE response with [`Subscription::into_sse`], or forwarded
+//! to a [`WebSocketStream`](crate::web::websocket::WebSocketStream).
+//!
+//! # Example
+//!
+//! ```
+//! use poem::{
+//!     EndpointExt, Result, Route, get, handler,
+//!     web::{Data, Path, hub::Hub, sse::SSE},
+//! };
+//!
+//! #[handler]
+//! async fn post_message(
+//!     Path(room): Path<String>,
+//!     message: String,
+//!     hub: Data<&Hub<String>>,
+//! ) -> Result<()> {
+//!     hub.publish(&room, message).await?;
+//!     Ok(())
+//! }
+//!
+//! #[handler]
+//! fn events(Path(room): Path<String>, hub: Data<&Hub<String>>) -> SSE {
+//!     hub.subscribe(room).into_sse()
+//! }
+//!
+//! let app = Route::new()
+//!     .at("/rooms/:room", get(events).post(post_message))
+//!     .data(Hub::<String>::new());
+//! ```
+//!
+//! With WebSocket, the subscription is usually forwarded to one half of the
+//! socket while the other half publishes the incoming messages:
+//!
+//! ```ignore
+//! #[handler]
+//! fn chat(Path(room): Path<String>, ws: WebSocket, hub: Data<&Hub<String>>) -> impl IntoResponse {
+//!     let channel = hub.channel(room);
+//!     ws.on_upgrade(move |socket| async move {
+//!         let (mut sink, mut stream) = socket.split();
+//!         let mut subscription = channel.subscribe();
+//!
+//!         tokio::spawn(async move {
+//!             while let Some(msg) = subscription.next().await {
+//!                 if sink.send(Message::text(msg)).await.is_err() {
+//!                     break;
+//!                 }
+//!             }
+//!         });
+//!
+//!         while let Some(Ok(Message::Text(text))) = stream.next().await {
+//!             let _ = channel.publish(text).await;
+//!         }
+//!     })
+//! }
+//! ```
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use futures_util::{Stream, StreamExt, stream::BoxStream};
+use parking_lot::Mutex;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::Result;
+
+/// What happens when a subscriber cannot keep up with the publishers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backpressure {
+    /// Skip the oldest messages the subscriber has not received yet.
+    #[default]
+    DropOldest,
+
+    /// End the subscription, so the connection of the slow consumer is
+    /// closed.
+    DisconnectSlowConsumer,
+}
+
+/// Configuration for [`Hub`].
+#[derive(Debug, Clone, Copy)]
+pub struct HubConfig {
+    capacity: usize,
+    backpressure: Backpressure,
+}
+
+impl Default for HubConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 128,
+            backpressure: Backpressure::default(),
+        }
+    }
+}
+
+impl HubConfig {
+    /// Create a new `HubConfig`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets the number of messages buffered for each subscriber of a topic.
+    /// Default is `128`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is `0`.
+    #[must_use]
+    pub fn capacity(self, capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity must be greater than 0");
+        Self { capacity, ..self }
+    }
+
+    /// Sets the policy applied to subscribers whose buffer is full. Default is
+    /// [`Backpressure::DropOldest`].
+    #[must_use]
+    pub fn backpressure(self, backpressure: Backpressure) -> Self {
+        Self {
+            backpressure,
+            ..self
+        }
+    }
+}
+
+/// Forwards the messages published on a [`Hub`] to the other instances of the
+/// application.
+///
+/// [`Hub::publish`] delivers a message to the local subscribers and then
+/// passes it to the broadcaster, for example to publish it on a Redis channel.
+/// Messages received from the other instances are delivered to the local
+/// subscribers with [`Hub::publish_local`]. Implementations are responsible
+/// for ignoring the messages published by their own instance.
+pub trait Broadcaster<T>: Send + Sync + 'static {
+    /// Forward a message published on this instance.
+    fn publish<'a>(
+        &'a self,
+        topic: &'a str,
+        message: &'a T,
+    ) -> impl Future<Output = Result<()>> + Send + 'a;
+}
+
+/// A [`Broadcaster`] for applications with a single instance, which does not
+/// forward the messages anywhere.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LocalBroadcaster;
+
+impl<T: Sync> Broadcaster<T> for LocalBroadcaster {
+    async fn publish<'a>(&'a self, _topic: &'a str, _message: &'a T) -> Result<()> {
+        Ok(())
+    }
+}
+
+struct HubInner<T, B> {
+    config: HubConfig,
+    topics: Mutex<HashMap<Arc<str>, broadcast::Sender<T>>>,
+    broadcaster: B,
+}
+
+/// A publish/subscribe hub which fans out messages to the subscribers of named
+/// topics.
+///
+/// The hub is cheap to clone, and is usually shared between handlers with
+/// [`EndpointExt::data`](crate::EndpointExt::data).
+pub struct Hub<T, B = LocalBroadcaster> {
+    inner: Arc<HubInner<T, B>>,
+}
+
+impl<T, B> Clone for Hub<T, B> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static> Default for Hub<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static> Hub<T> {
+    /// Create a new hub with the default configuration.
+    pub fn new() -> Self {
+        Self::with_config(HubConfig::default())
+    }
+
+    /// Create a new hub with the specified configuration.
+    pub fn with_config(config: HubConfig) -> Self {
+        Self::with_broadcaster(config, LocalBroadcaster)
+    }
+}
+
+impl<T, B> Hub<T, B>
+where
+    T: Clone + Send + Sync + 'static,
+    B: Broadcaster<T>,
+{
+    /// Create a new hub which forwards published messages with the specified
+    /// [`Broadcaster`].
+    pub fn with_broadcaster(config: HubConfig, broadcaster: B) -> Self {
+        Self {
+            inner: Arc::new(HubInner {
+                config,
+                topics: Default::default(),
+                broadcaster,
+            }),
+        }
+    }
+
+    /// Returns the broadcaster of this hub.
+    pub fn broadcaster(&self) -> &B {
+        &self.inner.broadcaster
+    }
+
+    /// Returns a handle for the specified topic.
+    pub fn channel(&self, topic: impl Into<String>) -> Channel<T, B> {
+        Channel {
+            hub: self.clone(),
+            topic: topic.into().into(),
+        }
+    }
+
+    /// Subscribe to the specified topic.
+    pub fn subscribe(&self, topic: impl Into<String>) -> Subscription<T> {
+        self.subscribe_topic(topic.into().into())
+    }
+
+    fn subscribe_topic(&self, topic: Arc<str>) -> Subscription<T> {
+        let receiver = self
+            .inner
+            .topics
+            .lock()
+            .entry(topic.clone())
+            .or_insert_with(|| broadcast::channel(self.inner.config.capacity).0)
+            .subscribe();
+
+        let backpressure = self.inner.config.backpressure;
+        let stream = futures_util::stream::unfold(receiver, move |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(message) => return Some((message, receiver)),
+                    Err(RecvError::Lagged(_)) if backpressure == Backpressure::DropOldest => {
+                        continue;
+                    }
+                    Err(_) => return None,
+                }
+            }
+        })
+        .boxed();
+
+        let hub = Arc::downgrade(&self.inner);
+        let cleanup = Box::new(move |topic: &Arc<str>| {
+            if let Some(hub) = hub.upgrade() {
+                let mut topics = hub.topics.lock();
+                if topics
+                    .get(topic)
+                    .is_some_and(|sender| sender.receiver_count() == 0)
+                {
+                    topics.remove(topic);
+                }
+            }
+        });
+
+        Subscription {
+            stream,
+            guard: CleanupGuard {
+                topic,
+                cleanup: Some(cleanup),
+            },
+        }
+    }
+
+    /// Publish a message to the subscribers of a topic on this instance and
+    /// forward it to the [`Broadcaster`].
+    ///
+    /// Returns the number of local subscribers that received the message.
+    pub async fn publish(&self, topic: &str, message: T) -> Result<usize> {
+        self.inner.broadcaster.publish(topic, &message).await?;
+        Ok(self.publish_local(topic, message))
+    }
+
+    /// Publish a message to the subscribers of a topic on this instance only.
+    ///
+    /// Returns the number of subscribers that received the message.
+    pub fn publish_local(&self, topic: &str, message: T) -> usize {
+        match self.inner.topics.lock().get(topic) {
+            Some(sender) => sender.send(message).unwrap_or_default(),
+            None => 0,
+        }
+    }
+
+    /// Returns the number of local subscribers of a topic.
+    pub fn presence(&self, topic: &str) -> usize {
+        self.inner
+            .topics
+            .lock()
+            .get(topic)
+            .map(|sender| sender.receiver_count())
+            .unwrap_or_default()
+    }
+
+    /// Returns the topics that have at least one local subscriber, with
+    /// their number of subscribers.
+    pub fn topics(&self) -> Vec<(String, usize)> {
+        self.inner
+            .topics
+            .lock()
+            .iter()
+            .map(|(topic, sender)| (topic.to_string(), sender.receiver_count()))
+            .filter(|(_, count)| *count > 0)
+            .collect()
+    }
+}
+
+/// A handle to a topic of a [`Hub`].
+pub struct Channel<T, B = LocalBroadcaster> {
+    hub: Hub<T, B>,
+    topic: Arc<str>,
+}
+
+impl<T, B> Clone for Channel<T, B> {
+    fn clone(&self) -> Self {
+        Self {
+            hub: self.hub.clone(),
+            topic: self.topic.clone(),
+        }
+    }
+}
+
+impl<T, B> Channel<T, B>
+where
+    T: Clone + Send + Sync + 'static,
+    B: Broadcaster<T>,
+{
+    /// Returns the name of the topic.
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+
+    /// Subscribe to the topic.
+    pub fn subscribe(&self) -> Subscription<T> {
+        self.hub.subscribe_topic(self.topic.clone())
+    }
+
+    /// Publish a message to the topic.
+    ///
+    /// See [`Hub::publish`].
+    pub async fn publish(&self, message: T) -> Result<usize> {
+        self.hub.publish(&self.topic, message).await
+    }
+
+    /// Returns the number of local subscribers of the topic.
+    pub fn presence(&self) -> usize {
+        self.hub.presence(&self.topic)
+    }
+}
+
+type Cleanup = Box<dyn FnOnce(&Arc<str>) + Send + Sync>;
+
+/// A stream of the messages published to a topic.
+///
+/// The stream ends if the subscriber is disconnected by
+/// [`Backpressure::DisconnectSlowConsumer`].
+pub struct Subscription<T> {
+    // dropped before the guard, so that the receiver is closed when the guard
+    // checks whether the topic is still in use
+    stream: BoxStream<'static, T>,
+    guard: CleanupGuard,
+}
+
+impl<T> Subscription<T> {
+    /// Returns the name of the topic.
+    pub fn topic(&self) -> &str {
+        &self.guard.topic
+    }
+
+    /// Create an SSE response which sends each message as an event.
+    #[cfg(feature = "sse")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "sse")))]
+    pub fn into_sse(self) -> crate::web::sse::SSE
+    where
+        T: Into<String> + 'static,
+    {
+        crate::web::sse::SSE::new(self.map(crate::web::sse::Event::message))
+    }
+}
+
+impl<T> Stream for Subscription<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.stream.poll_next_unpin(cx)
+    }
+}
+
+struct CleanupGuard {
+    topic: Arc<str>,
+    cleanup: Option<Cleanup>,
+}
+
+impl Drop for CleanupGuard {
+    fn drop(&mut self) {
+        if let Some(cleanup) = self.cleanup.take() {
+            cleanup(&self.topic);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn publish_subscribe() {
+        let hub = Hub::<i32>::new();
+        assert_eq!(hub.publish("a", 1).await.unwrap(), 0);
+
+        let mut sub_a1 = hub.subscribe("a");
+        let mut sub_a2 = hub.subscribe("a");
+        let mut sub_b = hub.subscribe("b");
+        assert_eq!(hub.presence("a"), 2);
+        assert_eq!(hub.presence("b"), 1);
+        assert_eq!(hub.presence("c"), 0);
+
+        assert_eq!(hub.publish("a", 1).await.unwrap(), 2);
+        assert_eq!(hub.channel("b").publish(2).await.unwrap(), 1);
+        assert_eq!(sub_a1.next().await, Some(1));
+        assert_eq!(sub_a2.next().await, Some(1));
+        assert_eq!(sub_b.next().await, Some(2));
+
+        drop(sub_a1);
+        assert_eq!(hub.presence("a"), 1);
+        drop(sub_a2);
+        assert_eq!(hub.presence("a"), 0);
+
+        let mut topics = hub.topics();
+        topics.sort();
+        assert_eq!(topics, vec![("b".to_string(), 1)]);
+    }
+
+    #[tokio::test]
+    async fn backpressure() {
+        let hub = Hub::<i32>::with_config(HubConfig::new().capacity(2));
+        let mut sub = hub.subscribe("a");
+        for i in 0..5 {
+            hub.publish_local("a", i);
+        }
+        assert_eq!(sub.next().await, Some(3));
+        assert_eq!(sub.next().await, Some(4));
+
+        let hub = Hub::<i32>::with_config(
+            HubConfig::new()
+                .capacity(2)
+                .backpressure(Backpressure::DisconnectSlowConsumer),
+        );
+        let mut slow = hub.subscribe("a");
+        let mut fast = hub.subscribe("a");
+        for i in 0..5 {
+            hub.publish_local("a", i);
+            assert_eq!(fast.next().await, Some(i));
+        }
+        assert_eq!(slow.next().await, None);
+        assert_eq!(hub.publish("a", 5).await.unwrap(), 1);
+        assert_eq!(fast.next().await, Some(5));
+    }
+
+    #[tokio::test]
+    async fn broadcaster() {
+        #[derive(Default)]
+        struct Counter(AtomicUsize);
+
+        impl Broadcaster<i32> for Counter {
+            async fn publish<'a>(&'a self, topic: &'a str, message: &'a i32) -> Result<()> {
+                assert_eq!(topic, "a");
+                self.0.fetch_add(*message as usize, Ordering::SeqCst);
+                Ok(())
+            }
+        }
+
+        let hub = Hub::with_broadcaster(HubConfig::new(), Counter::default());
+        let mut sub = hub.subscribe("a");
+        hub.publish("a", 10).await.unwrap();
+        hub.publish("a", 20).await.unwrap();
+        // messages from other instances
+        hub.publish_local("a", 30);
+        assert_eq!(hub.broadcaster().0.load(Ordering::SeqCst), 30);
+        assert_eq!(sub.next().await, Some(10));
+        assert_eq!(sub.next().await, Some(20));
+        assert_eq!(sub.next().await, Some(30));
+    }
+
+    #[cfg(feature = "sse")]
+    #[tokio::test]
+    async fn sse() {
+        use crate::IntoResponse;
+
+        let hub = Hub::<String>::new();
+        let resp = hub.subscribe("a").into_sse().into_response();
+        hub.publish("a", "hello".to_string()).await.unwrap();
+        drop(hub);
+
+        assert_eq!(
+            resp.into_body().into_string().await.unwrap(),
+            "data: hello\n\n"
+        );
+    }
+}
@@ -0,0 +1,37 @@
+use std::ops::{Deref, DerefMut};
+
+use crate::{FromRequest, Request, RequestBody, Result, error::JwtError};
+
+/// An extractor that extracts the claims of the JSON Web Token validated by
+/// the [`JwtAuth`](crate::middleware::JwtAuth) middleware.
+///
+/// It returns [`JwtError::MissingToken`] if the request has no token, so
+/// `Option<JwtClaims<T>>` can be used with an
+/// [`optional`](crate::middleware::JwtAuth::optional) middleware.
+#[cfg_attr(docsrs, doc(cfg(feature = "jwt")))]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct JwtClaims<T>(pub T);
+
+impl<T> Deref for JwtClaims<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for JwtClaims<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<'a, T: Clone + Send + Sync + 'static> FromRequest<'a> for JwtClaims<T> {
+    async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
+        Ok(req
+            .extensions()
+            .get::<JwtClaims<T>>()
+            .cloned()
+            .ok_or(JwtError::MissingToken)?)
+    }
+}
@@ -7,12 +7,19 @@ mod compress;
 #[cfg(feature = "cookie")]
 #[cfg_attr(docsrs, doc(cfg(feature = "cookie")))]
 pub mod cookie;
+#[cfg(feature = "security-headers")]
+mod csp_nonce;
 mod data;
 mod form;
+pub mod hub;
 mod json;
+#[cfg(feature = "jwt")]
+mod jwt_claims;
 #[cfg(feature = "multipart")]
 mod multipart;
+mod negotiated;
 mod path;
+mod problem_details;
 mod query;
 mod real_ip;
 mod redirect;
@@ -46,10 +53,16 @@ use http::header;
 
 #[cfg(feature = "compression")]
 pub use self::compress::{Compress, CompressionAlgo};
+#[cfg(feature = "security-headers")]
+pub use self::csp_nonce::CspNonce;
 #[cfg(feature = "csrf")]
 pub use self::csrf::{CsrfToken, CsrfVerifier};
+#[cfg(feature = "jwt")]
+pub use self::jwt_claims::JwtClaims;
+#[cfg(all(feature = "multipart", feature = "tempfile"))]
+pub use self::multipart::UploadedFile;
 #[cfg(feature = "multipart")]
-pub use self::multipart::{Field, Multipart};
+pub use self::multipart::{Field, Multipart, MultipartConfig};
 pub(crate) use self::path::PathDeserializer;
 #[cfg(feature = "static-files")]
 pub use self::static_file::{StaticFileRequest, StaticFileResponse};
@@ -65,7 +78,9 @@ pub use self::{
     data::Data,
     form::Form,
     json::Json,
+    negotiated::Negotiated,
     path::Path,
+    problem_details::ProblemDetails,
     query::Query,
     real_ip::RealIp,
     redirect::Redirect,
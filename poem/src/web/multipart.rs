@@ -1,17 +1,23 @@
 use std::{
     fmt::{self, Debug, Formatter},
+    path::Path,
     str::FromStr,
 };
 
 use futures_util::TryStreamExt;
 use mime::Mime;
 #[cfg(feature = "tempfile")]
-use tokio::fs::File;
-use tokio::io::{AsyncRead, AsyncReadExt};
-#[cfg(feature = "tempfile")]
 use tokio::io::{AsyncSeekExt, SeekFrom};
+use tokio::{
+    fs::File,
+    io::{AsyncRead, AsyncWriteExt},
+};
 
-use crate::{FromRequest, Request, RequestBody, Result, error::ParseMultipartError, http::header};
+use crate::{
+    FromRequest, Request, RequestBody, Result,
+    error::{MultipartLimitError, ParseMultipartError},
+    http::header,
+};
 
 /// A single field in a multipart stream.
 #[cfg_attr(docsrs, doc(cfg(feature = "multipart")))]
@@ -57,19 +63,11 @@ impl Field {
     }
 
     /// Get the full data of the field as bytes.
-    pub async fn bytes(self) -> Result<Vec<u8>, ParseMultipartError> {
+    pub async fn bytes(mut self) -> Result<Vec<u8>, ParseMultipartError> {
         let mut data = Vec::new();
-        let mut buf = [0; 2048];
-        let mut reader = self.into_async_read();
-        loop {
-            let sz = reader.read(&mut buf[..]).await?;
-            if sz > 0 {
-                data.extend_from_slice(&buf[..sz]);
-            } else {
-                break;
-            }
+        while let Some(chunk) = self.0.chunk().await? {
+            data.extend_from_slice(&chunk);
         }
-
         Ok(data)
     }
 
@@ -83,13 +81,58 @@ impl Field {
     #[cfg(feature = "tempfile")]
     #[cfg_attr(docsrs, doc(cfg(feature = "tempfile")))]
     pub async fn tempfile(self) -> Result<File, ParseMultipartError> {
-        let mut reader = self.into_async_read();
         let mut file = tokio::fs::File::from_std(::libtempfile::tempfile()?);
-        tokio::io::copy(&mut reader, &mut file).await?;
+        self.write_to(&mut file).await?;
         file.seek(SeekFrom::Start(0)).await?;
         Ok(file)
     }
 
+    /// Write the full field data to a named temporary file, which is deleted
+    /// when the returned [`UploadedFile`] is dropped.
+    #[cfg(feature = "tempfile")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "tempfile")))]
+    pub async fn named_tempfile(self) -> Result<UploadedFile, ParseMultipartError> {
+        let (file, path) = ::libtempfile::NamedTempFile::new()?.into_parts();
+        let file_name = self.file_name().map(ToString::to_string);
+        let content_type = self.content_type().map(ToString::to_string);
+        let size = self.write_to(&mut File::from_std(file)).await?;
+        Ok(UploadedFile {
+            path,
+            size,
+            file_name,
+            content_type,
+        })
+    }
+
+    /// Write the full field data to a file at the specified path, and return
+    /// the number of bytes written.
+    ///
+    /// The file is created or truncated, and is removed if the field can't be
+    /// read completely, for example because it exceeds the size limits of the
+    /// [`MultipartConfig`].
+    pub async fn save_to(self, path: impl AsRef<Path>) -> Result<u64, ParseMultipartError> {
+        let path = path.as_ref();
+        let mut file = File::create(path).await?;
+        match self.write_to(&mut file).await {
+            Ok(size) => Ok(size),
+            Err(err) => {
+                drop(file);
+                let _ = tokio::fs::remove_file(path).await;
+                Err(err)
+            }
+        }
+    }
+
+    async fn write_to(mut self, file: &mut File) -> Result<u64, ParseMultipartError> {
+        let mut size = 0;
+        while let Some(chunk) = self.0.chunk().await? {
+            file.write_all(&chunk).await?;
+            size += chunk.len() as u64;
+        }
+        file.flush().await?;
+        Ok(size)
+    }
+
     /// Consume this field to return a reader.
     pub fn into_async_read(self) -> impl AsyncRead + Send {
         tokio_util::io::StreamReader::new(
@@ -98,6 +141,226 @@ impl Field {
     }
 }
 
+/// A field that has been written to a temporary file by
+/// [`Field::named_tempfile`].
+///
+/// The file is deleted when this value is dropped, unless it is moved with
+/// [`UploadedFile::persist`].
+#[cfg(feature = "tempfile")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tempfile")))]
+#[derive(Debug)]
+pub struct UploadedFile {
+    path: ::libtempfile::TempPath,
+    size: u64,
+    file_name: Option<String>,
+    content_type: Option<String>,
+}
+
+#[cfg(feature = "tempfile")]
+impl UploadedFile {
+    /// Returns the path of the temporary file.
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the size of the file in bytes.
+    #[inline]
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// The file name found in the `Content-Disposition` header of the field.
+    #[inline]
+    pub fn file_name(&self) -> Option<&str> {
+        self.file_name.as_deref()
+    }
+
+    /// The content type of the field.
+    #[inline]
+    pub fn content_type(&self) -> Option<&str> {
+        self.content_type.as_deref()
+    }
+
+    /// Opens the file for reading.
+    pub async fn open(&self) -> std::io::Result<File> {
+        File::open(&self.path).await
+    }
+
+    /// Moves the file to the specified path, so that it is no longer deleted.
+    pub fn persist(self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        self.path.persist(path).map_err(|err| err.error)
+    }
+}
+
+/// The limits applied when parsing a [`Multipart`] request.
+///
+/// The configuration is read from the request data, so it can be added to an
+/// endpoint with [`EndpointExt::data`](crate::EndpointExt::data). The limits
+/// are enforced while the request body is streamed, so oversized requests
+/// are rejected before they are read into memory or written to disk.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     EndpointExt, Result, Route, handler, post,
+///     web::{Multipart, MultipartConfig},
+/// };
+///
+/// #[handler]
+/// async fn upload(mut multipart: Multipart) -> Result<()> {
+///     while let Some(field) = multipart.next_field().await? {
+///         field.save_to("/tmp/upload").await?;
+///     }
+///     Ok(())
+/// }
+///
+/// let app = Route::new().at(
+///     "/upload",
+///     post(upload).data(
+///         MultipartConfig::new()
+///             .max_fields(4)
+///             .max_field_size(10 * 1024 * 1024)
+///             .allowed_content_types(["image/*", "application/pdf"]),
+///     ),
+/// );
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "multipart")))]
+#[derive(Debug, Clone, Default)]
+pub struct MultipartConfig {
+    max_fields: Option<usize>,
+    max_size: Option<u64>,
+    max_field_size: Option<u64>,
+    field_sizes: Vec<(String, u64)>,
+    allowed_fields: Option<Vec<String>>,
+    allowed_content_types: Option<Vec<String>>,
+}
+
+impl MultipartConfig {
+    /// Create a new `MultipartConfig` without limits.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets the maximum number of fields.
+    #[must_use]
+    pub fn max_fields(self, max_fields: usize) -> Self {
+        Self {
+            max_fields: Some(max_fields),
+            ..self
+        }
+    }
+
+    /// Sets the maximum size of the whole request body in bytes.
+    #[must_use]
+    pub fn max_size(self, max_size: u64) -> Self {
+        Self {
+            max_size: Some(max_size),
+            ..self
+        }
+    }
+
+    /// Sets the maximum size of each field in bytes.
+    #[must_use]
+    pub fn max_field_size(self, max_field_size: u64) -> Self {
+        Self {
+            max_field_size: Some(max_field_size),
+            ..self
+        }
+    }
+
+    /// Sets the maximum size in bytes of the field with the specified name,
+    /// which overrides [`MultipartConfig::max_field_size`].
+    #[must_use]
+    pub fn field_size(mut self, name: impl Into<String>, max_size: u64) -> Self {
+        self.field_sizes.push((name.into(), max_size));
+        self
+    }
+
+    /// Sets the allowed field names, other fields are rejected.
+    #[must_use]
+    pub fn allowed_fields<I>(self, names: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        Self {
+            allowed_fields: Some(names.into_iter().map(Into::into).collect()),
+            ..self
+        }
+    }
+
+    /// Sets the allowed content types of file fields, other files are
+    /// rejected.
+    ///
+    /// The content types can contain a wildcard subtype, such as `image/*`.
+    /// Files without a `Content-Type` are treated as
+    /// `application/octet-stream`, and fields without a file name are not
+    /// checked.
+    #[must_use]
+    pub fn allowed_content_types<I>(self, content_types: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        Self {
+            allowed_content_types: Some(content_types.into_iter().map(Into::into).collect()),
+            ..self
+        }
+    }
+
+    fn constraints(&self) -> multer::Constraints {
+        let mut size_limit = multer::SizeLimit::new();
+        if let Some(max_size) = self.max_size {
+            size_limit = size_limit.whole_stream(max_size);
+        }
+        if let Some(max_field_size) = self.max_field_size {
+            size_limit = size_limit.per_field(max_field_size);
+        }
+        for (name, max_size) in &self.field_sizes {
+            size_limit = size_limit.for_field(name.clone(), *max_size);
+        }
+
+        let mut constraints = multer::Constraints::new().size_limit(size_limit);
+        if let Some(allowed_fields) = &self.allowed_fields {
+            constraints = constraints.allowed_fields(allowed_fields.clone());
+        }
+        constraints
+    }
+
+    fn check_field(&self, index: usize, field: &Field) -> Result<(), ParseMultipartError> {
+        if let Some(max_fields) = self.max_fields {
+            if index >= max_fields {
+                return Err(MultipartLimitError::TooManyFields(max_fields).into());
+            }
+        }
+
+        if let Some(allowed_content_types) = &self.allowed_content_types {
+            if field.file_name().is_some() {
+                let content_type = field
+                    .content_type()
+                    .unwrap_or("application/octet-stream")
+                    .to_ascii_lowercase();
+                let is_allowed =
+                    allowed_content_types
+                        .iter()
+                        .any(|allowed| match allowed.strip_suffix("/*") {
+                            Some(ty) => content_type
+                                .split_once('/')
+                                .is_some_and(|(t, _)| t.eq_ignore_ascii_case(ty)),
+                            None => allowed.eq_ignore_ascii_case(&content_type),
+                        });
+                if !is_allowed {
+                    return Err(MultipartLimitError::ContentTypeNotAllowed(content_type).into());
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
 /// An extractor that parses `multipart/form-data` requests commonly used with
 /// file uploads.
 ///
@@ -106,6 +369,9 @@ impl Field {
 /// - [`ReadBodyError`](crate::error::ReadBodyError)
 /// - [`ParseMultipartError`]
 ///
+/// The limits of the [`MultipartConfig`] in the request data are applied
+/// while parsing.
+///
 /// # Example
 ///
 /// ```
@@ -126,6 +392,8 @@ impl Field {
 #[cfg_attr(docsrs, doc(cfg(feature = "multipart")))]
 pub struct Multipart {
     inner: multer::Multipart<'static>,
+    config: MultipartConfig,
+    fields: usize,
 }
 
 impl<'a> FromRequest<'a> for Multipart {
@@ -146,11 +414,15 @@ impl<'a> FromRequest<'a> for Multipart {
 
         let boundary = multer::parse_boundary(content_type.as_ref())
             .map_err(ParseMultipartError::Multipart)?;
+        let config = req.data::<MultipartConfig>().cloned().unwrap_or_default();
         Ok(Self {
-            inner: multer::Multipart::new(
+            inner: multer::Multipart::with_constraints(
                 tokio_util::io::ReaderStream::new(body.take()?.into_async_read()),
                 boundary,
+                config.constraints(),
             ),
+            config,
+            fields: 0,
         })
     }
 }
@@ -159,7 +431,12 @@ impl Multipart {
     /// Yields the next [`Field`] if available.
     pub async fn next_field(&mut self) -> Result<Option<Field>, ParseMultipartError> {
         match self.inner.next_field().await? {
-            Some(field) => Ok(Some(Field(field))),
+            Some(field) => {
+                let field = Field(field);
+                self.config.check_field(self.fields, &field)?;
+                self.fields += 1;
+                Ok(Some(field))
+            }
             None => Ok(None),
         }
     }
@@ -168,7 +445,7 @@ impl Multipart {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{handler, http::StatusCode, test::TestClient};
+    use crate::{EndpointExt, error::ResponseError, handler, http::StatusCode, test::TestClient};
 
     #[tokio::test]
     async fn test_multipart_extractor_content_type() {
@@ -216,4 +493,134 @@ mod tests {
             .await;
         resp.assert_status_is_ok();
     }
+
+    #[tokio::test]
+    async fn test_multipart_config() {
+        #[handler(internal)]
+        async fn index(mut multipart: Multipart) -> Result<()> {
+            while let Some(field) = multipart.next_field().await? {
+                field.bytes().await?;
+            }
+            Ok(())
+        }
+
+        let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"a\"\r\n\r\nabcd\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"b\"; filename=\"b.txt\"\r\nContent-Type: text/plain\r\n\r\nHello world\r\n--X-BOUNDARY--\r\n";
+        let send = |config: MultipartConfig| async move {
+            TestClient::new(index.data(config))
+                .post("/")
+                .header("content-type", "multipart/form-data; boundary=X-BOUNDARY")
+                .body(data)
+                .send()
+                .await
+        };
+
+        send(MultipartConfig::new()).await.assert_status_is_ok();
+        send(MultipartConfig::new().max_fields(1))
+            .await
+            .assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+        send(MultipartConfig::new().max_field_size(4))
+            .await
+            .assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+        send(MultipartConfig::new().max_field_size(4).field_size("b", 11))
+            .await
+            .assert_status_is_ok();
+        send(MultipartConfig::new().allowed_fields(["a"]))
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+        send(MultipartConfig::new().allowed_content_types(["text/*"]))
+            .await
+            .assert_status_is_ok();
+        send(MultipartConfig::new().allowed_content_types(["image/png"]))
+            .await
+            .assert_status(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let err = ParseMultipartError::from(MultipartLimitError::TooManyFields(1));
+        assert_eq!(err.to_string(), "too many fields, limit: 1");
+        assert_eq!(err.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn test_multipart_save_to() {
+        #[handler(internal)]
+        async fn index(mut multipart: Multipart) -> Result<String> {
+            let dir = std::env::temp_dir().join(format!("poem-multipart-{}", std::process::id()));
+            tokio::fs::create_dir_all(&dir).await.unwrap();
+            let path = dir.join("upload.txt");
+
+            let field = multipart.next_field().await?.unwrap();
+            let res = field.save_to(&path).await;
+            let exists = tokio::fs::try_exists(&path).await.unwrap();
+            let content = tokio::fs::read_to_string(&path).await.unwrap_or_default();
+            tokio::fs::remove_dir_all(&dir).await.unwrap();
+
+            match res {
+                Ok(_) => {
+                    assert!(exists);
+                    Ok(content)
+                }
+                Err(err) => {
+                    // the partial file is removed
+                    assert!(matches!(
+                        err,
+                        ParseMultipartError::Multipart(multer::Error::FieldSizeExceeded { .. })
+                    ));
+                    assert!(!exists);
+                    Err(err.into())
+                }
+            }
+        }
+
+        let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"file\"; filename=\"a.txt\"\r\n\r\nHello world\r\n--X-BOUNDARY--\r\n";
+        let cli = TestClient::new(index.data(MultipartConfig::new().max_field_size(11)));
+        let resp = cli
+            .post("/")
+            .header("content-type", "multipart/form-data; boundary=X-BOUNDARY")
+            .body(data)
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_text("Hello world").await;
+
+        let cli = TestClient::new(index.data(MultipartConfig::new().max_field_size(4)));
+        let resp = cli
+            .post("/")
+            .header("content-type", "multipart/form-data; boundary=X-BOUNDARY")
+            .body(data)
+            .send()
+            .await;
+        resp.assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[cfg(feature = "tempfile")]
+    #[tokio::test]
+    async fn test_multipart_named_tempfile() {
+        #[handler(internal)]
+        async fn index(mut multipart: Multipart) -> Result<String> {
+            let file = multipart
+                .next_field()
+                .await?
+                .unwrap()
+                .named_tempfile()
+                .await?;
+            assert_eq!(file.size(), 11);
+            assert_eq!(file.file_name(), Some("a.txt"));
+            assert_eq!(file.content_type(), None);
+
+            let path = file.path().to_path_buf();
+            let content = tokio::fs::read_to_string(&path).await.unwrap();
+            drop(file);
+            assert!(!path.exists());
+            Ok(content)
+        }
+
+        let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"file\"; filename=\"a.txt\"\r\n\r\nHello world\r\n--X-BOUNDARY--\r\n";
+        let resp = TestClient::new(index)
+            .post("/")
+            .header("content-type", "multipart/form-data; boundary=X-BOUNDARY")
+            .body(data)
+            .send()
+            .await;
+        resp.assert_status_is_ok();
+        resp.assert_text("Hello world").await;
+    }
 }
@@ -0,0 +1,283 @@
+use mime::Mime;
+use serde::Serialize;
+
+use crate::{
+    IntoResponse, Response,
+    http::{HeaderValue, StatusCode, header},
+    web::{Accept, Json},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Json,
+    #[cfg(feature = "yaml")]
+    Yaml,
+    #[cfg(feature = "xml")]
+    Xml,
+}
+
+impl Format {
+    /// All the supported formats, in order of preference.
+    const ALL: &'static [Format] = &[
+        Format::Json,
+        #[cfg(feature = "yaml")]
+        Format::Yaml,
+        #[cfg(feature = "xml")]
+        Format::Xml,
+    ];
+
+    fn media_types(self) -> &'static [&'static str] {
+        match self {
+            Format::Json => &["application/json"],
+            #[cfg(feature = "yaml")]
+            Format::Yaml => &["application/yaml", "application/x-yaml", "text/yaml"],
+            #[cfg(feature = "xml")]
+            Format::Xml => &["application/xml", "text/xml"],
+        }
+    }
+}
+
+fn quality(mime: &Mime) -> f32 {
+    mime.get_param("q")
+        .and_then(|value| value.as_str().parse::<f32>().ok())
+        .unwrap_or(1.0)
+}
+
+/// Returns the quality of the media type, which is the quality of the most
+/// specific media range that matches it.
+fn media_type_quality(accept: &[Mime], media_type: &str) -> f32 {
+    let (ty, subtype) = media_type.split_once('/').unwrap_or((media_type, ""));
+    accept
+        .iter()
+        .filter_map(|range| {
+            let specificity = match (range.type_().as_str(), range.subtype().as_str()) {
+                (t, s) if t == ty && s == subtype => 2,
+                (t, "*") if t == ty => 1,
+                ("*", "*") => 0,
+                _ => return None,
+            };
+            Some((specificity, quality(range)))
+        })
+        .max_by_key(|(specificity, _)| *specificity)
+        .map(|(_, q)| q)
+        .unwrap_or_default()
+}
+
+fn negotiate(accept: &[Mime]) -> Option<Format> {
+    if accept.is_empty() {
+        return Some(Format::Json);
+    }
+
+    let mut best = None;
+    for format in Format::ALL {
+        let q = format
+            .media_types()
+            .iter()
+            .map(|media_type| media_type_quality(accept, media_type))
+            .fold(0.0, f32::max);
+        if q > 0.0 && best.is_none_or(|(_, best_q)| q > best_q) {
+            best = Some((*format, q));
+        }
+    }
+    best.map(|(format, _)| format)
+}
+
+/// A response type that serializes the value in the representation that best
+/// matches the `Accept` header of the request.
+///
+/// The supported representations are `application/json`, and
+/// `application/yaml` and `application/xml` when the `yaml` and `xml`
+/// features are enabled. If the request has no `Accept` header, JSON is used.
+/// If none of them are acceptable, it returns `406 Not Acceptable` with the
+/// list of supported media types.
+///
+/// The response always contains the `Vary: Accept` header.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     Route, get, handler,
+///     http::{StatusCode, header},
+///     test::TestClient,
+///     web::{Accept, Negotiated},
+/// };
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct User {
+///     name: String,
+/// }
+///
+/// #[handler]
+/// fn index(accept: Accept) -> Negotiated<User> {
+///     Negotiated::new(
+///         &accept,
+///         User {
+///             name: "sunli".to_string(),
+///         },
+///     )
+/// }
+///
+/// let cli = TestClient::new(Route::new().at("/", get(index)));
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli
+///     .get("/")
+///     .header(header::ACCEPT, "text/html, application/json;q=0.9")
+///     .send()
+///     .await;
+/// resp.assert_status_is_ok();
+/// resp.assert_header(header::VARY, "accept");
+/// resp.assert_json(serde_json::json!({ "name": "sunli" }))
+///     .await;
+///
+/// let resp = cli.get("/").header(header::ACCEPT, "text/html").send().await;
+/// resp.assert_status(StatusCode::NOT_ACCEPTABLE);
+/// # });
+/// ```
+#[derive(Debug, Clone)]
+pub struct Negotiated<T> {
+    value: T,
+    format: Option<Format>,
+}
+
+impl<T> Negotiated<T> {
+    /// Create a response that selects the representation of `value` from the
+    /// `Accept` header.
+    pub fn new(accept: &Accept, value: T) -> Self {
+        Self {
+            value,
+            format: negotiate(&accept.0),
+        }
+    }
+
+    /// Returns `true` if any of the supported representations is acceptable.
+    pub fn is_acceptable(&self) -> bool {
+        self.format.is_some()
+    }
+
+    /// Returns the supported media types, in order of preference.
+    pub fn supported_types() -> Vec<&'static str> {
+        Format::ALL
+            .iter()
+            .map(|format| format.media_types()[0])
+            .collect()
+    }
+
+    /// Consumes this object and returns the value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T: Serialize + Send> IntoResponse for Negotiated<T> {
+    fn into_response(self) -> Response {
+        let mut resp = match self.format {
+            Some(Format::Json) => Json(self.value).into_response(),
+            #[cfg(feature = "yaml")]
+            Some(Format::Yaml) => crate::web::Yaml(self.value).into_response(),
+            #[cfg(feature = "xml")]
+            Some(Format::Xml) => crate::web::Xml(self.value).into_response(),
+            None => Response::builder()
+                .status(StatusCode::NOT_ACCEPTABLE)
+                .body(format!(
+                    "not acceptable, supported types: {}",
+                    Self::supported_types().join(", ")
+                )),
+        };
+        resp.headers_mut()
+            .append(header::VARY, HeaderValue::from_static("accept"));
+        resp
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+
+    use super::*;
+    use crate::{handler, test::TestClient};
+
+    #[derive(Serialize)]
+    struct Resource {
+        value: i32,
+    }
+
+    #[test]
+    fn test_negotiate() {
+        let parse = |s: &str| {
+            s.split(',')
+                .map(|item| item.trim().parse::<Mime>().unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(negotiate(&[]), Some(Format::Json));
+        assert_eq!(negotiate(&parse("*/*")), Some(Format::Json));
+        assert_eq!(negotiate(&parse("application/*")), Some(Format::Json));
+        assert_eq!(negotiate(&parse("text/html")), None);
+        assert_eq!(negotiate(&parse("application/json;q=0")), None);
+        // JSON is excluded, so the next supported format in order of preference
+        // is used
+        #[cfg(feature = "yaml")]
+        let expected = Some(Format::Yaml);
+        #[cfg(all(not(feature = "yaml"), feature = "xml"))]
+        let expected = Some(Format::Xml);
+        #[cfg(not(any(feature = "yaml", feature = "xml")))]
+        let expected = None;
+        assert_eq!(negotiate(&parse("*/*, application/json;q=0")), expected);
+
+        #[cfg(feature = "xml")]
+        {
+            assert_eq!(
+                negotiate(&parse("application/json;q=0.5, text/xml")),
+                Some(Format::Xml)
+            );
+            assert_eq!(
+                negotiate(&parse("application/json, application/xml")),
+                Some(Format::Json)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn negotiated_response() {
+        #[handler(internal)]
+        fn index(accept: Accept) -> Negotiated<Resource> {
+            Negotiated::new(&accept, Resource { value: 10 })
+        }
+
+        let cli = TestClient::new(index);
+
+        let resp = cli.get("/").send().await;
+        resp.assert_status_is_ok();
+        resp.assert_content_type("application/json; charset=utf-8");
+        resp.assert_header(header::VARY, "accept");
+        resp.assert_json(serde_json::json!({ "value": 10 })).await;
+
+        let resp = cli
+            .get("/")
+            .header(header::ACCEPT, "text/html")
+            .send()
+            .await;
+        resp.assert_status(StatusCode::NOT_ACCEPTABLE);
+        resp.assert_header(header::VARY, "accept");
+        resp.assert_text(format!(
+            "not acceptable, supported types: {}",
+            Negotiated::<Resource>::supported_types().join(", ")
+        ))
+        .await;
+
+        #[cfg(feature = "yaml")]
+        {
+            let resp = cli
+                .get("/")
+                .header(header::ACCEPT, "application/json;q=0.5, text/yaml")
+                .send()
+                .await;
+            resp.assert_status_is_ok();
+            resp.assert_content_type("application/yaml; charset=utf-8");
+            resp.assert_text("value: 10\n").await;
+        }
+    }
+}
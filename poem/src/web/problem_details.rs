@@ -0,0 +1,195 @@
+use std::fmt::{self, Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{
+    IntoResponse, Response,
+    error::ResponseError,
+    http::{StatusCode, header},
+};
+
+const PROBLEM_JSON: &str = "application/problem+json";
+
+fn about_blank() -> String {
+    "about:blank".to_string()
+}
+
+/// A problem details object as defined in
+/// [RFC 9457](https://www.rfc-editor.org/rfc/rfc9457), which is rendered as
+/// `application/problem+json`.
+///
+/// It can be used as a response, or returned as an error. The
+/// [`ProblemJson`](crate::middleware::ProblemJson) middleware renders all
+/// the errors as problem details.
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     Result, handler,
+///     http::StatusCode,
+///     test::TestClient,
+///     web::{Path, ProblemDetails},
+/// };
+///
+/// #[handler]
+/// fn withdraw(Path(amount): Path<u32>) -> Result<()> {
+///     Err(ProblemDetails::new(StatusCode::FORBIDDEN)
+///         .ty("https://example.com/probs/out-of-credit")
+///         .title("You do not have enough credit.")
+///         .detail(format!("Your balance is 30, but that costs {amount}."))
+///         .extension("balance", 30)
+///         .into())
+/// }
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = TestClient::new(poem::Route::new().at("/withdraw/:amount", withdraw))
+///     .get("/withdraw/50")
+///     .send()
+///     .await;
+/// resp.assert_status(StatusCode::FORBIDDEN);
+/// resp.assert_content_type("application/problem+json");
+/// resp.assert_json(serde_json::json!({
+///     "type": "https://example.com/probs/out-of-credit",
+///     "title": "You do not have enough credit.",
+///     "status": 403,
+///     "detail": "Your balance is 30, but that costs 50.",
+///     "balance": 30,
+/// }))
+/// .await;
+/// # });
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProblemDetails {
+    /// A URI reference that identifies the problem type.
+    #[serde(rename = "type", default = "about_blank")]
+    pub ty: String,
+
+    /// A short, human-readable summary of the problem type.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+
+    /// The HTTP status code.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+
+    /// A human-readable explanation specific to this occurrence of the
+    /// problem.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+
+    /// A URI reference that identifies the specific occurrence of the
+    /// problem.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+
+    /// Extension members.
+    #[serde(flatten)]
+    pub extensions: Map<String, Value>,
+}
+
+impl ProblemDetails {
+    /// Create a problem details object with the specified status, whose title
+    /// is the reason phrase of the status.
+    pub fn new(status: StatusCode) -> Self {
+        Self {
+            ty: about_blank(),
+            title: status.canonical_reason().map(ToString::to_string),
+            status: Some(status.as_u16()),
+            detail: None,
+            instance: None,
+            extensions: Map::new(),
+        }
+    }
+
+    /// Sets the problem type URI.
+    #[must_use]
+    pub fn ty(self, ty: impl Into<String>) -> Self {
+        Self {
+            ty: ty.into(),
+            ..self
+        }
+    }
+
+    /// Sets the title.
+    #[must_use]
+    pub fn title(self, title: impl Into<String>) -> Self {
+        Self {
+            title: Some(title.into()),
+            ..self
+        }
+    }
+
+    /// Sets the detail.
+    #[must_use]
+    pub fn detail(self, detail: impl Into<String>) -> Self {
+        Self {
+            detail: Some(detail.into()),
+            ..self
+        }
+    }
+
+    /// Sets the instance URI.
+    #[must_use]
+    pub fn instance(self, instance: impl Into<String>) -> Self {
+        Self {
+            instance: Some(instance.into()),
+            ..self
+        }
+    }
+
+    /// Adds an extension member.
+    ///
+    /// Values that fail to serialize are ignored.
+    #[must_use]
+    pub fn extension(mut self, name: impl Into<String>, value: impl Serialize) -> Self {
+        if let Ok(value) = serde_json::to_value(value) {
+            self.extensions.insert(name.into(), value);
+        }
+        self
+    }
+
+    fn status_code(&self) -> StatusCode {
+        self.status
+            .and_then(|status| StatusCode::from_u16(status).ok())
+            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+}
+
+impl Display for ProblemDetails {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match (&self.title, &self.detail) {
+            (Some(title), Some(detail)) => write!(f, "{title}: {detail}"),
+            (Some(msg), None) | (None, Some(msg)) => write!(f, "{msg}"),
+            (None, None) => write!(f, "{}", self.ty),
+        }
+    }
+}
+
+impl std::error::Error for ProblemDetails {}
+
+impl ResponseError for ProblemDetails {
+    fn status(&self) -> StatusCode {
+        self.status_code()
+    }
+
+    fn as_response(&self) -> Response {
+        self.clone().into_response()
+    }
+}
+
+impl IntoResponse for ProblemDetails {
+    fn into_response(self) -> Response {
+        let status = self.status_code();
+        match serde_json::to_vec(&self) {
+            Ok(data) => Response::builder()
+                .status(status)
+                .header(header::CONTENT_TYPE, PROBLEM_JSON)
+                .body(data),
+            Err(err) => Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(err.to_string()),
+        }
+    }
+}
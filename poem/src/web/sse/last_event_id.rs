@@ -0,0 +1,52 @@
+use crate::{FromRequest, Request, RequestBody, Result};
+
+/// An extractor for the `Last-Event-ID` header, which a client sends when it
+/// reconnects to an event stream.
+///
+/// The value is the id of the last event received by the client, or `None` for
+/// a new connection.
+///
+/// # Example
+///
+/// ```
+/// use futures_util::stream;
+/// use poem::{
+///     handler,
+///     http::StatusCode,
+///     test::TestClient,
+///     web::sse::{Event, LastEventId, SSE},
+/// };
+///
+/// #[handler]
+/// fn index(LastEventId(last_event_id): LastEventId) -> SSE {
+///     let start = last_event_id
+///         .and_then(|id| id.parse::<u32>().ok())
+///         .map(|id| id + 1)
+///         .unwrap_or_default();
+///     SSE::new(stream::iter(
+///         (start..3).map(|id| Event::message(format!("event {id}")).id(id.to_string())),
+///     ))
+/// }
+///
+/// let cli = TestClient::new(index);
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let resp = cli.get("/").header("Last-Event-ID", "1").send().await;
+/// resp.assert_status_is_ok();
+/// resp.assert_text("id: 2\ndata: event 2\n\n").await;
+/// # });
+/// ```
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct LastEventId(pub Option<String>);
+
+impl<'a> FromRequest<'a> for LastEventId {
+    async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
+        Ok(Self(
+            req.headers()
+                .get("last-event-id")
+                .and_then(|value| value.to_str().ok())
+                .filter(|value| !value.is_empty())
+                .map(ToString::to_string),
+        ))
+    }
+}
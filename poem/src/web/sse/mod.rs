@@ -1,9 +1,13 @@
 //! Server-Sent Events (SSE) types.
 
 mod event;
+mod last_event_id;
+mod replay;
 mod response;
 
 pub use event::Event;
+pub use last_event_id::LastEventId;
+pub use replay::ReplayBuffer;
 pub use response::SSE;
 
 #[cfg(test)]
@@ -71,4 +75,63 @@ data: 5
             s = now;
         }
     }
+
+    #[tokio::test]
+    async fn retry() {
+        let sse = SSE::new(futures_util::stream::iter(vec![Event::message("a")]))
+            .retry(Duration::from_secs(3));
+        let data = sse.into_response().into_body().into_string().await.unwrap();
+        assert_eq!(data, "retry: 3000\n\ndata: a\n\n");
+    }
+
+    #[tokio::test]
+    async fn last_event_id() {
+        use crate::{FromRequest, Request};
+
+        let req = Request::builder().header("Last-Event-ID", "42").finish();
+        assert_eq!(
+            LastEventId::from_request_without_body(&req).await.unwrap(),
+            LastEventId(Some("42".to_string()))
+        );
+
+        let req = Request::builder().finish();
+        assert_eq!(
+            LastEventId::from_request_without_body(&req).await.unwrap(),
+            LastEventId(None)
+        );
+    }
+
+    #[tokio::test]
+    async fn replay_buffer() {
+        use futures_util::StreamExt;
+
+        let buffer = ReplayBuffer::new(3);
+        for value in ["a", "b", "c", "d"] {
+            buffer.send(Event::message(value));
+        }
+        assert_eq!(
+            buffer.send(Event::message("e").id("x")).as_deref(),
+            Some("x")
+        );
+
+        // replays the missed events, then resumes the live stream
+        let mut stream = buffer.subscribe(Some("3")).boxed();
+        buffer.send(Event::message("f"));
+        assert_eq!(stream.next().await, Some(Event::message("d").id("4")));
+        assert_eq!(stream.next().await, Some(Event::message("e").id("x")));
+        assert_eq!(stream.next().await, Some(Event::message("f").id("5")));
+
+        // unknown ids replay the whole buffer
+        assert_eq!(
+            buffer.events_after(Some("1")),
+            vec![
+                Event::message("d").id("4"),
+                Event::message("e").id("x"),
+                Event::message("f").id("5"),
+            ]
+        );
+
+        // new connections only receive live events
+        assert!(buffer.events_after(None).is_empty());
+    }
 }
@@ -0,0 +1,163 @@
+use std::{collections::VecDeque, sync::Arc};
+
+use futures_util::{Stream, StreamExt};
+use parking_lot::Mutex;
+use tokio::sync::broadcast;
+
+use super::{Event, SSE};
+
+struct Inner {
+    events: VecDeque<Event>,
+    next_id: u64,
+}
+
+/// A bounded in-memory buffer of recent events, which lets reconnecting
+/// clients receive the events they missed before the live stream resumes.
+///
+/// Events are sent with [`ReplayBuffer::send`], which records them and
+/// delivers them to the current subscribers. [`ReplayBuffer::subscribe`]
+/// first replays the buffered events that follow the [`LastEventId`] sent by
+/// the client, then yields the live events.
+///
+/// If a subscriber falls behind by more than the capacity of the buffer, its
+/// stream ends. The client then reconnects and catches up from the buffer.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use poem::{
+///     EndpointExt, Route, get, handler,
+///     web::{
+///         Data,
+///         sse::{LastEventId, ReplayBuffer, SSE},
+///     },
+/// };
+///
+/// #[handler]
+/// fn events(last_event_id: LastEventId, buffer: Data<&ReplayBuffer>) -> SSE {
+///     buffer
+///         .sse(last_event_id)
+///         .retry(Duration::from_secs(3))
+///         .keep_alive(Duration::from_secs(15))
+/// }
+///
+/// let app = Route::new()
+///     .at("/events", get(events))
+///     .data(ReplayBuffer::new(100));
+/// ```
+///
+/// [`LastEventId`]: super::LastEventId
+#[derive(Clone)]
+pub struct ReplayBuffer {
+    capacity: usize,
+    inner: Arc<Mutex<Inner>>,
+    sender: broadcast::Sender<Event>,
+}
+
+impl ReplayBuffer {
+    /// Create a buffer which keeps the last `capacity` events.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is `0`.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity must be greater than 0");
+        Self {
+            capacity,
+            inner: Arc::new(Mutex::new(Inner {
+                events: VecDeque::with_capacity(capacity),
+                next_id: 0,
+            })),
+            sender: broadcast::channel(capacity).0,
+        }
+    }
+
+    /// Record an event and deliver it to the subscribers.
+    ///
+    /// Messages without an id are given a sequential one, which is returned.
+    /// [`Event::Retry`] events are delivered without being recorded.
+    pub fn send(&self, event: Event) -> Option<String> {
+        let mut inner = self.inner.lock();
+
+        let event = match event {
+            Event::Message { id, event, data } => {
+                let id = if id.is_empty() {
+                    inner.next_id += 1;
+                    inner.next_id.to_string()
+                } else {
+                    id
+                };
+                Event::Message { id, event, data }
+            }
+            event @ Event::Retry { .. } => {
+                let _ = self.sender.send(event);
+                return None;
+            }
+        };
+
+        if inner.events.len() == self.capacity {
+            inner.events.pop_front();
+        }
+        inner.events.push_back(event.clone());
+        let id = event_id(&event).map(ToString::to_string);
+        let _ = self.sender.send(event);
+        id
+    }
+
+    /// Returns the buffered events that follow the event with the specified
+    /// id.
+    ///
+    /// If the id is not in the buffer, because it is too old or unknown, all
+    /// the buffered events are returned.
+    pub fn events_after(&self, last_event_id: Option<&str>) -> Vec<Event> {
+        Self::replay(&self.inner.lock(), last_event_id)
+    }
+
+    fn replay(inner: &Inner, last_event_id: Option<&str>) -> Vec<Event> {
+        let Some(last_event_id) = last_event_id else {
+            return Vec::new();
+        };
+        let start = inner
+            .events
+            .iter()
+            .rposition(|event| event_id(event) == Some(last_event_id))
+            .map(|idx| idx + 1)
+            .unwrap_or_default();
+        inner.events.range(start..).cloned().collect()
+    }
+
+    /// Returns a stream of the missed events followed by the live events.
+    ///
+    /// New connections, with no `Last-Event-ID`, only receive the live
+    /// events.
+    pub fn subscribe(&self, last_event_id: Option<&str>) -> impl Stream<Item = Event> + use<> {
+        // subscribe while holding the lock, so that no event is missed or
+        // delivered twice between the replay and the live stream
+        let inner = self.inner.lock();
+        let replay = Self::replay(&inner, last_event_id);
+        let receiver = self.sender.subscribe();
+        drop(inner);
+
+        let live = futures_util::stream::unfold(receiver, |mut receiver| async move {
+            // a lagging subscriber ends its stream, and resumes from the
+            // buffer when it reconnects
+            let event = receiver.recv().await.ok()?;
+            Some((event, receiver))
+        });
+        futures_util::stream::iter(replay).chain(live)
+    }
+
+    /// Create an SSE response for a client, which replays the missed events.
+    pub fn sse(&self, last_event_id: super::LastEventId) -> SSE {
+        SSE::new(self.subscribe(last_event_id.0.as_deref()))
+    }
+}
+
+fn event_id(event: &Event) -> Option<&str> {
+    match event {
+        Event::Message { id, .. } if !id.is_empty() => Some(id),
+        _ => None,
+    }
+}
@@ -0,0 +1,327 @@
+use std::{
+    borrow::Cow,
+    io::{Error as IoError, ErrorKind, Result as IoResult},
+};
+
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader},
+    net::TcpStream,
+};
+use tokio_tungstenite::tungstenite::{handshake::client::generate_key, protocol::Role};
+
+use super::{DeflateConfig, WebSocketConfig, WebSocketStream, stream::WebSocketIo, utils::sign};
+use crate::{
+    Body, Response,
+    http::{
+        HeaderMap, StatusCode, Uri,
+        header::{self, HeaderName, HeaderValue},
+    },
+};
+
+const MAX_RESPONSE_HEAD_SIZE: usize = 64 * 1024;
+
+/// A WebSocket client.
+///
+/// The client performs the opening handshake and returns a
+/// [`WebSocketStream`], which yields the same [`Message`](super::Message)
+/// type as the server side.
+///
+/// # Example
+///
+/// ```no_run
+/// use futures_util::{SinkExt, StreamExt};
+/// use poem::web::websocket::{DeflateConfig, Message, WebSocketClient};
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let (mut stream, _resp) = WebSocketClient::new()
+///     .protocols(["graphql-transport-ws"])
+///     .deflate(DeflateConfig::new())
+///     .connect("ws://127.0.0.1:3000/ws")
+///     .await
+///     .unwrap();
+///
+/// stream.send(Message::text("hello")).await.unwrap();
+/// let reply = stream.next().await;
+/// # });
+/// ```
+#[derive(Default)]
+pub struct WebSocketClient {
+    headers: HeaderMap,
+    protocols: Vec<Cow<'static, str>>,
+    config: Option<WebSocketConfig>,
+    deflate: Option<DeflateConfig>,
+}
+
+impl WebSocketClient {
+    /// Create a new `WebSocketClient`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Appends a header to the handshake request.
+    #[must_use]
+    pub fn header<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: TryInto<HeaderName>,
+        V: TryInto<HeaderValue>,
+    {
+        let key = key.try_into();
+        let value = value.try_into();
+        if let (Ok(key), Ok(value)) = (key, value) {
+            self.headers.append(key, value);
+        }
+        self
+    }
+
+    /// Sets the subprotocols requested with the `Sec-WebSocket-Protocol`
+    /// header.
+    #[must_use]
+    pub fn protocols<I>(mut self, protocols: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<Cow<'static, str>>,
+    {
+        self.protocols = protocols.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets the WebSocket configuration.
+    #[must_use]
+    pub fn config(self, config: WebSocketConfig) -> Self {
+        Self {
+            config: Some(config),
+            ..self
+        }
+    }
+
+    /// Offer the `permessage-deflate` extension to the server.
+    #[must_use]
+    pub fn deflate(self, config: DeflateConfig) -> Self {
+        Self {
+            deflate: Some(config),
+            ..self
+        }
+    }
+
+    /// Connect to a `ws://` URI.
+    ///
+    /// Returns the stream and the handshake response of the server.
+    pub async fn connect(self, uri: impl AsRef<str>) -> IoResult<(WebSocketStream, Response)> {
+        let uri: Uri = uri
+            .as_ref()
+            .parse()
+            .map_err(|err| IoError::new(ErrorKind::InvalidInput, err))?;
+        if uri.scheme_str() != Some("ws") {
+            return Err(IoError::new(
+                ErrorKind::InvalidInput,
+                "only `ws://` URIs are supported, use `connect_with_io` for other transports",
+            ));
+        }
+
+        let host = uri
+            .host()
+            .ok_or_else(|| IoError::new(ErrorKind::InvalidInput, "missing host"))?;
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        let io = TcpStream::connect((host, uri.port_u16().unwrap_or(80))).await?;
+        self.connect_with_io(uri, io).await
+    }
+
+    /// Performs the handshake over an established connection.
+    ///
+    /// This can be used to connect through transports that are not supported
+    /// by [`WebSocketClient::connect`], such as a TLS stream.
+    pub async fn connect_with_io<T>(self, uri: Uri, io: T) -> IoResult<(WebSocketStream, Response)>
+    where
+        T: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+    {
+        let mut headers = HeaderMap::new();
+        if let Some(authority) = uri.authority() {
+            headers.insert(
+                header::HOST,
+                HeaderValue::from_str(authority.as_str())
+                    .map_err(|err| IoError::new(ErrorKind::InvalidInput, err))?,
+            );
+        }
+        let key = self.prepare_headers(&mut headers);
+
+        let mut head = format!(
+            "GET {} HTTP/1.1\r\n",
+            uri.path_and_query().map(|p| p.as_str()).unwrap_or("/")
+        )
+        .into_bytes();
+        for (name, value) in &headers {
+            head.extend_from_slice(name.as_str().as_bytes());
+            head.extend_from_slice(b": ");
+            head.extend_from_slice(value.as_bytes());
+            head.extend_from_slice(b"\r\n");
+        }
+        head.extend_from_slice(b"\r\n");
+
+        let mut io = BufReader::new(io);
+        io.get_mut().write_all(&head).await?;
+        io.get_mut().flush().await?;
+
+        let (status, headers) = read_response_head(&mut io).await?;
+        let stream = self.finish(&key, status, &headers, io).await?;
+
+        let mut resp = Response::builder().status(status).body(Body::empty());
+        *resp.headers_mut() = headers;
+        Ok((stream, resp))
+    }
+
+    /// Adds the handshake headers to the request, keeping any value that has
+    /// already been set, and returns the `Sec-WebSocket-Key`.
+    pub(crate) fn prepare_headers(&self, headers: &mut HeaderMap) -> HeaderValue {
+        headers.extend(self.headers.clone());
+        headers.insert(header::CONNECTION, HeaderValue::from_static("upgrade"));
+        headers.insert(header::UPGRADE, HeaderValue::from_static("websocket"));
+        headers.insert(
+            header::SEC_WEBSOCKET_VERSION,
+            HeaderValue::from_static("13"),
+        );
+
+        if !self.protocols.is_empty() && !headers.contains_key(header::SEC_WEBSOCKET_PROTOCOL) {
+            if let Ok(value) = HeaderValue::try_from(self.protocols.join(", ")) {
+                headers.insert(header::SEC_WEBSOCKET_PROTOCOL, value);
+            }
+        }
+        if let Some(deflate) = &self.deflate {
+            headers
+                .entry(header::SEC_WEBSOCKET_EXTENSIONS)
+                .or_insert_with(|| deflate.offer());
+        }
+
+        headers
+            .entry(header::SEC_WEBSOCKET_KEY)
+            .or_insert_with(|| HeaderValue::try_from(generate_key()).unwrap())
+            .clone()
+    }
+
+    /// Validates the handshake response and creates the stream.
+    pub(crate) async fn finish(
+        &self,
+        key: &HeaderValue,
+        status: StatusCode,
+        headers: &HeaderMap,
+        io: impl WebSocketIo,
+    ) -> IoResult<WebSocketStream> {
+        if status != StatusCode::SWITCHING_PROTOCOLS {
+            return Err(handshake_error(format!("unexpected status code: {status}")));
+        }
+
+        let has_token = |name: HeaderName, token: &str| {
+            headers
+                .get_all(name)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .flat_map(|value| value.split(','))
+                .any(|value| value.trim().eq_ignore_ascii_case(token))
+        };
+        if !has_token(header::UPGRADE, "websocket") || !has_token(header::CONNECTION, "upgrade") {
+            return Err(handshake_error("missing upgrade headers"));
+        }
+        if headers.get(header::SEC_WEBSOCKET_ACCEPT) != Some(&sign(key.as_bytes())) {
+            return Err(handshake_error("invalid `Sec-WebSocket-Accept` header"));
+        }
+
+        if let Some(protocol) = headers.get(header::SEC_WEBSOCKET_PROTOCOL) {
+            if !self
+                .protocols
+                .iter()
+                .any(|p| p.as_bytes() == protocol.as_bytes())
+            {
+                return Err(handshake_error(
+                    "unexpected `Sec-WebSocket-Protocol` header",
+                ));
+            }
+        }
+
+        let deflate = match headers.get(header::SEC_WEBSOCKET_EXTENSIONS) {
+            Some(value) => {
+                let params = self
+                    .deflate
+                    .as_ref()
+                    .zip(value.to_str().ok())
+                    .and_then(|(config, extensions)| config.accept_response(extensions));
+                if params.is_none() {
+                    return Err(handshake_error(
+                        "unexpected `Sec-WebSocket-Extensions` header",
+                    ));
+                }
+                params
+            }
+            None => None,
+        };
+
+        Ok(WebSocketStream::from_raw_socket(io, Role::Client, self.config, deflate).await)
+    }
+}
+
+/// Connect to a `ws://` URI with the default configuration.
+///
+/// See [`WebSocketClient`] for more options.
+pub async fn connect(uri: impl AsRef<str>) -> IoResult<WebSocketStream> {
+    WebSocketClient::new()
+        .connect(uri)
+        .await
+        .map(|(stream, _)| stream)
+}
+
+fn handshake_error(msg: impl Into<String>) -> IoError {
+    IoError::new(
+        ErrorKind::InvalidData,
+        format!("websocket handshake failed: {}", msg.into()),
+    )
+}
+
+async fn read_line<T: WebSocketIo>(
+    io: &mut BufReader<T>,
+    line: &mut String,
+    size: &mut usize,
+) -> IoResult<()> {
+    line.clear();
+    let n = io.read_line(line).await?;
+    if n == 0 {
+        return Err(IoError::from(ErrorKind::UnexpectedEof));
+    }
+    *size += n;
+    if *size > MAX_RESPONSE_HEAD_SIZE {
+        return Err(handshake_error("response head is too large"));
+    }
+    Ok(())
+}
+
+async fn read_response_head<T: WebSocketIo>(
+    io: &mut BufReader<T>,
+) -> IoResult<(StatusCode, HeaderMap)> {
+    let mut size = 0;
+    let mut line = String::new();
+
+    read_line(io, &mut line, &mut size).await?;
+    let status = line
+        .split_ascii_whitespace()
+        .nth(1)
+        .and_then(|status| status.parse::<u16>().ok())
+        .and_then(|status| StatusCode::from_u16(status).ok())
+        .ok_or_else(|| handshake_error("invalid status line"))?;
+
+    let mut headers = HeaderMap::new();
+    loop {
+        read_line(io, &mut line, &mut size).await?;
+        let line = line.trim_end_matches(['\r', '\n']);
+        if line.is_empty() {
+            break;
+        }
+
+        let (name, value) = line
+            .split_once(':')
+            .ok_or_else(|| handshake_error("invalid header"))?;
+        let name = HeaderName::from_bytes(name.trim().as_bytes())
+            .map_err(|_| handshake_error("invalid header name"))?;
+        let value = HeaderValue::from_str(value.trim())
+            .map_err(|_| handshake_error("invalid header value"))?;
+        headers.append(name, value);
+    }
+
+    Ok((status, headers))
+}
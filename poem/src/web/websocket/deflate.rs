@@ -0,0 +1,903 @@
+use std::{
+    fmt::Write,
+    io::{Error as IoError, ErrorKind, Result as IoResult},
+    pin::Pin,
+    task::{Context, Poll, ready},
+};
+
+use bytes::{Buf, BufMut, BytesMut};
+use flate2::{Compress, Compression, Decompress, FlushCompress, FlushDecompress, Status};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio_util::io::poll_read_buf;
+
+use crate::http::header::HeaderValue;
+
+const EXTENSION_NAME: &str = "permessage-deflate";
+const DEFLATE_TRAILER: [u8; 4] = [0x00, 0x00, 0xff, 0xff];
+const MIN_WINDOW_BITS: u8 = 9;
+const MAX_WINDOW_BITS: u8 = 15;
+/// The max number of bytes reserved at once for a partially received frame,
+/// so that the buffer grows with the received data instead of the length
+/// claimed by the frame header.
+const MAX_RESERVE: usize = 64 * 1024;
+
+/// Configuration of the `permessage-deflate` extension
+/// ([RFC 7692](https://datatracker.ietf.org/doc/html/rfc7692)).
+///
+/// When enabled with [`WebSocket::deflate`](super::WebSocket::deflate), the
+/// extension is negotiated through the `Sec-WebSocket-Extensions` header and
+/// text and binary messages are compressed and decompressed transparently by
+/// [`WebSocketStream`](super::WebSocketStream).
+///
+/// # Example
+///
+/// ```
+/// use poem::{
+///     IntoResponse, Route, get, handler,
+///     web::websocket::{DeflateConfig, WebSocket},
+/// };
+///
+/// #[handler]
+/// async fn index(ws: WebSocket) -> impl IntoResponse {
+///     ws.deflate(
+///         DeflateConfig::new()
+///             .server_max_window_bits(12)
+///             .server_no_context_takeover(true),
+///     )
+///     .on_upgrade(|socket| async move {
+///         // ...
+///     })
+/// }
+///
+/// let app = Route::new().at("/", get(index));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeflateConfig {
+    compression_level: u32,
+    server_max_window_bits: u8,
+    client_max_window_bits: u8,
+    server_no_context_takeover: bool,
+    client_no_context_takeover: bool,
+}
+
+impl Default for DeflateConfig {
+    fn default() -> Self {
+        Self {
+            compression_level: Compression::default().level(),
+            server_max_window_bits: MAX_WINDOW_BITS,
+            client_max_window_bits: MAX_WINDOW_BITS,
+            server_no_context_takeover: false,
+            client_no_context_takeover: false,
+        }
+    }
+}
+
+impl DeflateConfig {
+    /// Create a new `DeflateConfig` with the default parameters.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets the compression level, in the range `0..=9`. Default is `6`.
+    #[must_use]
+    pub fn compression_level(self, level: u32) -> Self {
+        Self {
+            compression_level: level.min(9),
+            ..self
+        }
+    }
+
+    /// Sets the maximum size of the LZ77 sliding window (as a base-2
+    /// logarithm) the server uses to compress messages, in the range
+    /// `9..=15`. Default is `15`.
+    ///
+    /// Smaller windows use less memory per connection at the cost of a worse
+    /// compression ratio.
+    #[must_use]
+    pub fn server_max_window_bits(self, bits: u8) -> Self {
+        Self {
+            server_max_window_bits: bits.clamp(MIN_WINDOW_BITS, MAX_WINDOW_BITS),
+            ..self
+        }
+    }
+
+    /// Sets the maximum size of the LZ77 sliding window (as a base-2
+    /// logarithm) the client is asked to use, in the range `9..=15`. Default
+    /// is `15`.
+    ///
+    /// The limit is only sent to clients that announce support for the
+    /// `client_max_window_bits` parameter.
+    #[must_use]
+    pub fn client_max_window_bits(self, bits: u8) -> Self {
+        Self {
+            client_max_window_bits: bits.clamp(MIN_WINDOW_BITS, MAX_WINDOW_BITS),
+            ..self
+        }
+    }
+
+    /// If `true`, the server resets its compression context after each
+    /// message. Default is `false`.
+    #[must_use]
+    pub fn server_no_context_takeover(self, value: bool) -> Self {
+        Self {
+            server_no_context_takeover: value,
+            ..self
+        }
+    }
+
+    /// If `true`, the client is asked to reset its compression context after
+    /// each message. Default is `false`.
+    #[must_use]
+    pub fn client_no_context_takeover(self, value: bool) -> Self {
+        Self {
+            client_no_context_takeover: value,
+            ..self
+        }
+    }
+
+    /// Select the first acceptable `permessage-deflate` offer from the
+    /// `Sec-WebSocket-Extensions` request header.
+    pub(crate) fn negotiate(&self, extensions: &str) -> Option<DeflateParams> {
+        extensions
+            .split(',')
+            .find_map(|offer| self.accept_offer(offer))
+    }
+
+    fn accept_offer(&self, offer: &str) -> Option<DeflateParams> {
+        let mut parts = offer.split(';').map(str::trim);
+        if !parts.next()?.eq_ignore_ascii_case(EXTENSION_NAME) {
+            return None;
+        }
+
+        let mut params = DeflateParams {
+            compression_level: self.compression_level,
+            server_max_window_bits: self.server_max_window_bits,
+            client_max_window_bits: MAX_WINDOW_BITS,
+            server_no_context_takeover: self.server_no_context_takeover,
+            client_no_context_takeover: self.client_no_context_takeover,
+        };
+
+        for param in parts {
+            let (name, value) = match param.split_once('=') {
+                Some((name, value)) => (name.trim(), Some(value.trim().trim_matches('"'))),
+                None => (param, None),
+            };
+
+            match (name.to_ascii_lowercase().as_str(), value) {
+                ("server_no_context_takeover", None) => {
+                    params.server_no_context_takeover = true;
+                }
+                ("client_no_context_takeover", None) => {
+                    params.client_no_context_takeover = true;
+                }
+                ("server_max_window_bits", Some(value)) => {
+                    let bits = parse_window_bits(value)?;
+                    if bits < MIN_WINDOW_BITS {
+                        // zlib cannot produce raw deflate streams with a
+                        // 256-byte window.
+                        return None;
+                    }
+                    params.server_max_window_bits = params.server_max_window_bits.min(bits);
+                }
+                ("client_max_window_bits", value) => {
+                    let bits = match value {
+                        Some(value) => parse_window_bits(value)?,
+                        None => MAX_WINDOW_BITS,
+                    };
+                    params.client_max_window_bits = self.client_max_window_bits.min(bits);
+                }
+                _ => return None,
+            }
+        }
+
+        Some(params)
+    }
+}
+
+impl DeflateConfig {
+    /// Returns the `Sec-WebSocket-Extensions` request header offering the
+    /// extension to a server.
+    pub(crate) fn offer(&self) -> HeaderValue {
+        let mut value = EXTENSION_NAME.to_string();
+        if self.server_no_context_takeover {
+            value.push_str("; server_no_context_takeover");
+        }
+        if self.client_no_context_takeover {
+            value.push_str("; client_no_context_takeover");
+        }
+        if self.server_max_window_bits < MAX_WINDOW_BITS {
+            let _ = write!(
+                value,
+                "; server_max_window_bits={}",
+                self.server_max_window_bits
+            );
+        }
+        if self.client_max_window_bits < MAX_WINDOW_BITS {
+            let _ = write!(
+                value,
+                "; client_max_window_bits={}",
+                self.client_max_window_bits
+            );
+        } else {
+            value.push_str("; client_max_window_bits");
+        }
+        HeaderValue::try_from(value).unwrap()
+    }
+
+    /// Parses a `permessage-deflate` offer into the configuration of the
+    /// client which sends it, or returns `None` if the offer has parameters
+    /// that the client can not honour.
+    pub(crate) fn from_offer(offer: &str) -> Option<Self> {
+        let mut parts = offer.split(';').map(str::trim);
+        if !parts.next()?.eq_ignore_ascii_case(EXTENSION_NAME) {
+            return None;
+        }
+
+        let mut config = Self::new();
+        for param in parts {
+            let (name, value) = match param.split_once('=') {
+                Some((name, value)) => (name.trim(), Some(value.trim().trim_matches('"'))),
+                None => (param, None),
+            };
+
+            match (name.to_ascii_lowercase().as_str(), value) {
+                ("server_no_context_takeover", None) => {
+                    config.server_no_context_takeover = true;
+                }
+                ("client_no_context_takeover", None) => {
+                    config.client_no_context_takeover = true;
+                }
+                ("server_max_window_bits", Some(value)) => {
+                    config.server_max_window_bits = parse_window_bits(value)?;
+                }
+                ("client_max_window_bits", None) => {}
+                ("client_max_window_bits", Some(value)) => {
+                    let bits = parse_window_bits(value)?;
+                    if bits < MIN_WINDOW_BITS {
+                        // zlib cannot produce raw deflate streams with a
+                        // 256-byte window.
+                        return None;
+                    }
+                    config.client_max_window_bits = bits;
+                }
+                _ => return None,
+            }
+        }
+
+        Some(config)
+    }
+
+    /// Validate the `Sec-WebSocket-Extensions` response header of a server
+    /// that accepted the offer.
+    pub(crate) fn accept_response(&self, extensions: &str) -> Option<DeflateParams> {
+        let mut parts = extensions.split(';').map(str::trim);
+        if !parts.next()?.eq_ignore_ascii_case(EXTENSION_NAME) {
+            return None;
+        }
+
+        let mut params = DeflateParams {
+            compression_level: self.compression_level,
+            server_max_window_bits: MAX_WINDOW_BITS,
+            client_max_window_bits: self.client_max_window_bits,
+            server_no_context_takeover: false,
+            client_no_context_takeover: self.client_no_context_takeover,
+        };
+
+        for param in parts {
+            let (name, value) = match param.split_once('=') {
+                Some((name, value)) => (name.trim(), Some(value.trim().trim_matches('"'))),
+                None => (param, None),
+            };
+
+            match (name.to_ascii_lowercase().as_str(), value) {
+                ("server_no_context_takeover", None) => {
+                    params.server_no_context_takeover = true;
+                }
+                ("client_no_context_takeover", None) => {
+                    params.client_no_context_takeover = true;
+                }
+                ("server_max_window_bits", Some(value)) => {
+                    params.server_max_window_bits = parse_window_bits(value)?;
+                }
+                ("client_max_window_bits", Some(value)) => {
+                    let bits = parse_window_bits(value)?;
+                    if bits < MIN_WINDOW_BITS {
+                        return None;
+                    }
+                    params.client_max_window_bits = params.client_max_window_bits.min(bits);
+                }
+                _ => return None,
+            }
+        }
+
+        if params.server_max_window_bits > self.server_max_window_bits
+            || (self.server_no_context_takeover && !params.server_no_context_takeover)
+        {
+            return None;
+        }
+
+        Some(params)
+    }
+}
+
+fn parse_window_bits(value: &str) -> Option<u8> {
+    value
+        .parse::<u8>()
+        .ok()
+        .filter(|bits| (8..=MAX_WINDOW_BITS).contains(bits))
+}
+
+/// The negotiated parameters of the `permessage-deflate` extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct DeflateParams {
+    pub(crate) compression_level: u32,
+    pub(crate) server_max_window_bits: u8,
+    pub(crate) client_max_window_bits: u8,
+    pub(crate) server_no_context_takeover: bool,
+    pub(crate) client_no_context_takeover: bool,
+}
+
+impl DeflateParams {
+    /// Returns the value of the `Sec-WebSocket-Extensions` response header.
+    pub(crate) fn to_header_value(self) -> HeaderValue {
+        let mut value = EXTENSION_NAME.to_string();
+        if self.server_no_context_takeover {
+            value.push_str("; server_no_context_takeover");
+        }
+        if self.client_no_context_takeover {
+            value.push_str("; client_no_context_takeover");
+        }
+        if self.server_max_window_bits < MAX_WINDOW_BITS {
+            let _ = write!(
+                value,
+                "; server_max_window_bits={}",
+                self.server_max_window_bits
+            );
+        }
+        if self.client_max_window_bits < MAX_WINDOW_BITS {
+            let _ = write!(
+                value,
+                "; client_max_window_bits={}",
+                self.client_max_window_bits
+            );
+        }
+        HeaderValue::try_from(value).unwrap()
+    }
+
+    /// Returns the compressor used by the server to send messages.
+    pub(crate) fn server_deflater(self) -> Deflater {
+        Deflater::new(
+            self.compression_level,
+            self.server_max_window_bits,
+            self.server_no_context_takeover,
+        )
+    }
+
+    /// Returns the decompressor used by the server to receive messages.
+    pub(crate) fn server_inflater(self) -> Inflater {
+        Inflater::new(self.client_max_window_bits, self.client_no_context_takeover)
+    }
+
+    /// Returns the compressor used by the client to send messages.
+    pub(crate) fn client_deflater(self) -> Deflater {
+        Deflater::new(
+            self.compression_level,
+            self.client_max_window_bits,
+            self.client_no_context_takeover,
+        )
+    }
+
+    /// Returns the decompressor used by the client to receive messages.
+    pub(crate) fn client_inflater(self) -> Inflater {
+        Inflater::new(self.server_max_window_bits, self.server_no_context_takeover)
+    }
+}
+
+/// Compresses the payload of outgoing messages.
+pub(crate) struct Deflater {
+    compress: Compress,
+    no_context_takeover: bool,
+}
+
+impl Deflater {
+    pub(crate) fn new(level: u32, window_bits: u8, no_context_takeover: bool) -> Self {
+        Self {
+            compress: Compress::new_with_window_bits(
+                Compression::new(level),
+                false,
+                window_bits.max(MIN_WINDOW_BITS),
+            ),
+            no_context_takeover,
+        }
+    }
+
+    pub(crate) fn compress(&mut self, data: &[u8]) -> IoResult<Vec<u8>> {
+        let mut output = Vec::with_capacity(data.len() / 2 + 64);
+        let start = self.compress.total_in();
+
+        loop {
+            let consumed = (self.compress.total_in() - start) as usize;
+            self.compress
+                .compress_vec(&data[consumed..], &mut output, FlushCompress::Sync)
+                .map_err(IoError::other)?;
+
+            let consumed = (self.compress.total_in() - start) as usize;
+            if consumed == data.len() && output.len() < output.capacity() {
+                break;
+            }
+            output.reserve(output.capacity().max(64));
+        }
+
+        if output.ends_with(&DEFLATE_TRAILER) {
+            output.truncate(output.len() - DEFLATE_TRAILER.len());
+        }
+        if self.no_context_takeover {
+            self.compress.reset();
+        }
+        Ok(output)
+    }
+}
+
+/// Decompresses the payload of incoming messages.
+pub(crate) struct Inflater {
+    decompress: Decompress,
+    no_context_takeover: bool,
+}
+
+impl Inflater {
+    pub(crate) fn new(window_bits: u8, no_context_takeover: bool) -> Self {
+        Self {
+            // A larger window can always decode a stream produced with a smaller one.
+            decompress: Decompress::new_with_window_bits(false, window_bits.max(MIN_WINDOW_BITS)),
+            no_context_takeover,
+        }
+    }
+
+    fn decompress(&mut self, mut data: Vec<u8>, max_size: Option<usize>) -> IoResult<Vec<u8>> {
+        data.extend_from_slice(&DEFLATE_TRAILER);
+        let mut output = Vec::with_capacity(data.len() * 2);
+        let start = self.decompress.total_in();
+
+        loop {
+            let consumed = (self.decompress.total_in() - start) as usize;
+            let status = self
+                .decompress
+                .decompress_vec(&data[consumed..], &mut output, FlushDecompress::Sync)
+                .map_err(|err| IoError::new(ErrorKind::InvalidData, err))?;
+
+            if max_size.is_some_and(|max_size| output.len() > max_size) {
+                return Err(IoError::new(
+                    ErrorKind::InvalidData,
+                    "decompressed message is too big",
+                ));
+            }
+
+            let consumed = (self.decompress.total_in() - start) as usize;
+            if status == Status::StreamEnd
+                || (consumed == data.len() && output.len() < output.capacity())
+            {
+                break;
+            }
+            output.reserve(output.capacity().max(64));
+        }
+
+        if self.no_context_takeover {
+            self.decompress.reset(false);
+        }
+        Ok(output)
+    }
+}
+
+struct FrameHead {
+    fin: bool,
+    rsv1: bool,
+    opcode: u8,
+    mask: Option<[u8; 4]>,
+    header_len: usize,
+    payload_len: usize,
+}
+
+impl FrameHead {
+    fn parse(buf: &[u8]) -> Option<Self> {
+        if buf.len() < 2 {
+            return None;
+        }
+
+        let (fin, rsv1, opcode) = (buf[0] & 0x80 != 0, buf[0] & 0x40 != 0, buf[0] & 0x0f);
+        let masked = buf[1] & 0x80 != 0;
+        let (payload_len, mut header_len) = match buf[1] & 0x7f {
+            126 => (
+                u16::from_be_bytes(buf.get(2..4)?.try_into().ok()?) as u64,
+                4,
+            ),
+            127 => (u64::from_be_bytes(buf.get(2..10)?.try_into().ok()?), 10),
+            len => (len as u64, 2),
+        };
+
+        let mask = if masked {
+            let mask = buf.get(header_len..header_len + 4)?.try_into().ok()?;
+            header_len += 4;
+            Some(mask)
+        } else {
+            None
+        };
+
+        Some(Self {
+            fin,
+            rsv1,
+            opcode,
+            mask,
+            header_len,
+            payload_len: usize::try_from(payload_len).unwrap_or(usize::MAX),
+        })
+    }
+}
+
+fn apply_mask(data: &mut [u8], mask: Option<[u8; 4]>) {
+    if let Some(mask) = mask {
+        for (i, byte) in data.iter_mut().enumerate() {
+            *byte ^= mask[i % 4];
+        }
+    }
+}
+
+struct CompressedMessage {
+    opcode: u8,
+    masked: bool,
+    payload: Vec<u8>,
+}
+
+enum Fragmented {
+    Compressed(CompressedMessage),
+    Plain,
+}
+
+/// An IO adapter that inflates compressed data frames before they reach the
+/// WebSocket protocol implementation.
+///
+/// Frames that carry the `RSV1` bit are collected until the end of the
+/// message, decompressed and handed on as a single uncompressed frame. Every
+/// other frame is passed through unchanged, so protocol errors are still
+/// reported by the protocol layer.
+pub(crate) struct DeflateIo<S> {
+    inner: S,
+    inflater: Inflater,
+    max_frame_size: Option<usize>,
+    max_message_size: Option<usize>,
+    read_buf: BytesMut,
+    output: BytesMut,
+    fragmented: Option<Fragmented>,
+}
+
+impl<S> DeflateIo<S> {
+    pub(crate) fn new(
+        inner: S,
+        inflater: Inflater,
+        max_frame_size: Option<usize>,
+        max_message_size: Option<usize>,
+    ) -> Self {
+        Self {
+            inner,
+            inflater,
+            max_frame_size,
+            max_message_size,
+            read_buf: BytesMut::new(),
+            output: BytesMut::new(),
+            fragmented: None,
+        }
+    }
+
+    fn process_frame(&mut self) -> IoResult<bool> {
+        let Some(head) = FrameHead::parse(&self.read_buf) else {
+            return Ok(false);
+        };
+
+        if self
+            .max_frame_size
+            .is_some_and(|max_size| head.payload_len > max_size)
+        {
+            return Err(IoError::new(ErrorKind::InvalidData, "frame is too big"));
+        }
+        let Some(frame_len) = head.header_len.checked_add(head.payload_len) else {
+            return Err(IoError::new(ErrorKind::InvalidData, "frame is too big"));
+        };
+        if self.read_buf.len() < frame_len {
+            self.read_buf
+                .reserve((frame_len - self.read_buf.len()).min(MAX_RESERVE));
+            return Ok(false);
+        }
+
+        let raw = self.read_buf.split_to(frame_len);
+
+        // control frames, and anything the protocol layer has to reject
+        if head.opcode >= 8 || (head.rsv1 && head.opcode == 0) {
+            self.output.extend_from_slice(&raw);
+            return Ok(true);
+        }
+
+        match self.fragmented.take() {
+            Some(Fragmented::Compressed(mut message)) if head.opcode == 0 => {
+                let mut payload = raw[head.header_len..].to_vec();
+                apply_mask(&mut payload, head.mask);
+                message.payload.extend_from_slice(&payload);
+
+                if self
+                    .max_message_size
+                    .is_some_and(|max_size| message.payload.len() > max_size)
+                {
+                    return Err(IoError::new(ErrorKind::InvalidData, "message is too big"));
+                }
+
+                if head.fin {
+                    self.write_inflated(message)?;
+                } else {
+                    self.fragmented = Some(Fragmented::Compressed(message));
+                }
+            }
+            fragmented if head.opcode == 0 || !head.rsv1 || fragmented.is_some() => {
+                self.output.extend_from_slice(&raw);
+                if !head.fin {
+                    self.fragmented = Some(Fragmented::Plain);
+                } else if head.opcode != 0 {
+                    self.fragmented = fragmented;
+                }
+            }
+            _ => {
+                let mut payload = raw[head.header_len..].to_vec();
+                apply_mask(&mut payload, head.mask);
+                let message = CompressedMessage {
+                    opcode: head.opcode,
+                    masked: head.mask.is_some(),
+                    payload,
+                };
+
+                if head.fin {
+                    self.write_inflated(message)?;
+                } else {
+                    self.fragmented = Some(Fragmented::Compressed(message));
+                }
+            }
+        }
+
+        Ok(true)
+    }
+
+    fn write_inflated(&mut self, message: CompressedMessage) -> IoResult<()> {
+        let data = self
+            .inflater
+            .decompress(message.payload, self.max_message_size)?;
+        let mask_bit = if message.masked { 0x80 } else { 0x00 };
+
+        self.output.put_u8(0x80 | message.opcode);
+        match data.len() {
+            len if len < 126 => self.output.put_u8(mask_bit | len as u8),
+            len if len <= u16::MAX as usize => {
+                self.output.put_u8(mask_bit | 126);
+                self.output.put_u16(len as u16);
+            }
+            len => {
+                self.output.put_u8(mask_bit | 127);
+                self.output.put_u64(len as u64);
+            }
+        }
+        if message.masked {
+            // masking with an all-zero key leaves the payload unchanged
+            self.output.put_slice(&[0; 4]);
+        }
+        self.output.put_slice(&data);
+        Ok(())
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for DeflateIo<S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<IoResult<()>> {
+        let this = self.get_mut();
+
+        loop {
+            if !this.output.is_empty() {
+                let len = buf.remaining().min(this.output.len());
+                buf.put_slice(&this.output[..len]);
+                this.output.advance(len);
+                return Poll::Ready(Ok(()));
+            }
+
+            if this.process_frame()? {
+                continue;
+            }
+
+            if this.read_buf.capacity() == this.read_buf.len() {
+                this.read_buf.reserve(4096);
+            }
+            if ready!(poll_read_buf(
+                Pin::new(&mut this.inner),
+                cx,
+                &mut this.read_buf
+            ))? == 0
+            {
+                // hand the incomplete frame to the protocol layer, which reports
+                // the unexpected end of the stream
+                let remaining = this.read_buf.split();
+                if remaining.is_empty() {
+                    return Poll::Ready(Ok(()));
+                }
+                this.output = remaining;
+            }
+        }
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for DeflateIo<S> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<IoResult<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<IoResult<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<IoResult<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_offer() {
+        assert_eq!(DeflateConfig::from_offer("foo"), None);
+        assert_eq!(
+            DeflateConfig::from_offer("permessage-deflate; client_max_window_bits"),
+            Some(DeflateConfig::new())
+        );
+        assert_eq!(
+            DeflateConfig::from_offer(
+                "permessage-deflate; server_no_context_takeover; client_no_context_takeover; \
+                 server_max_window_bits=10; client_max_window_bits=11"
+            ),
+            Some(
+                DeflateConfig::new()
+                    .server_no_context_takeover(true)
+                    .client_no_context_takeover(true)
+                    .server_max_window_bits(10)
+                    .client_max_window_bits(11)
+            )
+        );
+        assert_eq!(
+            DeflateConfig::from_offer("permessage-deflate; client_max_window_bits=8"),
+            None
+        );
+        assert_eq!(DeflateConfig::from_offer("permessage-deflate; foo"), None);
+    }
+
+    #[test]
+    fn negotiate() {
+        let config = DeflateConfig::new();
+        assert_eq!(config.negotiate("foo, bar"), None);
+
+        let params = config.negotiate("permessage-deflate").unwrap();
+        assert_eq!(
+            params.to_header_value(),
+            HeaderValue::from_static("permessage-deflate")
+        );
+
+        let params = config
+            .negotiate("permessage-deflate; server_max_window_bits=10; client_max_window_bits")
+            .unwrap();
+        assert_eq!(params.server_max_window_bits, 10);
+        assert_eq!(params.client_max_window_bits, 15);
+        assert_eq!(
+            params.to_header_value(),
+            HeaderValue::from_static("permessage-deflate; server_max_window_bits=10")
+        );
+
+        let params = DeflateConfig::new()
+            .client_max_window_bits(11)
+            .client_no_context_takeover(true)
+            .negotiate("permessage-deflate; client_max_window_bits; server_no_context_takeover")
+            .unwrap();
+        assert_eq!(
+            params.to_header_value(),
+            HeaderValue::from_static(
+                "permessage-deflate; server_no_context_takeover; client_no_context_takeover; client_max_window_bits=11"
+            )
+        );
+
+        // the server cannot restrict the window of a client that doesn't support it
+        let params = DeflateConfig::new()
+            .client_max_window_bits(11)
+            .negotiate("permessage-deflate")
+            .unwrap();
+        assert_eq!(params.client_max_window_bits, 15);
+
+        // unknown parameters and unsupported windows skip to the next offer
+        let params = config
+            .negotiate(
+                "permessage-deflate; foo, permessage-deflate; server_max_window_bits=8, permessage-deflate; server_max_window_bits=9",
+            )
+            .unwrap();
+        assert_eq!(params.server_max_window_bits, 9);
+    }
+
+    #[test]
+    fn accept_response() {
+        let config = DeflateConfig::new();
+        assert_eq!(
+            config.offer(),
+            HeaderValue::from_static("permessage-deflate; client_max_window_bits")
+        );
+
+        let params = config
+            .accept_response("permessage-deflate; server_max_window_bits=10")
+            .unwrap();
+        assert_eq!(params.server_max_window_bits, 10);
+        assert_eq!(params.client_max_window_bits, 15);
+        assert!(config.accept_response("permessage-deflate; foo").is_none());
+
+        let config = DeflateConfig::new()
+            .server_max_window_bits(10)
+            .server_no_context_takeover(true);
+        assert_eq!(
+            config.offer(),
+            HeaderValue::from_static(
+                "permessage-deflate; server_no_context_takeover; server_max_window_bits=10; client_max_window_bits"
+            )
+        );
+        assert!(config.accept_response("permessage-deflate").is_none());
+        assert!(
+            config
+                .accept_response(
+                    "permessage-deflate; server_no_context_takeover; server_max_window_bits=9"
+                )
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn compress_roundtrip() {
+        let params = DeflateConfig::new()
+            .negotiate("permessage-deflate")
+            .unwrap();
+        let mut deflater = params.server_deflater();
+        let mut inflater = Inflater::new(15, false);
+
+        let data = "hello, world! ".repeat(100);
+        for _ in 0..3 {
+            let compressed = deflater.compress(data.as_bytes()).unwrap();
+            assert!(compressed.len() < data.len());
+            assert_eq!(
+                inflater.decompress(compressed, None).unwrap(),
+                data.as_bytes()
+            );
+        }
+
+        let compressed = deflater.compress(data.as_bytes()).unwrap();
+        assert!(inflater.decompress(compressed, Some(100)).is_err());
+    }
+
+    #[test]
+    fn huge_frame_length() {
+        let mut io = DeflateIo::new((), Inflater::new(15, false), None, None);
+
+        // the reservation is bounded by the data received, not the header
+        io.read_buf
+            .extend_from_slice(&[0xc2, 127, 0, 0, 0, 1, 0, 0, 0, 0, 1, 2, 3]);
+        assert!(!io.process_frame().unwrap());
+        assert!(io.read_buf.capacity() <= io.read_buf.len() + MAX_RESERVE);
+
+        // the frame length overflows
+        io.read_buf.clear();
+        io.read_buf
+            .extend_from_slice(&[0xc2, 127, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]);
+        assert!(io.process_frame().is_err());
+    }
+}
@@ -4,7 +4,7 @@ use futures_util::{FutureExt, future::BoxFuture};
 use headers::HeaderMapExt;
 use tokio_tungstenite::tungstenite::protocol::{Role, WebSocketConfig};
 
-use super::{WebSocketStream, utils::sign};
+use super::{DeflateConfig, WebSocketStream, utils::sign};
 use crate::{
     Body, FromRequest, IntoResponse, OnUpgrade, Request, RequestBody, Response, Result,
     error::WebSocketError,
@@ -24,7 +24,9 @@ pub struct WebSocket {
     on_upgrade: OnUpgrade,
     protocols: Option<Box<[Cow<'static, str>]>>,
     sec_websocket_protocol: Option<HeaderValue>,
+    sec_websocket_extensions: Option<String>,
     config: Option<WebSocketConfig>,
+    deflate: Option<DeflateConfig>,
 }
 
 impl WebSocket {
@@ -57,13 +59,23 @@ impl WebSocket {
             .ok_or(WebSocketError::InvalidProtocol)?;
 
         let sec_websocket_protocol = req.headers().get(header::SEC_WEBSOCKET_PROTOCOL).cloned();
+        let sec_websocket_extensions = req
+            .headers()
+            .get_all(header::SEC_WEBSOCKET_EXTENSIONS)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .collect::<Vec<_>>();
+        let sec_websocket_extensions =
+            (!sec_websocket_extensions.is_empty()).then(|| sec_websocket_extensions.join(","));
 
         Ok(Self {
             key,
             on_upgrade: req.take_upgrade()?,
             protocols: None,
             sec_websocket_protocol,
+            sec_websocket_extensions,
             config: None,
+            deflate: None,
         })
     }
 }
@@ -119,6 +131,19 @@ impl WebSocket {
         }
     }
 
+    /// Enable the `permessage-deflate` extension.
+    ///
+    /// If the client offers the extension in the `Sec-WebSocket-Extensions`
+    /// header, the upgrade response accepts it and the messages are compressed
+    /// transparently.
+    #[must_use]
+    pub fn deflate(self, config: DeflateConfig) -> Self {
+        Self {
+            deflate: Some(config),
+            ..self
+        }
+    }
+
     /// Finalize upgrading the connection and call the provided `callback` with
     /// the stream.
     ///
@@ -200,6 +225,17 @@ where
             );
         }
 
+        // check requested extensions
+        let deflate = self
+            .websocket
+            .deflate
+            .zip(self.websocket.sec_websocket_extensions.as_deref())
+            .and_then(|(config, extensions)| config.negotiate(extensions));
+
+        if let Some(params) = deflate {
+            builder = builder.header(header::SEC_WEBSOCKET_EXTENSIONS, params.to_header_value());
+        }
+
         let resp = builder.body(Body::empty());
 
         tokio::spawn(async move {
@@ -208,13 +244,14 @@ where
                 Err(_) => return,
             };
 
-            let stream = tokio_tungstenite::WebSocketStream::from_raw_socket(
+            let stream = WebSocketStream::from_raw_socket(
                 upgraded,
                 Role::Server,
                 self.websocket.config,
+                deflate,
             )
             .await;
-            (self.callback)(WebSocketStream::new(stream)).await;
+            (self.callback)(stream).await;
         });
 
         resp
@@ -21,11 +21,15 @@
 //! let app = Route::new().at("/", get(index));
 //! ```
 
+mod client;
+mod deflate;
 mod extractor;
 mod message;
 mod stream;
 mod utils;
 
+pub use client::{WebSocketClient, connect};
+pub use deflate::DeflateConfig;
 pub use extractor::{BoxWebSocketUpgraded, WebSocket, WebSocketUpgraded};
 pub use message::{CloseCode, Message};
 pub use stream::WebSocketStream;
@@ -154,4 +158,245 @@ mod tests {
 
         handle.abort();
     }
+
+    #[tokio::test]
+    async fn test_websocket_deflate() {
+        use tokio_tungstenite::tungstenite::{
+            self,
+            protocol::frame::{
+                Frame, FrameHeader,
+                coding::{Data, OpCode},
+            },
+        };
+
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio_tungstenite::tungstenite::protocol::Role;
+
+        use super::deflate::{DeflateIo, Deflater, Inflater};
+
+        #[handler(internal)]
+        async fn index(ws: WebSocket) -> impl IntoResponse {
+            ws.deflate(DeflateConfig::new())
+                .on_upgrade(|mut stream| async move {
+                    assert!(stream.is_compressed());
+                    while let Some(Ok(msg)) = stream.next().await {
+                        if let Message::Text(text) = msg {
+                            if stream
+                                .send(Message::Text(text.to_uppercase()))
+                                .await
+                                .is_err()
+                            {
+                                break;
+                            }
+                        }
+                    }
+                })
+        }
+
+        let acceptor = TcpListener::bind("127.0.0.1:0")
+            .into_acceptor()
+            .await
+            .unwrap();
+        let addr = acceptor
+            .local_addr()
+            .remove(0)
+            .as_socket_addr()
+            .cloned()
+            .unwrap();
+
+        let handle = tokio::spawn(async move {
+            let _ = Server::new_with_acceptor(acceptor).run(index).await;
+        });
+
+        let mut tcp = tokio::net::TcpStream::connect(addr).await.unwrap();
+        tcp.write_all(
+            b"GET / HTTP/1.1\r\n\
+            Host: localhost\r\n\
+            Connection: upgrade\r\n\
+            Upgrade: websocket\r\n\
+            Sec-WebSocket-Version: 13\r\n\
+            Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\
+            Sec-WebSocket-Extensions: permessage-deflate; client_max_window_bits\r\n\r\n",
+        )
+        .await
+        .unwrap();
+
+        let mut resp = Vec::new();
+        while !resp.ends_with(b"\r\n\r\n") {
+            resp.push(tcp.read_u8().await.unwrap());
+        }
+        let resp = String::from_utf8(resp).unwrap().to_ascii_lowercase();
+        assert!(resp.starts_with("http/1.1 101"));
+        assert!(resp.contains("sec-websocket-extensions: permessage-deflate\r\n"));
+
+        let mut client_stream = tokio_tungstenite::WebSocketStream::from_raw_socket(
+            DeflateIo::new(tcp, Inflater::new(15, false), None, None),
+            Role::Client,
+            None,
+        )
+        .await;
+
+        let mut deflater = Deflater::new(6, 15, false);
+        for text in ["aBc", "def", "aBc"] {
+            let payload = deflater.compress(text.as_bytes()).unwrap();
+            let header = FrameHeader {
+                rsv1: true,
+                opcode: OpCode::Data(Data::Text),
+                ..FrameHeader::default()
+            };
+            client_stream
+                .send(tungstenite::Message::Frame(Frame::from_payload(
+                    header,
+                    payload.into(),
+                )))
+                .await
+                .unwrap();
+            assert_eq!(
+                client_stream.next().await.unwrap().unwrap(),
+                tungstenite::Message::Text(text.to_uppercase().into())
+            );
+        }
+
+        // uncompressed messages are still accepted
+        client_stream
+            .send(tungstenite::Message::Text("xyz".into()))
+            .await
+            .unwrap();
+        assert_eq!(
+            client_stream.next().await.unwrap().unwrap(),
+            tungstenite::Message::Text("XYZ".into())
+        );
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_websocket_client() {
+        #[handler(internal)]
+        async fn index(ws: WebSocket) -> impl IntoResponse {
+            ws.protocols(["aaa", "bbb"])
+                .deflate(DeflateConfig::new())
+                .on_upgrade(|mut stream| async move {
+                    while let Some(Ok(msg)) = stream.next().await {
+                        if let Message::Text(text) = msg {
+                            if stream
+                                .send(Message::Text(text.to_uppercase()))
+                                .await
+                                .is_err()
+                            {
+                                break;
+                            }
+                        }
+                    }
+                })
+        }
+
+        let acceptor = TcpListener::bind("127.0.0.1:0")
+            .into_acceptor()
+            .await
+            .unwrap();
+        let addr = acceptor
+            .local_addr()
+            .remove(0)
+            .as_socket_addr()
+            .cloned()
+            .unwrap();
+
+        let handle = tokio::spawn(async move {
+            let _ = Server::new_with_acceptor(acceptor).run(index).await;
+        });
+
+        let mut stream = connect(format!("ws://{addr}")).await.unwrap();
+        assert!(!stream.is_compressed());
+        stream.send(Message::text("aBc")).await.unwrap();
+        assert_eq!(stream.next().await.unwrap().unwrap(), Message::text("ABC"));
+
+        let (mut stream, resp) = WebSocketClient::new()
+            .protocols(["bbb"])
+            .deflate(DeflateConfig::new().client_no_context_takeover(true))
+            .connect(format!("ws://{addr}/"))
+            .await
+            .unwrap();
+        assert_eq!(resp.header(header::SEC_WEBSOCKET_PROTOCOL), Some("bbb"));
+        assert!(stream.is_compressed());
+        for text in ["aBc", "def", &"x".repeat(100_000)] {
+            stream.send(Message::text(text)).await.unwrap();
+            assert_eq!(
+                stream.next().await.unwrap().unwrap(),
+                Message::text(text.to_uppercase())
+            );
+        }
+
+        assert!(connect("http://127.0.0.1:1").await.is_err());
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_test_client_websocket() {
+        use crate::{get, test::TestClient};
+
+        #[handler(internal)]
+        async fn index(ws: WebSocket) -> impl IntoResponse {
+            ws.protocols(["aaa"])
+                .deflate(DeflateConfig::new())
+                .on_upgrade(|mut stream| async move {
+                    let _ = stream
+                        .send(Message::text(format!("{}", stream.is_compressed())))
+                        .await;
+                    while let Some(Ok(Message::Text(text))) = stream.next().await {
+                        if stream
+                            .send(Message::Text(text.to_uppercase()))
+                            .await
+                            .is_err()
+                        {
+                            break;
+                        }
+                    }
+                })
+        }
+
+        let cli = TestClient::new(crate::Route::new().at("/ws", get(index)));
+
+        let mut stream = cli.get("/ws").websocket().await;
+        assert_eq!(
+            stream.next().await.unwrap().unwrap(),
+            Message::text("false")
+        );
+        stream.send(Message::text("aBc")).await.unwrap();
+        assert_eq!(stream.next().await.unwrap().unwrap(), Message::text("ABC"));
+
+        let mut stream = cli
+            .get("/ws")
+            .header(header::SEC_WEBSOCKET_PROTOCOL, "aaa")
+            .header(header::SEC_WEBSOCKET_EXTENSIONS, "permessage-deflate")
+            .websocket()
+            .await;
+        assert!(stream.is_compressed());
+        assert_eq!(stream.next().await.unwrap().unwrap(), Message::text("true"));
+        stream.send(Message::text("def")).await.unwrap();
+        assert_eq!(stream.next().await.unwrap().unwrap(), Message::text("DEF"));
+
+        // the parameters of the offer are honoured by the client
+        let mut stream = cli
+            .get("/ws")
+            .header(header::SEC_WEBSOCKET_PROTOCOL, "aaa")
+            .header(
+                header::SEC_WEBSOCKET_EXTENSIONS,
+                "permessage-deflate; client_no_context_takeover; client_max_window_bits=9",
+            )
+            .websocket()
+            .await;
+        assert!(stream.is_compressed());
+        assert_eq!(stream.next().await.unwrap().unwrap(), Message::text("true"));
+        for _ in 0..2 {
+            stream.send(Message::text("ghi")).await.unwrap();
+            assert_eq!(stream.next().await.unwrap().unwrap(), Message::text("GHI"));
+        }
+
+        cli.get("/ws")
+            .send()
+            .await
+            .assert_status(http::StatusCode::BAD_REQUEST);
+    }
 }
@@ -5,19 +5,66 @@ use std::{
 };
 
 use futures_util::{Sink, SinkExt, Stream, StreamExt};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_tungstenite::tungstenite::protocol::{
+    Role,
+    frame::{
+        Frame, FrameHeader,
+        coding::{Data, OpCode},
+    },
+};
+
+use super::{
+    Message, WebSocketConfig,
+    deflate::{DeflateIo, DeflateParams, Deflater},
+    utils::tungstenite_error_to_io_error,
+};
+
+pub(crate) trait WebSocketIo: AsyncRead + AsyncWrite + Send + Unpin + 'static {}
 
-use super::{Message, WebSocketConfig, utils::tungstenite_error_to_io_error};
-use crate::Upgraded;
+impl<T: AsyncRead + AsyncWrite + Send + Unpin + 'static> WebSocketIo for T {}
+
+pub(crate) type BoxWebSocketIo = Box<dyn WebSocketIo>;
 
 /// A `WebSocket` stream, which implements [`Stream<Message>`] and
 /// [`Sink<Message>`].
 pub struct WebSocketStream {
-    inner: tokio_tungstenite::WebSocketStream<Upgraded>,
+    inner: tokio_tungstenite::WebSocketStream<BoxWebSocketIo>,
+    deflater: Option<Deflater>,
 }
 
 impl WebSocketStream {
-    pub(crate) fn new(inner: tokio_tungstenite::WebSocketStream<Upgraded>) -> Self {
-        Self { inner }
+    /// Create a stream from a connection that has completed the handshake.
+    pub(crate) async fn from_raw_socket(
+        io: impl WebSocketIo,
+        role: Role,
+        config: Option<WebSocketConfig>,
+        deflate: Option<DeflateParams>,
+    ) -> Self {
+        let (io, deflater): (BoxWebSocketIo, _) = match deflate {
+            Some(params) => {
+                let (inflater, deflater) = match role {
+                    Role::Server => (params.server_inflater(), params.server_deflater()),
+                    Role::Client => (params.client_inflater(), params.client_deflater()),
+                };
+                let config = config.unwrap_or_default();
+                let io =
+                    DeflateIo::new(io, inflater, config.max_frame_size, config.max_message_size);
+                (Box::new(io), Some(deflater))
+            }
+            None => (Box::new(io), None),
+        };
+
+        Self {
+            inner: tokio_tungstenite::WebSocketStream::from_raw_socket(io, role, config).await,
+            deflater,
+        }
+    }
+
+    /// Returns `true` if the `permessage-deflate` extension has been
+    /// negotiated for this stream.
+    pub fn is_compressed(&self) -> bool {
+        self.deflater.is_some()
     }
 
     /// Returns a reference to the configuration of the stream.
@@ -51,8 +98,18 @@ impl Sink<Message> for WebSocketStream {
     }
 
     fn start_send(mut self: Pin<&mut Self>, item: Message) -> Result<(), Self::Error> {
-        self.inner
-            .start_send_unpin(item.into())
+        let this = &mut *self;
+        let msg = match (&mut this.deflater, item) {
+            (Some(deflater), Message::Text(text)) => {
+                compressed_frame(deflater, Data::Text, text.as_bytes())?
+            }
+            (Some(deflater), Message::Binary(data)) => {
+                compressed_frame(deflater, Data::Binary, &data)?
+            }
+            (_, item) => item.into(),
+        };
+        this.inner
+            .start_send_unpin(msg)
             .map_err(tungstenite_error_to_io_error)
     }
 
@@ -68,3 +125,19 @@ impl Sink<Message> for WebSocketStream {
             .map_err(tungstenite_error_to_io_error)
     }
 }
+
+fn compressed_frame(
+    deflater: &mut Deflater,
+    data: Data,
+    payload: &[u8],
+) -> IoResult<tokio_tungstenite::tungstenite::Message> {
+    let header = FrameHeader {
+        rsv1: true,
+        opcode: OpCode::Data(data),
+        ..FrameHeader::default()
+    };
+    let payload = deflater.compress(payload)?;
+    Ok(tokio_tungstenite::tungstenite::Message::Frame(
+        Frame::from_payload(header, payload.into()),
+    ))
+}